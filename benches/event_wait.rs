@@ -0,0 +1,123 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Latency of small fetches waited on through the progress thread versus
+// polled inline with DaosEventQueueConfig::inline_poll.
+
+use daos_rust_api::daos_cont::DaosContainer;
+use daos_rust_api::daos_obj::{
+    CondFlags, DaosObjAsyncOps, DaosObject, DAOS_OC_HINTS_NONE, DAOS_OC_UNKNOWN,
+    DAOS_OT_MULTI_HASHED,
+};
+use daos_rust_api::daos_oid_allocator::DaosAsyncOidAllocator;
+use daos_rust_api::daos_pool::DaosPool;
+use daos_rust_api::daos_txn::DaosTxn;
+use daos_rust_api::DaosEventQueueConfig;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const POOL_NAME: &str = "pool1";
+const CONT_NAME: &str = "cont1";
+const WARMUP: usize = 100;
+const ITERATIONS: usize = 10000;
+
+async fn fetch_latencies(pool: &DaosPool, config: DaosEventQueueConfig) -> Vec<Duration> {
+    let mut cont = DaosContainer::new(CONT_NAME);
+    cont.set_event_queue_config(config);
+    cont.connect(pool).expect("Failed to connect to container");
+    let cont = Arc::new(cont);
+
+    let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+    let obj = DaosObject::create_async(
+        &cont,
+        allocator,
+        DAOS_OT_MULTI_HASHED,
+        DAOS_OC_UNKNOWN,
+        DAOS_OC_HINTS_NONE,
+        0,
+    )
+    .await
+    .expect("Failed to create object");
+
+    let txn = DaosTxn::txn_none();
+    let (dkey, akey) = (b"bench".to_vec(), b"a".to_vec());
+    obj.update_async(
+        &txn,
+        CondFlags::empty(),
+        dkey.clone(),
+        akey.clone(),
+        &[7u8; 64],
+    )
+    .await
+    .expect("Failed to update");
+
+    let mut buf = [0u8; 64];
+    let mut latencies = Vec::with_capacity(ITERATIONS);
+    for i in 0..WARMUP + ITERATIONS {
+        let start = Instant::now();
+        obj.fetch_async(
+            &txn,
+            CondFlags::empty(),
+            dkey.clone(),
+            akey.clone(),
+            &mut buf,
+        )
+        .await
+        .expect("Failed to fetch");
+        if i >= WARMUP {
+            latencies.push(start.elapsed());
+        }
+    }
+    latencies
+}
+
+fn report(name: &str, mut latencies: Vec<Duration>) {
+    latencies.sort();
+    let total: Duration = latencies.iter().sum();
+    let pct = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+    println!(
+        "{:<24} mean {:>8.1?}  p50 {:>8.1?}  p99 {:>8.1?}",
+        name,
+        total / latencies.len() as u32,
+        pct(50),
+        pct(99)
+    );
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let mut pool = DaosPool::new(POOL_NAME);
+    pool.connect().expect("Failed to connect to pool");
+
+    let modes = [
+        ("progress thread", None),
+        ("inline poll 20us", Some(Duration::from_micros(20))),
+        ("inline poll 100us", Some(Duration::from_micros(100))),
+    ];
+    for (name, inline_poll) in modes {
+        let config = DaosEventQueueConfig {
+            inline_poll,
+            ..Default::default()
+        };
+        let latencies = runtime.block_on(fetch_latencies(&pool, config));
+        report(name, latencies);
+    }
+}
@@ -15,9 +15,17 @@
  *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+#[cfg(feature = "libdaos")]
 use std::env;
+#[cfg(feature = "libdaos")]
 use std::path::PathBuf;
 
+// Without libdaos there is nothing to link or generate, the crate only
+// builds the memory backend.
+#[cfg(not(feature = "libdaos"))]
+fn main() {}
+
+#[cfg(feature = "libdaos")]
 fn main() {
     // Tell cargo to look for shared libraries in the specified directory
     println!("cargo:rustc-link-search=/usr/lib64");
@@ -26,20 +34,30 @@ fn main() {
     // shared library.
     println!("cargo:rustc-link-lib=daos");
     println!("cargo:rustc-link-lib=daos_common");
+    // DaosPool::create/destroy go through the dmg helper library.
+    if feature("MGMT") {
+        println!("cargo:rustc-link-lib=dmg");
+    }
+    if feature("DFS") {
+        println!("cargo:rustc-link-lib=dfs");
+    }
+    if feature("UNS") {
+        println!("cargo:rustc-link-lib=duns");
+    }
+    if feature("S3") {
+        println!("cargo:rustc-link-lib=ds3");
+    }
 
     // The bindgen::Builder is the main entry point
     // to bindgen, and lets you build up options for
     // the resulting bindings.
-    let bindings = bindgen::Builder::default()
+    let mut builder = bindgen::Builder::default()
         // The input header we would like to generate
         // bindings for.
         .header("/usr/include/daos.h")
         .allowlist_file("/usr/include/daos_api.h")
-        .allowlist_file("/usr/include/daos_fs.h")
         .allowlist_file("/usr/include/daos_obj_class.h")
         .allowlist_file("/usr/include/daos_security.h")
-        .allowlist_file("/usr/include/daos_array.h")
-        .allowlist_file("/usr/include/daos_fs_sys.h")
         .allowlist_file("/usr/include/daos_obj.h")
         .allowlist_file("/usr/include/daos_task.h")
         .allowlist_file("/usr/include/daos_cont.h")
@@ -47,14 +65,34 @@ fn main() {
         .allowlist_file("/usr/include/daos_pool.h")
         .allowlist_file("/usr/include/daos_types.h")
         .allowlist_file("/usr/include/daos_errno.h")
-        .allowlist_file("/usr/include/daos_kv.h")
         .allowlist_file("/usr/include/daos_prop.h")
-        .allowlist_file("/usr/include/daos_uns.h")
         .allowlist_file("/usr/include/daos_event.h")
-        .allowlist_file("/usr/include/daos_mgmt.h")
-        .allowlist_file("/usr/include/daos_s3.h")
         .allowlist_file("/usr/include/daos_version.h")
-        .allowlist_file("/usr/include/gurt/types.h")
+        .allowlist_file("/usr/include/gurt/types.h");
+
+    // Headers of the optional subsystems, only parsed when their feature
+    // is enabled. daos.h doesn't pull in all of them, so they are added as
+    // extra inputs too.
+    let optional = [
+        ("MGMT", &["daos_mgmt.h"][..]),
+        ("KV", &["daos_kv.h"][..]),
+        ("ARRAY", &["daos_array.h"][..]),
+        ("DFS", &["daos_fs.h", "daos_fs_sys.h"][..]),
+        ("UNS", &["daos_uns.h"][..]),
+        ("S3", &["daos_s3.h"][..]),
+        ("PIPELINE", &["daos_pipeline.h"][..]),
+    ];
+    for (name, headers) in optional {
+        if !feature(name) {
+            continue;
+        }
+        for header in headers {
+            let path = format!("/usr/include/{}", header);
+            builder = builder.header(path.as_str()).allowlist_file(path.as_str());
+        }
+    }
+
+    let bindings = builder
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed.
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
@@ -69,3 +107,8 @@ fn main() {
         .write_to_file(out_path.join("daos-bindings.rs"))
         .expect("Couldn't write bindings!");
 }
+
+#[cfg(feature = "libdaos")]
+fn feature(name: &str) -> bool {
+    env::var(format!("CARGO_FEATURE_{}", name)).is_ok()
+}
@@ -19,7 +19,10 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
+#[cfg(feature = "libdaos")]
 include!(concat!(env!("OUT_DIR"), "/daos-bindings.rs"));
+#[cfg(not(feature = "libdaos"))]
+include!("bindings_stub.rs");
 
 pub const DAOS_TXN_NONE: daos_handle_t = daos_handle_t {cookie: 0u64};
 
@@ -28,11 +31,16 @@ pub fn daos_anchor_is_eof(anchor: &daos_anchor_t) -> bool
     daos_anchor_type_t_DAOS_ANCHOR_TYPE_EOF == anchor.da_type.into()
 }
 
+#[cfg(feature = "libdaos")]
 unsafe impl Send for d_iov_t {}
+#[cfg(feature = "libdaos")]
 unsafe impl Send for daos_iod_t {}
+#[cfg(feature = "libdaos")]
 unsafe impl Send for d_sg_list_t {}
+#[cfg(feature = "libdaos")]
+unsafe impl Send for daos_iom_t {}
 
-#[cfg(test)]
+#[cfg(all(test, feature = "libdaos"))]
 mod tests {
     use super::*;
     use std::ptr;
@@ -0,0 +1,116 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// The plain types and constants of the generated bindings that the memory
+// backend shares with DaosObject, for builds without libdaos. Layouts follow
+// the DAOS headers. Nothing reaches libdaos, so the DER_* codes only have to
+// tell the error variants apart.
+
+pub type daos_size_t = u64;
+pub type daos_otype_t = ::std::os::raw::c_uint;
+pub type daos_oclass_id_t = u32;
+pub type daos_oclass_hints_t = u16;
+pub type daos_anchor_type_t = ::std::os::raw::c_uint;
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct daos_obj_id_t {
+    pub lo: u64,
+    pub hi: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct daos_handle_t {
+    pub cookie: u64,
+}
+
+pub const DAOS_ANCHOR_BUF_MAX: u32 = 104;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct daos_anchor_t {
+    pub da_type: u16,
+    pub da_shard: u16,
+    pub da_flags: u32,
+    pub da_sub_anchors: u64,
+    pub da_buf: [u8; DAOS_ANCHOR_BUF_MAX as usize],
+}
+
+pub const daos_anchor_type_t_DAOS_ANCHOR_TYPE_ZERO: daos_anchor_type_t = 0;
+pub const daos_anchor_type_t_DAOS_ANCHOR_TYPE_HKEY: daos_anchor_type_t = 1;
+pub const daos_anchor_type_t_DAOS_ANCHOR_TYPE_KEY: daos_anchor_type_t = 2;
+pub const daos_anchor_type_t_DAOS_ANCHOR_TYPE_EOF: daos_anchor_type_t = 3;
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct daos_key_desc_t {
+    pub kd_key_len: daos_size_t,
+    pub kd_val_type: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct daos_epoch_range_t {
+    pub epr_lo: u64,
+    pub epr_hi: u64,
+}
+
+pub const daos_otype_t_DAOS_OT_MULTI_HASHED: daos_otype_t = 0;
+pub const daos_otype_t_DAOS_OT_ARRAY_BYTE: daos_otype_t = 13;
+pub const OC_UNKNOWN: daos_oclass_id_t = 0;
+pub const OC_REDUN_SHIFT: u32 = 24;
+
+pub const OID_FMT_TYPE_BITS: u32 = 8;
+pub const OID_FMT_CLASS_BITS: u32 = 8;
+pub const OID_FMT_META_BITS: u32 = 16;
+pub const OID_FMT_TYPE_SHIFT: u32 = 56;
+pub const OID_FMT_CLASS_SHIFT: u32 = 48;
+pub const OID_FMT_META_SHIFT: u32 = 32;
+
+pub const DAOS_COND_DKEY_INSERT: u32 = 1;
+pub const DAOS_COND_DKEY_UPDATE: u32 = 2;
+pub const DAOS_COND_DKEY_FETCH: u32 = 4;
+pub const DAOS_COND_PUNCH: u32 = 8;
+pub const DAOS_COND_AKEY_INSERT: u32 = 16;
+pub const DAOS_COND_AKEY_UPDATE: u32 = 32;
+pub const DAOS_COND_AKEY_FETCH: u32 = 64;
+pub const DAOS_COND_PER_AKEY: u32 = 128;
+pub const DAOS_COND_MASK: u32 = 255;
+
+pub const daos_errno_DER_NO_PERM: u32 = 1002;
+pub const daos_errno_DER_NO_HDL: u32 = 1003;
+pub const daos_errno_DER_INVAL: u32 = 1004;
+pub const daos_errno_DER_EXIST: u32 = 1005;
+pub const daos_errno_DER_NONEXIST: u32 = 1006;
+pub const daos_errno_DER_UNREACH: u32 = 1007;
+pub const daos_errno_DER_NOSPACE: u32 = 1008;
+pub const daos_errno_DER_NOMEM: u32 = 1010;
+pub const daos_errno_DER_NOSYS: u32 = 1011;
+pub const daos_errno_DER_TIMEDOUT: u32 = 1012;
+pub const daos_errno_DER_BUSY: u32 = 1013;
+pub const daos_errno_DER_AGAIN: u32 = 1014;
+pub const daos_errno_DER_CANCELED: u32 = 1019;
+pub const daos_errno_DER_MISMATCH: u32 = 1032;
+pub const daos_errno_DER_EVICTED: u32 = 1033;
+pub const daos_errno_DER_IO: u32 = 2001;
+pub const daos_errno_DER_KEY2BIG: u32 = 2012;
+pub const daos_errno_DER_REC2BIG: u32 = 2013;
+pub const daos_errno_DER_CSUM: u32 = 2021;
+pub const daos_errno_DER_TX_RESTART: u32 = 2024;
+pub const daos_errno_DER_TX_BUSY: u32 = 2030;
+pub const daos_errno_DER_TX_UNCOMMITTED: u32 = 2032;
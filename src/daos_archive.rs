@@ -0,0 +1,502 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::daos_cont::DaosContainer;
+use crate::daos_obj::{
+    update_many_objects_async, CondFlags, DaosFanOutPolicy, DaosFanOutUpdate, DaosObjAsyncOps,
+    DaosObject, KeyListPool, META_DKEY,
+};
+use crate::daos_pool::DaosObjectId;
+use crate::daos_txn::DaosTxn;
+use crate::error::{DaosError, Result};
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const ARCHIVE_MAGIC: &[u8; 7] = b"DAOSARC";
+const ARCHIVE_VERSION: u8 = 1;
+
+const TAG_END: u8 = 0;
+const TAG_SINGLE: u8 = 1;
+const TAG_EXTENT: u8 = 2;
+
+// first buffer tried for single values, grown when a value doesn't fit
+const SINGLE_BUF_SIZE: usize = 4 * 1024;
+// extents larger than this are fetched and archived in pieces
+const EXTENT_CHUNK: u64 = 1024 * 1024;
+// single values written by one update_many_objects_async call on import
+const IMPORT_BATCH: usize = 64;
+
+// What export_object_async wrote or import_object_async read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArchiveStats {
+    pub dkeys: u64,
+    pub single_values: u64,
+    pub extents: u64,
+    // value bytes, keys and framing not included
+    pub bytes: u64,
+}
+
+// One entry of an archive between the header and the end tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveRecord {
+    Single {
+        dkey: Vec<u8>,
+        akey: Vec<u8>,
+        value: Vec<u8>,
+    },
+    Extent {
+        dkey: Vec<u8>,
+        akey: Vec<u8>,
+        offset: u64,
+        data: Vec<u8>,
+    },
+}
+
+// Layout, little endian: magic, version and the oid lo/hi, then records
+// made of a tag, dkey and akey as a u32 length followed by the bytes, and
+// the value as a u32 length and bytes, with a u64 record offset before it
+// for extents. A lone end tag closes the archive. Only byte arrays are
+// supported, so extent offsets and lengths count bytes.
+fn encode_header(oid: DaosObjectId) -> Vec<u8> {
+    let mut out = Vec::with_capacity(ARCHIVE_MAGIC.len() + 1 + 16);
+    out.extend_from_slice(ARCHIVE_MAGIC);
+    out.push(ARCHIVE_VERSION);
+    out.extend_from_slice(&oid.lo.to_le_bytes());
+    out.extend_from_slice(&oid.hi.to_le_bytes());
+    out
+}
+
+fn encode_record(record: &ArchiveRecord) -> Vec<u8> {
+    let (tag, dkey, akey, offset, value) = match record {
+        ArchiveRecord::Single { dkey, akey, value } => (TAG_SINGLE, dkey, akey, None, value),
+        ArchiveRecord::Extent {
+            dkey,
+            akey,
+            offset,
+            data,
+        } => (TAG_EXTENT, dkey, akey, Some(*offset), data),
+    };
+    let mut out = Vec::with_capacity(1 + 12 + 8 + dkey.len() + akey.len() + value.len());
+    out.push(tag);
+    for buf in [dkey, akey] {
+        out.extend_from_slice(&(buf.len() as u32).to_le_bytes());
+        out.extend_from_slice(buf);
+    }
+    if let Some(offset) = offset {
+        out.extend_from_slice(&offset.to_le_bytes());
+    }
+    out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+    out.extend_from_slice(value);
+    out
+}
+
+async fn read_exact<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> Result<()> {
+    match reader.read_exact(buf).await {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            Err(DaosError::inval("truncated archive"))
+        }
+        Err(e) => Err(DaosError::io_error(format!("archive read failed: {}", e))),
+    }
+}
+
+async fn read_u32<R: AsyncRead + Unpin>(reader: &mut R) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    read_exact(reader, &mut buf).await?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+async fn read_u64<R: AsyncRead + Unpin>(reader: &mut R) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    read_exact(reader, &mut buf).await?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+async fn read_bytes<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Vec<u8>> {
+    let len = read_u32(reader).await?;
+    let mut buf = vec![0u8; len as usize];
+    read_exact(reader, &mut buf).await?;
+    Ok(buf)
+}
+
+// Reads the header and returns the oid of the archived object.
+pub async fn read_archive_header<R: AsyncRead + Unpin>(reader: &mut R) -> Result<DaosObjectId> {
+    let mut magic = [0u8; 7];
+    read_exact(reader, &mut magic).await?;
+    if &magic != ARCHIVE_MAGIC {
+        return Err(DaosError::inval("not an object archive"));
+    }
+    let mut version = [0u8; 1];
+    read_exact(reader, &mut version).await?;
+    if version[0] != ARCHIVE_VERSION {
+        return Err(DaosError::inval("unknown object archive version"));
+    }
+    Ok(DaosObjectId {
+        lo: read_u64(reader).await?,
+        hi: read_u64(reader).await?,
+    })
+}
+
+// The next record, or None once the end tag is read.
+pub async fn read_archive_record<R: AsyncRead + Unpin>(
+    reader: &mut R,
+) -> Result<Option<ArchiveRecord>> {
+    let mut tag = [0u8; 1];
+    read_exact(reader, &mut tag).await?;
+    if tag[0] == TAG_END {
+        return Ok(None);
+    }
+    if tag[0] != TAG_SINGLE && tag[0] != TAG_EXTENT {
+        return Err(DaosError::inval("unknown object archive record"));
+    }
+    let dkey = read_bytes(reader).await?;
+    let akey = read_bytes(reader).await?;
+    if tag[0] == TAG_SINGLE {
+        let value = read_bytes(reader).await?;
+        return Ok(Some(ArchiveRecord::Single { dkey, akey, value }));
+    }
+    let offset = read_u64(reader).await?;
+    let data = read_bytes(reader).await?;
+    Ok(Some(ArchiveRecord::Extent {
+        dkey,
+        akey,
+        offset,
+        data,
+    }))
+}
+
+async fn write_all<W: AsyncWrite + Unpin>(writer: &mut W, buf: &[u8]) -> Result<()> {
+    writer
+        .write_all(buf)
+        .await
+        .map_err(|e| DaosError::io_error(format!("archive write failed: {}", e)))
+}
+
+// Every key the list yields from where it stands until the end.
+pub(crate) async fn collect_keys(
+    obj: &DaosObject,
+    txn: &DaosTxn,
+    dkey: Option<&[u8]>,
+) -> Result<Vec<Vec<u8>>> {
+    let mut keys = Vec::new();
+    let mut key_lst = KeyListPool::shared().checkout();
+    while !key_lst.reach_end() {
+        key_lst = match dkey {
+            Some(dkey) => obj.list_akey_async(txn, dkey.to_vec(), key_lst).await?,
+            None => obj.list_dkey_async(txn, key_lst).await?,
+        };
+        keys.extend(key_lst.keys().map(|key| key.to_vec()));
+    }
+    KeyListPool::shared().checkin(key_lst);
+    Ok(keys)
+}
+
+// The single value under dkey and akey, empty when the akey holds an
+// array instead.
+pub(crate) async fn fetch_single(
+    obj: &DaosObject,
+    txn: &DaosTxn,
+    dkey: &[u8],
+    akey: &[u8],
+) -> Result<Vec<u8>> {
+    let mut buf = vec![0u8; SINGLE_BUF_SIZE];
+    loop {
+        match obj
+            .fetch_async(txn, CondFlags::empty(), dkey, akey, &mut buf)
+            .await
+        {
+            Ok(size) => {
+                buf.truncate(size);
+                return Ok(buf);
+            }
+            Err(e) => match e.required_size() {
+                Some(required) if required > buf.len() => buf.resize(required, 0),
+                _ => return Err(e),
+            },
+        }
+    }
+}
+
+// Writes every dkey, akey and value of obj to writer, outside of any
+// transaction, so concurrent writers can leave a mix of old and new values
+// in the archive. The archive doesn't depend on the pool or container it
+// came from and is restored with import_object_async.
+pub async fn export_object_async<W: AsyncWrite + Unpin>(
+    obj: &DaosObject,
+    writer: &mut W,
+) -> Result<ArchiveStats> {
+    let txn = DaosTxn::txn_none();
+    let mut stats = ArchiveStats::default();
+    write_all(writer, &encode_header(obj.oid)).await?;
+
+    for dkey in collect_keys(obj, &txn, None).await? {
+        stats.dkeys += 1;
+        for akey in collect_keys(obj, &txn, Some(&dkey)).await? {
+            let value = fetch_single(obj, &txn, &dkey, &akey).await?;
+            if !value.is_empty() {
+                stats.single_values += 1;
+                stats.bytes += value.len() as u64;
+                let record = ArchiveRecord::Single {
+                    dkey: dkey.clone(),
+                    akey,
+                    value,
+                };
+                write_all(writer, &encode_record(&record)).await?;
+                continue;
+            }
+
+            let recxs = obj
+                .list_recx_async(&txn, dkey.clone(), akey.clone())
+                .await?;
+            if !recxs.extents.is_empty() && recxs.rec_size != 1 {
+                return Err(DaosError::no_sys(
+                    "only arrays of 1 byte records can be archived",
+                ));
+            }
+            for extent in recxs.extents {
+                let mut offset = extent.offset;
+                let end = extent.offset + extent.len;
+                while offset < end {
+                    let len = (end - offset).min(EXTENT_CHUNK);
+                    let mut data = vec![0u8; len as usize];
+                    obj.fetch_recx_async(&txn, CondFlags::empty(), &dkey, &akey, offset, &mut data)
+                        .await?;
+                    stats.extents += 1;
+                    stats.bytes += len;
+                    let record = ArchiveRecord::Extent {
+                        dkey: dkey.clone(),
+                        akey: akey.clone(),
+                        offset,
+                        data,
+                    };
+                    write_all(writer, &encode_record(&record)).await?;
+                    offset += len;
+                }
+            }
+        }
+    }
+
+    write_all(writer, &[TAG_END]).await?;
+    writer
+        .flush()
+        .await
+        .map_err(|e| DaosError::io_error(format!("archive flush failed: {}", e)))?;
+    Ok(stats)
+}
+
+// Opens the archived oid read-write in cont and writes every record of the
+// archive to it. Records land on top of what the object already holds, so
+// punch it first for an exact copy. The object must have been created in
+// cont with a compatible class, e.g. by importing into the container the
+// archive came from or one whose oids were allocated the same way. The
+// object metadata is archived like any other single value and restored
+// directly, since updates refuse its reserved akeys.
+pub async fn import_object_async<R: AsyncRead + Unpin>(
+    cont: &DaosContainer,
+    reader: &mut R,
+) -> Result<Arc<DaosObject>> {
+    let oid = read_archive_header(reader).await?;
+    let obj: Arc<DaosObject> = DaosObject::open_async(cont, oid, false).await?.into();
+    let txn = DaosTxn::txn_none();
+
+    let mut batch: Vec<DaosFanOutUpdate> = Vec::with_capacity(IMPORT_BATCH);
+    while let Some(record) = read_archive_record(reader).await? {
+        match record {
+            ArchiveRecord::Single { dkey, akey, value } if dkey == META_DKEY => {
+                obj.update_single(
+                    "import_object_async",
+                    &txn,
+                    CondFlags::empty(),
+                    &dkey,
+                    &akey,
+                    &value,
+                )
+                .await?;
+            }
+            ArchiveRecord::Single { dkey, akey, value } => {
+                batch.push((obj.clone(), dkey, akey, value));
+                if batch.len() == IMPORT_BATCH {
+                    let updates = std::mem::take(&mut batch);
+                    update_many_objects_async(updates, DaosFanOutPolicy::AllSuccess).await?;
+                }
+            }
+            ArchiveRecord::Extent {
+                dkey,
+                akey,
+                offset,
+                data,
+            } => {
+                obj.update_recx_async(&txn, CondFlags::empty(), dkey, akey, offset, &data)
+                    .await?;
+            }
+        }
+    }
+    if !batch.is_empty() {
+        update_many_objects_async(batch, DaosFanOutPolicy::AllSuccess).await?;
+    }
+    Ok(obj)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daos_obj::DaosObjSyncOps;
+    use crate::daos_obj::{
+        DAOS_OC_HINTS_NONE, DAOS_OC_UNKNOWN, DAOS_OT_MULTI_HASHED, META_CONTENT_TYPE,
+    };
+    use crate::daos_oid_allocator::DaosAsyncOidAllocator;
+    use crate::daos_pool::DaosPool;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test]
+    async fn test_archive_format() {
+        let oid = DaosObjectId { lo: 5, hi: 9 };
+        let record = ArchiveRecord::Extent {
+            dkey: b"d".to_vec(),
+            akey: b"a".to_vec(),
+            offset: 42,
+            data: vec![1, 2, 3],
+        };
+        let mut archive = encode_header(oid);
+        archive.extend(encode_record(&record));
+        archive.push(TAG_END);
+
+        let mut reader = archive.as_slice();
+        assert_eq!(read_archive_header(&mut reader).await.unwrap(), oid);
+        assert_eq!(
+            read_archive_record(&mut reader).await.unwrap(),
+            Some(record)
+        );
+        assert_eq!(read_archive_record(&mut reader).await.unwrap(), None);
+
+        let mut bad_magic = archive.clone();
+        bad_magic[0] = b'X';
+        let res = read_archive_header(&mut bad_magic.as_slice()).await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+
+        let mut truncated = &archive[..archive.len() - 3];
+        read_archive_header(&mut truncated).await.unwrap();
+        let res = read_archive_record(&mut truncated).await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+    }
+
+    #[tokio::test]
+    async fn test_export_import_object() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        cont.connect(&pool).expect("Failed to connect to container");
+        let cont = Arc::new(cont);
+
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            DAOS_OT_MULTI_HASHED,
+            DAOS_OC_UNKNOWN,
+            DAOS_OC_HINTS_NONE,
+            0,
+        )
+        .await
+        .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        let large = vec![7u8; 3 * SINGLE_BUF_SIZE];
+        obj.update_async(
+            &txn,
+            CondFlags::empty(),
+            b"d1".to_vec(),
+            b"a1".to_vec(),
+            b"small",
+        )
+        .await
+        .unwrap();
+        obj.update_async(
+            &txn,
+            CondFlags::empty(),
+            b"d1".to_vec(),
+            b"a2".to_vec(),
+            &large,
+        )
+        .await
+        .unwrap();
+        obj.update_recx_async(
+            &txn,
+            CondFlags::empty(),
+            b"d2".to_vec(),
+            b"arr".to_vec(),
+            16,
+            b"extent",
+        )
+        .await
+        .unwrap();
+        obj.set_meta_async(&txn, META_CONTENT_TYPE, b"text/plain")
+            .await
+            .unwrap();
+
+        let mut archive = Vec::new();
+        let stats = export_object_async(&obj, &mut archive).await.unwrap();
+        assert_eq!(stats.dkeys, 3);
+        assert_eq!(stats.single_values, 3);
+        assert_eq!(stats.extents, 1);
+        assert_eq!(stats.bytes, (5 + large.len() + 6 + 10) as u64);
+
+        obj.punch(&txn).unwrap();
+        let restored = import_object_async(cont.as_ref(), &mut archive.as_slice())
+            .await
+            .unwrap();
+        assert_eq!(restored.oid, obj.oid);
+
+        let mut buf = vec![0u8; large.len()];
+        let size = restored
+            .fetch_async(
+                &txn,
+                CondFlags::empty(),
+                b"d1".to_vec(),
+                b"a2".to_vec(),
+                &mut buf,
+            )
+            .await
+            .unwrap();
+        assert_eq!(&buf[..size], large.as_slice());
+        let mut buf = vec![0u8; 6];
+        restored
+            .fetch_recx_async(
+                &txn,
+                CondFlags::empty(),
+                b"d2".to_vec(),
+                b"arr".to_vec(),
+                16,
+                &mut buf,
+            )
+            .await
+            .unwrap();
+        assert_eq!(&buf, b"extent");
+        let content_type = restored
+            .get_meta_async(&txn, META_CONTENT_TYPE)
+            .await
+            .unwrap();
+        assert_eq!(content_type.as_deref(), Some(b"text/plain".as_slice()));
+
+        let mut again = Vec::new();
+        export_object_async(&restored, &mut again).await.unwrap();
+        assert_eq!(again, archive);
+    }
+}
@@ -0,0 +1,214 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Client side compression of single values, for containers without server
+// side compression. A compressed value starts with a header:
+//   magic (8 bytes), codec (1 byte), original size (u32 little endian),
+//   FNV-1a hash of the bytes after the header (u32 little endian)
+// Values that don't shrink are stored with CODEC_RAW instead, so a stored
+// value is never more than HEADER_LEN larger than the original. Values
+// written without compression may start with anything, so a header only
+// counts when both the magic and the hash match, anything else is read
+// back as it is.
+
+use crate::error::{DaosError, Result};
+
+pub(crate) const HEADER_LEN: usize = 17;
+
+const MAGIC: [u8; 8] = [0xda, 0x05, 0xc0, 0x3e, 0x9a, 0x71, 0x0c, 0x52];
+const CODEC_RAW: u8 = 0;
+const CODEC_LZ4: u8 = 1;
+const CODEC_ZSTD: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaosCompression {
+    #[cfg(feature = "lz4")]
+    Lz4,
+    // zstd level, 0 picks the library default
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
+}
+
+impl DaosCompression {
+    fn codec(&self) -> u8 {
+        match *self {
+            #[cfg(feature = "lz4")]
+            DaosCompression::Lz4 => CODEC_LZ4,
+            #[cfg(feature = "zstd")]
+            DaosCompression::Zstd(_) => CODEC_ZSTD,
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "lz4", feature = "zstd")), allow(unused_variables))]
+    fn compress(&self, data: &[u8]) -> Option<Vec<u8>> {
+        match *self {
+            #[cfg(feature = "lz4")]
+            DaosCompression::Lz4 => Some(lz4_flex::block::compress(data)),
+            #[cfg(feature = "zstd")]
+            DaosCompression::Zstd(level) => zstd::bulk::compress(data, level).ok(),
+        }
+    }
+}
+
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn with_header(codec: u8, len: usize, payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + payload.len());
+    out.extend_from_slice(&MAGIC);
+    out.push(codec);
+    out.extend_from_slice(&(len as u32).to_le_bytes());
+    out.extend_from_slice(&fnv1a(payload).to_le_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+// The codec, original size and payload of stored, None unless it starts
+// with a header written by encode().
+fn parse_header(stored: &[u8]) -> Option<(u8, usize, &[u8])> {
+    if stored.len() < HEADER_LEN || stored[..MAGIC.len()] != MAGIC {
+        return None;
+    }
+    let codec = stored[MAGIC.len()];
+    let len = u32::from_le_bytes(stored[9..13].try_into().unwrap()) as usize;
+    let hash = u32::from_le_bytes(stored[13..HEADER_LEN].try_into().unwrap());
+    let payload = &stored[HEADER_LEN..];
+    if fnv1a(payload) != hash || (codec == CODEC_RAW && len != payload.len()) {
+        return None;
+    }
+    Some((codec, len, payload))
+}
+
+pub(crate) fn encode(compression: DaosCompression, data: &[u8]) -> Vec<u8> {
+    // the header can't describe larger values
+    if data.len() <= u32::MAX as usize {
+        if let Some(compressed) = compression.compress(data) {
+            if compressed.len() < data.len() {
+                return with_header(compression.codec(), data.len(), &compressed);
+            }
+        }
+    }
+    with_header(CODEC_RAW, data.len(), data)
+}
+
+// Writes the original value of stored into out and returns its size.
+// Stored bytes without a valid header are taken as an uncompressed value.
+pub(crate) fn decode(stored: &[u8], out: &mut [u8]) -> Result<usize> {
+    let (codec, len, payload) = parse_header(stored).unwrap_or((CODEC_RAW, stored.len(), stored));
+    if len > out.len() {
+        return Err(DaosError::rec2big("record is larger than the buffer", len));
+    }
+
+    let out = &mut out[..len];
+    let res: std::result::Result<usize, String> = match codec {
+        CODEC_RAW => {
+            out.copy_from_slice(payload);
+            Ok(len)
+        }
+        #[cfg(feature = "lz4")]
+        CODEC_LZ4 => lz4_flex::block::decompress_into(payload, out).map_err(|e| e.to_string()),
+        #[cfg(feature = "zstd")]
+        CODEC_ZSTD => zstd::bulk::decompress_to_buffer(payload, out).map_err(|e| e.to_string()),
+        #[cfg(not(feature = "lz4"))]
+        CODEC_LZ4 => return Err(DaosError::no_sys("value is lz4 compressed, enable lz4")),
+        #[cfg(not(feature = "zstd"))]
+        CODEC_ZSTD => return Err(DaosError::no_sys("value is zstd compressed, enable zstd")),
+        _ => return Err(DaosError::inval("unknown value compression codec")),
+    };
+    match res {
+        Ok(size) if size == len => Ok(len),
+        Ok(_) => Err(DaosError::inval("compressed value has the wrong size")),
+        Err(e) => Err(DaosError::inval(format!(
+            "compressed value is corrupt: {}",
+            e
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_plain() {
+        let mut out = [0u8; 8];
+        assert_eq!(decode(b"plain", &mut out).unwrap(), 5);
+        assert_eq!(&out[..5], b"plain");
+        assert!(matches!(
+            decode(b"too long value", &mut out),
+            Err(DaosError::Rec2Big {
+                required: Some(14),
+                ..
+            })
+        ));
+
+        let raw = with_header(CODEC_RAW, 3, b"abc");
+        assert_eq!(decode(&raw, &mut out).unwrap(), 3);
+        assert_eq!(&out[..3], b"abc");
+
+        let unknown = with_header(9, 3, b"abc");
+        assert!(matches!(
+            decode(&unknown, &mut out),
+            Err(DaosError::Inval(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_header_lookalike() {
+        // a plain value that happens to start with the magic is read as is
+        let mut value = MAGIC.to_vec();
+        value.extend_from_slice(&[9, 3, 0, 0, 0, 1, 2, 3, 4]);
+        value.extend_from_slice(b"abc");
+        let mut out = [0u8; 32];
+        assert_eq!(decode(&value, &mut out).unwrap(), value.len());
+        assert_eq!(&out[..value.len()], value.as_slice());
+
+        // so is a raw value whose payload changed behind the header
+        let mut stored = with_header(CODEC_RAW, 3, b"abc");
+        stored[HEADER_LEN] = b'x';
+        assert_eq!(decode(&stored, &mut out).unwrap(), stored.len());
+    }
+
+    #[cfg(any(feature = "lz4", feature = "zstd"))]
+    #[test]
+    fn test_compression_roundtrip() {
+        const CODECS: &[DaosCompression] = &[
+            #[cfg(feature = "lz4")]
+            DaosCompression::Lz4,
+            #[cfg(feature = "zstd")]
+            DaosCompression::Zstd(3),
+        ];
+
+        let value = b"abcdefgh".repeat(512);
+        for &codec in CODECS {
+            let stored = encode(codec, &value);
+            assert!(stored.len() < value.len());
+            let mut out = vec![0u8; value.len()];
+            assert_eq!(decode(&stored, &mut out).unwrap(), value.len());
+            assert_eq!(out, value);
+            assert!(decode(&stored, &mut out[..10]).is_err());
+
+            // incompressible values are stored raw behind the header
+            let stored = encode(codec, b"xy");
+            assert_eq!(stored.len(), HEADER_LEN + 2);
+            assert_eq!(decode(&stored, &mut out).unwrap(), 2);
+        }
+    }
+}
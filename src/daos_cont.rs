@@ -15,16 +15,48 @@
  *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::daos_event::*;
+use crate::bindings;
 use crate::bindings::{
-    daos_cont_close, daos_cont_open2, daos_cont_props_DAOS_PROP_CO_ROOTS, daos_cont_query, daos_prop_alloc, daos_prop_co_roots, daos_prop_entry_get,
-    daos_prop_free, daos_prop_t, DAOS_COO_RW,
+    d_iov_t, daos_anchor_is_eof, daos_anchor_t, daos_anchor_type_t_DAOS_ANCHOR_TYPE_EOF,
+    daos_anchor_type_t_DAOS_ANCHOR_TYPE_ZERO, daos_cont_aggregate, daos_cont_alloc_oids,
+    daos_cont_close, daos_cont_create_snap, daos_cont_create_snap_opt, daos_cont_create_with_label,
+    daos_cont_destroy, daos_cont_destroy_snap, daos_cont_global2local, daos_cont_info_t,
+    daos_cont_list_snap, daos_cont_local2global, daos_cont_open2,
+    daos_cont_props_DAOS_PROP_CO_COMPRESS, daos_cont_props_DAOS_PROP_CO_CSUM,
+    daos_cont_props_DAOS_PROP_CO_DEDUP, daos_cont_props_DAOS_PROP_CO_EC_CELL_SZ,
+    daos_cont_props_DAOS_PROP_CO_REDUN_FAC, daos_cont_props_DAOS_PROP_CO_ROOTS, daos_cont_query,
+    daos_cont_set_prop, daos_epoch_range_t, daos_obj_generate_oid2, daos_prop_alloc,
+    daos_prop_co_roots, daos_prop_entry_get, daos_prop_entry_set_ptr, daos_prop_free, daos_prop_t,
+    daos_snapshot_opts_DAOS_SNAP_OPT_CR, daos_snapshot_opts_DAOS_SNAP_OPT_OIT, DAOS_ANCHOR_BUF_MAX,
+    DAOS_COO_RW,
 };
-use crate::daos_pool::{DaosHandle, DaosObjectId, DaosPool};
-use std::ffi::CString;
+use crate::daos_event::*;
+use crate::daos_obj::{DaosObject, DaosObjectOpenOpts};
+use crate::daos_oid_allocator::DaosOidAllocatorConfig;
+use crate::daos_pool::{DaosHandle, DaosObjectId, DaosPool, HandleFmt};
+use crate::daos_runtime::DaosRuntime;
+use crate::error::{DaosError, Result};
+use std::ffi::{CStr, CString};
+use std::fmt;
 use std::future::Future;
-use std::io::{Error, ErrorKind, Result};
+use std::mem;
+use std::ops::Range;
 use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+// Container property types for DaosProperty::with_types and get_value.
+pub const DAOS_PROP_CO_LABEL: u32 = crate::bindings::daos_cont_props_DAOS_PROP_CO_LABEL;
+pub const DAOS_PROP_CO_CSUM: u32 = crate::bindings::daos_cont_props_DAOS_PROP_CO_CSUM;
+pub const DAOS_PROP_CO_REDUN_FAC: u32 = crate::bindings::daos_cont_props_DAOS_PROP_CO_REDUN_FAC;
+pub const DAOS_PROP_CO_COMPRESS: u32 = crate::bindings::daos_cont_props_DAOS_PROP_CO_COMPRESS;
+pub const DAOS_PROP_CO_DEDUP: u32 = crate::bindings::daos_cont_props_DAOS_PROP_CO_DEDUP;
+pub const DAOS_PROP_CO_EC_CELL_SZ: u32 = crate::bindings::daos_cont_props_DAOS_PROP_CO_EC_CELL_SZ;
+pub const DAOS_PROP_CO_STATUS: u32 = crate::bindings::daos_cont_props_DAOS_PROP_CO_STATUS;
+pub const DAOS_PROP_CO_ROOTS: u32 = crate::bindings::daos_cont_props_DAOS_PROP_CO_ROOTS;
+// Number of root objects a container's CO_ROOTS holds.
+pub const DAOS_CO_ROOTS_NR: usize = 4;
 
 #[derive(Debug)]
 pub struct DaosProperty {
@@ -35,18 +67,95 @@ unsafe impl Send for DaosProperty {}
 
 impl DaosProperty {
     fn new() -> Result<Self> {
-        let prop = unsafe { daos_prop_alloc(1) };
-        if !prop.is_null() {
-            unsafe { (*(*prop).dpp_entries).dpe_type = daos_cont_props_DAOS_PROP_CO_ROOTS; }
-            Ok(DaosProperty {
-                raw_prop: Some(prop),
-            })
-        } else {
-            Err(Error::new(
-                ErrorKind::Other,
-                "Failed to allocate DAOS property",
-            ))
+        Self::with_types(&[DAOS_PROP_CO_ROOTS])
+    }
+
+    pub fn with_co_roots(roots: &[DaosObjectId; 4]) -> Result<Self> {
+        let prop = DaosProperty::new()?;
+        let raw_roots = daos_prop_co_roots { cr_oids: *roots };
+        let ret = unsafe {
+            daos_prop_entry_set_ptr(
+                (*prop.raw_prop.unwrap()).dpp_entries,
+                &raw_roots as *const daos_prop_co_roots as *const std::os::raw::c_void,
+                mem::size_of::<daos_prop_co_roots>() as u64,
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_prop_entry_set_ptr",
+                ret,
+                "Failed to set CO roots prop entry",
+            ));
+        }
+        Ok(prop)
+    }
+
+    // An empty entry of each DAOS_PROP_CO_* type in types, for
+    // query_props to fill in one call.
+    pub fn with_types(types: &[u32]) -> Result<Self> {
+        if types.is_empty() {
+            return Err(DaosError::inval("no property types to query"));
+        }
+        let raw = unsafe { daos_prop_alloc(types.len() as u32) };
+        if raw.is_null() {
+            return Err(DaosError::no_mem("Failed to allocate DAOS property"));
+        }
+        for (idx, ty) in types.iter().enumerate() {
+            unsafe { (*(*raw).dpp_entries.add(idx)).dpe_type = *ty };
+        }
+        Ok(DaosProperty {
+            raw_prop: Some(raw),
+        })
+    }
+
+    // The raw value of the entry of type ty, for the types filled as a
+    // number.
+    pub fn get_value(&self, ty: u32) -> Result<u64> {
+        let entry = unsafe { daos_prop_entry_get(self.raw_prop.unwrap(), ty) };
+        if entry.is_null() {
+            return Err(DaosError::non_exist(format!(
+                "no entry of prop type {}",
+                ty
+            )));
+        }
+        Ok(unsafe { (*entry).__bindgen_anon_1.dpe_val })
+    }
+
+    fn with_value(ty: u32, val: u64) -> Result<Self> {
+        let prop = Self::with_types(&[ty])?;
+        unsafe {
+            (*(*prop.raw_prop.unwrap()).dpp_entries)
+                .__bindgen_anon_1
+                .dpe_val = val
+        };
+        Ok(prop)
+    }
+
+    pub fn get_label(&self) -> Result<String> {
+        let entry = unsafe { daos_prop_entry_get(self.raw_prop.unwrap(), DAOS_PROP_CO_LABEL) };
+        if entry.is_null() {
+            return Err(DaosError::non_exist("no label prop entry"));
+        }
+        let label = unsafe { (*entry).__bindgen_anon_1.dpe_str };
+        if label.is_null() {
+            return Err(DaosError::non_exist("empty label in the prop entry"));
         }
+        unsafe { CStr::from_ptr(label) }
+            .to_str()
+            .map(|label| label.to_string())
+            .map_err(|_| DaosError::inval("container label is not utf-8"))
+    }
+
+    pub fn get_checksum(&self) -> Result<ContChecksum> {
+        ContChecksum::from_raw(self.get_value(DAOS_PROP_CO_CSUM)?)
+    }
+
+    pub fn get_redundancy_factor(&self) -> Result<u32> {
+        Ok(self.get_value(DAOS_PROP_CO_REDUN_FAC)? as u32)
+    }
+
+    pub fn get_status(&self) -> Result<ContStatus> {
+        ContStatus::from_raw(self.get_value(DAOS_PROP_CO_STATUS)?)
     }
 
     pub fn get_co_roots(&self) -> Result<Box<[DaosObjectId; 4]>> {
@@ -57,24 +166,341 @@ impl DaosProperty {
             )
         };
         if entry.is_null() {
-            return Err(Error::new(
-                ErrorKind::Other,
-                "Failed to get a CO roots prop entry",
-            ));
+            return Err(DaosError::non_exist("Failed to get a CO roots prop entry"));
         }
 
         let raw_roots = unsafe { (*entry).__bindgen_anon_1.dpe_val_ptr as *mut daos_prop_co_roots };
 
         if raw_roots.is_null() {
-            return Err(Error::new(
-                ErrorKind::Other,
-                "empty CO roots in the prop entry",
-            ));
+            return Err(DaosError::non_exist("empty CO roots in the prop entry"));
         }
 
         let roots = Box::new(unsafe { (*raw_roots).cr_oids });
         Ok(roots)
     }
+
+    // Root idx of CO_ROOTS, failing with NonExist while it is unset.
+    pub fn get_root(&self, idx: usize) -> Result<DaosObjectId> {
+        check_root_idx(idx)?;
+        let oid = self.get_co_roots()?[idx];
+        if oid.lo == 0 && oid.hi == 0 {
+            return Err(DaosError::non_exist(format!("root {} is not set", idx)));
+        }
+        Ok(oid)
+    }
+}
+
+fn check_root_idx(idx: usize) -> Result<()> {
+    if idx >= DAOS_CO_ROOTS_NR {
+        return Err(DaosError::inval(format!(
+            "root {} is out of range, containers have {} roots",
+            idx, DAOS_CO_ROOTS_NR
+        )));
+    }
+    Ok(())
+}
+
+// The roots of prop with root idx replaced by oid, unset roots staying
+// zero.
+fn replace_root(
+    prop: &DaosProperty,
+    idx: usize,
+    oid: DaosObjectId,
+) -> Result<[DaosObjectId; DAOS_CO_ROOTS_NR]> {
+    let mut roots = match prop.get_co_roots() {
+        Ok(roots) => *roots,
+        Err(DaosError::NonExist(_)) => [DaosObjectId { lo: 0, hi: 0 }; DAOS_CO_ROOTS_NR],
+        Err(e) => return Err(e),
+    };
+    roots[idx] = oid;
+    Ok(roots)
+}
+
+// Server side checksum of the data written to a container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContChecksum {
+    Off,
+    Crc16,
+    Crc32,
+    Crc64,
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl ContChecksum {
+    fn from_raw(val: u64) -> Result<Self> {
+        let checksum = match val as u32 {
+            bindings::DAOS_PROP_CO_CSUM_OFF => ContChecksum::Off,
+            bindings::DAOS_PROP_CO_CSUM_CRC16 => ContChecksum::Crc16,
+            bindings::DAOS_PROP_CO_CSUM_CRC32 => ContChecksum::Crc32,
+            bindings::DAOS_PROP_CO_CSUM_CRC64 => ContChecksum::Crc64,
+            bindings::DAOS_PROP_CO_CSUM_SHA1 => ContChecksum::Sha1,
+            bindings::DAOS_PROP_CO_CSUM_SHA256 => ContChecksum::Sha256,
+            bindings::DAOS_PROP_CO_CSUM_SHA512 => ContChecksum::Sha512,
+            other => {
+                return Err(DaosError::inval(format!(
+                    "unknown container checksum type {}",
+                    other
+                )))
+            }
+        };
+        Ok(checksum)
+    }
+
+    fn to_raw(self) -> u64 {
+        (match self {
+            ContChecksum::Off => bindings::DAOS_PROP_CO_CSUM_OFF,
+            ContChecksum::Crc16 => bindings::DAOS_PROP_CO_CSUM_CRC16,
+            ContChecksum::Crc32 => bindings::DAOS_PROP_CO_CSUM_CRC32,
+            ContChecksum::Crc64 => bindings::DAOS_PROP_CO_CSUM_CRC64,
+            ContChecksum::Sha1 => bindings::DAOS_PROP_CO_CSUM_SHA1,
+            ContChecksum::Sha256 => bindings::DAOS_PROP_CO_CSUM_SHA256,
+            ContChecksum::Sha512 => bindings::DAOS_PROP_CO_CSUM_SHA512,
+        }) as u64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContHealth {
+    Healthy,
+    // lost data beyond its redundancy factor, reads may fail
+    Unclean,
+}
+
+// DAOS_PROP_CO_STATUS, packed by libdaos as the health above the pool map
+// version it was set at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContStatus {
+    pub health: ContHealth,
+    pub pool_map_version: u32,
+}
+
+impl ContStatus {
+    // Marking a container healthy takes DAOS_PROP_CO_CLEAR in the flag
+    // bits above the health, as DAOS_PROP_CO_STATUS_VAL packs it. The
+    // service fills in the pool map version itself.
+    fn healthy_raw() -> u64 {
+        ((bindings::DAOS_PROP_CO_CLEAR as u64) << 48)
+            | ((bindings::DAOS_PROP_CO_HEALTHY as u64) << 32)
+    }
+
+    fn from_raw(val: u64) -> Result<Self> {
+        let health = match ((val >> 32) & 0xffff) as u32 {
+            bindings::DAOS_PROP_CO_HEALTHY => ContHealth::Healthy,
+            bindings::DAOS_PROP_CO_UNCLEAN => ContHealth::Unclean,
+            other => {
+                return Err(DaosError::inval(format!(
+                    "unknown container status {}",
+                    other
+                )))
+            }
+        };
+        Ok(ContStatus {
+            health,
+            pool_map_version: val as u32,
+        })
+    }
+}
+
+// Server side compression, unlike DaosCompression which runs in the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContCompression {
+    Off,
+    Lz4,
+    Deflate,
+}
+
+impl ContCompression {
+    fn to_raw(self) -> u64 {
+        (match self {
+            ContCompression::Off => bindings::DAOS_PROP_CO_COMPRESS_OFF,
+            ContCompression::Lz4 => bindings::DAOS_PROP_CO_COMPRESS_LZ4,
+            ContCompression::Deflate => bindings::DAOS_PROP_CO_COMPRESS_DEFLATE,
+        }) as u64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContDedup {
+    Off,
+    // duplicates found by hash are compared byte by byte
+    Memcmp,
+    Hash,
+}
+
+impl ContDedup {
+    fn to_raw(self) -> u64 {
+        (match self {
+            ContDedup::Off => bindings::DAOS_PROP_CO_DEDUP_OFF,
+            ContDedup::Memcmp => bindings::DAOS_PROP_CO_DEDUP_MEMCMP,
+            ContDedup::Hash => bindings::DAOS_PROP_CO_DEDUP_HASH,
+        }) as u64
+    }
+}
+
+// DAOS_PROP_MIN_EC_CELL_SZ and DAOS_PROP_MAX_EC_CELL_SZ
+const EC_CELL_MIN: u32 = 1 << 10;
+const EC_CELL_MAX: u32 = 1 << 30;
+const REDUN_FAC_MAX: u32 = 4;
+
+// Properties given to DaosContainer::create. The ones left unset take the
+// pool's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DaosContProps {
+    redun_fac: Option<u32>,
+    ec_cell_size: Option<u32>,
+    checksum: Option<ContChecksum>,
+    compression: Option<ContCompression>,
+    dedup: Option<ContDedup>,
+    roots: Option<[DaosObjectId; 4]>,
+    // an out of range index given to root(), reported by validate
+    bad_root: Option<usize>,
+}
+
+impl DaosContProps {
+    pub fn new() -> Self {
+        DaosContProps::default()
+    }
+
+    // Engine failures the container survives without losing data, 0 to 4.
+    // Objects of a class with less redundancy can't be created in it.
+    pub fn redundancy_factor(mut self, rf: u32) -> Self {
+        self.redun_fac = Some(rf);
+        self
+    }
+
+    // Bytes per EC cell, a multiple of 1 KiB up to 1 GiB.
+    pub fn ec_cell_size(mut self, bytes: u32) -> Self {
+        self.ec_cell_size = Some(bytes);
+        self
+    }
+
+    pub fn checksum(mut self, checksum: ContChecksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    pub fn compression(mut self, compression: ContCompression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn dedup(mut self, dedup: ContDedup) -> Self {
+        self.dedup = Some(dedup);
+        self
+    }
+
+    // Like init_roots_async, but set when the container is created.
+    pub fn co_roots(mut self, roots: &[DaosObjectId; 4]) -> Self {
+        self.roots = Some(*roots);
+        self
+    }
+
+    // Sets root idx alone, the other roots left zero unless set too.
+    pub fn root(mut self, idx: usize, oid: DaosObjectId) -> Self {
+        if idx >= DAOS_CO_ROOTS_NR {
+            self.bad_root = Some(idx);
+            return self;
+        }
+        let roots = self
+            .roots
+            .get_or_insert([DaosObjectId { lo: 0, hi: 0 }; DAOS_CO_ROOTS_NR]);
+        roots[idx] = oid;
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        if let Some(idx) = self.bad_root {
+            check_root_idx(idx)?;
+        }
+        if let Some(rf) = self.redun_fac {
+            if rf > REDUN_FAC_MAX {
+                return Err(DaosError::inval(format!(
+                    "redundancy factor {} is above {}",
+                    rf, REDUN_FAC_MAX
+                )));
+            }
+        }
+        if let Some(size) = self.ec_cell_size {
+            if !(EC_CELL_MIN..=EC_CELL_MAX).contains(&size) || size % EC_CELL_MIN != 0 {
+                return Err(DaosError::inval(format!(
+                    "EC cell size {} is not a multiple of 1 KiB up to 1 GiB",
+                    size
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn to_prop(&self) -> Result<Option<DaosProperty>> {
+        self.validate()?;
+        let values: Vec<(u32, u64)> = [
+            (
+                daos_cont_props_DAOS_PROP_CO_REDUN_FAC,
+                self.redun_fac.map(u64::from),
+            ),
+            (
+                daos_cont_props_DAOS_PROP_CO_EC_CELL_SZ,
+                self.ec_cell_size.map(u64::from),
+            ),
+            (
+                daos_cont_props_DAOS_PROP_CO_CSUM,
+                self.checksum.map(ContChecksum::to_raw),
+            ),
+            (
+                daos_cont_props_DAOS_PROP_CO_COMPRESS,
+                self.compression.map(ContCompression::to_raw),
+            ),
+            (
+                daos_cont_props_DAOS_PROP_CO_DEDUP,
+                self.dedup.map(ContDedup::to_raw),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(ty, val)| val.map(|val| (ty, val)))
+        .collect();
+        let nr = values.len() + self.roots.is_some() as usize;
+        if nr == 0 {
+            return Ok(None);
+        }
+
+        let raw = unsafe { daos_prop_alloc(nr as u32) };
+        if raw.is_null() {
+            return Err(DaosError::no_mem("Failed to allocate DAOS property"));
+        }
+        // freed by the drop from here on
+        let prop = DaosProperty {
+            raw_prop: Some(raw),
+        };
+        for (idx, (ty, val)) in values.iter().enumerate() {
+            unsafe {
+                let entry = (*raw).dpp_entries.add(idx);
+                (*entry).dpe_type = *ty;
+                (*entry).__bindgen_anon_1.dpe_val = *val;
+            }
+        }
+        if let Some(roots) = self.roots {
+            let raw_roots = daos_prop_co_roots { cr_oids: roots };
+            let ret = unsafe {
+                let entry = (*raw).dpp_entries.add(values.len());
+                (*entry).dpe_type = daos_cont_props_DAOS_PROP_CO_ROOTS;
+                daos_prop_entry_set_ptr(
+                    entry,
+                    &raw_roots as *const daos_prop_co_roots as *const std::os::raw::c_void,
+                    mem::size_of::<daos_prop_co_roots>() as u64,
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_prop_entry_set_ptr",
+                    ret,
+                    "Failed to set CO roots prop entry",
+                ));
+            }
+        }
+        Ok(Some(prop))
+    }
 }
 
 impl Drop for DaosProperty {
@@ -87,19 +513,263 @@ impl Drop for DaosProperty {
     }
 }
 
+const MIN_SNAP_EPOCHS: usize = 16;
+
+// Epochs of the container's snapshots, filled one page per call to
+// list_snapshot_async and resumable from its anchor like DaosKeyList.
+#[derive(Debug)]
+pub struct DaosSnapshotList {
+    anchor: Box<daos_anchor_t>,
+    epochs: Vec<u64>,
+    nr: usize,
+}
+
+impl DaosSnapshotList {
+    pub fn new() -> Box<Self> {
+        Self::with_capacity(MIN_SNAP_EPOCHS)
+    }
+
+    // Capacity is only the initial page size, a page grows to whatever
+    // libdaos reports instead of dropping snapshots.
+    pub fn with_capacity(capacity: usize) -> Box<Self> {
+        Box::new(DaosSnapshotList {
+            anchor: Box::new(daos_anchor_t {
+                da_type: 0,
+                da_shard: 0,
+                da_flags: 0,
+                da_sub_anchors: 0,
+                da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
+            }),
+            epochs: vec![0; capacity.max(1)],
+            nr: 0,
+        })
+    }
+
+    // Rewind to the first snapshot so the list can be reused.
+    pub fn reset(&mut self) {
+        *self.anchor = daos_anchor_t {
+            da_type: 0,
+            da_shard: 0,
+            da_flags: 0,
+            da_sub_anchors: 0,
+            da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
+        };
+        self.nr = 0;
+    }
+
+    pub fn reach_end(&self) -> bool {
+        daos_anchor_is_eof(self.anchor.as_ref())
+    }
+
+    // Epochs of the last page.
+    pub fn epochs(&self) -> &[u64] {
+        &self.epochs[..self.nr]
+    }
+}
+
+async fn list_snap_page(
+    cont_hdl: Option<DaosHandle>,
+    eq: Option<DaosEventQueue>,
+    deadline: Option<Duration>,
+    snap_lst: Box<DaosSnapshotList>,
+) -> Result<Box<DaosSnapshotList>> {
+    let cont_hdl = cont_hdl.ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+    let eq = eq.ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+
+    let mut snap_lst = snap_lst;
+    if snap_lst.reach_end() {
+        snap_lst.nr = 0;
+        return Ok(snap_lst);
+    }
+
+    let start = *snap_lst.anchor;
+    loop {
+        let mut event = eq.create_event()?;
+        let rx = event.register_callback()?;
+
+        let mut nr = snap_lst.epochs.len() as i32;
+        let ret = unsafe {
+            daos_cont_list_snap(
+                cont_hdl,
+                &mut nr,
+                snap_lst.epochs.as_mut_ptr(),
+                ptr::null_mut(),
+                snap_lst.anchor.as_mut(),
+                event.as_mut(),
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_list_snap",
+                ret,
+                "Failed to list DAOS container snapshots",
+            ));
+        }
+        let ret = event.wait(rx, deadline).await?;
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_list_snap",
+                ret,
+                "async list container snapshots failed",
+            ));
+        }
+
+        // nr comes back as the number of snapshots available, which may be
+        // more than fit, so grow the page and ask for it again
+        let nr = nr.max(0) as usize;
+        if nr > snap_lst.epochs.len() {
+            snap_lst.epochs.resize(nr, 0);
+            *snap_lst.anchor = start;
+            continue;
+        }
+        snap_lst.nr = nr;
+
+        // libdaos versions that hand out every snapshot in one go leave
+        // the anchor untouched
+        if snap_lst.anchor.da_type as u32 == daos_anchor_type_t_DAOS_ANCHOR_TYPE_ZERO {
+            snap_lst.anchor.da_type = daos_anchor_type_t_DAOS_ANCHOR_TYPE_EOF as u16;
+        }
+        return Ok(snap_lst);
+    }
+}
+
+// What daos_cont_query reports about a container. The md times are hybrid
+// logical clock stamps taken by the container service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContainerInfo {
+    pub uuid: [u8; 16],
+    // epoch of the latest snapshot, 0 without snapshots
+    pub latest_snapshot: u64,
+    pub snapshots: u32,
+    // handles open on the container, from every client
+    pub handles: u32,
+    pub md_open_time: u64,
+    pub md_modify_time: u64,
+    pub redundancy_factor: u32,
+}
+
+impl ContainerInfo {
+    // daos_cont_info_t doesn't carry the redundancy factor, it is queried
+    // as a property in the same call.
+    fn query_prop() -> Result<DaosProperty> {
+        DaosProperty::with_types(&[DAOS_PROP_CO_REDUN_FAC])
+    }
+
+    fn from_raw(info: &daos_cont_info_t, prop: &DaosProperty) -> Result<Self> {
+        Ok(ContainerInfo {
+            uuid: info.ci_uuid,
+            latest_snapshot: info.ci_lsnapshot,
+            snapshots: info.ci_nsnapshots,
+            handles: info.ci_nhandles,
+            md_open_time: info.ci_md_otime,
+            md_modify_time: info.ci_md_mtime,
+            redundancy_factor: prop.get_redundancy_factor()?,
+        })
+    }
+}
+
 pub trait DaosContainerSyncOps {
+    // Queries CO_ROOTS, see query_props for other types.
     fn query_prop(&self) -> Result<DaosProperty>;
+    // Queries every DAOS_PROP_CO_* type in types in one call.
+    fn query_props(&self, types: &[u32]) -> Result<DaosProperty>;
+    fn query_info(&self) -> Result<ContainerInfo>;
+    // Reserves count object ids with the container service and returns
+    // them as a range of oid.lo values, to be passed through
+    // daos_obj_generate_oid2 before use.
+    fn alloc_oids(&self, count: u64) -> Result<Range<u64>>;
+    fn query_status(&self) -> Result<ContStatus>;
+    // Marks an Unclean container Healthy again, once whatever made it
+    // Unclean, e.g. a failed writer or a lost fault domain, has been dealt
+    // with.
+    fn clear_status(&self) -> Result<()>;
+    fn set_prop(&self, prop: &DaosProperty) -> Result<()>;
+    // Root idx of CO_ROOTS, NonExist while it is unset.
+    fn query_root(&self, idx: usize) -> Result<DaosObjectId>;
+    // Replaces root idx, keeping the other three. The roots are read and
+    // written back in two calls, so concurrent set_root calls on the same
+    // container can lose one another's update.
+    fn set_root(&self, idx: usize, oid: DaosObjectId) -> Result<()>;
 }
 
 pub trait DaosContainerAsyncOps {
     fn query_prop_async(&self) -> impl Future<Output = Result<DaosProperty>> + Send + 'static;
+    fn query_props_async(
+        &self,
+        types: &[u32],
+    ) -> impl Future<Output = Result<DaosProperty>> + Send + 'static;
+    fn query_info_async(&self) -> impl Future<Output = Result<ContainerInfo>> + Send + 'static;
+    fn alloc_oids_async(
+        &self,
+        count: u64,
+    ) -> impl Future<Output = Result<Range<u64>>> + Send + 'static;
+    fn query_status_async(&self) -> impl Future<Output = Result<ContStatus>> + Send + 'static;
+    fn clear_status_async(&self) -> impl Future<Output = Result<()>> + Send + 'static;
+    fn set_prop_async(
+        &self,
+        prop: DaosProperty,
+    ) -> impl Future<Output = Result<()>> + Send + 'static;
+    fn query_root_async(
+        &self,
+        idx: usize,
+    ) -> impl Future<Output = Result<DaosObjectId>> + Send + 'static;
+    // Async DaosContainerSyncOps::set_root, with the same caveat about
+    // concurrent updates. The future owns a clone of the container.
+    fn set_root_async(
+        self: &Arc<Self>,
+        idx: usize,
+        oid: DaosObjectId,
+    ) -> impl Future<Output = Result<()>> + Send + 'static;
+    // Takes a snapshot of the container and returns its epoch.
+    fn create_snapshot_async(&self) -> impl Future<Output = Result<u64>> + Send + 'static;
+    // A snapshot that also records the container's object index table, for
+    // listing its objects with DaosOit.
+    fn create_oit_snapshot_async(&self) -> impl Future<Output = Result<u64>> + Send + 'static;
+    fn destroy_snapshot_async(
+        &self,
+        epoch: u64,
+    ) -> impl Future<Output = Result<()>> + Send + 'static;
+    // Fetches the next page of snapshots into snap_lst, for listings too
+    // long to hold at once. Call until snap_lst.reach_end().
+    fn list_snapshot_async(
+        &self,
+        snap_lst: Box<DaosSnapshotList>,
+    ) -> impl Future<Output = Result<Box<DaosSnapshotList>>> + Send + 'static;
+    // Epochs of every snapshot, oldest first.
+    fn list_snapshots_async(&self) -> impl Future<Output = Result<Vec<u64>>> + Send + 'static;
+    // Asks the servers to aggregate the container's epochs up to epoch now
+    // instead of waiting for background aggregation, merging the versions
+    // no snapshot refers to any more. Fails with NoSys on servers that
+    // don't support it.
+    fn aggregate_async(&self, epoch: u64) -> impl Future<Output = Result<()>> + Send + 'static;
+}
+
+// Deadlines for async operations, None meaning no deadline. A container's
+// deadlines are inherited by the objects and transactions opened from it,
+// which can override them with their own set_deadlines(). with_timeout()
+// bounds the operations of a single call instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DaosDeadlines {
+    // open, punch, enumeration, container and transaction calls
+    pub metadata: Option<Duration>,
+    // fetch and update
+    pub bulk: Option<Duration>,
 }
 
-#[derive(Debug)]
 pub struct DaosContainer {
     pub label: String,
     handle: Option<DaosHandle>,
-    event_queue: Option<DaosEventQueue>,
+    // one unless DaosEventQueueConfig::queues asks for more
+    event_queues: Vec<DaosEventQueue>,
+    // the next queue get_event_queue hands out
+    next_queue: AtomicUsize,
+    // false when the queue is shared with others or left out on purpose,
+    // connect() then doesn't create one
+    own_event_queue: bool,
+    event_queue_config: DaosEventQueueConfig,
+    deadlines: DaosDeadlines,
+    // the pool's, taken on connect so the container can outlive it
+    runtime: Option<DaosRuntime>,
 }
 
 impl DaosContainer {
@@ -107,16 +777,60 @@ impl DaosContainer {
         DaosContainer {
             label: label.to_string(),
             handle: None,
-            event_queue: None,
+            event_queues: Vec::new(),
+            next_queue: AtomicUsize::new(0),
+            own_event_queue: true,
+            event_queue_config: DaosEventQueueConfig::default(),
+            deadlines: DaosDeadlines::default(),
+            runtime: None,
         }
     }
 
+    // A container whose async operations complete on eq, e.g. the pool's
+    // or one shared by many containers, instead of a queue and polling
+    // thread of its own.
+    pub fn with_event_queue(label: &str, eq: DaosEventQueue) -> Self {
+        let mut cont = DaosContainer::new(label);
+        cont.event_queues.push(eq);
+        cont.own_event_queue = false;
+        cont
+    }
+
+    // A container for sync operations only, its async ones fail with
+    // NoHandle.
+    pub fn without_event_queue(label: &str) -> Self {
+        let mut cont = DaosContainer::new(label);
+        cont.own_event_queue = false;
+        cont
+    }
+
+    // Takes effect for the event queue created by the next connect(), not
+    // for a shared one.
+    pub fn set_event_queue_config(&mut self, config: DaosEventQueueConfig) {
+        self.event_queue_config = config;
+    }
+
+    pub fn set_deadlines(&mut self, deadlines: DaosDeadlines) {
+        self.deadlines = deadlines;
+    }
+
+    pub fn get_deadlines(&self) -> DaosDeadlines {
+        self.deadlines
+    }
+
     pub fn get_handle(&self) -> Option<DaosHandle> {
         self.handle.clone()
     }
 
     pub fn get_event_queue(&self) -> Option<&DaosEventQueue> {
-        self.event_queue.as_ref()
+        match self.event_queues.len() {
+            0 => None,
+            1 => self.event_queues.first(),
+            n => {
+                let next = self.next_queue.fetch_add(1, Ordering::Relaxed);
+                self.event_queues.get(next % n)
+            }
+        }
     }
 
     // Should not be called in async executer like tokio.
@@ -127,8 +841,9 @@ impl DaosContainer {
         }
 
         if daos_pool.get_handle().is_none() {
-            return Err(Error::new(ErrorKind::Other, "Pool is not connected"));
+            return Err(DaosError::no_handle("Pool is not connected"));
         }
+        self.check_event_queue_config()?;
 
         let c_label = CString::new(self.label.clone()).unwrap();
         let mut coh: DaosHandle = DaosHandle { cookie: 0u64 };
@@ -143,16 +858,133 @@ impl DaosContainer {
             )
         };
         if res == 0 {
-            self.handle.replace(coh);
-            self.create_eq()
+            self.attach_handle(daos_pool, coh)
         } else {
-            Err(Error::new(
-                ErrorKind::Other,
+            Err(DaosError::from_call(
+                "daos_cont_open2",
+                res,
                 "Failed to open DAOS container",
             ))
         }
     }
 
+    // connect() for async callers, the open goes through the pool's event
+    // queue instead of blocking.
+    pub async fn connect_async(&mut self, daos_pool: &DaosPool) -> Result<()> {
+        if self.handle.is_some() {
+            return Ok(());
+        }
+
+        let poh = daos_pool
+            .get_handle()
+            .ok_or_else(|| DaosError::no_handle("Pool is not connected"))?;
+        let eq = daos_pool
+            .get_event_queue()
+            .ok_or_else(|| DaosError::no_handle("Pool has no event queue"))?;
+        let c_label = CString::new(self.label.clone())
+            .map_err(|_| DaosError::inval("label contains a NUL byte"))?;
+        self.check_event_queue_config()?;
+        let mut event = eq.create_event()?;
+        let rx = event.register_callback()?;
+
+        let mut coh = Box::new(DaosHandle { cookie: 0u64 });
+        let ret = unsafe {
+            daos_cont_open2(
+                poh,
+                c_label.as_ptr(),
+                DAOS_COO_RW,
+                coh.as_mut(),
+                ptr::null_mut(),
+                event.as_mut(),
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_open2",
+                ret,
+                "Failed to open DAOS container",
+            ));
+        }
+        let ret = event.wait(rx, self.deadlines.metadata).await?;
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_open2",
+                ret,
+                "async open container failed",
+            ));
+        }
+        self.attach_handle(daos_pool, *coh)
+    }
+
+    // Serializes the open handle for other processes of the same job, e.g.
+    // broadcast to MPI ranks, which share it through from_global instead of
+    // each opening the container against the server.
+    pub fn to_global(&self) -> Result<Vec<u8>> {
+        let cont_hdl = self
+            .handle
+            .ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+        // a first call without a buffer reports the size needed
+        let mut glob = d_iov_t {
+            iov_buf: ptr::null_mut(),
+            iov_buf_len: 0,
+            iov_len: 0,
+        };
+        let ret = unsafe { daos_cont_local2global(cont_hdl, &mut glob) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_local2global",
+                ret,
+                "Failed to get global container handle size",
+            ));
+        }
+
+        let mut buf = vec![0u8; glob.iov_buf_len];
+        glob.iov_buf = buf.as_mut_ptr() as *mut std::os::raw::c_void;
+        glob.iov_len = buf.len();
+        let ret = unsafe { daos_cont_local2global(cont_hdl, &mut glob) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_local2global",
+                ret,
+                "Failed to serialize container handle",
+            ));
+        }
+        buf.truncate(glob.iov_len);
+        Ok(buf)
+    }
+
+    // The container whose handle to_global serialized, opened in daos_pool
+    // under the same label. The handle is only valid while the process that
+    // opened it keeps it open, disconnect() here just releases it locally.
+    pub fn from_global(daos_pool: &DaosPool, label: &str, glob: &[u8]) -> Result<Self> {
+        let poh = daos_pool
+            .get_handle()
+            .ok_or_else(|| DaosError::no_handle("Pool is not connected"))?;
+        if glob.is_empty() {
+            return Err(DaosError::inval("empty global container handle"));
+        }
+
+        let mut buf = glob.to_vec();
+        let iov = d_iov_t {
+            iov_buf: buf.as_mut_ptr() as *mut std::os::raw::c_void,
+            iov_buf_len: buf.len(),
+            iov_len: buf.len(),
+        };
+        let mut coh = DaosHandle { cookie: 0u64 };
+        let ret = unsafe { daos_cont_global2local(poh, iov, &mut coh) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_global2local",
+                ret,
+                "Failed to deserialize container handle",
+            ));
+        }
+
+        let mut cont = DaosContainer::new(label);
+        cont.attach_handle(daos_pool, coh)?;
+        Ok(cont)
+    }
+
     // Should not be called in async executer like tokio.
     // Consider spawning a new thread to open/close pools.
     pub fn disconnect(&mut self) -> Result<()> {
@@ -162,8 +994,9 @@ impl DaosContainer {
                 self.handle.take();
                 Ok(())
             } else {
-                Err(Error::new(
-                    ErrorKind::Other,
+                Err(DaosError::from_call(
+                    "daos_cont_close",
+                    res,
                     "Failed to close DAOS container",
                 ))
             }
@@ -172,102 +1005,685 @@ impl DaosContainer {
         }
     }
 
-    fn create_eq(&mut self) -> Result<()> {
-        if self.event_queue.is_some() {
-            return Ok(());
-        }
+    // Creates a container labelled label in the pool and returns its uuid.
+    // Blocks like connect().
+    pub fn create(daos_pool: &DaosPool, label: &str, props: &DaosContProps) -> Result<[u8; 16]> {
+        let poh = daos_pool
+            .get_handle()
+            .ok_or_else(|| DaosError::no_handle("Pool is not connected"))?;
+        let c_label =
+            CString::new(label).map_err(|_| DaosError::inval("label contains a NUL byte"))?;
+        let prop = props.to_prop()?;
 
-        let res = DaosEventQueue::new();
-        match res {
-            Ok(eqh) => {
-                self.event_queue.replace(eqh);
-                Ok(())
-            }
-            Err(e) => Err(e),
+        let mut uuid = [0u8; 16];
+        let res = unsafe {
+            daos_cont_create_with_label(
+                poh,
+                c_label.as_ptr(),
+                prop.as_ref()
+                    .map_or(ptr::null_mut(), |prop| prop.raw_prop.unwrap()),
+                &mut uuid,
+                ptr::null_mut(),
+            )
+        };
+        if res != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_create_with_label",
+                res,
+                "Failed to create DAOS container",
+            ));
         }
+        Ok(uuid)
     }
-}
 
-impl Drop for DaosContainer {
-    fn drop(&mut self) {
-        let res = self.disconnect();
-        match res {
-            Ok(_) => {}
-            Err(e) => {
-                eprintln!("Failed to drop DAOS container: {:?}", e);
-            }
+    // Destroys the container labelled label. Without force it fails with
+    // Busy while the container is open anywhere. Blocks like connect().
+    pub fn destroy(daos_pool: &DaosPool, label: &str, force: bool) -> Result<()> {
+        let poh = daos_pool
+            .get_handle()
+            .ok_or_else(|| DaosError::no_handle("Pool is not connected"))?;
+        let c_label =
+            CString::new(label).map_err(|_| DaosError::inval("label contains a NUL byte"))?;
+        let res =
+            unsafe { daos_cont_destroy(poh, c_label.as_ptr(), force as i32, ptr::null_mut()) };
+        if res != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_destroy",
+                res,
+                "Failed to destroy DAOS container",
+            ));
         }
+        Ok(())
     }
-}
 
-impl DaosContainerAsyncOps for DaosContainer {
-    fn query_prop_async(&self) -> impl Future<Output = Result<DaosProperty>> + Send + 'static {
-        let cont_hdl = self.handle.clone();
-        let eq = self.get_event_queue();
-        let ev = eq.map(|e| e.create_event());
+    // The returned future owns a clone of the container, so it can be
+    // handed to tokio::spawn without borrowing from the caller.
+    pub fn open_object_async(
+        self: &Arc<Self>,
+        oid: DaosObjectId,
+        opts: DaosObjectOpenOpts,
+    ) -> impl Future<Output = Result<Box<DaosObject>>> + Send + 'static {
+        let cont = self.clone();
+        async move { DaosObject::open_async(cont.as_ref(), oid, opts.read_only).await }
+    }
 
-        async move {
-            if ev.is_none() {
-                return Err(Error::new(ErrorKind::InvalidInput, "empty event queue"));
-            }
-            let mut event = ev.unwrap()?;
+    // Give a fresh container its CO_ROOTS, root 0 being the meta object used
+    // by the oid allocators. Root ids are taken below OID_BATCH_CURSOR_START
+    // so they never collide with allocated oids. If the container already
+    // has roots they are returned untouched.
+    pub async fn init_roots_async(
+        &self,
+        config: &DaosOidAllocatorConfig,
+    ) -> Result<Box<[DaosObjectId; 4]>> {
+        let cont_hdl = match self.handle {
+            Some(hdl) => hdl,
+            None => return Err(DaosError::no_handle("empty container handle")),
+        };
+        if config.nr_roots == 0 || config.nr_roots > 4 {
+            return Err(DaosError::inval("nr_roots must be between 1 and 4"));
+        }
 
+        let prop = self.query_prop_async().await?;
+        if let Ok(roots) = prop.get_co_roots() {
+            if roots.iter().any(|oid| oid.lo != 0 || oid.hi != 0) {
+                return Ok(roots);
+            }
+        }
+
+        let mut roots = Box::new([DaosObjectId { lo: 0, hi: 0 }; 4]);
+        for (idx, oid) in roots.iter_mut().take(config.nr_roots).enumerate() {
+            oid.lo = idx as u64;
+            let ret = unsafe {
+                daos_obj_generate_oid2(
+                    cont_hdl,
+                    oid,
+                    config.otype,
+                    config.cid,
+                    config.hints,
+                    config.args,
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_obj_generate_oid2",
+                    ret,
+                    "can't generate root object id",
+                ));
+            }
+        }
+
+        let prop = DaosProperty::with_co_roots(roots.as_ref())?;
+        self.set_prop_async(prop).await?;
+        Ok(roots)
+    }
+
+    // Rejects an event queue config create_eq would fail on, so connect
+    // can refuse it before opening the container.
+    fn check_event_queue_config(&self) -> Result<()> {
+        if !self.own_event_queue {
+            return Ok(());
+        }
+        if self.event_queue_config.queues == Some(0) {
+            return Err(DaosError::inval(
+                "a container needs at least one event queue",
+            ));
+        }
+        self.event_queue_config.check()
+    }
+
+    // Keeps coh as the open handle, or closes it again when the event
+    // queues can't be created, so a failed connect leaves nothing open.
+    fn attach_handle(&mut self, daos_pool: &DaosPool, coh: DaosHandle) -> Result<()> {
+        self.handle.replace(coh);
+        self.runtime = daos_pool.get_runtime().cloned();
+        if let Err(e) = self.create_eq() {
+            // the handle is dropped even if closing it fails
+            let _ = self.disconnect();
+            self.handle = None;
+            self.runtime = None;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn create_eq(&mut self) -> Result<()> {
+        if !self.event_queues.is_empty() || !self.own_event_queue {
+            return Ok(());
+        }
+        self.check_event_queue_config()?;
+        let queues = self.event_queue_config.queues.unwrap_or(1);
+        // kept aside until all are created so a failed connect can retry
+        let mut eqs = Vec::with_capacity(queues);
+        for _ in 0..queues {
+            eqs.push(DaosEventQueue::with_config(&self.event_queue_config)?);
+        }
+        self.event_queues = eqs;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for DaosContainer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DaosContainer")
+            .field("label", &self.label)
+            .field("connected", &self.handle.is_some())
+            .field("handle", &HandleFmt(self.handle))
+            .field("event_queues", &self.event_queues)
+            .field("own_event_queue", &self.own_event_queue)
+            .field("deadlines", &self.deadlines)
+            .finish()
+    }
+}
+
+impl Drop for DaosContainer {
+    fn drop(&mut self) {
+        let res = self.disconnect();
+        match res {
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Failed to drop DAOS container: {:?}", e);
+            }
+        }
+    }
+}
+
+impl DaosContainerAsyncOps for DaosContainer {
+    fn query_prop_async(&self) -> impl Future<Output = Result<DaosProperty>> + Send + 'static {
+        self.query_props_async(&[DAOS_PROP_CO_ROOTS])
+    }
+
+    fn query_props_async(
+        &self,
+        types: &[u32],
+    ) -> impl Future<Output = Result<DaosProperty>> + Send + 'static {
+        let cont_hdl = self.handle;
+        let eq = self.get_event_queue().cloned();
+        let deadline = self.deadlines.metadata;
+        let prop = DaosProperty::with_types(types);
+
+        async move {
+            let cont_hdl =
+                cont_hdl.ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+            let eq = eq.ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+            let prop = prop?;
+            let mut event = eq.create_event()?;
             let rx = event.register_callback()?;
 
-            let prop = DaosProperty::new()?;
+            let ret = unsafe {
+                daos_cont_query(
+                    cont_hdl,
+                    ptr::null_mut(),
+                    prop.raw_prop.unwrap(),
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_query",
+                    ret,
+                    "Failed to query DAOS container",
+                ));
+            }
+            let ret = event.wait(rx, deadline).await?;
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_query",
+                    ret,
+                    "async query container failed",
+                ));
+            }
+            Ok(prop)
+        }
+    }
+
+    fn query_info_async(&self) -> impl Future<Output = Result<ContainerInfo>> + Send + 'static {
+        let cont_hdl = self.handle;
+        let eq = self.get_event_queue().cloned();
+        let deadline = self.deadlines.metadata;
 
+        async move {
+            let cont_hdl =
+                cont_hdl.ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+            let eq = eq.ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let prop = ContainerInfo::query_prop()?;
+            // boxed so the buffer stays put while libdaos fills it
+            let mut info: Box<daos_cont_info_t> = Box::new(unsafe { mem::zeroed() });
             let ret = unsafe {
                 daos_cont_query(
+                    cont_hdl,
+                    info.as_mut(),
+                    prop.raw_prop.unwrap(),
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_query",
+                    ret,
+                    "Failed to query DAOS container",
+                ));
+            }
+            let ret = event.wait(rx, deadline).await?;
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_query",
+                    ret,
+                    "async query container failed",
+                ));
+            }
+            ContainerInfo::from_raw(info.as_ref(), &prop)
+        }
+    }
+
+    fn alloc_oids_async(
+        &self,
+        count: u64,
+    ) -> impl Future<Output = Result<Range<u64>>> + Send + 'static {
+        let cont_hdl = self.handle;
+        let eq = self.get_event_queue().cloned();
+        let deadline = self.deadlines.metadata;
+
+        async move {
+            let cont_hdl =
+                cont_hdl.ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+            let eq = eq.ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+            if count == 0 {
+                return Err(DaosError::inval("can't allocate zero oids"));
+            }
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut base = Box::new(0u64);
+            let ret =
+                unsafe { daos_cont_alloc_oids(cont_hdl, count, base.as_mut(), event.as_mut()) };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_alloc_oids",
+                    ret,
+                    "Failed to allocate DAOS object ids",
+                ));
+            }
+            let ret = event.wait(rx, deadline).await?;
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_alloc_oids",
+                    ret,
+                    "async allocate object ids failed",
+                ));
+            }
+            Ok(*base..*base + count)
+        }
+    }
+
+    fn query_status_async(&self) -> impl Future<Output = Result<ContStatus>> + Send + 'static {
+        let query = self.query_props_async(&[DAOS_PROP_CO_STATUS]);
+        async move { query.await?.get_status() }
+    }
+
+    fn query_root_async(
+        &self,
+        idx: usize,
+    ) -> impl Future<Output = Result<DaosObjectId>> + Send + 'static {
+        let query = self.query_prop_async();
+        async move { query.await?.get_root(idx) }
+    }
+
+    fn set_root_async(
+        self: &Arc<Self>,
+        idx: usize,
+        oid: DaosObjectId,
+    ) -> impl Future<Output = Result<()>> + Send + 'static {
+        let cont = self.clone();
+        async move {
+            check_root_idx(idx)?;
+            let roots = replace_root(&cont.query_prop_async().await?, idx, oid)?;
+            cont.set_prop_async(DaosProperty::with_co_roots(&roots)?)
+                .await
+        }
+    }
+
+    fn clear_status_async(&self) -> impl Future<Output = Result<()>> + Send + 'static {
+        let set = DaosProperty::with_value(DAOS_PROP_CO_STATUS, ContStatus::healthy_raw())
+            .map(|prop| self.set_prop_async(prop));
+        async move { set?.await }
+    }
+
+    fn set_prop_async(
+        &self,
+        prop: DaosProperty,
+    ) -> impl Future<Output = Result<()>> + Send + 'static {
+        let cont_hdl = self.handle.clone();
+        let eq = self.get_event_queue();
+        let ev = eq.map(|e| e.create_event());
+        let deadline = self.deadlines.metadata;
+
+        async move {
+            let prop = prop;
+            if cont_hdl.is_none() {
+                return Err(DaosError::no_handle("empty container handle"));
+            }
+            if ev.is_none() {
+                return Err(DaosError::no_handle("empty event queue"));
+            }
+            let mut event = ev.unwrap()?;
+
+            let rx = event.register_callback()?;
+
+            let ret = unsafe {
+                daos_cont_set_prop(
                     cont_hdl.unwrap(),
-                    ptr::null_mut(),
                     prop.raw_prop.clone().unwrap(),
                     event.as_mut(),
                 )
             };
 
             if ret != 0 {
-                return Err(Error::new(
-                    ErrorKind::Other,
-                    "Failed to query DAOS container",
+                return Err(DaosError::from_call(
+                    "daos_cont_set_prop",
+                    ret,
+                    "Failed to set DAOS container prop",
                 ));
             }
 
-            match rx.await {
+            match event.wait(rx, deadline).await {
                 Ok(res) => {
                     if res != 0 {
-                        Err(Error::new(ErrorKind::Other, "async query container failed"))
+                        Err(DaosError::from_call(
+                            "daos_cont_set_prop",
+                            res,
+                            "async set container prop failed",
+                        ))
                     } else {
-                        Ok(prop)
+                        Ok(())
                     }
                 }
-                Err(_) => Err(Error::new(
-                    ErrorKind::Other,
-                    "can't get response from the receiver",
-                )),
+                Err(e) => Err(e),
+            }
+        }
+    }
+
+    fn create_snapshot_async(&self) -> impl Future<Output = Result<u64>> + Send + 'static {
+        let cont_hdl = self.handle;
+        let eq = self.get_event_queue().cloned();
+        let deadline = self.deadlines.metadata;
+
+        async move {
+            let cont_hdl =
+                cont_hdl.ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+            let eq = eq.ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut epoch = Box::new(0u64);
+            let ret = unsafe {
+                daos_cont_create_snap(cont_hdl, epoch.as_mut(), ptr::null_mut(), event.as_mut())
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_create_snap",
+                    ret,
+                    "Failed to create DAOS container snapshot",
+                ));
+            }
+            let ret = event.wait(rx, deadline).await?;
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_create_snap",
+                    ret,
+                    "async create container snapshot failed",
+                ));
             }
+            Ok(*epoch)
+        }
+    }
+
+    fn create_oit_snapshot_async(&self) -> impl Future<Output = Result<u64>> + Send + 'static {
+        let cont_hdl = self.handle;
+        let eq = self.get_event_queue().cloned();
+        let deadline = self.deadlines.metadata;
+
+        async move {
+            let cont_hdl =
+                cont_hdl.ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+            let eq = eq.ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut epoch = Box::new(0u64);
+            let ret = unsafe {
+                daos_cont_create_snap_opt(
+                    cont_hdl,
+                    epoch.as_mut(),
+                    ptr::null_mut(),
+                    daos_snapshot_opts_DAOS_SNAP_OPT_CR | daos_snapshot_opts_DAOS_SNAP_OPT_OIT,
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_create_snap_opt",
+                    ret,
+                    "Failed to create DAOS container OIT snapshot",
+                ));
+            }
+            let ret = event.wait(rx, deadline).await?;
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_create_snap_opt",
+                    ret,
+                    "async create container OIT snapshot failed",
+                ));
+            }
+            Ok(*epoch)
+        }
+    }
+
+    fn destroy_snapshot_async(
+        &self,
+        epoch: u64,
+    ) -> impl Future<Output = Result<()>> + Send + 'static {
+        let cont_hdl = self.handle;
+        let eq = self.get_event_queue().cloned();
+        let deadline = self.deadlines.metadata;
+
+        async move {
+            let cont_hdl =
+                cont_hdl.ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+            let eq = eq.ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let epr = daos_epoch_range_t {
+                epr_lo: epoch,
+                epr_hi: epoch,
+            };
+            let ret = unsafe { daos_cont_destroy_snap(cont_hdl, epr, event.as_mut()) };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_destroy_snap",
+                    ret,
+                    "Failed to destroy DAOS container snapshot",
+                ));
+            }
+            let ret = event.wait(rx, deadline).await?;
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_destroy_snap",
+                    ret,
+                    "async destroy container snapshot failed",
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    fn list_snapshot_async(
+        &self,
+        snap_lst: Box<DaosSnapshotList>,
+    ) -> impl Future<Output = Result<Box<DaosSnapshotList>>> + Send + 'static {
+        let cont_hdl = self.handle;
+        let eq = self.get_event_queue().cloned();
+        let deadline = self.deadlines.metadata;
+        list_snap_page(cont_hdl, eq, deadline, snap_lst)
+    }
+
+    fn list_snapshots_async(&self) -> impl Future<Output = Result<Vec<u64>>> + Send + 'static {
+        let cont_hdl = self.handle;
+        let eq = self.get_event_queue().cloned();
+        let deadline = self.deadlines.metadata;
+
+        async move {
+            let mut epochs = Vec::new();
+            let mut snap_lst = DaosSnapshotList::new();
+            while !snap_lst.reach_end() {
+                snap_lst = list_snap_page(cont_hdl, eq.clone(), deadline, snap_lst).await?;
+                epochs.extend_from_slice(snap_lst.epochs());
+            }
+            Ok(epochs)
+        }
+    }
+
+    fn aggregate_async(&self, epoch: u64) -> impl Future<Output = Result<()>> + Send + 'static {
+        let cont_hdl = self.handle;
+        let eq = self.get_event_queue().cloned();
+        let deadline = self.deadlines.metadata;
+
+        async move {
+            let cont_hdl =
+                cont_hdl.ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+            let eq = eq.ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let ret = unsafe { daos_cont_aggregate(cont_hdl, epoch, event.as_mut()) };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_aggregate",
+                    ret,
+                    "Failed to aggregate DAOS container",
+                ));
+            }
+            let ret = event.wait(rx, deadline).await?;
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_cont_aggregate",
+                    ret,
+                    "async aggregate container failed",
+                ));
+            }
+            Ok(())
         }
     }
 }
 
 impl DaosContainerSyncOps for DaosContainer {
     fn query_prop(&self) -> Result<DaosProperty> {
-        let prop = DaosProperty::new()?;
+        self.query_props(&[DAOS_PROP_CO_ROOTS])
+    }
+
+    fn query_props(&self, types: &[u32]) -> Result<DaosProperty> {
+        let cont_hdl = self
+            .handle
+            .ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+        let prop = DaosProperty::with_types(types)?;
         let ret = unsafe {
             daos_cont_query(
-                self.handle.clone().unwrap(),
+                cont_hdl,
                 ptr::null_mut(),
-                prop.raw_prop.clone().unwrap(),
+                prop.raw_prop.unwrap(),
                 ptr::null_mut(),
             )
         };
         if ret != 0 {
-            return Err(Error::new(
-                ErrorKind::Other,
+            return Err(DaosError::from_call(
+                "daos_cont_query",
+                ret,
                 "Failed to query DAOS container",
             ));
         }
         Ok(prop)
     }
+
+    fn query_info(&self) -> Result<ContainerInfo> {
+        let cont_hdl = self
+            .handle
+            .ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+        let prop = ContainerInfo::query_prop()?;
+        let mut info: daos_cont_info_t = unsafe { mem::zeroed() };
+        let ret = unsafe {
+            daos_cont_query(cont_hdl, &mut info, prop.raw_prop.unwrap(), ptr::null_mut())
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_query",
+                ret,
+                "Failed to query DAOS container",
+            ));
+        }
+        ContainerInfo::from_raw(&info, &prop)
+    }
+
+    fn alloc_oids(&self, count: u64) -> Result<Range<u64>> {
+        let cont_hdl = self
+            .handle
+            .ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+        if count == 0 {
+            return Err(DaosError::inval("can't allocate zero oids"));
+        }
+        let mut base = 0u64;
+        let ret = unsafe { daos_cont_alloc_oids(cont_hdl, count, &mut base, ptr::null_mut()) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_alloc_oids",
+                ret,
+                "Failed to allocate DAOS object ids",
+            ));
+        }
+        Ok(base..base + count)
+    }
+
+    fn query_status(&self) -> Result<ContStatus> {
+        self.query_props(&[DAOS_PROP_CO_STATUS])?.get_status()
+    }
+
+    fn query_root(&self, idx: usize) -> Result<DaosObjectId> {
+        self.query_prop()?.get_root(idx)
+    }
+
+    fn set_root(&self, idx: usize, oid: DaosObjectId) -> Result<()> {
+        check_root_idx(idx)?;
+        let roots = replace_root(&self.query_prop()?, idx, oid)?;
+        self.set_prop(&DaosProperty::with_co_roots(&roots)?)
+    }
+
+    fn clear_status(&self) -> Result<()> {
+        let prop = DaosProperty::with_value(DAOS_PROP_CO_STATUS, ContStatus::healthy_raw())?;
+        self.set_prop(&prop)
+    }
+
+    fn set_prop(&self, prop: &DaosProperty) -> Result<()> {
+        if self.handle.is_none() {
+            return Err(DaosError::no_handle("empty container handle"));
+        }
+        let ret = unsafe {
+            daos_cont_set_prop(
+                self.handle.clone().unwrap(),
+                prop.raw_prop.clone().unwrap(),
+                ptr::null_mut(),
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_cont_set_prop",
+                ret,
+                "Failed to set DAOS container prop",
+            ));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -278,6 +1694,220 @@ mod tests {
     const TEST_POOL_NAME: &str = "pool1";
     const TEST_CONT_NAME: &str = "cont1";
 
+    #[test]
+    fn test_cont_props_validate() {
+        let props = DaosContProps::new()
+            .redundancy_factor(2)
+            .ec_cell_size(64 << 10)
+            .checksum(ContChecksum::Crc32)
+            .dedup(ContDedup::Off);
+        assert!(props.validate().is_ok());
+        assert!(DaosContProps::new().to_prop().unwrap().is_none());
+
+        let bad_rf = DaosContProps::new().redundancy_factor(5);
+        assert!(matches!(bad_rf.validate(), Err(DaosError::Inval(_))));
+        for size in [512, 1536 + 1, 2 << 30] {
+            let bad_cell = DaosContProps::new().ec_cell_size(size);
+            assert!(matches!(bad_cell.validate(), Err(DaosError::Inval(_))));
+        }
+    }
+
+    #[test]
+    fn test_prop_values() {
+        let raw = ContChecksum::Crc64.to_raw();
+        assert_eq!(ContChecksum::from_raw(raw).unwrap(), ContChecksum::Crc64);
+        assert!(ContChecksum::from_raw(99).is_err());
+        let status = ContStatus::from_raw((1 << 32) | 7).unwrap();
+        assert_eq!(status.health, ContHealth::Unclean);
+        assert_eq!(status.pool_map_version, 7);
+        assert!(ContStatus::from_raw(9 << 32).is_err());
+        let raw = ContStatus::healthy_raw();
+        assert_eq!(raw >> 48, bindings::DAOS_PROP_CO_CLEAR as u64);
+        assert_eq!(
+            ContStatus::from_raw(raw).unwrap().health,
+            ContHealth::Healthy
+        );
+    }
+
+    #[test]
+    fn test_create_with_props() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let label = "rust_props_cont";
+        let roots = [DaosObjectId { lo: 1, hi: 2 }; 4];
+        let props = DaosContProps::new()
+            .redundancy_factor(0)
+            .checksum(ContChecksum::Crc64)
+            .compression(ContCompression::Lz4)
+            .co_roots(&roots);
+        let uuid = DaosContainer::create(&pool, label, &props).expect("Failed to create container");
+        assert_ne!(uuid, [0u8; 16]);
+        let res = DaosContainer::create(&pool, label, &props);
+        assert!(matches!(res, Err(DaosError::Exist(_))));
+
+        let mut cont = DaosContainer::new(label);
+        cont.connect(&pool).expect("Failed to connect to container");
+        let prop = cont.query_prop().expect("Failed to query container");
+        assert_eq!(*prop.get_co_roots().unwrap(), roots);
+        let types = [
+            DAOS_PROP_CO_LABEL,
+            DAOS_PROP_CO_CSUM,
+            DAOS_PROP_CO_REDUN_FAC,
+            DAOS_PROP_CO_STATUS,
+        ];
+        let prop = cont.query_props(&types).expect("Failed to query container");
+        assert_eq!(prop.get_label().unwrap(), label);
+        assert_eq!(prop.get_checksum().unwrap(), ContChecksum::Crc64);
+        assert_eq!(prop.get_redundancy_factor().unwrap(), 0);
+        assert_eq!(prop.get_status().unwrap().health, ContHealth::Healthy);
+        assert!(matches!(prop.get_co_roots(), Err(DaosError::NonExist(_))));
+        assert!(matches!(cont.query_props(&[]), Err(DaosError::Inval(_))));
+        cont.disconnect().unwrap();
+
+        DaosContainer::destroy(&pool, label, false).expect("Failed to destroy container");
+    }
+
+    #[test]
+    fn test_global_handle() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        assert!(matches!(cont.to_global(), Err(DaosError::NoHandle(_))));
+        cont.connect(&pool).expect("Failed to connect to container");
+        let glob = cont.to_global().expect("Failed to serialize handle");
+        assert!(!glob.is_empty());
+
+        let shared = DaosContainer::from_global(&pool, TEST_CONT_NAME, &glob)
+            .expect("Failed to deserialize handle");
+        assert!(shared.get_event_queue().is_some());
+        let info = shared.query_info().expect("Failed to query shared handle");
+        assert_eq!(info.uuid, cont.query_info().unwrap().uuid);
+
+        let res = DaosContainer::from_global(&pool, TEST_CONT_NAME, &[]);
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+    }
+
+    #[tokio::test]
+    async fn test_alloc_oids() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        cont.connect(&pool).expect("Failed to connect to container");
+        let first = cont.alloc_oids(16).expect("Failed to allocate oids");
+        assert_eq!(first.end - first.start, 16);
+        let second = cont
+            .alloc_oids_async(16)
+            .await
+            .expect("Failed to allocate oids");
+        assert!(second.start >= first.end || second.end <= first.start);
+        assert!(matches!(cont.alloc_oids(0), Err(DaosError::Inval(_))));
+    }
+
+    #[tokio::test]
+    async fn test_container_status() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        assert!(matches!(cont.query_status(), Err(DaosError::NoHandle(_))));
+        cont.connect(&pool).expect("Failed to connect to container");
+        cont.clear_status()
+            .expect("Failed to clear container status");
+        let status = cont.query_status().expect("Failed to query status");
+        assert_eq!(status.health, ContHealth::Healthy);
+
+        cont.clear_status_async()
+            .await
+            .expect("Failed to clear container status");
+        let status = cont.query_status_async().await.unwrap();
+        assert_eq!(status.health, ContHealth::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_shared_event_queue() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+        let eq = pool.get_event_queue().cloned().unwrap();
+
+        let mut shared = Vec::new();
+        for _ in 0..2 {
+            let mut cont = DaosContainer::with_event_queue(TEST_CONT_NAME, eq.clone());
+            cont.connect(&pool).expect("Failed to connect to container");
+            assert_eq!(
+                cont.get_event_queue().unwrap().get_handle().unwrap().cookie,
+                eq.get_handle().unwrap().cookie
+            );
+            shared.push(cont);
+        }
+        for cont in shared.iter() {
+            cont.query_prop_async()
+                .await
+                .expect("Failed to query container");
+        }
+
+        let mut sync_only = DaosContainer::without_event_queue(TEST_CONT_NAME);
+        sync_only
+            .connect(&pool)
+            .expect("Failed to connect to container");
+        assert!(sync_only.get_event_queue().is_none());
+        assert!(sync_only.query_prop().is_ok());
+        let res = sync_only.query_prop_async().await;
+        assert!(matches!(res, Err(DaosError::NoHandle(_))));
+    }
+
+    #[tokio::test]
+    async fn test_multiple_event_queues() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        cont.set_event_queue_config(DaosEventQueueConfig {
+            poll_batch: Some(32),
+            poll_timeout: Some(Duration::from_micros(10)),
+            queues: Some(2),
+            ..Default::default()
+        });
+        cont.connect(&pool).expect("Failed to connect to container");
+        let first = cont.get_event_queue().unwrap().get_handle().unwrap().cookie;
+        let second = cont.get_event_queue().unwrap().get_handle().unwrap().cookie;
+        assert_ne!(first, second);
+        for _ in 0..4 {
+            cont.query_prop_async()
+                .await
+                .expect("Failed to query container");
+        }
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        cont.set_event_queue_config(DaosEventQueueConfig {
+            queues: Some(0),
+            ..Default::default()
+        });
+        let res = cont.connect(&pool);
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        assert!(cont.get_handle().is_none());
+
+        // a bad config is refused before the container is opened
+        for config in [
+            DaosEventQueueConfig {
+                poll_batch: Some(0),
+                ..Default::default()
+            },
+            DaosEventQueueConfig {
+                affinity: Some(vec![]),
+                ..Default::default()
+            },
+        ] {
+            let mut cont = DaosContainer::new(TEST_CONT_NAME);
+            cont.set_event_queue_config(config);
+            let res = cont.connect_async(&pool).await;
+            assert!(matches!(res, Err(DaosError::Inval(_))));
+            assert!(cont.get_handle().is_none());
+        }
+    }
+
     #[test]
     fn test_daos_container_connect() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
@@ -341,4 +1971,210 @@ mod tests {
         let prop = container.query_prop_async().await;
         assert_eq!(prop.is_ok(), true);
     }
+
+    #[tokio::test]
+    async fn test_query_info() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut container = DaosContainer::new(TEST_CONT_NAME);
+        assert!(matches!(
+            container.query_info(),
+            Err(DaosError::NoHandle(_))
+        ));
+        container
+            .connect(&pool)
+            .expect("Failed to connect to container");
+
+        let info = container.query_info().expect("Failed to query container");
+        assert!(info.handles >= 1);
+        assert!(info.redundancy_factor <= REDUN_FAC_MAX);
+        let async_info = container
+            .query_info_async()
+            .await
+            .expect("Failed to query container");
+        assert_eq!(async_info.uuid, info.uuid);
+        assert_eq!(async_info.redundancy_factor, info.redundancy_factor);
+
+        let epoch = container.create_snapshot_async().await.unwrap();
+        let info = container.query_info_async().await.unwrap();
+        assert!(info.snapshots >= 1);
+        container.destroy_snapshot_async(epoch).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_open_object_async_spawned() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        let result = pool.connect();
+        assert_eq!(result.is_ok(), true);
+
+        let mut container = DaosContainer::new(TEST_CONT_NAME);
+        let result = container.connect(&pool);
+        assert_eq!(result.is_ok(), true);
+
+        let container = Arc::new(container);
+        let roots = container.query_prop().unwrap().get_co_roots().unwrap();
+        let opts = DaosObjectOpenOpts { read_only: true };
+        let handle = tokio::spawn(container.open_object_async(roots[0], opts));
+        let obj = handle.await.unwrap();
+        assert_eq!(obj.is_ok(), true);
+        assert_eq!(obj.unwrap().oid, roots[0]);
+    }
+
+    #[tokio::test]
+    async fn test_deadlines_inherited() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        let result = pool.connect();
+        assert_eq!(result.is_ok(), true);
+
+        let mut container = DaosContainer::new(TEST_CONT_NAME);
+        let result = container.connect(&pool);
+        assert_eq!(result.is_ok(), true);
+
+        let deadlines = DaosDeadlines {
+            metadata: Some(Duration::from_secs(2)),
+            bulk: Some(Duration::from_secs(30)),
+        };
+        container.set_deadlines(deadlines);
+
+        let container = Arc::new(container);
+        let result = container.query_prop_async().await;
+        assert_eq!(result.is_ok(), true);
+        let roots = result.unwrap().get_co_roots().unwrap();
+
+        let opts = DaosObjectOpenOpts { read_only: true };
+        let mut obj = container.open_object_async(roots[0], opts).await.unwrap();
+        assert_eq!(obj.get_deadlines(), deadlines);
+
+        obj.set_deadlines(DaosDeadlines::default());
+        assert_eq!(obj.get_deadlines().bulk, None);
+    }
+
+    #[tokio::test]
+    async fn test_init_roots_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        let result = pool.connect();
+        assert_eq!(result.is_ok(), true);
+
+        let mut container = DaosContainer::new(TEST_CONT_NAME);
+        let result = container.connect(&pool);
+        assert_eq!(result.is_ok(), true);
+
+        let config = DaosOidAllocatorConfig::default();
+        let roots = container.init_roots_async(&config).await;
+        assert_eq!(roots.is_ok(), true);
+        let roots = roots.unwrap();
+        assert_ne!(roots[0], DaosObjectId { lo: 0, hi: 0 });
+
+        let again = container.init_roots_async(&config).await;
+        assert_eq!(again.is_ok(), true);
+        assert_eq!(again.unwrap()[0], roots[0]);
+    }
+
+    #[test]
+    fn test_root_props() {
+        let oid = DaosObjectId { lo: 5, hi: 9 };
+        let props = DaosContProps::new().root(2, oid);
+        assert!(props.validate().is_ok());
+        let roots = props.roots.unwrap();
+        assert_eq!(roots[2], oid);
+        assert_eq!(roots[0], DaosObjectId { lo: 0, hi: 0 });
+
+        let props = DaosContProps::new().root(DAOS_CO_ROOTS_NR, oid);
+        assert!(matches!(props.validate(), Err(DaosError::Inval(_))));
+    }
+
+    #[tokio::test]
+    async fn test_set_root() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let label = "rust_roots_cont";
+        let first = DaosObjectId { lo: 1, hi: 3 };
+        let props = DaosContProps::new().root(0, first);
+        DaosContainer::create(&pool, label, &props).expect("Failed to create container");
+
+        let mut cont = DaosContainer::new(label);
+        cont.connect(&pool).expect("Failed to connect to container");
+        let cont = Arc::new(cont);
+        assert_eq!(cont.query_root(0).unwrap(), first);
+        assert!(matches!(cont.query_root(1), Err(DaosError::NonExist(_))));
+        assert!(matches!(cont.query_root(4), Err(DaosError::Inval(_))));
+
+        let second = DaosObjectId { lo: 2, hi: 3 };
+        cont.set_root(1, second).expect("Failed to set root");
+        let third = DaosObjectId { lo: 3, hi: 3 };
+        tokio::spawn(cont.set_root_async(3, third))
+            .await
+            .unwrap()
+            .expect("Failed to set root");
+        assert!(matches!(cont.set_root(4, third), Err(DaosError::Inval(_))));
+
+        assert_eq!(cont.query_root_async(0).await.unwrap(), first);
+        assert_eq!(cont.query_root_async(1).await.unwrap(), second);
+        assert_eq!(cont.query_root_async(3).await.unwrap(), third);
+        assert!(matches!(cont.query_root(2), Err(DaosError::NonExist(_))));
+
+        let mut cont = Arc::into_inner(cont).unwrap();
+        cont.disconnect().unwrap();
+        DaosContainer::destroy(&pool, label, true).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_snapshots_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut container = DaosContainer::new(TEST_CONT_NAME);
+        container
+            .connect(&pool)
+            .expect("Failed to connect to container");
+
+        let mut created = Vec::new();
+        for _ in 0..3 {
+            created.push(container.create_snapshot_async().await.unwrap());
+        }
+
+        let epochs = container.list_snapshots_async().await.unwrap();
+        assert!(created.iter().all(|e| epochs.contains(e)));
+
+        // a one-epoch page grows instead of truncating
+        let mut paged = Vec::new();
+        let mut snap_lst = DaosSnapshotList::with_capacity(1);
+        while !snap_lst.reach_end() {
+            snap_lst = container.list_snapshot_async(snap_lst).await.unwrap();
+            paged.extend_from_slice(snap_lst.epochs());
+        }
+        assert_eq!(paged, epochs);
+
+        snap_lst.reset();
+        assert!(!snap_lst.reach_end());
+        assert!(snap_lst.epochs().is_empty());
+
+        for epoch in created.iter() {
+            container.destroy_snapshot_async(*epoch).await.unwrap();
+        }
+        let epochs = container.list_snapshots_async().await.unwrap();
+        assert!(created.iter().all(|e| !epochs.contains(e)));
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut container = DaosContainer::new(TEST_CONT_NAME);
+        let res = container.aggregate_async(0).await;
+        assert!(matches!(res, Err(DaosError::NoHandle(_))));
+        container
+            .connect(&pool)
+            .expect("Failed to connect to container");
+
+        let epoch = container.create_snapshot_async().await.unwrap();
+        match container.aggregate_async(epoch).await {
+            Ok(()) | Err(DaosError::NoSys(_)) => {}
+            Err(e) => panic!("aggregate failed: {:?}", e),
+        }
+        container.destroy_snapshot_async(epoch).await.unwrap();
+    }
 }
@@ -0,0 +1,264 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::bindings::{
+    daos_version_get, DAOS_API_VERSION_FIX, DAOS_API_VERSION_MAJOR, DAOS_API_VERSION_MINOR,
+};
+use crate::daos_cont::DaosContainer;
+use crate::daos_event::DaosEventQueue;
+use crate::daos_obj::{
+    CondFlags, DaosObjSyncOps, DaosObject, DAOS_OC_HINTS_NONE, DAOS_OC_UNKNOWN,
+    DAOS_OT_MULTI_HASHED,
+};
+use crate::daos_oid_allocator::DaosSyncOidAllocator;
+use crate::daos_pool::DaosPool;
+use crate::daos_txn::DaosTxn;
+use crate::error::{DaosError, Result};
+use std::env;
+use std::fmt;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const AGENT_SOCKET: &str = "daos_agent.sock";
+const DEFAULT_AGENT_DIR: &str = "/var/run/daos_agent";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckStatus {
+    // with a short description of what was found
+    Passed(String),
+    Failed(DaosError),
+    // a check it depends on failed
+    Skipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub elapsed: Duration,
+}
+
+// Outcome of doctor(), one entry per check in the order they ran. Its
+// Display form is a table meant for logs and support requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    pub fn is_healthy(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|c| matches!(c.status, CheckStatus::Passed(_)))
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &DoctorCheck> {
+        self.checks
+            .iter()
+            .filter(|c| matches!(c.status, CheckStatus::Failed(_)))
+    }
+
+    pub fn check(&self, name: &str) -> Option<&DoctorCheck> {
+        self.checks.iter().find(|c| c.name == name)
+    }
+
+    fn run<T>(&mut self, name: &'static str, f: impl FnOnce() -> Result<(T, String)>) -> Option<T> {
+        let start = Instant::now();
+        let (val, status) = match f() {
+            Ok((val, detail)) => (Some(val), CheckStatus::Passed(detail)),
+            Err(e) => (None, CheckStatus::Failed(e)),
+        };
+        self.checks.push(DoctorCheck {
+            name,
+            status,
+            elapsed: start.elapsed(),
+        });
+        val
+    }
+
+    fn skip(&mut self, name: &'static str) {
+        self.checks.push(DoctorCheck {
+            name,
+            status: CheckStatus::Skipped,
+            elapsed: Duration::ZERO,
+        });
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in self.checks.iter() {
+            let (tag, detail) = match &check.status {
+                CheckStatus::Passed(detail) => ("ok", detail.clone()),
+                CheckStatus::Failed(e) => ("FAIL", e.to_string()),
+                CheckStatus::Skipped => ("skip", String::new()),
+            };
+            writeln!(
+                f,
+                "[{:>4}] {:<12} {:>10.1?}  {}",
+                tag, check.name, check.elapsed, detail
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// The agent's dRPC socket, DAOS_AGENT_DRPC_DIR overriding the default
+// runtime directory like it does for libdaos.
+fn agent_socket() -> PathBuf {
+    let dir = env::var("DAOS_AGENT_DRPC_DIR").unwrap_or_else(|_| DEFAULT_AGENT_DIR.to_string());
+    PathBuf::from(dir).join(AGENT_SOCKET)
+}
+
+fn check_agent() -> Result<((), String)> {
+    let socket = agent_socket();
+    UnixStream::connect(&socket).map_err(|e| {
+        DaosError::unreach(format!(
+            "can't reach daos_agent at {}: {}",
+            socket.display(),
+            e
+        ))
+    })?;
+    Ok(((), socket.display().to_string()))
+}
+
+// The client library has to be the API major version the bindings were
+// generated from, and at least as new.
+fn check_version() -> Result<((), String)> {
+    let (mut major, mut minor, mut fix) = (0, 0, 0);
+    let ret = unsafe { daos_version_get(&mut major, &mut minor, &mut fix) };
+    if ret != 0 {
+        return Err(DaosError::from_call(
+            "daos_version_get",
+            ret,
+            "Failed to get libdaos version",
+        ));
+    }
+    let detail = format!(
+        "libdaos {}.{}.{}, built against {}.{}.{}",
+        major, minor, fix, DAOS_API_VERSION_MAJOR, DAOS_API_VERSION_MINOR, DAOS_API_VERSION_FIX
+    );
+    if major as u32 != DAOS_API_VERSION_MAJOR || (minor as u32) < DAOS_API_VERSION_MINOR {
+        return Err(DaosError::no_sys(format!("incompatible {}", detail)));
+    }
+    Ok(((), detail))
+}
+
+fn check_round_trip(cont: Arc<DaosContainer>) -> Result<((), String)> {
+    let allocator = Arc::from(DaosSyncOidAllocator::new(cont.clone())?);
+    let obj = DaosObject::create(
+        &cont,
+        allocator,
+        DAOS_OT_MULTI_HASHED,
+        DAOS_OC_UNKNOWN,
+        DAOS_OC_HINTS_NONE,
+        0,
+    )?;
+
+    let txn = DaosTxn::txn_none();
+    let (dkey, akey) = (b"doctor".to_vec(), b"probe".to_vec());
+    let data = b"daos-rust-api doctor";
+    let res = obj
+        .update(&txn, CondFlags::empty(), dkey.clone(), akey.clone(), data)
+        .and_then(|_| obj.fetch(&txn, CondFlags::empty(), dkey, akey, data.len() as u32));
+    // whatever happened, don't leave the probe object behind
+    let punched = obj.punch(&txn);
+
+    if res? != data {
+        return Err(DaosError::inval("read back different data than written"));
+    }
+    punched?;
+    Ok(((), format!("{} bytes in object {:?}", data.len(), obj.oid)))
+}
+
+// Runs the checks a service needs to work against pool_label and
+// cont_label, from the agent to a write and read back of a probe object
+// in the container, and reports each of them. Checks that depend on a
+// failed one are skipped. Blocks like DaosPool::connect.
+pub fn doctor(pool_label: &str, cont_label: &str) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    report.run("agent", check_agent);
+    report.run("version", check_version);
+
+    let pool = report.run("pool", || {
+        let mut pool = DaosPool::builder().label(pool_label).build()?;
+        pool.connect()?;
+        Ok((pool, format!("connected to {}", pool_label)))
+    });
+
+    let cont = match &pool {
+        Some(pool) => report.run("container", || {
+            let mut cont = DaosContainer::new(cont_label);
+            cont.connect(pool)?;
+            Ok((Arc::new(cont), format!("opened {}", cont_label)))
+        }),
+        None => {
+            report.skip("container");
+            None
+        }
+    };
+
+    if pool.is_some() {
+        report.run("event queue", || {
+            let eq = DaosEventQueue::new()?;
+            eq.create_event()?;
+            Ok(((), "created an event queue and event".to_string()))
+        });
+    } else {
+        report.skip("event queue");
+    }
+
+    match cont {
+        Some(cont) => {
+            report.run("round trip", || check_round_trip(cont));
+        }
+        None => report.skip("round trip"),
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[test]
+    fn test_doctor() {
+        let report = doctor(TEST_POOL_NAME, TEST_CONT_NAME);
+        assert!(report.is_healthy(), "{}", report);
+        assert_eq!(report.checks.len(), 6);
+        assert!(report.to_string().contains("round trip"));
+
+        let report = doctor("no_such_pool", TEST_CONT_NAME);
+        assert!(!report.is_healthy());
+        assert!(matches!(
+            report.check("pool").unwrap().status,
+            CheckStatus::Failed(_)
+        ));
+        assert_eq!(
+            report.check("round trip").unwrap().status,
+            CheckStatus::Skipped
+        );
+        assert_eq!(report.failures().count(), 1);
+    }
+}
@@ -16,16 +16,54 @@
  */
 
 use crate::bindings::{
-    daos_eq_create, daos_eq_destroy, daos_eq_poll, daos_event__bindgen_ty_1, daos_event_fini,
-    daos_event_init, daos_event_register_comp_cb, daos_event_t, daos_event_test, daos_handle_t,
-    DAOS_EQ_NOWAIT,
+    daos_eq_create, daos_eq_destroy, daos_eq_poll, daos_errno_DER_CANCELED,
+    daos_event__bindgen_ty_1, daos_event_abort, daos_event_fini, daos_event_init,
+    daos_event_register_comp_cb, daos_event_t, daos_event_test, daos_handle_t, DAOS_EQ_NOWAIT,
 };
-use std::io::{Error, ErrorKind, Result};
+use crate::daos_pool::HandleFmt;
+use crate::daos_runtime::DaosRuntime;
+use crate::error::{DaosError, Result};
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::mem;
 use std::ptr;
 use std::sync::mpsc;
+use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, RuntimeFlavor};
 use tokio::sync::oneshot;
 
+tokio::task_local! {
+    // when the operations awaited inside with_timeout have to be done by
+    static OP_DEADLINE: Instant;
+}
+
+// Runs fut with every DAOS operation it waits for bounded by timeout from
+// now, on top of the deadlines of the objects and transactions involved.
+// An operation still pending when it expires is aborted and fails with
+// Timedout, as one past its own deadline does. Nested calls can only
+// shorten it. Futures fut hands to tokio::spawn run outside of it.
+pub async fn with_timeout<F: Future>(timeout: Duration, fut: F) -> F::Output {
+    let deadline = Instant::now() + timeout;
+    let deadline = OP_DEADLINE
+        .try_with(|outer| deadline.min(*outer))
+        .unwrap_or(deadline);
+    OP_DEADLINE.scope(deadline, fut).await
+}
+
+// The shorter of deadline and what is left of an enclosing with_timeout.
+fn op_deadline(deadline: Option<Duration>) -> Option<Duration> {
+    let left = OP_DEADLINE
+        .try_with(|end| end.saturating_duration_since(Instant::now()))
+        .ok();
+    match (deadline, left) {
+        (Some(deadline), Some(left)) => Some(deadline.min(left)),
+        (deadline, left) => deadline.or(left),
+    }
+}
+
 #[derive(Debug)]
 pub struct CallbackArg {
     _magic: u32,
@@ -35,6 +73,8 @@ pub struct CallbackArg {
 #[derive(Debug)]
 pub struct DaosEvent {
     event: Option<Box<daos_event_t>>,
+    // keeps the queue alive until the event is finalized
+    queue: Arc<EventQueueInner>,
 }
 
 unsafe extern "C" fn event_callback(
@@ -58,7 +98,8 @@ unsafe extern "C" fn event_callback(
 }
 
 impl DaosEvent {
-    pub fn new(eqh: daos_handle_t) -> Result<Self> {
+    pub fn new(eq: &DaosEventQueue) -> Result<Self> {
+        let eqh = eq.get_handle().unwrap();
         let mut event = Box::new(daos_event_t {
             ev_error: 0,
             ev_private: daos_event__bindgen_ty_1 { space: [0u64; 20] },
@@ -67,10 +108,17 @@ impl DaosEvent {
 
         let ret = unsafe { daos_event_init(event.as_mut(), eqh, ptr::null_mut()) };
         if ret != 0 {
-            return Err(Error::new(ErrorKind::Other, "can't init daos event"));
+            return Err(DaosError::from_call(
+                "daos_event_init",
+                ret,
+                "can't init daos event",
+            ));
         }
 
-        Ok(DaosEvent { event: Some(event) })
+        Ok(DaosEvent {
+            event: Some(event),
+            queue: eq.inner.clone(),
+        })
     }
 
     pub fn as_mut(&mut self) -> &mut daos_event_t {
@@ -92,14 +140,105 @@ impl DaosEvent {
             )
         };
         if ret != 0 {
-            return Err(Error::new(
-                ErrorKind::Other,
+            return Err(DaosError::from_call(
+                "daos_event_register_comp_cb",
+                ret,
                 "can't register event callback",
             ));
         }
 
         Ok(rx)
     }
+
+    // Waits for the operation submitted with this event and returns its rc.
+    // Once the deadline passes the operation is aborted, but its completion
+    // is still awaited so libdaos is done with the event and the caller's
+    // buffers before they can be reused.
+    pub async fn wait(
+        &mut self,
+        rx: oneshot::Receiver<i32>,
+        deadline: Option<Duration>,
+    ) -> Result<i32> {
+        let mut rx = rx;
+        let deadline = op_deadline(deadline);
+        let start = Instant::now();
+        if let Some(ret) = self.poll_inline(&mut rx, deadline) {
+            return Ok(ret);
+        }
+        let deadline = deadline.map(|d| d.saturating_sub(start.elapsed()));
+
+        let res = match deadline {
+            None => rx.await,
+            Some(deadline) => match tokio::time::timeout(deadline, &mut rx).await {
+                Ok(res) => res,
+                Err(_) => {
+                    self.abort();
+                    match rx.await {
+                        Ok(ret) if ret == -(daos_errno_DER_CANCELED as i32) => {
+                            return Err(DaosError::timed_out("operation deadline exceeded"))
+                        }
+                        res => res,
+                    }
+                }
+            },
+        };
+        res.map_err(|_| DaosError::canceled("rx is closed early"))
+    }
+
+    // With DaosEventQueueConfig::inline_poll set, drives the queue from the
+    // waiting worker thread for up to that long, so a fast completion is
+    // seen without waiting for the progress thread to wake the task. Only a
+    // multi-threaded tokio runtime allows block_in_place, elsewhere this
+    // returns None right away like a timed out window.
+    fn poll_inline(
+        &mut self,
+        rx: &mut oneshot::Receiver<i32>,
+        deadline: Option<Duration>,
+    ) -> Option<i32> {
+        let window = match (self.queue.inline_poll, deadline) {
+            (Some(window), Some(deadline)) => window.min(deadline),
+            (window, _) => window?,
+        };
+        let eqh = self.queue.handle?;
+        let multi_thread =
+            Handle::try_current().is_ok_and(|h| h.runtime_flavor() == RuntimeFlavor::MultiThread);
+        if !multi_thread {
+            return None;
+        }
+
+        tokio::task::block_in_place(|| {
+            let start = Instant::now();
+            let mut events = [ptr::null_mut::<daos_event_t>(); 16];
+            loop {
+                match rx.try_recv() {
+                    Ok(ret) => return Some(ret),
+                    Err(oneshot::error::TryRecvError::Closed) => return None,
+                    Err(oneshot::error::TryRecvError::Empty) => {}
+                }
+                let left = window.saturating_sub(start.elapsed());
+                if left.is_zero() {
+                    return None;
+                }
+                // completion callbacks run inside the poll, whichever
+                // thread it is on
+                unsafe {
+                    daos_eq_poll(
+                        eqh,
+                        1,
+                        left.as_micros() as i64,
+                        events.len() as u32,
+                        events.as_mut_ptr(),
+                    )
+                };
+            }
+        })
+    }
+
+    // Asks libdaos to abort the operation, which still completes through
+    // the registered callback.
+    pub fn abort(&mut self) {
+        unsafe { daos_event_abort(self.as_mut()) };
+    }
 }
 
 impl Drop for DaosEvent {
@@ -130,54 +269,192 @@ impl Drop for DaosEvent {
     }
 }
 
-#[derive(Debug)]
+// The progress thread of an event queue competes with application threads,
+// affinity restricts it to the given CPUs, None leaves it unpinned.
+//
+// inline_poll lets a waiting task poll the queue itself for up to the given
+// time before handing over to the progress thread. It saves a thread wakeup
+// per operation on low-latency fabrics at the cost of occupying a tokio
+// worker, so it only pays off for operations that finish within it. Needs
+// a multi-threaded runtime, it is ignored elsewhere.
+//
+// The progress thread reaps up to poll_batch completions per daos_eq_poll,
+// each poll waiting up to poll_timeout for the first one, 10 and 50us when
+// None. A shorter timeout lowers the latency of a completion arriving
+// between polls at the cost of more CPU on the progress thread.
+//
+// queues is how many queues, each with its own progress thread,
+// DaosContainer::connect creates, one when None. The objects and
+// transactions opened from the container take them in turn.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DaosEventQueueConfig {
+    pub affinity: Option<Vec<usize>>,
+    pub inline_poll: Option<Duration>,
+    pub poll_batch: Option<u32>,
+    pub poll_timeout: Option<Duration>,
+    pub queues: Option<usize>,
+}
+
+const DEFAULT_POLL_BATCH: u32 = 10;
+const DEFAULT_POLL_TIMEOUT: Duration = Duration::from_micros(50);
+
+extern "C" {
+    fn sched_setaffinity(
+        pid: std::os::raw::c_int,
+        cpusetsize: usize,
+        mask: *const u64,
+    ) -> std::os::raw::c_int;
+}
+
+// glibc's cpu_set_t, CPU_SETSIZE is 1024
+type CpuSet = [u64; 16];
+
+fn cpu_set(cpus: &[usize]) -> Result<CpuSet> {
+    if cpus.is_empty() {
+        return Err(DaosError::inval("empty event queue affinity"));
+    }
+    let mut set: CpuSet = [0; 16];
+    for &cpu in cpus {
+        if cpu >= set.len() * 64 {
+            return Err(DaosError::inval(format!("cpu {} is out of range", cpu)));
+        }
+        set[cpu / 64] |= 1u64 << (cpu % 64);
+    }
+    Ok(set)
+}
+
+impl DaosEventQueueConfig {
+    // What with_config rejects before creating anything, for callers that
+    // must not acquire other resources with a config that can't work.
+    pub(crate) fn check(&self) -> Result<()> {
+        if let Some(cpus) = self.affinity.as_ref() {
+            cpu_set(cpus)?;
+        }
+        if self.poll_batch == Some(0) {
+            return Err(DaosError::inval("event queue poll batch must not be zero"));
+        }
+        Ok(())
+    }
+}
+
+// Pins the calling thread to set.
+fn pin_thread(set: &CpuSet) -> Result<()> {
+    let ret = unsafe { sched_setaffinity(0, mem::size_of::<CpuSet>(), set.as_ptr()) };
+    if ret != 0 {
+        return Err(DaosError::inval(format!(
+            "can't pin event queue thread: {}",
+            io::Error::last_os_error()
+        )));
+    }
+    Ok(())
+}
+
+// The eq and its polling thread are torn down once the last DaosEventQueue
+// clone and the last DaosEvent created from it are gone.
+#[derive(Clone)]
 pub struct DaosEventQueue {
+    inner: Arc<EventQueueInner>,
+}
+
+struct EventQueueInner {
     handle: Option<daos_handle_t>,
+    inline_poll: Option<Duration>,
     sender: mpsc::Sender<i32>,
     thread_handle: Option<thread::JoinHandle<()>>,
+    // the eq is destroyed before libdaos may be finalized
+    _runtime: Option<DaosRuntime>,
 }
 
 impl DaosEventQueue {
     pub fn new() -> Result<DaosEventQueue> {
+        Self::with_config(&DaosEventQueueConfig::default())
+    }
+
+    pub fn with_config(config: &DaosEventQueueConfig) -> Result<DaosEventQueue> {
+        config.check()?;
+        let affinity = match config.affinity.as_ref() {
+            Some(cpus) => Some(cpu_set(cpus)?),
+            None => None,
+        };
+        let n_events = config.poll_batch.unwrap_or(DEFAULT_POLL_BATCH);
+        let poll_timeout = config
+            .poll_timeout
+            .unwrap_or(DEFAULT_POLL_TIMEOUT)
+            .as_micros()
+            .min(i64::MAX as u128) as i64;
+
         let mut eqh: daos_handle_t = daos_handle_t { cookie: 0u64 };
         let res = unsafe { daos_eq_create(&mut eqh) };
 
         let (snd, rcv) = mpsc::channel::<i32>();
+        let (pin_snd, pin_rcv) = mpsc::channel::<Result<()>>();
 
         let t_handle = thread::spawn(move || {
-            let n_events = 10u32;
-            let mut events = std::vec::Vec::with_capacity(n_events as usize);
-            events.resize(10, ptr::null_mut::<daos_event_t>());
+            let pinned = affinity.as_ref().map_or(Ok(()), pin_thread);
+            let _ = pin_snd.send(pinned);
+
+            let mut events = vec![ptr::null_mut::<daos_event_t>(); n_events as usize];
 
             while rcv.try_recv().is_err() {
-                let ret = unsafe { daos_eq_poll(eqh, 1, 50, n_events, events.as_mut_ptr()) };
+                let ret =
+                    unsafe { daos_eq_poll(eqh, 1, poll_timeout, n_events, events.as_mut_ptr()) };
                 if ret < 0 {
-                    eprintln!("pool event queue failed, ret={}", ret);
+                    let err = DaosError::from_call("daos_eq_poll", ret, "pool event queue failed");
+                    eprintln!("{}", err);
                 }
             }
         });
 
         if res == 0 {
-            Ok(DaosEventQueue {
-                handle: Some(eqh),
-                sender: snd,
-                thread_handle: Some(t_handle),
-            })
+            let eq = DaosEventQueue {
+                inner: Arc::new(EventQueueInner {
+                    handle: Some(eqh),
+                    inline_poll: config.inline_poll,
+                    sender: snd,
+                    thread_handle: Some(t_handle),
+                    _runtime: DaosRuntime::current(),
+                }),
+            };
+            // on failure dropping eq stops the thread and destroys the eq
+            pin_rcv
+                .recv()
+                .map_err(|_| DaosError::canceled("event queue thread exited"))??;
+            Ok(eq)
         } else {
-            Err(Error::new(ErrorKind::Other, "can't create event queue"))
+            Err(DaosError::from_call(
+                "daos_eq_create",
+                res,
+                "can't create event queue",
+            ))
         }
     }
 
     pub fn get_handle(&self) -> Option<daos_handle_t> {
-        self.handle.clone()
+        self.inner.handle.clone()
     }
 
     pub fn create_event(&self) -> Result<DaosEvent> {
-        DaosEvent::new(self.handle.unwrap())
+        DaosEvent::new(self)
     }
 }
 
-impl Drop for DaosEventQueue {
+impl fmt::Debug for DaosEventQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl fmt::Debug for EventQueueInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DaosEventQueue")
+            .field("handle", &HandleFmt(self.handle))
+            .field("inline_poll", &self.inline_poll)
+            .field("polling", &self.thread_handle.is_some())
+            .finish()
+    }
+}
+
+impl Drop for EventQueueInner {
     fn drop(&mut self) {
         if let Some(eqh) = self.handle {
             match self.sender.send(0) {
@@ -204,6 +481,32 @@ mod tests {
 
     use crate::daos_pool::DaosPool;
 
+    #[tokio::test]
+    async fn test_with_timeout() {
+        assert_eq!(op_deadline(None), None);
+        let secs = Some(Duration::from_secs(1));
+        assert_eq!(op_deadline(secs), secs);
+
+        with_timeout(Duration::from_millis(100), async {
+            let left = op_deadline(None).unwrap();
+            assert!(left <= Duration::from_millis(100));
+            assert_eq!(op_deadline(Some(Duration::ZERO)), Some(Duration::ZERO));
+            assert!(op_deadline(secs).unwrap() <= Duration::from_millis(100));
+
+            // an inner timeout can't outlast the outer one
+            with_timeout(Duration::from_secs(10), async {
+                assert!(op_deadline(None).unwrap() <= Duration::from_millis(100));
+            })
+            .await;
+            with_timeout(Duration::from_millis(10), async {
+                assert!(op_deadline(None).unwrap() <= Duration::from_millis(10));
+            })
+            .await;
+        })
+        .await;
+        assert_eq!(op_deadline(secs), secs);
+    }
+
     #[tokio::test]
     async fn test_create_async_event1() {
         let _pool = DaosPool::new("pool1");
@@ -222,4 +525,107 @@ mod tests {
         let eqh = DaosEventQueue::new().unwrap();
         drop(eqh);
     }
+
+    #[test]
+    fn test_event_queue_affinity() {
+        let _pool = DaosPool::new("pool1");
+
+        let config = DaosEventQueueConfig {
+            affinity: Some(vec![0]),
+            ..Default::default()
+        };
+        let eqh = DaosEventQueue::with_config(&config).unwrap();
+        assert!(eqh.create_event().is_ok());
+
+        for cpus in [vec![], vec![1024]] {
+            let config = DaosEventQueueConfig {
+                affinity: Some(cpus),
+                ..Default::default()
+            };
+            let res = DaosEventQueue::with_config(&config);
+            assert!(matches!(res, Err(DaosError::Inval(_))));
+        }
+    }
+
+    #[test]
+    fn test_event_queue_polling() {
+        let _pool = DaosPool::new("pool1");
+
+        let config = DaosEventQueueConfig {
+            poll_batch: Some(64),
+            poll_timeout: Some(Duration::from_micros(5)),
+            ..Default::default()
+        };
+        let eqh = DaosEventQueue::with_config(&config).unwrap();
+        assert!(eqh.create_event().is_ok());
+
+        let config = DaosEventQueueConfig {
+            poll_batch: Some(0),
+            ..Default::default()
+        };
+        let res = DaosEventQueue::with_config(&config);
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+    }
+
+    #[test]
+    fn test_cpu_set() {
+        let set = cpu_set(&[0, 3, 65]).unwrap();
+        assert_eq!(set[0], 0b1001);
+        assert_eq!(set[1], 0b10);
+        assert!(set[2..].iter().all(|w| *w == 0));
+    }
+
+    #[test]
+    fn test_event_keeps_queue_alive() {
+        let _pool = DaosPool::new("pool1");
+
+        let eqh = DaosEventQueue::new().unwrap();
+        let evt = eqh.create_event().unwrap();
+        let weak = Arc::downgrade(&eqh.inner);
+        drop(eqh);
+        assert!(weak.upgrade().is_some());
+
+        drop(evt);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_inline_poll_without_completion() {
+        let _pool = DaosPool::new("pool1");
+
+        let config = DaosEventQueueConfig {
+            inline_poll: Some(Duration::from_millis(5)),
+            ..Default::default()
+        };
+        let eqh = DaosEventQueue::with_config(&config).unwrap();
+        let mut evt = eqh.create_event().unwrap();
+        let mut rx = evt.register_callback().unwrap();
+
+        // nothing was submitted, the window runs out
+        let start = Instant::now();
+        assert_eq!(evt.poll_inline(&mut rx, None), None);
+        assert!(start.elapsed() >= Duration::from_millis(5));
+
+        // and is capped by the deadline
+        let start = Instant::now();
+        assert_eq!(evt.poll_inline(&mut rx, Some(Duration::ZERO)), None);
+        assert!(start.elapsed() < Duration::from_millis(5));
+    }
+
+    #[tokio::test]
+    async fn test_inline_poll_needs_multi_thread() {
+        let _pool = DaosPool::new("pool1");
+
+        let config = DaosEventQueueConfig {
+            inline_poll: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let eqh = DaosEventQueue::with_config(&config).unwrap();
+        let mut evt = eqh.create_event().unwrap();
+        let mut rx = evt.register_callback().unwrap();
+
+        let start = Instant::now();
+        assert_eq!(evt.poll_inline(&mut rx, None), None);
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
 }
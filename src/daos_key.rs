@@ -0,0 +1,198 @@
+//
+//  Copyright (C) 2024 github.com/chel-data
+//
+//  This program is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  This program is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+
+// Order-preserving encoding of composite keys. Comparing two encoded keys
+// byte by byte gives the same order as comparing their components one after
+// another, which is what range scans over lexical objects
+// (DAOS_OT_DKEY_LEXICAL, DAOS_OT_AKEY_LEXICAL, ...) rely on.
+//
+// Integers are stored big-endian, signed ones with the sign bit flipped.
+// Strings and byte strings escape 0x00 as 0x00 0xff and end with 0x00 0x01,
+// so a shorter component always sorts before a longer one sharing its prefix.
+
+use crate::error::{DaosError, Result};
+
+const ESCAPE: u8 = 0x00;
+const ESCAPED_ZERO: u8 = 0xff;
+const TERMINATOR: u8 = 0x01;
+
+#[derive(Debug, Clone, Default)]
+pub struct KeyEncoder {
+    buf: Vec<u8>,
+}
+
+impl KeyEncoder {
+    pub fn new() -> Self {
+        KeyEncoder { buf: Vec::new() }
+    }
+
+    pub fn push_u64(&mut self, val: u64) -> &mut Self {
+        self.buf.extend_from_slice(&val.to_be_bytes());
+        self
+    }
+
+    pub fn push_i64(&mut self, val: i64) -> &mut Self {
+        self.push_u64((val as u64) ^ (1u64 << 63))
+    }
+
+    pub fn push_bytes(&mut self, val: &[u8]) -> &mut Self {
+        for &b in val {
+            self.buf.push(b);
+            if b == ESCAPE {
+                self.buf.push(ESCAPED_ZERO);
+            }
+        }
+        self.buf.push(ESCAPE);
+        self.buf.push(TERMINATOR);
+        self
+    }
+
+    pub fn push_str(&mut self, val: &str) -> &mut Self {
+        self.push_bytes(val.as_bytes())
+    }
+
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn finish(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buf)
+    }
+}
+
+// Reads components back in the order they were pushed.
+#[derive(Debug, Clone)]
+pub struct KeyDecoder<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> KeyDecoder<'a> {
+    pub fn new(key: &'a [u8]) -> Self {
+        KeyDecoder { buf: key }
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64> {
+        if self.buf.len() < 8 {
+            return Err(DaosError::inval("key too short for u64 component"));
+        }
+        let (head, rest) = self.buf.split_at(8);
+        self.buf = rest;
+        Ok(u64::from_be_bytes(head.try_into().unwrap()))
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64> {
+        Ok((self.read_u64()? ^ (1u64 << 63)) as i64)
+    }
+
+    pub fn read_bytes(&mut self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut idx = 0;
+        while idx < self.buf.len() {
+            let b = self.buf[idx];
+            if b != ESCAPE {
+                out.push(b);
+                idx += 1;
+                continue;
+            }
+            match self.buf.get(idx + 1) {
+                Some(&ESCAPED_ZERO) => {
+                    out.push(ESCAPE);
+                    idx += 2;
+                }
+                Some(&TERMINATOR) => {
+                    self.buf = &self.buf[idx + 2..];
+                    return Ok(out);
+                }
+                _ => return Err(DaosError::inval("malformed escape in key component")),
+            }
+        }
+        Err(DaosError::inval("unterminated key component"))
+    }
+
+    pub fn read_str(&mut self) -> Result<String> {
+        String::from_utf8(self.read_bytes()?)
+            .map_err(|_| DaosError::inval("key component is not utf-8"))
+    }
+
+    pub fn remaining(&self) -> &'a [u8] {
+        self.buf
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_roundtrip() {
+        let key = KeyEncoder::new()
+            .push_str("tenant\0a")
+            .push_u64(42)
+            .push_i64(-7)
+            .push_bytes(&[0u8, 1u8, 0xffu8])
+            .finish();
+
+        let mut dec = KeyDecoder::new(&key);
+        assert_eq!(dec.read_str().unwrap(), "tenant\0a");
+        assert_eq!(dec.read_u64().unwrap(), 42);
+        assert_eq!(dec.read_i64().unwrap(), -7);
+        assert_eq!(dec.read_bytes().unwrap(), vec![0u8, 1u8, 0xffu8]);
+        assert!(dec.is_empty());
+        assert!(dec.read_u64().is_err());
+    }
+
+    #[test]
+    fn test_key_order() {
+        let enc = |tenant: &str, ts: i64| KeyEncoder::new().push_str(tenant).push_i64(ts).finish();
+
+        let mut keys = vec![
+            enc("b", 1),
+            enc("a", 100),
+            enc("ab", -5),
+            enc("a", -100),
+            enc("a\0", 0),
+            enc("a", 0),
+        ];
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![
+                enc("a", -100),
+                enc("a", 0),
+                enc("a", 100),
+                enc("a\0", 0),
+                enc("ab", -5),
+                enc("b", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_key_malformed() {
+        assert!(KeyDecoder::new(&[b'a']).read_bytes().is_err());
+        assert!(KeyDecoder::new(&[b'a', 0u8, 7u8]).read_bytes().is_err());
+        assert!(KeyDecoder::new(&[0xc3u8, 0u8, 1u8]).read_str().is_err());
+    }
+}
@@ -0,0 +1,652 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// The flat key-value API of daos_kv.h. A KV object maps string keys to
+// values, libdaos lays them out as dkeys of an object of type
+// DAOS_OT_KV_HASHED. Every call is driven through the container's event
+// queue like the async ops of DaosObject.
+
+use crate::bindings::{
+    d_iov_t, d_sg_list_t, daos_errno_DER_NONEXIST, daos_kv_close, daos_kv_get, daos_kv_list,
+    daos_kv_open, daos_kv_put, daos_kv_remove, daos_obj_generate_oid2, daos_oclass_hints_t,
+    daos_oclass_id_t, daos_otype_t_DAOS_OT_KV_HASHED, DAOS_OO_RO, DAOS_OO_RW, DAOS_TXN_NONE,
+};
+use crate::daos_cont::{DaosContainer, DaosDeadlines};
+use crate::daos_event::{DaosEvent, DaosEventQueue};
+use crate::daos_obj::{DaosKeyList, KeyListPool};
+use crate::daos_oid_allocator::DaosAsyncOidAllocator;
+use crate::daos_pool::{DaosHandle, DaosObjectId};
+use crate::daos_txn::DaosTxn;
+use crate::error::{DaosError, DaosErrorContext, Result};
+use futures_core::Stream;
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::fmt;
+use std::future::{self, Future};
+use std::pin::Pin;
+use std::ptr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::oneshot;
+
+pub struct DaosKvObject {
+    pub oid: DaosObjectId,
+    handle: Option<DaosHandle>,
+    event_que: Option<DaosEventQueue>,
+    deadlines: DaosDeadlines,
+    read_only: bool,
+}
+
+// daos_kv takes nul terminated keys.
+fn kv_key(key: &[u8]) -> Result<CString> {
+    if key.is_empty() {
+        return Err(DaosError::inval("kv key must not be empty"));
+    }
+    CString::new(key).map_err(|_| DaosError::inval("kv key contains a nul byte"))
+}
+
+impl DaosKvObject {
+    // A new KV object of class cid, its oid taken from oid_allocator.
+    pub async fn create_async(
+        cont: &DaosContainer,
+        oid_allocator: Arc<DaosAsyncOidAllocator>,
+        cid: daos_oclass_id_t,
+        hints: daos_oclass_hints_t,
+    ) -> Result<Box<DaosKvObject>> {
+        let cont_hdl = cont
+            .get_handle()
+            .ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+        let mut oid = oid_allocator.allocate().await?;
+        let ret = unsafe {
+            daos_obj_generate_oid2(
+                cont_hdl,
+                &mut oid,
+                daos_otype_t_DAOS_OT_KV_HASHED,
+                cid,
+                hints,
+                0,
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_obj_generate_oid2",
+                ret,
+                "can't generate object id",
+            ));
+        }
+        Self::open_async(cont, oid, false).await
+    }
+
+    pub async fn open_async(
+        cont: &DaosContainer,
+        oid: DaosObjectId,
+        read_only: bool,
+    ) -> Result<Box<DaosKvObject>> {
+        let eq = cont.get_event_queue().cloned();
+        let deadlines = cont.get_deadlines();
+        let res: Result<DaosHandle> = async {
+            let cont_hdl = cont
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+            let mut event = eq
+                .as_ref()
+                .ok_or_else(|| DaosError::no_handle("event queue is nil"))?
+                .create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut kv_hdl = Box::new(DaosHandle { cookie: 0u64 });
+            let ret = unsafe {
+                daos_kv_open(
+                    cont_hdl,
+                    oid,
+                    if read_only { DAOS_OO_RO } else { DAOS_OO_RW },
+                    kv_hdl.as_mut(),
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_kv_open",
+                    ret,
+                    "can't open kv object",
+                ));
+            }
+
+            match event.wait(rx, deadlines.metadata).await? {
+                0 => Ok(*kv_hdl),
+                ret => Err(DaosError::from_call(
+                    "daos_kv_open",
+                    ret,
+                    "async open kv object fail",
+                )),
+            }
+        }
+        .await;
+        let handle = res.map_err(|e| e.with_context(DaosErrorContext::new("open_async", oid)))?;
+        Ok(Box::new(DaosKvObject {
+            oid,
+            handle: Some(handle),
+            event_que: eq,
+            deadlines,
+            read_only,
+        }))
+    }
+
+    pub fn get_handle(&self) -> Option<DaosHandle> {
+        self.handle
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    // Inserts key, or replaces its value.
+    pub async fn put_async(
+        &self,
+        txn: &DaosTxn,
+        key: impl AsRef<[u8]>,
+        value: &[u8],
+    ) -> Result<()> {
+        let key = key.as_ref();
+        let res: Result<()> = async {
+            if self.read_only {
+                return Err(DaosError::read_only("put read-only kv object"));
+            }
+            let c_key = kv_key(key)?;
+            let (mut event, rx) = self.prepare_event()?;
+            let ret = unsafe {
+                daos_kv_put(
+                    self.get_handle().unwrap(),
+                    txn.get_handle().unwrap_or(DAOS_TXN_NONE),
+                    0,
+                    c_key.as_ptr(),
+                    value.len() as u64,
+                    value.as_ptr() as *const std::os::raw::c_void,
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call("daos_kv_put", ret, "can't put kv"));
+            }
+
+            match event.wait(rx, self.deadlines.bulk).await? {
+                0 => Ok(()),
+                ret => Err(DaosError::from_call(
+                    "daos_kv_put",
+                    ret,
+                    "async put operation fail",
+                )),
+            }
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.key_context("put_async", key)))
+    }
+
+    // Reads the value of key into buf and returns its size, None when key
+    // isn't there. Fails with Rec2Big carrying the size of the value when
+    // buf is too small.
+    pub async fn get_async(
+        &self,
+        txn: &DaosTxn,
+        key: impl AsRef<[u8]>,
+        buf: &mut [u8],
+    ) -> Result<Option<usize>> {
+        let key = key.as_ref();
+        self.get_into(txn, key, Some(buf))
+            .await
+            .map_err(|e| e.with_context(self.key_context("get_async", key)))
+    }
+
+    // The value of key, None when key isn't there. The size is probed
+    // first so the value is read into a Vec of exactly its size; a value
+    // that grew in between is read again at its new size.
+    pub async fn get_auto_async(
+        &self,
+        txn: &DaosTxn,
+        key: impl AsRef<[u8]>,
+    ) -> Result<Option<Vec<u8>>> {
+        let key = key.as_ref();
+        let res: Result<Option<Vec<u8>>> = async {
+            let mut size = match self.get_into(txn, key, None).await? {
+                Some(size) => size,
+                None => return Ok(None),
+            };
+            loop {
+                let mut value = vec![0u8; size];
+                match self.get_into(txn, key, Some(&mut value)).await {
+                    Ok(Some(len)) => {
+                        value.truncate(len);
+                        return Ok(Some(value));
+                    }
+                    Ok(None) => return Ok(None),
+                    Err(DaosError::Rec2Big {
+                        required: Some(required),
+                        ..
+                    }) if required > size => size = required,
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.key_context("get_auto_async", key)))
+    }
+
+    // daos_kv_get into buf, or only the size of the value when buf is None.
+    async fn get_into(
+        &self,
+        txn: &DaosTxn,
+        key: &[u8],
+        buf: Option<&mut [u8]>,
+    ) -> Result<Option<usize>> {
+        let c_key = kv_key(key)?;
+        let (mut event, rx) = self.prepare_event()?;
+        // in: the room in buf, out: the size of the value
+        let mut size = Box::new(buf.as_ref().map_or(0, |buf| buf.len()) as u64);
+        let ret = unsafe {
+            daos_kv_get(
+                self.get_handle().unwrap(),
+                txn.get_handle().unwrap_or(DAOS_TXN_NONE),
+                0,
+                c_key.as_ptr(),
+                size.as_mut(),
+                buf.map_or(ptr::null_mut(), |buf| buf.as_mut_ptr() as *mut _),
+                event.as_mut(),
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call("daos_kv_get", ret, "can't get kv"));
+        }
+
+        match event.wait(rx, self.deadlines.bulk).await? {
+            0 => Ok(Some(*size as usize)),
+            ret if ret == -(daos_errno_DER_NONEXIST as i32) => Ok(None),
+            ret => Err(
+                DaosError::from_call("daos_kv_get", ret, "async get operation fail")
+                    .with_required_size(*size as usize),
+            ),
+        }
+    }
+
+    pub async fn remove_async(&self, txn: &DaosTxn, key: impl AsRef<[u8]>) -> Result<()> {
+        let key = key.as_ref();
+        let res: Result<()> = async {
+            if self.read_only {
+                return Err(DaosError::read_only("remove read-only kv object"));
+            }
+            let c_key = kv_key(key)?;
+            let (mut event, rx) = self.prepare_event()?;
+            let ret = unsafe {
+                daos_kv_remove(
+                    self.get_handle().unwrap(),
+                    txn.get_handle().unwrap_or(DAOS_TXN_NONE),
+                    0,
+                    c_key.as_ptr(),
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_kv_remove",
+                    ret,
+                    "can't remove kv",
+                ));
+            }
+
+            match event.wait(rx, self.deadlines.metadata).await? {
+                0 => Ok(()),
+                ret => Err(DaosError::from_call(
+                    "daos_kv_remove",
+                    ret,
+                    "async remove operation fail",
+                )),
+            }
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.key_context("remove_async", key)))
+    }
+
+    // Fetches the next page of keys into key_lst. Call until
+    // key_lst.reach_end(), the keys come back in no particular order.
+    pub async fn list_async(
+        &self,
+        txn: &DaosTxn,
+        mut key_lst: Box<DaosKeyList>,
+    ) -> Result<Box<DaosKeyList>> {
+        let res: Result<()> = async {
+            if key_lst.reach_end() {
+                *key_lst.ndesc = 0;
+                return Ok(());
+            }
+
+            loop {
+                let (mut event, rx) = self.prepare_event()?;
+                key_lst.prepare_next_query();
+
+                let mut sg_iov = Box::new(d_iov_t {
+                    iov_buf: key_lst.out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
+                    iov_buf_len: key_lst.out_buf.len(),
+                    iov_len: key_lst.out_buf.len(),
+                });
+                let mut sgl = Box::new(d_sg_list_t {
+                    sg_nr: 1,
+                    sg_nr_out: 0,
+                    sg_iovs: sg_iov.as_mut(),
+                });
+                let ret = unsafe {
+                    daos_kv_list(
+                        self.get_handle().unwrap(),
+                        txn.get_handle().unwrap_or(DAOS_TXN_NONE),
+                        key_lst.ndesc.as_mut(),
+                        key_lst.key_descs.as_mut_ptr(),
+                        sgl.as_mut(),
+                        key_lst.anchor.as_mut(),
+                        event.as_mut(),
+                    )
+                };
+                if ret != 0 {
+                    return Err(DaosError::from_call("daos_kv_list", ret, "list kv fail"));
+                }
+
+                let ret = event.wait(rx, self.deadlines.metadata).await?;
+                if ret != 0 {
+                    // on KEY2BIG the first descriptor holds the size of the
+                    // key that didn't fit
+                    let required = key_lst.key_descs[0].kd_key_len as usize;
+                    let err = DaosError::from_call("daos_kv_list", ret, "async list kv fail")
+                        .with_required_size(required);
+                    if key_lst.grow_to_fit(&err) {
+                        continue;
+                    }
+                    return Err(err);
+                }
+                key_lst.adapt_to_last_query();
+                return Ok(());
+            }
+        }
+        .await;
+        res.map_err(|e| e.with_context(DaosErrorContext::new("list_async", self.oid)))?;
+        Ok(key_lst)
+    }
+
+    // Every key of the object, listed a page at a time.
+    pub fn keys<'a>(&'a self, txn: &'a DaosTxn) -> DaosKvKeys<'a> {
+        DaosKvKeys {
+            kv: self,
+            txn,
+            key_lst: Some(KeyListPool::shared().checkout()),
+            keys: VecDeque::new(),
+            pending: None,
+        }
+    }
+
+    // An event on the object's queue, checking the handles a call needs.
+    fn prepare_event(&self) -> Result<(DaosEvent, oneshot::Receiver<i32>)> {
+        let eq = self
+            .event_que
+            .as_ref()
+            .ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+        if self.handle.is_none() {
+            return Err(DaosError::no_handle("uninitialized kv object"));
+        }
+        let mut event = eq.create_event()?;
+        let rx = event.register_callback()?;
+        Ok((event, rx))
+    }
+
+    fn key_context(&self, op: &'static str, key: &[u8]) -> DaosErrorContext {
+        DaosErrorContext {
+            dkey: Some(key.to_vec()),
+            ..DaosErrorContext::new(op, self.oid)
+        }
+    }
+
+    fn close(&mut self) -> Result<()> {
+        if let Some(handle) = self.handle {
+            let ret = unsafe { daos_kv_close(handle, ptr::null_mut()) };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_kv_close",
+                    ret,
+                    "Failed to close kv object",
+                )
+                .with_context(DaosErrorContext::new("close", self.oid)));
+            }
+            self.handle.take();
+        }
+        Ok(())
+    }
+}
+
+type KeysFuture<'a> = Pin<Box<dyn Future<Output = Result<Box<DaosKeyList>>> + Send + 'a>>;
+
+// The keys of a DaosKvObject as a Stream, see DaosKvObject::keys. A page
+// is listed once the keys of the previous one are handed out.
+pub struct DaosKvKeys<'a> {
+    kv: &'a DaosKvObject,
+    txn: &'a DaosTxn,
+    // None once the listing reached its end or failed
+    key_lst: Option<Box<DaosKeyList>>,
+    keys: VecDeque<Vec<u8>>,
+    // listing of the next page, holding key_lst meanwhile
+    pending: Option<KeysFuture<'a>>,
+}
+
+impl DaosKvKeys<'_> {
+    // The next key, None at the end. A failed listing ends the keys after
+    // its error is returned.
+    pub async fn next_async(&mut self) -> Option<Result<Vec<u8>>> {
+        future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    pub async fn collect_async(mut self) -> Result<Vec<Vec<u8>>> {
+        let mut keys = Vec::new();
+        while let Some(key) = self.next_async().await {
+            keys.push(key?);
+        }
+        Ok(keys)
+    }
+}
+
+impl Stream for DaosKvKeys<'_> {
+    type Item = Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(key) = this.keys.pop_front() {
+                return Poll::Ready(Some(Ok(key)));
+            }
+
+            if this.pending.is_none() {
+                let key_lst = match this.key_lst.take() {
+                    Some(key_lst) if !key_lst.reach_end() => key_lst,
+                    Some(key_lst) => {
+                        KeyListPool::shared().checkin(key_lst);
+                        return Poll::Ready(None);
+                    }
+                    None => return Poll::Ready(None),
+                };
+                let (kv, txn) = (this.kv, this.txn);
+                this.pending = Some(Box::pin(kv.list_async(txn, key_lst)));
+            }
+            let res = match this.pending.as_mut().unwrap().as_mut().poll(cx) {
+                Poll::Ready(res) => res,
+                Poll::Pending => return Poll::Pending,
+            };
+            this.pending = None;
+            match res {
+                Ok(key_lst) => {
+                    this.keys.extend(key_lst.keys().map(|key| key.to_vec()));
+                    this.key_lst = Some(key_lst);
+                }
+                Err(e) => return Poll::Ready(Some(Err(e))),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.keys.len(), None)
+    }
+}
+
+impl Drop for DaosKvKeys<'_> {
+    fn drop(&mut self) {
+        if let Some(key_lst) = self.key_lst.take() {
+            KeyListPool::shared().checkin(key_lst);
+        }
+    }
+}
+
+impl fmt::Debug for DaosKvObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DaosKvObject")
+            .field("oid", &self.oid)
+            .field("read_only", &self.read_only)
+            .finish()
+    }
+}
+
+impl Drop for DaosKvObject {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            eprintln!("Failed to drop DAOS kv object: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::OC_UNKNOWN;
+    use crate::daos_pool::DaosPool;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test]
+    async fn test_kv_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let kv = DaosKvObject::create_async(cont.as_ref(), allocator, OC_UNKNOWN, 0)
+            .await
+            .unwrap();
+
+        let txn = DaosTxn::txn_none();
+        kv.put_async(&txn, "alpha", b"one").await.unwrap();
+        kv.put_async(&txn, "beta", b"two").await.unwrap();
+        assert!(kv.put_async(&txn, "bad\0key", b"x").await.is_err());
+
+        let mut buf = [0u8; 16];
+        let size = kv.get_async(&txn, "alpha", &mut buf).await.unwrap();
+        assert_eq!(size, Some(3));
+        assert_eq!(&buf[..3], b"one");
+        let res = kv.get_async(&txn, "beta", &mut buf[..1]).await;
+        assert_eq!(res.unwrap_err().required_size(), Some(3));
+        assert_eq!(kv.get_async(&txn, "gamma", &mut buf).await.unwrap(), None);
+
+        let mut keys = Vec::new();
+        let mut key_lst = DaosKeyList::new();
+        while !key_lst.reach_end() {
+            key_lst = kv.list_async(&txn, key_lst).await.unwrap();
+            keys.extend(key_lst.keys().map(|key| key.to_vec()));
+        }
+        keys.sort();
+        assert_eq!(keys, vec![b"alpha".to_vec(), b"beta".to_vec()]);
+
+        kv.remove_async(&txn, "alpha").await.unwrap();
+        assert_eq!(kv.get_async(&txn, "alpha", &mut buf).await.unwrap(), None);
+
+        let reopened = DaosKvObject::open_async(cont.as_ref(), kv.oid, true)
+            .await
+            .unwrap();
+        assert!(reopened.put_async(&txn, "delta", b"four").await.is_err());
+        let size = reopened.get_async(&txn, "beta", &mut buf).await.unwrap();
+        assert_eq!(size, Some(3));
+    }
+
+    #[tokio::test]
+    async fn test_kv_get_auto() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let kv = DaosKvObject::create_async(cont.as_ref(), allocator, OC_UNKNOWN, 0)
+            .await
+            .unwrap();
+
+        let txn = DaosTxn::txn_none();
+        let value: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+        kv.put_async(&txn, "large", &value).await.unwrap();
+        kv.put_async(&txn, "small", b"x").await.unwrap();
+
+        assert_eq!(kv.get_auto_async(&txn, "large").await.unwrap(), Some(value));
+        assert_eq!(
+            kv.get_auto_async(&txn, "small").await.unwrap(),
+            Some(b"x".to_vec())
+        );
+        assert_eq!(kv.get_auto_async(&txn, "missing").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_kv_keys() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let kv = DaosKvObject::create_async(cont.as_ref(), allocator, OC_UNKNOWN, 0)
+            .await
+            .unwrap();
+
+        // more keys than the first page holds, and one longer than the
+        // initial key buffer
+        let txn = DaosTxn::txn_none();
+        let mut expected: Vec<Vec<u8>> = (0..100)
+            .map(|i| format!("key-{:03}", i).into_bytes())
+            .collect();
+        expected.push(vec![b'k'; 4096]);
+        for key in expected.iter() {
+            kv.put_async(&txn, key, b"v").await.unwrap();
+        }
+
+        let mut keys = kv.keys(&txn).collect_async().await.unwrap();
+        keys.sort();
+        expected.sort();
+        assert_eq!(keys, expected);
+
+        let mut iter = kv.keys(&txn);
+        let mut count = 0;
+        while let Some(key) = iter.next_async().await {
+            key.unwrap();
+            count += 1;
+        }
+        assert_eq!(count, 101);
+    }
+}
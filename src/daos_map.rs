@@ -0,0 +1,224 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Key-value map stored in one object:
+//   dkey = the map key
+//   akey = MAP_AKEY, holding the value as a single value
+// DAOS stores no empty single values, an update of zero bytes would punch
+// the record, so empty values are refused. Dkeys of the object without a
+// MAP_AKEY record are not part of the map and are skipped by iter.
+
+use crate::daos_archive::fetch_single;
+use crate::daos_cont::DaosContainer;
+use crate::daos_obj::{CondFlags, DaosKeyList, DaosObjAsyncOps, DaosObject, KeyListPool};
+use crate::daos_pool::DaosObjectId;
+use crate::daos_txn::DaosTxn;
+use crate::error::{DaosError, Result};
+use std::collections::VecDeque;
+
+const MAP_AKEY: &[u8] = b"v";
+
+#[derive(Debug)]
+pub struct DaosMap {
+    obj: Box<DaosObject>,
+}
+
+impl DaosMap {
+    pub fn new(obj: Box<DaosObject>) -> Self {
+        DaosMap { obj }
+    }
+
+    pub async fn open_async(
+        cont: &DaosContainer,
+        oid: DaosObjectId,
+        read_only: bool,
+    ) -> Result<Self> {
+        Ok(DaosMap::new(
+            DaosObject::open_async(cont, oid, read_only).await?,
+        ))
+    }
+
+    pub fn get_object(&self) -> &DaosObject {
+        self.obj.as_ref()
+    }
+
+    // Inserts key, or replaces its value.
+    pub async fn insert_async(
+        &self,
+        txn: &DaosTxn,
+        key: impl AsRef<[u8]>,
+        value: &[u8],
+    ) -> Result<()> {
+        let key = key.as_ref();
+        if key.is_empty() {
+            return Err(DaosError::inval("map key must not be empty"));
+        }
+        if value.is_empty() {
+            return Err(DaosError::inval("map value must not be empty"));
+        }
+        self.obj
+            .update_async(txn, CondFlags::empty(), key, MAP_AKEY, value)
+            .await
+    }
+
+    pub async fn get_async(&self, txn: &DaosTxn, key: impl AsRef<[u8]>) -> Result<Option<Vec<u8>>> {
+        let key = key.as_ref();
+        if key.is_empty() {
+            return Err(DaosError::inval("map key must not be empty"));
+        }
+        let value = fetch_single(self.obj.as_ref(), txn, key, MAP_AKEY).await?;
+        Ok(if value.is_empty() { None } else { Some(value) })
+    }
+
+    // Tells whether key was in the map.
+    pub async fn remove_async(&self, txn: &DaosTxn, key: impl AsRef<[u8]>) -> Result<bool> {
+        let key = key.as_ref();
+        if key.is_empty() {
+            return Err(DaosError::inval("map key must not be empty"));
+        }
+        self.obj
+            .remove_async(txn, key.to_vec(), Some(MAP_AKEY.to_vec()))
+            .await
+    }
+
+    // Entries in dkey order of the object, listed a page of keys at a time.
+    pub fn iter<'a>(&'a self, txn: &'a DaosTxn) -> DaosMapIter<'a> {
+        DaosMapIter {
+            map: self,
+            txn,
+            key_lst: Some(KeyListPool::shared().checkout()),
+            keys: VecDeque::new(),
+        }
+    }
+}
+
+pub struct DaosMapIter<'a> {
+    map: &'a DaosMap,
+    txn: &'a DaosTxn,
+    // None once the listing reached its end or failed
+    key_lst: Option<Box<DaosKeyList>>,
+    keys: VecDeque<Vec<u8>>,
+}
+
+impl DaosMapIter<'_> {
+    // The next key and value, None at the end. A failed listing ends the
+    // iteration after its error is returned.
+    pub async fn next_async(&mut self) -> Option<Result<(Vec<u8>, Vec<u8>)>> {
+        loop {
+            if let Some(key) = self.keys.pop_front() {
+                match fetch_single(self.map.obj.as_ref(), self.txn, &key, MAP_AKEY).await {
+                    // not a map entry, or removed since it was listed
+                    Ok(value) if value.is_empty() => continue,
+                    Ok(value) => return Some(Ok((key, value))),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+
+            let key_lst = self.key_lst.take()?;
+            if key_lst.reach_end() {
+                KeyListPool::shared().checkin(key_lst);
+                return None;
+            }
+            match self.map.obj.list_dkey_async(self.txn, key_lst).await {
+                Ok(key_lst) => {
+                    self.keys.extend(key_lst.keys().map(|key| key.to_vec()));
+                    self.key_lst = Some(key_lst);
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    pub async fn collect_async(mut self) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut entries = Vec::new();
+        while let Some(entry) = self.next_async().await {
+            entries.push(entry?);
+        }
+        Ok(entries)
+    }
+}
+
+impl Drop for DaosMapIter<'_> {
+    fn drop(&mut self) {
+        if let Some(key_lst) = self.key_lst.take() {
+            KeyListPool::shared().checkin(key_lst);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::{daos_otype_t_DAOS_OT_MULTI_HASHED, OC_UNKNOWN};
+    use crate::daos_oid_allocator::DaosAsyncOidAllocator;
+    use crate::daos_pool::DaosPool;
+    use std::sync::Arc;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test]
+    async fn test_map() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .unwrap();
+
+        let map = DaosMap::new(obj);
+        let txn = DaosTxn::txn_none();
+        map.insert_async(&txn, "apple", b"red").await.unwrap();
+        map.insert_async(&txn, "banana", b"yellow").await.unwrap();
+        map.insert_async(&txn, "apple", b"green").await.unwrap();
+        assert!(map.insert_async(&txn, "cherry", b"").await.is_err());
+
+        assert_eq!(
+            map.get_async(&txn, "apple").await.unwrap(),
+            Some(b"green".to_vec())
+        );
+        assert_eq!(map.get_async(&txn, "cherry").await.unwrap(), None);
+
+        let mut entries = map.iter(&txn).collect_async().await.unwrap();
+        entries.sort();
+        assert_eq!(
+            entries,
+            vec![
+                (b"apple".to_vec(), b"green".to_vec()),
+                (b"banana".to_vec(), b"yellow".to_vec()),
+            ]
+        );
+
+        assert!(map.remove_async(&txn, "apple").await.unwrap());
+        assert!(!map.remove_async(&txn, "apple").await.unwrap());
+        assert_eq!(map.get_async(&txn, "apple").await.unwrap(), None);
+        let entries = map.iter(&txn).collect_async().await.unwrap();
+        assert_eq!(entries.len(), 1);
+    }
+}
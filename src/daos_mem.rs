@@ -0,0 +1,1422 @@
+//
+//  Copyright (C) 2024 github.com/chel-data
+//
+//  This program is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  This program is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+
+// In-process stand-ins for pools, containers and objects, enabled by the
+// `memory-backend` feature. MemObject implements DaosObjSyncOps and
+// DaosObjAsyncOps with the conditional flag semantics of DaosObject, so
+// code written against the traits runs without a DAOS cluster, or without
+// libdaos when built with --no-default-features. Transactions are accepted
+// but not isolated, and array values are plain byte vectors.
+
+use crate::bindings::{
+    daos_anchor_t, daos_anchor_type_t_DAOS_ANCHOR_TYPE_EOF, daos_anchor_type_t_DAOS_ANCHOR_TYPE_KEY,
+};
+#[cfg(feature = "list-obj")]
+use crate::daos_obj::DaosObjList;
+use crate::daos_obj::{
+    check_fetch_flags, check_punch_flags, check_update_flags, per_akey_flags, records_in,
+    write_uninit, CondFlags, DaosAkeyFetch, DaosAkeyUpdate, DaosBatchUpdate, DaosDefaultFlags,
+    DaosExtent, DaosKeyList, DaosObjAsyncOps, DaosObjSyncOps, EpochValue, FetchResult, RecxFetch,
+    RecxList, AKEY_FETCH_FLAGS, AKEY_UPDATE_FLAGS, DAOS_COND_AKEY_FETCH, DAOS_COND_AKEY_INSERT,
+    DAOS_COND_AKEY_UPDATE, DAOS_COND_DKEY_FETCH, DAOS_COND_DKEY_INSERT, DAOS_COND_DKEY_UPDATE,
+    DAOS_COND_PER_AKEY,
+};
+use crate::daos_pool::DaosObjectId;
+use crate::daos_txn::DaosTxn;
+use crate::error::{DaosError, Result};
+#[cfg(feature = "bytes")]
+use bytes::{Bytes, BytesMut};
+use std::collections::{BTreeMap, HashMap};
+use std::future::{self, Future};
+use std::io::{IoSlice, IoSliceMut};
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Same starting point as the real oid allocator, ids below are reserved.
+const MEM_OID_START: u64 = 1024;
+
+type MemRecords = BTreeMap<Vec<u8>, BTreeMap<Vec<u8>, Vec<u8>>>;
+
+#[derive(Debug)]
+pub struct MemPool {
+    pub label: String,
+    containers: Mutex<HashMap<String, Arc<MemContainer>>>,
+}
+
+impl MemPool {
+    pub fn new(label: &str) -> Self {
+        MemPool {
+            label: label.to_string(),
+            containers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    // Containers are created on first use and shared by later lookups.
+    pub fn container(&self, label: &str) -> Arc<MemContainer> {
+        let mut conts = self.containers.lock().unwrap();
+        conts
+            .entry(label.to_string())
+            .or_insert_with(|| Arc::new(MemContainer::new(label)))
+            .clone()
+    }
+}
+
+#[derive(Debug)]
+pub struct MemContainer {
+    pub label: String,
+    objects: Mutex<HashMap<DaosObjectId, Arc<Mutex<MemRecords>>>>,
+    next_oid: AtomicU64,
+}
+
+impl MemContainer {
+    fn new(label: &str) -> Self {
+        MemContainer {
+            label: label.to_string(),
+            objects: Mutex::new(HashMap::new()),
+            next_oid: AtomicU64::new(MEM_OID_START),
+        }
+    }
+
+    pub fn create_object(&self) -> Box<MemObject> {
+        let lo = self.next_oid.fetch_add(1, Ordering::Relaxed);
+        let oid = DaosObjectId { lo, hi: 0 };
+        self.open_object(oid, false)
+    }
+
+    // Like daos_obj_open, opening an unknown oid yields an empty object.
+    pub fn open_object(&self, oid: DaosObjectId, read_only: bool) -> Box<MemObject> {
+        let mut objs = self.objects.lock().unwrap();
+        let records = objs.entry(oid).or_default().clone();
+        Box::new(MemObject {
+            oid,
+            read_only,
+            records,
+            default_flags: DaosDefaultFlags::default(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct MemObject {
+    pub oid: DaosObjectId,
+    read_only: bool,
+    records: Arc<Mutex<MemRecords>>,
+    default_flags: DaosDefaultFlags,
+}
+
+// Listing positions are indexes into the sorted keys, kept in the first
+// bytes of the anchor buffer.
+fn anchor_index(anchor: &daos_anchor_t) -> usize {
+    u64::from_le_bytes(anchor.da_buf[..8].try_into().unwrap()) as usize
+}
+
+fn set_anchor_index(anchor: &mut daos_anchor_t, idx: usize, eof: bool) {
+    anchor.da_buf[..8].copy_from_slice(&(idx as u64).to_le_bytes());
+    anchor.da_type = if eof {
+        daos_anchor_type_t_DAOS_ANCHOR_TYPE_EOF
+    } else {
+        daos_anchor_type_t_DAOS_ANCHOR_TYPE_KEY
+    } as u16;
+}
+
+// Fills key_lst with the next batch of keys, growing its buffer when the
+// first key doesn't fit like DaosObject does on KEY2BIG.
+fn list_keys<'a>(
+    keys: impl ExactSizeIterator<Item = &'a Vec<u8>>,
+    mut key_lst: Box<DaosKeyList>,
+) -> Box<DaosKeyList> {
+    if key_lst.reach_end() {
+        *key_lst.ndesc = 0;
+        return key_lst;
+    }
+
+    key_lst.prepare_next_query();
+    let total = keys.len();
+    let start = anchor_index(&key_lst.anchor);
+    let (mut nr, mut used) = (0usize, 0usize);
+    for key in keys.skip(start) {
+        if nr == key_lst.key_descs.len() {
+            break;
+        }
+        if used + key.len() > key_lst.out_buf.len() {
+            if nr > 0 {
+                break;
+            }
+            let grown = key.len().max(key_lst.out_buf.len() * 2);
+            key_lst.out_buf.resize(grown, 0);
+        }
+        key_lst.out_buf[used..used + key.len()].copy_from_slice(key);
+        key_lst.key_descs[nr].kd_key_len = key.len() as u64;
+        used += key.len();
+        nr += 1;
+    }
+    *key_lst.ndesc = nr as u32;
+    let next = (start + nr).min(total);
+    set_anchor_index(&mut key_lst.anchor, next, next == total);
+    key_lst.adapt_to_last_query();
+    key_lst
+}
+
+impl MemObject {
+    pub fn set_default_flags(&mut self, default_flags: DaosDefaultFlags) -> Result<()> {
+        check_update_flags(default_flags.update.bits())?;
+        check_fetch_flags(default_flags.fetch.bits())?;
+        self.default_flags = default_flags;
+        Ok(())
+    }
+
+    pub fn get_default_flags(&self) -> DaosDefaultFlags {
+        self.default_flags
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(DaosError::read_only("object is opened read only"));
+        }
+        Ok(())
+    }
+
+    pub fn list_dkeys(&self, _txn: &DaosTxn) -> Result<Vec<Vec<u8>>> {
+        let records = self.records.lock().unwrap();
+        Ok(records.keys().cloned().collect())
+    }
+}
+
+impl DaosObjSyncOps for MemObject {
+    fn punch(&self, txn: &DaosTxn) -> Result<()> {
+        self.punch_with_flags(txn, CondFlags::empty()).map(|_| ())
+    }
+
+    // An object without records counts as missing for DAOS_COND_PUNCH.
+    fn punch_with_flags(&self, _txn: &DaosTxn, flags: CondFlags) -> Result<bool> {
+        check_punch_flags(flags.bits())?;
+        self.check_writable()?;
+
+        let mut records = self.records.lock().unwrap();
+        if records.is_empty() && flags.contains(CondFlags::PUNCH) {
+            return Ok(false);
+        }
+        records.clear();
+        Ok(true)
+    }
+
+    fn fetch(
+        &self,
+        _txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<Vec<u8>> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let flags = self.default_flags.apply_fetch(flags);
+        check_fetch_flags(flags)?;
+
+        let records = self.records.lock().unwrap();
+        let akeys = records.get(dkey);
+        if akeys.is_none() {
+            if flags & DAOS_COND_DKEY_FETCH as u64 != 0 {
+                return Err(DaosError::non_exist("dkey does not exist"));
+            }
+            return Ok(Vec::new());
+        }
+        let value = akeys.unwrap().get(akey);
+        if value.is_none() {
+            if flags & DAOS_COND_AKEY_FETCH as u64 != 0 {
+                return Err(DaosError::non_exist("akey does not exist"));
+            }
+            return Ok(Vec::new());
+        }
+        let value = value.unwrap();
+        if value.len() > max_size as usize {
+            return Err(DaosError::rec2big(
+                format!("record size {} exceeds buffer {}", value.len(), max_size),
+                value.len(),
+            ));
+        }
+        Ok(value.clone())
+    }
+
+    fn fetch_with_size(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<FetchResult> {
+        match self.fetch(txn, flags, dkey, akey, max_size) {
+            Ok(data) => Ok(FetchResult {
+                total_size: data.len(),
+                data,
+                truncated: false,
+            }),
+            Err(DaosError::Rec2Big {
+                required: Some(total_size),
+                ..
+            }) => Ok(FetchResult {
+                data: Vec::new(),
+                total_size,
+                truncated: true,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn update(
+        &self,
+        _txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        data: &[u8],
+    ) -> Result<()> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let flags = self.default_flags.apply_update(flags);
+        check_update_flags(flags)?;
+        self.check_writable()?;
+
+        let mut records = self.records.lock().unwrap();
+        let dkey_exists = records.contains_key(dkey);
+        if dkey_exists && flags & DAOS_COND_DKEY_INSERT as u64 != 0 {
+            return Err(DaosError::exist("dkey already exists"));
+        }
+        if !dkey_exists && flags & DAOS_COND_DKEY_UPDATE as u64 != 0 {
+            return Err(DaosError::non_exist("dkey does not exist"));
+        }
+
+        let akeys = records.entry(dkey.to_vec()).or_default();
+        let akey_exists = akeys.contains_key(akey);
+        if akey_exists && flags & DAOS_COND_AKEY_INSERT as u64 != 0 {
+            return Err(DaosError::exist("akey already exists"));
+        }
+        if !akey_exists && flags & DAOS_COND_AKEY_UPDATE as u64 != 0 {
+            return Err(DaosError::non_exist("akey does not exist"));
+        }
+        akeys.insert(akey.to_vec(), data.to_vec());
+        Ok(())
+    }
+
+    fn punch_dkeys(&self, _txn: &DaosTxn, dkeys: &[Vec<u8>]) -> Result<()> {
+        self.check_writable()?;
+        if dkeys.is_empty() {
+            return Err(DaosError::inval("no keys to punch"));
+        }
+
+        let mut records = self.records.lock().unwrap();
+        for dkey in dkeys {
+            records.remove(dkey);
+        }
+        Ok(())
+    }
+
+    fn punch_akeys(&self, txn: &DaosTxn, dkey: Vec<u8>, akeys: &[Vec<u8>]) -> Result<()> {
+        self.punch_akeys_with_flags(txn, CondFlags::empty(), dkey, akeys)
+            .map(|_| ())
+    }
+
+    // Array akeys hold a byte extent, unwritten ranges read back as zeros.
+    fn fetch_recx(
+        &self,
+        _txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        out_buf: &mut [u8],
+    ) -> Result<usize> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let flags = self.default_flags.apply_fetch(flags);
+        check_fetch_flags(flags)?;
+
+        out_buf.fill(0);
+        let records = self.records.lock().unwrap();
+        let value = records.get(dkey).and_then(|akeys| akeys.get(akey));
+        if let Some(value) = value {
+            let start = (offset as usize).min(value.len());
+            let end = (offset as usize + out_buf.len()).min(value.len());
+            out_buf[..end - start].copy_from_slice(&value[start..end]);
+            return Ok(end - start);
+        }
+        Ok(0)
+    }
+
+    fn update_recx(
+        &self,
+        _txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let flags = self.default_flags.apply_update(flags);
+        check_update_flags(flags)?;
+        self.check_writable()?;
+
+        let mut records = self.records.lock().unwrap();
+        let value = records
+            .entry(dkey.to_vec())
+            .or_default()
+            .entry(akey.to_vec())
+            .or_default();
+        let end = offset as usize + data.len();
+        if value.len() < end {
+            value.resize(end, 0);
+        }
+        value[offset as usize..end].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn list_dkey(&self, _txn: &DaosTxn, key_lst: Box<DaosKeyList>) -> Result<Box<DaosKeyList>> {
+        let records = self.records.lock().unwrap();
+        Ok(list_keys(records.keys(), key_lst))
+    }
+
+    fn list_akey(
+        &self,
+        _txn: &DaosTxn,
+        dkey: Vec<u8>,
+        key_lst: Box<DaosKeyList>,
+    ) -> Result<Box<DaosKeyList>> {
+        let records = self.records.lock().unwrap();
+        match records.get(&dkey) {
+            Some(akeys) => Ok(list_keys(akeys.keys(), key_lst)),
+            None => Ok(list_keys(std::iter::empty(), key_lst)),
+        }
+    }
+
+    // Values aren't typed, so any non-empty one is listed as a single
+    // extent of byte records, holes included.
+    fn list_recx(&self, _txn: &DaosTxn, dkey: Vec<u8>, akey: Vec<u8>) -> Result<RecxList> {
+        let records = self.records.lock().unwrap();
+        let len = records
+            .get(&dkey)
+            .and_then(|akeys| akeys.get(&akey))
+            .map_or(0, |value| value.len() as u64);
+        let extents = match len {
+            0 => Vec::new(),
+            len => vec![DaosExtent { offset: 0, len }],
+        };
+        Ok(RecxList {
+            rec_size: 1,
+            extents,
+        })
+    }
+
+    #[cfg(feature = "list-obj")]
+    fn list_obj(&self, _txn: &DaosTxn, _obj_lst: Box<DaosObjList>) -> Result<Box<DaosObjList>> {
+        Err(DaosError::no_sys(
+            "the memory backend can't enumerate objects",
+        ))
+    }
+
+    fn punch_akeys_with_flags(
+        &self,
+        _txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: &[Vec<u8>],
+    ) -> Result<bool> {
+        check_punch_flags(flags.bits())?;
+        self.check_writable()?;
+        if akeys.is_empty() {
+            return Err(DaosError::inval("no keys to punch"));
+        }
+
+        let mut records = self.records.lock().unwrap();
+        let values = match records.get_mut(&dkey) {
+            Some(values) => values,
+            None => return Ok(!flags.contains(CondFlags::PUNCH)),
+        };
+        if flags.contains(CondFlags::PUNCH) && akeys.iter().any(|akey| !values.contains_key(akey)) {
+            return Ok(false);
+        }
+        for akey in akeys {
+            values.remove(akey);
+        }
+        if values.is_empty() {
+            records.remove(&dkey);
+        }
+        Ok(true)
+    }
+
+    // A dkey whose last akey is removed goes away with it, as in DAOS.
+    fn remove(&self, _txn: &DaosTxn, dkey: Vec<u8>, akey: Option<Vec<u8>>) -> Result<bool> {
+        self.check_writable()?;
+
+        let mut records = self.records.lock().unwrap();
+        let akey = match akey {
+            Some(akey) => akey,
+            None => return Ok(records.remove(&dkey).is_some()),
+        };
+        let akeys = match records.get_mut(&dkey) {
+            Some(akeys) => akeys,
+            None => return Ok(false),
+        };
+        let removed = akeys.remove(&akey).is_some();
+        if akeys.is_empty() {
+            records.remove(&dkey);
+        }
+        Ok(removed)
+    }
+
+    // Writes all akeys or, when a condition fails, none of them.
+    fn update_akeys(
+        &self,
+        _txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyUpdate>,
+    ) -> Result<()> {
+        let flags = self.default_flags.apply_update(flags);
+        check_update_flags(flags)?;
+        self.check_writable()?;
+        if akeys.is_empty() {
+            return Err(DaosError::inval("no akeys to update"));
+        }
+        let akey_flags: Vec<CondFlags> = akeys.iter().map(|(_, flags, _)| *flags).collect();
+        let (flags, iod_flags) = per_akey_flags(flags, &akey_flags, AKEY_UPDATE_FLAGS)?;
+        iod_flags
+            .iter()
+            .try_for_each(|iod_flags| check_update_flags(*iod_flags))?;
+
+        let mut records = self.records.lock().unwrap();
+        let existing = records.get(&dkey);
+        if existing.is_some() && flags & DAOS_COND_DKEY_INSERT as u64 != 0 {
+            return Err(DaosError::exist("dkey already exists"));
+        }
+        if existing.is_none() && flags & DAOS_COND_DKEY_UPDATE as u64 != 0 {
+            return Err(DaosError::non_exist("dkey does not exist"));
+        }
+        for ((akey, _, _), iod_flags) in akeys.iter().zip(iod_flags) {
+            let cond = if flags & DAOS_COND_PER_AKEY as u64 != 0 {
+                iod_flags
+            } else {
+                flags
+            };
+            let akey_exists = existing.is_some_and(|values| values.contains_key(akey));
+            if akey_exists && cond & DAOS_COND_AKEY_INSERT as u64 != 0 {
+                return Err(DaosError::exist("akey already exists"));
+            }
+            if !akey_exists && cond & DAOS_COND_AKEY_UPDATE as u64 != 0 {
+                return Err(DaosError::non_exist("akey does not exist"));
+            }
+        }
+
+        let values = records.entry(dkey).or_default();
+        for (akey, _, data) in akeys {
+            values.insert(akey, data);
+        }
+        Ok(())
+    }
+
+    fn fetch_akeys(
+        &self,
+        _txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyFetch>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        if akeys.is_empty() {
+            return Err(DaosError::inval("no akeys to fetch"));
+        }
+        let flags = self.default_flags.apply_fetch(flags);
+        check_fetch_flags(flags)?;
+        let akey_flags: Vec<CondFlags> = akeys.iter().map(|(_, flags)| *flags).collect();
+        let (flags, iod_flags) = per_akey_flags(flags, &akey_flags, AKEY_FETCH_FLAGS)?;
+
+        let records = self.records.lock().unwrap();
+        let values = records.get(&dkey);
+        if values.is_none() && flags & DAOS_COND_DKEY_FETCH as u64 != 0 {
+            return Err(DaosError::non_exist("dkey does not exist"));
+        }
+        let mut fetched = Vec::with_capacity(akeys.len());
+        for ((akey, _), iod_flags) in akeys.iter().zip(iod_flags) {
+            let cond = if flags & DAOS_COND_PER_AKEY as u64 != 0 {
+                iod_flags
+            } else {
+                flags
+            };
+            let value = values.and_then(|values| values.get(akey));
+            match value {
+                None if cond & DAOS_COND_AKEY_FETCH as u64 != 0 => {
+                    return Err(DaosError::non_exist("akey does not exist"));
+                }
+                Some(value) if value.len() > max_size as usize => {
+                    return Err(DaosError::rec2big(
+                        format!("record size {} exceeds buffer {}", value.len(), max_size),
+                        value.len(),
+                    ));
+                }
+                None => fetched.push(None),
+                Some(value) if value.is_empty() => fetched.push(None),
+                Some(value) => fetched.push(Some(value.clone())),
+            }
+        }
+        Ok(fetched)
+    }
+}
+
+// The operations complete before the call returns, the futures only hand
+// over their result.
+impl DaosObjAsyncOps for MemObject {
+    fn punch_async(&self, txn: &DaosTxn) -> impl Future<Output = Result<()>> + Send + 'static {
+        future::ready(self.punch(txn))
+    }
+
+    fn punch_with_flags_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+    ) -> impl Future<Output = Result<bool>> + Send + 'static {
+        future::ready(self.punch_with_flags(txn, flags))
+    }
+
+    async fn fetch_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        out_buf: &mut [u8],
+    ) -> Result<usize> {
+        let value = self
+            .fetch(txn, flags, dkey, akey, out_buf.len() as u32)
+            .map_err(|e| e.into_truncated(0))?;
+        out_buf[..value.len()].copy_from_slice(&value);
+        Ok(value.len())
+    }
+
+    async fn fetch_uninit_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        out_buf: &mut [MaybeUninit<u8>],
+    ) -> Result<usize> {
+        let value = self
+            .fetch(txn, flags, dkey, akey, out_buf.len() as u32)
+            .map_err(|e| e.into_truncated(0))?;
+        write_uninit(&mut out_buf[..value.len()], &value);
+        Ok(value.len())
+    }
+
+    #[cfg(feature = "bytes")]
+    async fn fetch_into_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        buf: &mut BytesMut,
+        max_size: u32,
+    ) -> Result<usize> {
+        let value = self
+            .fetch(txn, flags, dkey, akey, max_size)
+            .map_err(|e| e.into_truncated(0))?;
+        buf.extend_from_slice(&value);
+        Ok(value.len())
+    }
+
+    async fn update_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        data: &[u8],
+    ) -> Result<()> {
+        self.update(txn, flags, dkey, akey, data)
+    }
+
+    async fn update_vectored_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        bufs: &[IoSlice<'_>],
+    ) -> Result<()> {
+        let data: Vec<u8> = bufs.iter().flat_map(|buf| buf.iter().copied()).collect();
+        self.update(txn, flags, dkey, akey, &data)
+    }
+
+    async fn fetch_vectored_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Result<usize> {
+        let max_size: usize = bufs.iter().map(|buf| buf.len()).sum();
+        let value = self
+            .fetch(txn, flags, dkey, akey, max_size as u32)
+            .map_err(|e| e.into_truncated(0))?;
+        let mut rest = value.as_slice();
+        for buf in bufs.iter_mut() {
+            let n = buf.len().min(rest.len());
+            buf[..n].copy_from_slice(&rest[..n]);
+            rest = &rest[n..];
+        }
+        Ok(value.len())
+    }
+
+    async fn update_akeys_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyUpdate>,
+    ) -> Result<()> {
+        self.update_akeys(txn, flags, dkey, akeys)
+    }
+
+    async fn fetch_akeys_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyFetch>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        self.fetch_akeys(txn, flags, dkey, akeys, max_size)
+    }
+
+    async fn fetch_recx_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        out_buf: &mut [u8],
+    ) -> Result<usize> {
+        self.fetch_recx(txn, flags, dkey, akey, offset, out_buf)
+    }
+
+    async fn fetch_recx_map_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        out_buf: &mut [u8],
+    ) -> Result<RecxFetch> {
+        let len = self.fetch_recx(txn, flags, dkey, akey, offset, out_buf)?;
+        let extents = match len {
+            0 => Vec::new(),
+            len => vec![DaosExtent {
+                offset,
+                len: len as u64,
+            }],
+        };
+        Ok(RecxFetch {
+            offset,
+            len,
+            extents,
+        })
+    }
+
+    async fn update_recx_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        self.update_recx(txn, flags, dkey, akey, offset, data)
+    }
+
+    async fn fetch_recs_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        rec_size: u64,
+        index: u64,
+        out_buf: &mut [u8],
+    ) -> Result<usize> {
+        records_in(out_buf.len(), rec_size)?;
+        let len = self.fetch_recx(txn, flags, dkey, akey, index * rec_size, out_buf)?;
+        Ok(len.div_ceil(rec_size as usize))
+    }
+
+    async fn update_recs_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        rec_size: u64,
+        index: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        records_in(data.len(), rec_size)?;
+        self.update_recx(txn, flags, dkey, akey, index * rec_size, data)
+    }
+
+    // Punched bytes read back as zeros, like any other unwritten range.
+    async fn punch_recx_async(
+        &self,
+        _txn: &DaosTxn,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        len: u64,
+    ) -> Result<()> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        self.check_writable()?;
+
+        let mut records = self.records.lock().unwrap();
+        let value = records.get_mut(dkey).and_then(|akeys| akeys.get_mut(akey));
+        if let Some(value) = value {
+            let start = (offset as usize).min(value.len());
+            let end = offset.saturating_add(len).min(value.len() as u64) as usize;
+            if end == value.len() {
+                value.truncate(start);
+            } else {
+                value[start..end].fill(0);
+            }
+        }
+        Ok(())
+    }
+
+    fn list_dkey_async(
+        &self,
+        txn: &DaosTxn,
+        key_lst: Box<DaosKeyList>,
+    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static {
+        future::ready(self.list_dkey(txn, key_lst))
+    }
+
+    fn list_akey_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        key_lst: Box<DaosKeyList>,
+    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static {
+        future::ready(self.list_akey(txn, dkey, key_lst))
+    }
+
+    fn key2anchor_async(
+        &self,
+        _txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akey: Option<Vec<u8>>,
+        mut key_lst: Box<DaosKeyList>,
+    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static {
+        let records = self.records.lock().unwrap();
+        let (idx, total) = match &akey {
+            None => (records.range(..dkey).count(), records.len()),
+            Some(akey) => match records.get(&dkey) {
+                Some(akeys) => (akeys.range(..akey.clone()).count(), akeys.len()),
+                None => (0, 0),
+            },
+        };
+        key_lst.reset();
+        set_anchor_index(&mut key_lst.anchor, idx, idx == total);
+        future::ready(Ok(key_lst))
+    }
+
+    fn list_recx_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akey: Vec<u8>,
+    ) -> impl Future<Output = Result<RecxList>> + Send + 'static {
+        future::ready(self.list_recx(txn, dkey, akey))
+    }
+
+    #[cfg(feature = "list-obj")]
+    fn list_obj_async(
+        &self,
+        txn: &DaosTxn,
+        obj_lst: Box<DaosObjList>,
+    ) -> impl Future<Output = Result<Box<DaosObjList>>> + Send + 'static {
+        future::ready(self.list_obj(txn, obj_lst))
+    }
+
+    async fn get_multi_async(
+        &self,
+        _txn: &DaosTxn,
+        dkeys: &[Vec<u8>],
+        akey: Vec<u8>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        let flags = self.default_flags.apply_fetch(CondFlags::empty());
+        check_fetch_flags(flags)?;
+
+        let records = self.records.lock().unwrap();
+        let mut values = Vec::with_capacity(dkeys.len());
+        for dkey in dkeys {
+            // DAOS keeps no empty single values, one written empty is missing
+            let value = records
+                .get(dkey)
+                .and_then(|akeys| akeys.get(&akey))
+                .filter(|value| !value.is_empty());
+            let value = match value {
+                Some(value) if value.len() > max_size as usize => {
+                    return Err(DaosError::rec2big(
+                        format!("record size {} exceeds buffer {}", value.len(), max_size),
+                        value.len(),
+                    )
+                    .into_truncated(0));
+                }
+                None if flags & (DAOS_COND_DKEY_FETCH | DAOS_COND_AKEY_FETCH) as u64 != 0 => {
+                    return Err(DaosError::non_exist("akey does not exist"));
+                }
+                value => value.cloned(),
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    async fn update_many_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        updates: Vec<DaosBatchUpdate>,
+        max_in_flight: usize,
+    ) -> Result<Vec<Result<()>>> {
+        if max_in_flight == 0 {
+            return Err(DaosError::inval("max_in_flight must not be zero"));
+        }
+        Ok(updates
+            .into_iter()
+            .map(|(dkey, akey, data)| self.update(txn, flags, dkey, akey, &data))
+            .collect())
+    }
+
+    async fn get_map_async(
+        &self,
+        txn: &DaosTxn,
+        dkeys: Vec<Vec<u8>>,
+        akey: Vec<u8>,
+        max_size: u32,
+    ) -> Result<HashMap<Vec<u8>, Option<Vec<u8>>>> {
+        let values = self
+            .get_multi_async(txn, dkeys.as_slice(), akey, max_size)
+            .await?;
+        Ok(dkeys.into_iter().zip(values).collect())
+    }
+
+    // There is a single copy of each value, so nothing to hedge against.
+    async fn fetch_hedged_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+        _hedge_delay: Duration,
+    ) -> Result<Vec<u8>> {
+        self.fetch(txn, flags, dkey, akey, max_size)
+            .map_err(|e| e.into_truncated(0))
+    }
+
+    async fn fetch_with_epoch_async(
+        &self,
+        _txn: &DaosTxn,
+        _flags: CondFlags,
+        _dkey: impl AsRef<[u8]>,
+        _akey: impl AsRef<[u8]>,
+        _max_size: u32,
+    ) -> Result<EpochValue> {
+        Err(DaosError::no_sys("the memory backend keeps no epochs"))
+    }
+
+    async fn remove_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akey: Option<Vec<u8>>,
+    ) -> Result<bool> {
+        self.remove(txn, dkey, akey)
+    }
+
+    async fn punch_dkeys_async(&self, txn: &DaosTxn, dkeys: &[Vec<u8>]) -> Result<()> {
+        self.punch_dkeys(txn, dkeys)
+    }
+
+    async fn punch_akeys_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akeys: &[Vec<u8>],
+    ) -> Result<()> {
+        self.punch_akeys(txn, dkey, akeys)
+    }
+
+    async fn punch_akeys_with_flags_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: &[Vec<u8>],
+    ) -> Result<bool> {
+        self.punch_akeys_with_flags(txn, flags, dkey, akeys)
+    }
+
+    #[cfg(feature = "bytes")]
+    async fn update_bytes_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        data: Bytes,
+    ) -> Result<()> {
+        self.update(txn, flags, dkey, akey, &data)
+    }
+
+    #[cfg(feature = "bytes")]
+    async fn fetch_bytes_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<Bytes> {
+        let value = self
+            .fetch(txn, flags, dkey, akey, max_size)
+            .map_err(|e| e.into_truncated(0))?;
+        Ok(Bytes::from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daos_obj::DaosKeyListConfig;
+
+    #[test]
+    fn test_mem_update_fetch() {
+        let pool = MemPool::new("pool1");
+        let cont = pool.container("cont1");
+        let obj = cont.create_object();
+        let txn = DaosTxn::txn_none();
+
+        let dkey = "dkey".as_bytes().to_vec();
+        let akey = vec![0u8];
+        let res = obj.update(
+            &txn,
+            CondFlags::DKEY_INSERT,
+            dkey.clone(),
+            akey.clone(),
+            "value".as_bytes(),
+        );
+        assert!(res.is_ok());
+
+        let res = obj.update(
+            &txn,
+            CondFlags::DKEY_INSERT,
+            dkey.clone(),
+            akey.clone(),
+            "again".as_bytes(),
+        );
+        assert!(matches!(res, Err(DaosError::Exist(_))));
+
+        let read = obj
+            .fetch(&txn, CondFlags::DKEY_FETCH, dkey, akey.clone(), 16)
+            .unwrap();
+        assert_eq!(read, "value".as_bytes());
+
+        let res = obj.fetch(&txn, CondFlags::DKEY_FETCH, vec![1u8], akey, 16);
+        assert!(matches!(res, Err(DaosError::NonExist(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mem_punch_keys() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        for dkey in 1u8..4 {
+            obj.update(&txn, CondFlags::empty(), vec![dkey], vec![0u8], &[7u8])
+                .unwrap();
+            obj.update(&txn, CondFlags::empty(), vec![dkey], vec![1u8], &[8u8])
+                .unwrap();
+        }
+
+        obj.punch_dkeys_async(&txn, &[vec![1u8], vec![9u8]])
+            .await
+            .unwrap();
+        assert_eq!(obj.list_dkeys(&txn).unwrap(), vec![vec![2u8], vec![3u8]]);
+
+        obj.punch_akeys(&txn, vec![2u8], &[vec![0u8]]).unwrap();
+        assert!(obj
+            .fetch(&txn, CondFlags::empty(), vec![2u8], vec![0u8], 1)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            obj.fetch(&txn, CondFlags::empty(), vec![2u8], vec![1u8], 1)
+                .unwrap(),
+            vec![8u8]
+        );
+        obj.punch_akeys_async(&txn, vec![2u8], &[vec![1u8]])
+            .await
+            .unwrap();
+        assert_eq!(obj.list_dkeys(&txn).unwrap(), vec![vec![3u8]]);
+
+        assert!(matches!(
+            obj.punch_dkeys(&txn, &[]),
+            Err(DaosError::Inval(_))
+        ));
+    }
+
+    #[test]
+    fn test_mem_open_shares_records() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        obj.update(&txn, CondFlags::empty(), vec![1u8], vec![0u8], &[7u8])
+            .unwrap();
+
+        let same = pool.container("cont1").open_object(obj.oid, true);
+        assert_eq!(same.list_dkeys(&txn).unwrap(), vec![vec![1u8]]);
+        assert!(same.is_read_only());
+        assert!(matches!(same.punch(&txn), Err(DaosError::ReadOnly(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mem_remove_async() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        obj.update(&txn, CondFlags::empty(), vec![1u8], vec![0u8], &[7u8])
+            .unwrap();
+        obj.update(&txn, CondFlags::empty(), vec![1u8], vec![1u8], &[8u8])
+            .unwrap();
+        obj.update(&txn, CondFlags::empty(), vec![2u8], vec![0u8], &[9u8])
+            .unwrap();
+
+        let removed = obj.remove_async(&txn, vec![1u8], Some(vec![0u8])).await;
+        assert!(removed.unwrap());
+        let removed = obj.remove_async(&txn, vec![1u8], Some(vec![0u8])).await;
+        assert!(!removed.unwrap());
+        assert!(obj
+            .remove_async(&txn, vec![1u8], Some(vec![1u8]))
+            .await
+            .unwrap());
+        assert_eq!(obj.list_dkeys(&txn).unwrap(), vec![vec![2u8]]);
+
+        assert!(obj.remove_async(&txn, vec![2u8], None).await.unwrap());
+        assert!(!obj.remove_async(&txn, vec![2u8], None).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_mem_get_multi_async() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        crate::daos_obj::check_get_multi_async(obj.as_ref()).await;
+    }
+
+    #[tokio::test]
+    async fn test_mem_fetch_vectored_async() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        obj.update(&txn, CondFlags::empty(), vec![1u8], vec![0u8], b"hdr:body")
+            .unwrap();
+
+        let (mut head, mut tail) = ([0u8; 4], [0u8; 8]);
+        let size = obj
+            .fetch_vectored_async(
+                &txn,
+                CondFlags::empty(),
+                vec![1u8],
+                vec![0u8],
+                &mut [IoSliceMut::new(&mut head), IoSliceMut::new(&mut tail)],
+            )
+            .await;
+        assert_eq!(size.unwrap(), 8);
+        assert_eq!(&head, b"hdr:");
+        assert_eq!(&tail[..4], b"body");
+
+        let mut small = [0u8; 4];
+        let res = obj
+            .fetch_vectored_async(
+                &txn,
+                CondFlags::empty(),
+                vec![1u8],
+                vec![0u8],
+                &mut [IoSliceMut::new(&mut small)],
+            )
+            .await;
+        assert!(matches!(
+            res,
+            Err(DaosError::Truncated {
+                actual_size: 8,
+                written: 0,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_mem_list_akeys() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        obj.update(&txn, CondFlags::empty(), vec![1u8], vec![2u8], &[7u8])
+            .unwrap();
+        obj.update(&txn, CondFlags::empty(), vec![1u8], vec![0u8], &[8u8])
+            .unwrap();
+        obj.update(&txn, CondFlags::empty(), vec![2u8], vec![1u8], &[9u8])
+            .unwrap();
+
+        let key_lst = obj.list_akey(&txn, vec![1u8], DaosKeyList::new()).unwrap();
+        assert_eq!(key_lst.keys().collect::<Vec<_>>(), vec![[0u8], [2u8]]);
+        let key_lst = obj.list_akey(&txn, vec![3u8], DaosKeyList::new()).unwrap();
+        assert_eq!(key_lst.get_key_num(), 0);
+    }
+
+    #[test]
+    fn test_mem_list_recx() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        obj.update_recx(&txn, CondFlags::empty(), vec![1u8], vec![0u8], 4, b"data")
+            .unwrap();
+
+        let recxs = obj.list_recx(&txn, vec![1u8], vec![0u8]).unwrap();
+        assert_eq!(recxs.rec_size, 1);
+        assert_eq!(recxs.extents, vec![DaosExtent { offset: 0, len: 8 }]);
+        let recxs = obj.list_recx(&txn, vec![1u8], vec![1u8]).unwrap();
+        assert!(recxs.extents.is_empty());
+    }
+
+    #[test]
+    fn test_mem_default_flags() {
+        let pool = MemPool::new("pool1");
+        let mut obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        obj.set_default_flags(DaosDefaultFlags {
+            update: CondFlags::DKEY_UPDATE,
+            fetch: CondFlags::DKEY_FETCH,
+        })
+        .unwrap();
+
+        let res = obj.update(&txn, CondFlags::empty(), vec![1u8], vec![0u8], &[7u8]);
+        assert!(matches!(res, Err(DaosError::NonExist(_))));
+        let res = obj.fetch(&txn, CondFlags::empty(), vec![1u8], vec![0u8], 16);
+        assert!(matches!(res, Err(DaosError::NonExist(_))));
+
+        let flags = CondFlags::DKEY_INSERT;
+        obj.update(&txn, flags, vec![1u8], vec![0u8], &[7u8])
+            .unwrap();
+        assert_eq!(
+            obj.fetch(&txn, CondFlags::empty(), vec![1u8], vec![0u8], 16)
+                .unwrap(),
+            vec![7u8]
+        );
+    }
+
+    #[test]
+    fn test_mem_update_akeys() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        let flags = CondFlags::empty();
+        obj.update(&txn, flags, vec![1u8], vec![0u8], &[7u8])
+            .unwrap();
+
+        let akeys = vec![
+            (vec![0u8], CondFlags::AKEY_UPDATE, vec![8u8]),
+            (vec![1u8], CondFlags::AKEY_INSERT, vec![9u8]),
+        ];
+        obj.update_akeys(&txn, flags, vec![1u8], akeys).unwrap();
+        assert_eq!(
+            obj.fetch(&txn, flags, vec![1u8], vec![0u8], 16).unwrap(),
+            vec![8u8]
+        );
+        assert_eq!(
+            obj.fetch(&txn, flags, vec![1u8], vec![1u8], 16).unwrap(),
+            vec![9u8]
+        );
+
+        // one failed condition leaves every akey untouched
+        let akeys = vec![
+            (vec![2u8], CondFlags::AKEY_INSERT, vec![1u8]),
+            (vec![0u8], CondFlags::AKEY_INSERT, vec![1u8]),
+        ];
+        let res = obj.update_akeys(&txn, flags, vec![1u8], akeys);
+        assert!(matches!(res, Err(DaosError::Exist(_))));
+        assert!(obj
+            .fetch(&txn, flags, vec![1u8], vec![2u8], 16)
+            .unwrap()
+            .is_empty());
+
+        let akeys = vec![(vec![0u8], CondFlags::DKEY_INSERT, vec![1u8])];
+        let res = obj.update_akeys(&txn, flags, vec![1u8], akeys);
+        assert!(matches!(res, Err(DaosError::InvalidFlags(_))));
+    }
+
+    #[tokio::test]
+    async fn test_mem_recx_async() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+
+        let res = obj
+            .update_recx_async(
+                &txn,
+                CondFlags::empty(),
+                vec![0u8],
+                vec![0u8],
+                4,
+                &[1u8, 2u8],
+            )
+            .await;
+        assert!(res.is_ok());
+
+        let mut buf = vec![0xffu8; 8];
+        let res = obj
+            .fetch_recx_async(
+                &txn,
+                CondFlags::empty(),
+                vec![0u8],
+                vec![0u8],
+                2,
+                buf.as_mut_slice(),
+            )
+            .await;
+        assert_eq!(res.unwrap(), 4);
+        assert_eq!(buf, vec![0u8, 0u8, 1u8, 2u8, 0u8, 0u8, 0u8, 0u8]);
+
+        obj.punch_recx_async(&txn, [0u8], [0u8], 4, 1)
+            .await
+            .unwrap();
+        let res = obj.fetch_recx(&txn, CondFlags::empty(), [0u8], [0u8], 2, &mut buf);
+        assert_eq!(res.unwrap(), 4);
+        assert_eq!(buf, vec![0u8, 0u8, 0u8, 2u8, 0u8, 0u8, 0u8, 0u8]);
+        obj.punch_recx_async(&txn, [0u8], [0u8], 5, 10)
+            .await
+            .unwrap();
+        let res = obj.fetch_recx(&txn, CondFlags::empty(), [0u8], [0u8], 2, &mut buf);
+        assert_eq!(res.unwrap(), 3);
+        assert_eq!(buf, vec![0u8; 8]);
+    }
+
+    // application code written against the traits, not against MemObject
+    async fn put_and_get(obj: &impl DaosObjAsyncOps, txn: &DaosTxn, value: &[u8]) -> Vec<u8> {
+        obj.update_async(txn, CondFlags::empty(), b"dkey", b"akey", value)
+            .await
+            .unwrap();
+        let mut buf = vec![0u8; 16];
+        let size = obj
+            .fetch_async(txn, CondFlags::empty(), b"dkey", b"akey", &mut buf)
+            .await
+            .unwrap();
+        buf.truncate(size);
+        buf
+    }
+
+    #[tokio::test]
+    async fn test_mem_trait_ops() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        assert_eq!(put_and_get(obj.as_ref(), &txn, b"value").await, b"value");
+
+        let mut buf = vec![0u8; 2];
+        let res = obj
+            .fetch_async(&txn, CondFlags::empty(), b"dkey", b"akey", &mut buf)
+            .await;
+        assert!(matches!(res, Err(DaosError::Truncated { .. })));
+        let res = obj
+            .fetch_hedged_async(
+                &txn,
+                CondFlags::empty(),
+                b"dkey",
+                b"akey",
+                2,
+                Duration::ZERO,
+            )
+            .await;
+        assert!(matches!(
+            res,
+            Err(DaosError::Truncated { actual_size: 5, .. })
+        ));
+
+        assert!(obj
+            .punch_with_flags_async(&txn, CondFlags::PUNCH)
+            .await
+            .unwrap());
+        assert!(!obj
+            .punch_with_flags_async(&txn, CondFlags::PUNCH)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_mem_list_keys() {
+        let pool = MemPool::new("pool1");
+        let obj = pool.container("cont1").create_object();
+        let txn = DaosTxn::txn_none();
+        for dkey in 0u8..5 {
+            obj.update(&txn, CondFlags::empty(), [dkey], [0u8], &[dkey])
+                .unwrap();
+        }
+        obj.update(&txn, CondFlags::empty(), [0u8], [1u8], &[1u8])
+            .unwrap();
+
+        let config = DaosKeyListConfig {
+            min_descs: 2,
+            max_descs: 2,
+            ..Default::default()
+        };
+        let mut key_lst = DaosKeyList::with_config(config);
+        let mut dkeys = Vec::new();
+        while !key_lst.reach_end() {
+            key_lst = obj.list_dkey_async(&txn, key_lst).await.unwrap();
+            assert!(key_lst.get_key_num() <= 2);
+            dkeys.extend(key_lst.keys().map(|key| key.to_vec()));
+        }
+        assert_eq!(dkeys, (0u8..5).map(|dkey| vec![dkey]).collect::<Vec<_>>());
+
+        let key_lst = obj
+            .key2anchor_async(&txn, vec![3u8], None, DaosKeyList::new())
+            .await
+            .unwrap();
+        let key_lst = obj.list_dkey_async(&txn, key_lst).await.unwrap();
+        assert_eq!(key_lst.keys().collect::<Vec<_>>(), vec![[3u8], [4u8]]);
+        assert!(key_lst.reach_end());
+
+        let key_lst = obj
+            .list_akey_async(&txn, vec![0u8], DaosKeyList::new())
+            .await
+            .unwrap();
+        assert_eq!(key_lst.keys().collect::<Vec<_>>(), vec![[0u8], [1u8]]);
+    }
+}
@@ -0,0 +1,156 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::daos_cont::{DaosContainer, DaosContainerAsyncOps, DaosContainerSyncOps};
+use crate::daos_obj::DaosObject;
+use crate::daos_pool::DaosObjectId;
+use crate::error::{DaosError, Result};
+
+// Number of CO_ROOTS slots left to applications.
+pub const META_USER_SLOTS: usize = 3;
+
+// The layout of a container's CO_ROOTS as set up by init_roots_async:
+// root 0 is the meta object shared by every tool, holding the oid
+// allocator's batch cursor, and roots 1 to 3 are user slots 0 to 2 whose
+// meaning is up to the application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetaObject {
+    roots: [DaosObjectId; 4],
+}
+
+impl MetaObject {
+    pub fn from_roots(roots: [DaosObjectId; 4]) -> Self {
+        MetaObject { roots }
+    }
+
+    pub fn load(cont: &DaosContainer) -> Result<Self> {
+        let roots = cont.query_prop()?.get_co_roots()?;
+        Ok(MetaObject::from_roots(*roots))
+    }
+
+    pub async fn load_async(cont: &DaosContainer) -> Result<Self> {
+        let roots = cont.query_prop_async().await?.get_co_roots()?;
+        Ok(MetaObject::from_roots(*roots))
+    }
+
+    pub fn roots(&self) -> &[DaosObjectId; 4] {
+        &self.roots
+    }
+
+    pub fn root_oid(&self) -> Result<DaosObjectId> {
+        Self::created(self.roots[0], "container has no meta root")
+    }
+
+    // Fails with NonExist for a slot init_roots_async didn't create, see
+    // DaosOidAllocatorConfig::nr_roots.
+    pub fn user_oid(&self, slot: usize) -> Result<DaosObjectId> {
+        if slot >= META_USER_SLOTS {
+            return Err(DaosError::inval(format!(
+                "user slot {} is out of range",
+                slot
+            )));
+        }
+        Self::created(self.roots[slot + 1], "user slot is not created")
+    }
+
+    pub fn open_root(&self, cont: &DaosContainer, read_only: bool) -> Result<Box<DaosObject>> {
+        DaosObject::open(cont, self.root_oid()?, read_only)
+    }
+
+    pub fn open_user(
+        &self,
+        cont: &DaosContainer,
+        slot: usize,
+        read_only: bool,
+    ) -> Result<Box<DaosObject>> {
+        DaosObject::open(cont, self.user_oid(slot)?, read_only)
+    }
+
+    pub async fn open_root_async(
+        &self,
+        cont: &DaosContainer,
+        read_only: bool,
+    ) -> Result<Box<DaosObject>> {
+        DaosObject::open_async(cont, self.root_oid()?, read_only).await
+    }
+
+    pub async fn open_user_async(
+        &self,
+        cont: &DaosContainer,
+        slot: usize,
+        read_only: bool,
+    ) -> Result<Box<DaosObject>> {
+        DaosObject::open_async(cont, self.user_oid(slot)?, read_only).await
+    }
+
+    fn created(oid: DaosObjectId, msg: &str) -> Result<DaosObjectId> {
+        if oid.lo == 0 && oid.hi == 0 {
+            Err(DaosError::non_exist(msg))
+        } else {
+            Ok(oid)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daos_oid_allocator::DaosOidAllocatorConfig;
+    use crate::daos_pool::DaosPool;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[test]
+    fn test_meta_slots() {
+        let none = DaosObjectId { lo: 0, hi: 0 };
+        let root = DaosObjectId { lo: 0, hi: 7 };
+        let user = DaosObjectId { lo: 2, hi: 7 };
+        let meta = MetaObject::from_roots([root, none, user, none]);
+
+        assert_eq!(meta.root_oid().unwrap(), root);
+        assert_eq!(meta.user_oid(1).unwrap(), user);
+        assert!(matches!(meta.user_oid(0), Err(DaosError::NonExist(_))));
+        assert!(matches!(meta.user_oid(3), Err(DaosError::Inval(_))));
+
+        let empty = MetaObject::from_roots([none; 4]);
+        assert!(matches!(empty.root_oid(), Err(DaosError::NonExist(_))));
+    }
+
+    #[tokio::test]
+    async fn test_meta_object_open() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let roots = cont
+            .init_roots_async(&DaosOidAllocatorConfig::default())
+            .await
+            .expect("Failed to init roots");
+        let meta = MetaObject::load_async(&cont).await.unwrap();
+        assert_eq!(meta.roots(), roots.as_ref());
+        assert_eq!(MetaObject::load(&cont).unwrap(), meta);
+
+        let obj = meta.open_root_async(&cont, true).await.unwrap();
+        assert_eq!(obj.oid, roots[0]);
+        assert!(obj.is_read_only());
+        let obj = meta.open_root(&cont, false).unwrap();
+        assert_eq!(obj.oid, roots[0]);
+    }
+}
@@ -0,0 +1,319 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Namespaces sharing one object, told apart by a dkey prefix:
+//   dkey = prefix followed by the caller's dkey, which must not be empty
+//   usage = u64 little endian under dkey = prefix, akey = USAGE_AKEY
+// Every write through a DaosNamespace updates the usage record in the same
+// transaction, so it always matches the single value bytes stored under the
+// prefix. Writes made to the object directly are not accounted, and no
+// prefix may start with another one sharing the object.
+
+use crate::daos_compress::{self, DaosCompression};
+use crate::daos_cont::DaosContainer;
+use crate::daos_obj::{CondFlags, DaosObjAsyncOps, DaosObject};
+use crate::daos_txn::{DaosTxn, DaosTxnAsyncOps};
+use crate::error::{DaosError, Result};
+use std::sync::Arc;
+
+const USAGE_AKEY: &[u8] = b"__usage";
+// attempts of one accounted write before a conflict is handed back
+const MAX_TXN_ATTEMPTS: u32 = 8;
+
+pub struct DaosNamespace {
+    cont: Arc<DaosContainer>,
+    obj: Arc<DaosObject>,
+    prefix: Vec<u8>,
+    // bytes the namespace may hold, no limit when None
+    quota: Option<u64>,
+    compression: Option<DaosCompression>,
+}
+
+impl DaosNamespace {
+    pub fn new(cont: Arc<DaosContainer>, obj: Arc<DaosObject>, prefix: &[u8]) -> Result<Self> {
+        if prefix.is_empty() {
+            return Err(DaosError::inval("namespace prefix must not be empty"));
+        }
+        Ok(DaosNamespace {
+            cont,
+            obj,
+            prefix: prefix.to_vec(),
+            quota: None,
+            compression: None,
+        })
+    }
+
+    // Writes that would grow the usage past bytes fail with NoSpace.
+    // Shrinking writes and removals always go through.
+    pub fn with_quota(mut self, bytes: u64) -> Self {
+        self.quota = Some(bytes);
+        self
+    }
+
+    // Like DaosObject::set_compression, for values of this namespace only.
+    // Usage counts the compressed bytes.
+    pub fn with_compression(mut self, compression: DaosCompression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    pub fn quota(&self) -> Option<u64> {
+        self.quota
+    }
+
+    pub fn get_object(&self) -> &DaosObject {
+        self.obj.as_ref()
+    }
+
+    fn data_dkey(&self, dkey: &[u8]) -> Result<Vec<u8>> {
+        if dkey.is_empty() {
+            return Err(DaosError::inval("namespace dkey must not be empty"));
+        }
+        let mut full = Vec::with_capacity(self.prefix.len() + dkey.len());
+        full.extend_from_slice(&self.prefix);
+        full.extend_from_slice(dkey);
+        Ok(full)
+    }
+
+    pub async fn usage_async(&self) -> Result<u64> {
+        usage_async(&self.obj, &self.prefix).await
+    }
+
+    pub async fn get_async(
+        &self,
+        dkey: &[u8],
+        akey: &[u8],
+        max_size: u32,
+    ) -> Result<Option<Vec<u8>>> {
+        let txn = DaosTxn::txn_none();
+        let dkey = self.data_dkey(dkey)?;
+        let mut buf = vec![0u8; max_size as usize];
+        let size = match self.compression {
+            Some(_) => {
+                let mut stored = vec![0u8; buf.len() + daos_compress::HEADER_LEN];
+                let size = self
+                    .obj
+                    .fetch_async(&txn, CondFlags::empty(), dkey, akey, &mut stored)
+                    .await?;
+                if size == 0 {
+                    return Ok(None);
+                }
+                daos_compress::decode(&stored[..size], &mut buf)?
+            }
+            None => {
+                self.obj
+                    .fetch_async(&txn, CondFlags::empty(), dkey, akey, &mut buf)
+                    .await?
+            }
+        };
+        if size == 0 {
+            return Ok(None);
+        }
+        buf.truncate(size);
+        Ok(Some(buf))
+    }
+
+    // Stores value under dkey and akey and returns the usage after it.
+    pub async fn put_async(&self, dkey: &[u8], akey: &[u8], value: &[u8]) -> Result<u64> {
+        if value.is_empty() {
+            return Err(DaosError::inval("use remove_async to delete a value"));
+        }
+        let encoded;
+        let value = match self.compression {
+            Some(codec) => {
+                encoded = daos_compress::encode(codec, value);
+                encoded.as_slice()
+            }
+            None => value,
+        };
+        self.accounted(self.data_dkey(dkey)?, akey, Some(value))
+            .await
+    }
+
+    // Removes the value under dkey and akey and returns the usage after it,
+    // which is unchanged when there was nothing to remove.
+    pub async fn remove_async(&self, dkey: &[u8], akey: &[u8]) -> Result<u64> {
+        self.accounted(self.data_dkey(dkey)?, akey, None).await
+    }
+
+    // Writes value, or removes the old one when None, in a transaction that
+    // also updates the usage record, retrying on conflicts.
+    async fn accounted(&self, dkey: Vec<u8>, akey: &[u8], value: Option<&[u8]>) -> Result<u64> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let txn = DaosTxn::open_async(&self.cont, 0).await?;
+            let res = match self.write_in(&txn, &dkey, akey, value).await {
+                Ok(usage) => txn.commit_async().await.map(|_| usage),
+                Err(e) => {
+                    let _ = txn.abort_async().await;
+                    Err(e)
+                }
+            };
+            let _ = txn.close_async().await;
+            match res {
+                Err(e) if e.is_tx_conflict() && attempt < MAX_TXN_ATTEMPTS => continue,
+                res => return res,
+            }
+        }
+    }
+
+    async fn write_in(
+        &self,
+        txn: &DaosTxn,
+        dkey: &[u8],
+        akey: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<u64> {
+        let old_size = value_size(&self.obj, txn, dkey, akey).await?;
+        let usage = read_usage(&self.obj, txn, &self.prefix).await?;
+        let new_size = value.map_or(0, |value| value.len() as u64);
+        let next = usage.saturating_sub(old_size) + new_size;
+        if let Some(quota) = self.quota {
+            if new_size > old_size && next > quota {
+                return Err(DaosError::no_space("namespace quota exceeded"));
+            }
+        }
+
+        match value {
+            Some(value) => {
+                self.obj
+                    .update_async(txn, CondFlags::empty(), dkey, akey, value)
+                    .await?
+            }
+            None if old_size == 0 => return Ok(usage),
+            None => {
+                self.obj
+                    .remove_async(txn, dkey.to_vec(), Some(akey.to_vec()))
+                    .await?;
+            }
+        }
+        self.obj
+            .update_async(
+                txn,
+                CondFlags::empty(),
+                &self.prefix,
+                USAGE_AKEY,
+                &next.to_le_bytes(),
+            )
+            .await?;
+        Ok(next)
+    }
+}
+
+// Bytes accounted to the namespace with prefix in obj, 0 for one never
+// written through a DaosNamespace.
+pub async fn usage_async(obj: &DaosObject, prefix: &[u8]) -> Result<u64> {
+    read_usage(obj, &DaosTxn::txn_none(), prefix).await
+}
+
+async fn read_usage(obj: &DaosObject, txn: &DaosTxn, prefix: &[u8]) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    let size = obj
+        .fetch_async(txn, CondFlags::empty(), prefix, USAGE_AKEY, &mut buf)
+        .await?;
+    match size {
+        0 => Ok(0),
+        8 => Ok(u64::from_le_bytes(buf)),
+        _ => Err(DaosError::inval("malformed namespace usage record")),
+    }
+}
+
+// Size of the single value under dkey and akey, 0 when there is none.
+async fn value_size(obj: &DaosObject, txn: &DaosTxn, dkey: &[u8], akey: &[u8]) -> Result<u64> {
+    match obj
+        .fetch_async(txn, CondFlags::empty(), dkey, akey, &mut [])
+        .await
+    {
+        Ok(size) => Ok(size as u64),
+        Err(DaosError::Truncated { actual_size, .. }) => Ok(actual_size as u64),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daos_obj::{DAOS_OC_HINTS_NONE, DAOS_OC_UNKNOWN, DAOS_OT_MULTI_HASHED};
+    use crate::daos_oid_allocator::DaosAsyncOidAllocator;
+    use crate::daos_pool::DaosPool;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test]
+    async fn test_namespace_usage() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        cont.connect(&pool).expect("Failed to connect to container");
+        let cont = Arc::new(cont);
+
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj: Arc<DaosObject> = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            DAOS_OT_MULTI_HASHED,
+            DAOS_OC_UNKNOWN,
+            DAOS_OC_HINTS_NONE,
+            0,
+        )
+        .await
+        .expect("Failed to create object")
+        .into();
+
+        assert!(DaosNamespace::new(cont.clone(), obj.clone(), b"").is_err());
+        let tenant_a = DaosNamespace::new(cont.clone(), obj.clone(), b"a/")
+            .unwrap()
+            .with_quota(16);
+        let tenant_b = DaosNamespace::new(cont.clone(), obj.clone(), b"b/").unwrap();
+
+        assert_eq!(
+            tenant_a
+                .put_async(b"k1", b"v", b"0123456789")
+                .await
+                .unwrap(),
+            10
+        );
+        assert_eq!(tenant_a.put_async(b"k1", b"v", b"0123").await.unwrap(), 4);
+        assert_eq!(
+            tenant_a.put_async(b"k2", b"v", b"456789").await.unwrap(),
+            10
+        );
+        let res = tenant_a.put_async(b"k3", b"v", b"0123456789").await;
+        assert!(matches!(res, Err(DaosError::NoSpace(_))));
+        assert_eq!(tenant_b.put_async(b"k1", b"v", b"xyz").await.unwrap(), 3);
+
+        assert_eq!(usage_async(&obj, b"a/").await.unwrap(), 10);
+        assert_eq!(tenant_b.usage_async().await.unwrap(), 3);
+        assert_eq!(usage_async(&obj, b"c/").await.unwrap(), 0);
+        assert_eq!(
+            tenant_a.get_async(b"k1", b"v", 64).await.unwrap(),
+            Some(b"0123".to_vec())
+        );
+        assert_eq!(tenant_b.get_async(b"k2", b"v", 64).await.unwrap(), None);
+
+        assert_eq!(tenant_a.remove_async(b"k1", b"v").await.unwrap(), 6);
+        assert_eq!(tenant_a.remove_async(b"k1", b"v").await.unwrap(), 6);
+        assert_eq!(tenant_a.usage_async().await.unwrap(), 6);
+    }
+}
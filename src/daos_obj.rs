@@ -15,37 +15,472 @@
 //  along with this program.  If not, see <https://www.gnu.org/licenses/>.
 //
 
+#[cfg(feature = "libdaos")]
 use crate::bindings::{
-    d_iov_t, d_sg_list_t, daos_anchor_is_eof, daos_anchor_t, daos_event_t, daos_iod_t,
-    daos_iod_type_t_DAOS_IOD_ARRAY, daos_iod_type_t_DAOS_IOD_SINGLE, daos_key_desc_t, daos_key_t,
-    daos_obj_close, daos_obj_fetch, daos_obj_generate_oid2, daos_obj_list_dkey, daos_obj_open,
-    daos_obj_punch, daos_obj_update, daos_oclass_hints_t, daos_oclass_id_t, daos_otype_t,
-    daos_recx_t, DAOS_ANCHOR_BUF_MAX, DAOS_OO_RO, DAOS_OO_RW, DAOS_REC_ANY, DAOS_TXN_NONE,
+    d_iov_t, d_sg_list_t, daos_event_t, daos_iod_t, daos_iod_type_t_DAOS_IOD_ARRAY,
+    daos_iod_type_t_DAOS_IOD_SINGLE, daos_iom_t, daos_key_t, daos_obj_close, daos_obj_fetch,
+    daos_obj_generate_oid2, daos_obj_key2anchor, daos_obj_list_akey, daos_obj_list_dkey,
+    daos_obj_list_recx, daos_obj_open, daos_obj_punch, daos_obj_punch_akeys, daos_obj_punch_dkeys,
+    daos_obj_update, daos_recx_t, DAOS_OO_RO, DAOS_OO_RW, DAOS_REC_ANY,
 };
-use crate::daos_cont::DaosContainer;
+use crate::bindings::{
+    daos_anchor_is_eof, daos_anchor_t, daos_epoch_range_t, daos_errno_DER_NONEXIST,
+    daos_key_desc_t, daos_oclass_hints_t, daos_oclass_id_t, daos_otype_t, DAOS_ANCHOR_BUF_MAX,
+    DAOS_TXN_NONE,
+};
+use crate::daos_compress::{self, DaosCompression};
+#[cfg(feature = "libdaos")]
+use crate::daos_cont::{DaosContainer, DaosDeadlines};
+#[cfg(feature = "libdaos")]
 use crate::daos_event::*;
+#[cfg(feature = "hedged-fetch")]
+use crate::daos_oclass::ObjectClass;
+#[cfg(feature = "libdaos")]
 use crate::daos_oid_allocator::{DaosAsyncOidAllocator, DaosSyncOidAllocator};
-use crate::daos_pool::{DaosHandle, DaosObjectId};
+use crate::daos_pool::{DaosHandle, DaosObjectId, HandleFmt};
 use crate::daos_txn::DaosTxn;
+#[cfg(feature = "libdaos")]
+use crate::daos_txn::DaosTxnAsyncOps;
+use crate::error::{DaosError, DaosErrorContext, Result};
+#[cfg(feature = "bytes")]
+use bytes::{Bytes, BytesMut};
+use std::borrow::Cow;
 use std::cmp::{Eq, PartialEq};
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use std::future::Future;
 use std::hash::Hash;
 use std::hash::Hasher;
-use std::io::{Error, ErrorKind, Result};
+use std::io::{IoSlice, IoSliceMut};
+use std::mem::MaybeUninit;
+use std::pin::Pin;
 use std::ptr;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
 use std::vec::Vec;
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::sync::oneshot;
 
-const MAX_KEY_DESCS: u32 = 128;
+const MIN_KEY_DESCS: u32 = 16;
+const MAX_KEY_DESCS: u32 = 1024;
 const KEY_BUF_SIZE: usize = 1024;
+const MAX_KEY_BUF_SIZE: usize = 64 * 1024;
+const RECX_BATCH: usize = 64;
+// extents the I/O map of a recx fetch starts with room for
+const IOM_RECXS: usize = 16;
+// DAOS_IOMF_DETAIL, asks for every extent in the I/O map
+const IOMF_DETAIL: u32 = 1;
+#[cfg(feature = "list-obj")]
+const OBJ_LIST_DESCS: usize = 128;
+#[cfg(feature = "list-obj")]
+const OBJ_LIST_BUF_SIZE: usize = 64 * 1024;
+// KEY2BIG grows the buffer of a DaosObjList up to this
+#[cfg(feature = "list-obj")]
+const MAX_OBJ_LIST_BUF_SIZE: usize = 16 * 1024 * 1024;
+
+// kd_val_type of the descriptors daos_obj_list_obj fills, from enum
+// obj_iter_type in daos/object.h. The epoch types carry punch epochs and
+// are skipped.
+#[cfg(feature = "list-obj")]
+const OBJ_ITER_DKEY: u32 = 2;
+#[cfg(feature = "list-obj")]
+const OBJ_ITER_AKEY: u32 = 3;
+#[cfg(feature = "list-obj")]
+const OBJ_ITER_SINGLE: u32 = 4;
+#[cfg(feature = "list-obj")]
+const OBJ_ITER_RECX: u32 = 5;
+// struct obj_enum_rec: a daos_recx_t, a daos_epoch_range_t, the record
+// size, a version and flags, followed by the data when RECX_INLINE is set.
+#[cfg(feature = "list-obj")]
+const OBJ_ENUM_REC_SIZE: usize = 48;
+#[cfg(feature = "list-obj")]
+const RECX_INLINE: u32 = 1;
+// DIOF_TO_SPEC_SHARD of enum daos_io_flags, sends a fetch to the shard its
+// extra_arg points at instead of one libdaos picks.
+#[cfg(feature = "hedged-fetch")]
+const DIOF_TO_SPEC_SHARD: u32 = 0x2;
+// spreads the first fetch of fetch_hedged_async over the replicas
+#[cfg(feature = "libdaos")]
+static NEXT_REPLICA: AtomicU64 = AtomicU64::new(0);
+// attempts of update_objects_atomic_async before a conflict is handed back
+const MAX_TXN_ATTEMPTS: u32 = 8;
+
+// Full object enumeration is internal to libdaos, the rebuild and
+// migration code use it. It is exported from libdaos.so but declared in
+// daos/object.h, which isn't installed, so the declaration below follows
+// the DAOS 2.x client and the list-obj feature refuses to build against
+// other API versions.
+#[cfg(feature = "list-obj")]
+const _: () = assert!(
+    crate::bindings::DAOS_API_VERSION_MAJOR == 2,
+    "daos_obj_list_obj is declared for the DAOS 2.x client API"
+);
+
+#[cfg(feature = "list-obj")]
+extern "C" {
+    fn daos_obj_list_obj(
+        oh: DaosHandle,
+        th: DaosHandle,
+        dkey: *mut daos_key_t,
+        akey: *mut daos_key_t,
+        size: *mut u64,
+        nr: *mut u32,
+        kds: *mut daos_key_desc_t,
+        eprs: *mut daos_epoch_range_t,
+        sgl: *mut d_sg_list_t,
+        anchor: *mut daos_anchor_t,
+        dkey_anchor: *mut daos_anchor_t,
+        akey_anchor: *mut daos_anchor_t,
+        incr_order: bool,
+        enum_csum: bool,
+        csum: *mut d_iov_t,
+        ev: *mut daos_event_t,
+    ) -> std::os::raw::c_int;
+}
+
+// Another internal of libdaos.so, declared in daos/object.h as well, so
+// the obj-verify feature checks the API version the same way.
+#[cfg(feature = "obj-verify")]
+const _: () = assert!(
+    crate::bindings::DAOS_API_VERSION_MAJOR == 2,
+    "daos_obj_verify is declared for the DAOS 2.x client API"
+);
+
+#[cfg(feature = "obj-verify")]
+extern "C" {
+    // The replica consistency check of the DAOS tests. It has no event and
+    // compares every replica or shard of oid at epoch.
+    fn daos_obj_verify(coh: DaosHandle, oid: DaosObjectId, epoch: u64) -> std::os::raw::c_int;
+}
+
+// The fetch task and object layout internals fetch_hedged_async pins its
+// fetches to replicas with, from daos/object.h and daos/task.h. They are
+// declared for the DAOS 2.x client and the hedged-fetch feature refuses to
+// build against other API versions.
+#[cfg(feature = "hedged-fetch")]
+const _: () = assert!(
+    crate::bindings::DAOS_API_VERSION_MAJOR == 2,
+    "dc_obj_fetch_task_create and daos_obj_layout_get are declared for the DAOS 2.x client API"
+);
+
+#[cfg(feature = "hedged-fetch")]
+extern "C" {
+    // The task daos_obj_fetch creates and schedules, whose extra_flags and
+    // extra_arg daos_obj_fetch leaves unset.
+    fn dc_obj_fetch_task_create(
+        oh: DaosHandle,
+        th: DaosHandle,
+        api_flags: u64,
+        dkey: *mut daos_key_t,
+        nr: u32,
+        extra_flags: u32,
+        iods: *mut daos_iod_t,
+        sgls: *mut d_sg_list_t,
+        ioms: *mut daos_iom_t,
+        extra_arg: *mut std::os::raw::c_void,
+        csum: *mut d_iov_t,
+        ev: *mut daos_event_t,
+        tse: *mut std::os::raw::c_void,
+        task: *mut *mut std::os::raw::c_void,
+    ) -> std::os::raw::c_int;
+    fn dc_task_schedule(task: *mut std::os::raw::c_void, instant: bool) -> std::os::raw::c_int;
+    // The placement of oid.
+    fn daos_obj_layout_get(
+        coh: DaosHandle,
+        oid: DaosObjectId,
+        layout: *mut *mut ObjLayout,
+    ) -> std::os::raw::c_int;
+    fn daos_obj_layout_free(layout: *mut ObjLayout) -> std::os::raw::c_int;
+}
+
+// struct daos_obj_layout, one daos_obj_shard per redundancy group.
+#[cfg(feature = "hedged-fetch")]
+#[repr(C)]
+#[allow(dead_code)]
+struct ObjLayout {
+    ol_ver: u32,
+    ol_class: u32,
+    ol_nr: u32,
+    ol_shards: [*mut ObjShard; 0],
+}
+
+// struct daos_obj_shard, followed by the location of every replica.
+#[cfg(feature = "hedged-fetch")]
+#[repr(C)]
+struct ObjShard {
+    os_replica_nr: u32,
+}
 
 pub const DAOS_OT_ARRAY_BYTE: daos_otype_t = crate::bindings::daos_otype_t_DAOS_OT_ARRAY_BYTE;
+pub const DAOS_OT_MULTI_HASHED: daos_otype_t = crate::bindings::daos_otype_t_DAOS_OT_MULTI_HASHED;
 pub const DAOS_OC_UNKNOWN: daos_oclass_id_t = crate::bindings::OC_UNKNOWN;
 pub const DAOS_OC_HINTS_NONE: daos_oclass_hints_t = 0;
 pub const DAOS_COND_DKEY_INSERT: u32 = crate::bindings::DAOS_COND_DKEY_INSERT;
 pub const DAOS_COND_DKEY_UPDATE: u32 = crate::bindings::DAOS_COND_DKEY_UPDATE;
 pub const DAOS_COND_DKEY_FETCH: u32 = crate::bindings::DAOS_COND_DKEY_FETCH;
+pub const DAOS_COND_AKEY_INSERT: u32 = crate::bindings::DAOS_COND_AKEY_INSERT;
+pub const DAOS_COND_AKEY_UPDATE: u32 = crate::bindings::DAOS_COND_AKEY_UPDATE;
+pub const DAOS_COND_AKEY_FETCH: u32 = crate::bindings::DAOS_COND_AKEY_FETCH;
+pub const DAOS_COND_PUNCH: u32 = crate::bindings::DAOS_COND_PUNCH;
+pub const DAOS_COND_PER_AKEY: u32 = crate::bindings::DAOS_COND_PER_AKEY;
+
+bitflags::bitflags! {
+    // Typed DAOS_COND_* flags for fetch, update and punch. Flags this crate
+    // doesn't name can still be passed with CondFlags::from_bits_retain.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct CondFlags: u64 {
+        const DKEY_INSERT = DAOS_COND_DKEY_INSERT as u64;
+        const DKEY_UPDATE = DAOS_COND_DKEY_UPDATE as u64;
+        const DKEY_FETCH = DAOS_COND_DKEY_FETCH as u64;
+        const AKEY_INSERT = DAOS_COND_AKEY_INSERT as u64;
+        const AKEY_UPDATE = DAOS_COND_AKEY_UPDATE as u64;
+        const AKEY_FETCH = DAOS_COND_AKEY_FETCH as u64;
+        const PUNCH = DAOS_COND_PUNCH as u64;
+        const PER_AKEY = DAOS_COND_PER_AKEY as u64;
+    }
+}
+
+impl From<u64> for CondFlags {
+    fn from(raw: u64) -> Self {
+        CondFlags::from_bits_retain(raw)
+    }
+}
+
+impl From<CondFlags> for u64 {
+    fn from(flags: CondFlags) -> Self {
+        flags.bits()
+    }
+}
+
+const COND_MASK: u64 = crate::bindings::DAOS_COND_MASK as u64;
+const UPDATE_COND_FLAGS: u64 = (DAOS_COND_DKEY_INSERT
+    | DAOS_COND_DKEY_UPDATE
+    | DAOS_COND_AKEY_INSERT
+    | DAOS_COND_AKEY_UPDATE
+    | DAOS_COND_PER_AKEY) as u64;
+const FETCH_COND_FLAGS: u64 =
+    (DAOS_COND_DKEY_FETCH | DAOS_COND_AKEY_FETCH | DAOS_COND_PER_AKEY) as u64;
+
+// Returned as DaosError::InvalidFlags for a rejected flag combination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidFlags {
+    pub flags: u64,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for InvalidFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid flags {:#x}: {}", self.flags, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidFlags {}
+
+fn invalid_flags(flags: u64, reason: &'static str) -> DaosError {
+    DaosError::InvalidFlags(InvalidFlags { flags, reason })
+}
+
+pub(crate) fn check_update_flags(flags: u64) -> Result<()> {
+    if flags & COND_MASK & !UPDATE_COND_FLAGS != 0 {
+        return Err(invalid_flags(
+            flags,
+            "fetch or punch condition passed to update",
+        ));
+    }
+    let dkey_both = (DAOS_COND_DKEY_INSERT | DAOS_COND_DKEY_UPDATE) as u64;
+    if flags & dkey_both == dkey_both {
+        return Err(invalid_flags(
+            flags,
+            "DKEY_INSERT together with DKEY_UPDATE",
+        ));
+    }
+    let akey_both = (DAOS_COND_AKEY_INSERT | DAOS_COND_AKEY_UPDATE) as u64;
+    if flags & akey_both == akey_both {
+        return Err(invalid_flags(
+            flags,
+            "AKEY_INSERT together with AKEY_UPDATE",
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_punch_flags(flags: u64) -> Result<()> {
+    if flags & COND_MASK & !(DAOS_COND_PUNCH as u64) != 0 {
+        return Err(invalid_flags(
+            flags,
+            "update or fetch condition passed to punch",
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) const AKEY_UPDATE_FLAGS: u64 = (DAOS_COND_AKEY_INSERT | DAOS_COND_AKEY_UPDATE) as u64;
+pub(crate) const AKEY_FETCH_FLAGS: u64 = DAOS_COND_AKEY_FETCH as u64;
+
+// Op flags and iod_flags of a call over several akeys of a dkey. Akeys
+// without a condition of their own take the akey conditions of flags, and
+// once any akey has one DAOS_COND_PER_AKEY makes DAOS read all of them from
+// iod_flags. akey_mask holds the conditions allowed per akey.
+pub(crate) fn per_akey_flags(
+    flags: u64,
+    akey_flags: &[CondFlags],
+    akey_mask: u64,
+) -> Result<(u64, Vec<u64>)> {
+    if let Some(bad) = akey_flags.iter().find(|f| f.bits() & !akey_mask != 0) {
+        return Err(invalid_flags(
+            bad.bits(),
+            "per-akey flags hold a condition that isn't an akey one for this call",
+        ));
+    }
+    if akey_flags.iter().all(|f| f.is_empty()) {
+        return Ok((flags, vec![0; akey_flags.len()]));
+    }
+    let shared = flags & akey_mask;
+    let iod_flags = akey_flags
+        .iter()
+        .map(|f| if f.is_empty() { shared } else { f.bits() })
+        .collect();
+    Ok(((flags & !akey_mask) | DAOS_COND_PER_AKEY as u64, iod_flags))
+}
+
+// buf as possibly uninitialized bytes, for the fetches that take those.
+// Only initialized bytes are ever written through it.
+fn as_uninit(buf: &mut [u8]) -> &mut [MaybeUninit<u8>] {
+    unsafe { &mut *(buf as *mut [u8] as *mut [MaybeUninit<u8>]) }
+}
+
+// Zeroes buf for the code paths that need it initialized.
+fn zero_init(buf: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+    buf.fill(MaybeUninit::new(0));
+    unsafe { &mut *(buf as *mut [MaybeUninit<u8>] as *mut [u8]) }
+}
+
+// The first len bytes of buf, which must have been written.
+unsafe fn assume_init(buf: &[MaybeUninit<u8>], len: usize) -> &[u8] {
+    std::slice::from_raw_parts(buf.as_ptr() as *const u8, len)
+}
+
+pub(crate) fn write_uninit(dst: &mut [MaybeUninit<u8>], src: &[u8]) {
+    assert_eq!(dst.len(), src.len());
+    unsafe { ptr::copy_nonoverlapping(src.as_ptr(), dst.as_mut_ptr() as *mut u8, src.len()) };
+}
+
+// d_iov_t view of buf, valid as long as buf is.
+#[cfg(feature = "libdaos")]
+fn buf_iov(buf: &[u8]) -> d_iov_t {
+    d_iov_t {
+        iov_buf: buf.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+        iov_buf_len: buf.len(),
+        iov_len: buf.len(),
+    }
+}
+
+// iods and sgls of a single value update or fetch over several akeys of
+// one dkey, one iod per akey scattering into the sg_iov of the same index.
+// They point into the akeys and buffers they were built from.
+#[cfg(feature = "libdaos")]
+struct AkeysIo {
+    iods: Vec<daos_iod_t>,
+    _sg_iovs: Vec<d_iov_t>,
+    sgls: Vec<d_sg_list_t>,
+}
+
+#[cfg(feature = "libdaos")]
+impl AkeysIo {
+    // With iod_size None each iod takes the size of its sg_iov.
+    fn new<'a>(
+        akeys: impl Iterator<Item = &'a [u8]>,
+        iod_flags: &[u64],
+        mut sg_iovs: Vec<d_iov_t>,
+        iod_size: Option<u64>,
+    ) -> Self {
+        let iods = akeys
+            .zip(iod_flags)
+            .zip(sg_iovs.iter())
+            .map(|((akey, iod_flags), sg_iov)| daos_iod_t {
+                iod_name: buf_iov(akey),
+                iod_type: daos_iod_type_t_DAOS_IOD_SINGLE,
+                iod_size: iod_size.unwrap_or(sg_iov.iov_len as u64),
+                iod_flags: *iod_flags,
+                iod_nr: 1,
+                iod_recxs: std::ptr::null_mut(),
+            })
+            .collect();
+        let sgls = sg_iovs
+            .iter_mut()
+            .map(|sg_iov| d_sg_list_t {
+                sg_nr: 1,
+                sg_nr_out: 0,
+                sg_iovs: sg_iov,
+            })
+            .collect();
+        AkeysIo {
+            iods,
+            _sg_iovs: sg_iovs,
+            sgls,
+        }
+    }
+}
+
+// Object metadata of set_meta_async lives under META_DKEY, one akey per
+// name, each akey starting with META_PREFIX. Updates of user akeys in that
+// namespace are refused so they can't clobber it.
+pub const META_PREFIX: &[u8] = b"\0meta.";
+pub(crate) const META_DKEY: &[u8] = META_PREFIX;
+// values are small, read in one fetch
+pub const META_MAX_SIZE: usize = 4 * 1024;
+// metadata names every application should spell the same way
+pub const META_CONTENT_TYPE: &str = "content-type";
+pub const META_SCHEMA_VERSION: &str = "schema-version";
+
+fn check_user_akey(akey: &[u8]) -> Result<()> {
+    if akey.starts_with(META_PREFIX) {
+        return Err(DaosError::inval(
+            "akey is in the reserved metadata namespace",
+        ));
+    }
+    Ok(())
+}
+
+// Punching META_DKEY would drop every metadata name at once.
+fn check_user_dkey(dkey: &[u8]) -> Result<()> {
+    if dkey == META_DKEY {
+        return Err(DaosError::inval("dkey is reserved for object metadata"));
+    }
+    Ok(())
+}
+
+fn meta_akey(name: &str) -> Result<Vec<u8>> {
+    if name.is_empty() {
+        return Err(DaosError::inval("metadata name must not be empty"));
+    }
+    Ok([META_PREFIX, name.as_bytes()].concat())
+}
+
+// daos_key_t views of keys, valid as long as keys is.
+#[cfg(feature = "libdaos")]
+fn key_iovs(keys: &[Vec<u8>]) -> Vec<daos_key_t> {
+    keys.iter()
+        .map(|key| daos_key_t {
+            iov_buf: key.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+            iov_buf_len: key.len(),
+            iov_len: key.len(),
+        })
+        .collect()
+}
+
+pub(crate) fn check_fetch_flags(flags: u64) -> Result<()> {
+    if flags & COND_MASK & !FETCH_COND_FLAGS != 0 {
+        return Err(invalid_flags(
+            flags,
+            "update or punch condition passed to fetch",
+        ));
+    }
+    Ok(())
+}
 
 impl Hash for DaosObjectId {
     fn hash<H: Hasher>(&self, state: &mut H) {
@@ -58,309 +493,582 @@ impl PartialEq for DaosObjectId {
     fn eq(&self, other: &Self) -> bool {
         self.lo == other.lo && self.hi == other.hi
     }
-    fn ne(&self, other: &Self) -> bool {
-        self.lo != other.lo || self.hi != other.hi
-    }
 }
 
 impl Eq for DaosObjectId {}
 
+const OID_TYPE_SHIFT: u64 = crate::bindings::OID_FMT_TYPE_SHIFT as u64;
+const OID_TYPE_MASK: u64 = (1 << crate::bindings::OID_FMT_TYPE_BITS as u64) - 1;
+const OID_CLASS_SHIFT: u64 = crate::bindings::OID_FMT_CLASS_SHIFT as u64;
+const OID_CLASS_MASK: u64 = (1 << crate::bindings::OID_FMT_CLASS_BITS as u64) - 1;
+const OID_META_SHIFT: u64 = crate::bindings::OID_FMT_META_SHIFT as u64;
+const OID_META_MASK: u64 = (1 << crate::bindings::OID_FMT_META_BITS as u64) - 1;
+
+impl DaosObjectId {
+    // The object type daos_obj_generate_oid2 put in the top bits of hi.
+    pub fn otype(&self) -> daos_otype_t {
+        ((self.hi >> OID_TYPE_SHIFT) & OID_TYPE_MASK) as daos_otype_t
+    }
+
+    // The object class, rebuilt from the redundancy and group bits of hi
+    // the way daos_obj_id2class does.
+    pub fn class_id(&self) -> daos_oclass_id_t {
+        let redun = (self.hi >> OID_CLASS_SHIFT) & OID_CLASS_MASK;
+        let grp_nr = (self.hi >> OID_META_SHIFT) & OID_META_MASK;
+        ((redun << crate::bindings::OC_REDUN_SHIFT as u64) | grp_nr) as daos_oclass_id_t
+    }
+
+    // lo then hi, little endian, as archives and scan cursors store oids.
+    pub fn to_bytes(self) -> [u8; 16] {
+        let mut buf = [0u8; 16];
+        buf[..8].copy_from_slice(&self.lo.to_le_bytes());
+        buf[8..].copy_from_slice(&self.hi.to_le_bytes());
+        buf
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<DaosObjectId> {
+        if buf.len() != 16 {
+            return Err(DaosError::inval("an oid takes 16 bytes"));
+        }
+        Ok(DaosObjectId {
+            lo: u64::from_le_bytes(buf[..8].try_into().unwrap()),
+            hi: u64::from_le_bytes(buf[8..].try_into().unwrap()),
+        })
+    }
+}
+
+// The hi.lo hex form of the daos tool.
 impl fmt::Display for DaosObjectId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}, {})", self.lo, self.hi)
+        write!(f, "{:x}.{:x}", self.hi, self.lo)
     }
 }
 
-#[derive(Debug)]
+impl FromStr for DaosObjectId {
+    type Err = DaosError;
+
+    fn from_str(s: &str) -> Result<DaosObjectId> {
+        let parse = |part: &str| {
+            let part = part.strip_prefix("0x").unwrap_or(part);
+            u64::from_str_radix(part, 16)
+                .map_err(|_| DaosError::inval(format!("invalid oid {:?}, expected hi.lo", s)))
+        };
+        let (hi, lo) = s
+            .split_once('.')
+            .ok_or_else(|| DaosError::inval(format!("invalid oid {:?}, expected hi.lo", s)))?;
+        Ok(DaosObjectId {
+            lo: parse(lo)?,
+            hi: parse(hi)?,
+        })
+    }
+}
+
+// hi in the upper 64 bits, lo in the lower ones.
+impl From<u128> for DaosObjectId {
+    fn from(oid: u128) -> Self {
+        DaosObjectId {
+            lo: oid as u64,
+            hi: (oid >> 64) as u64,
+        }
+    }
+}
+
+impl From<DaosObjectId> for u128 {
+    fn from(oid: DaosObjectId) -> Self {
+        ((oid.hi as u128) << 64) | oid.lo as u128
+    }
+}
+
+// The hi.lo string in human readable formats like JSON, a u128 otherwise.
+#[cfg(feature = "serde")]
+impl serde::Serialize for DaosObjectId {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_u128(u128::from(*self))
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DaosObjectId {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+            s.parse().map_err(serde::de::Error::custom)
+        } else {
+            <u128 as serde::Deserialize>::deserialize(deserializer).map(DaosObjectId::from)
+        }
+    }
+}
+
+// Cond flags an object applies to calls that pass empty flags, e.g. update
+// CondFlags::DKEY_UPDATE so writes never create rows, or fetch
+// CondFlags::DKEY_FETCH for strict reads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DaosDefaultFlags {
+    pub update: CondFlags,
+    pub fetch: CondFlags,
+}
+
+impl DaosDefaultFlags {
+    pub(crate) fn apply_update(&self, flags: CondFlags) -> u64 {
+        if flags.is_empty() {
+            self.update.bits()
+        } else {
+            flags.bits()
+        }
+    }
+
+    pub(crate) fn apply_fetch(&self, flags: CondFlags) -> u64 {
+        if flags.is_empty() {
+            self.fetch.bits()
+        } else {
+            flags.bits()
+        }
+    }
+}
+
+// A cloneable stand-in for an object handle that can be moved into
+// spawn_blocking closures. close() waits for tokens in use and turns later
+// ones away with NoHandle, so the handle never outlives the object.
+#[derive(Debug, Clone)]
+pub struct DaosHandleToken {
+    oid: DaosObjectId,
+    handle: Arc<RwLock<Option<DaosHandle>>>,
+}
+
+impl DaosHandleToken {
+    // Runs f with the object handle, which stays open until f returns.
+    pub fn with_handle<R>(&self, f: impl FnOnce(DaosHandle) -> R) -> Result<R> {
+        let handle = self.handle.read().unwrap();
+        match *handle {
+            Some(hdl) => Ok(f(hdl)),
+            None => Err(DaosError::no_handle("object is closed")
+                .with_context(DaosErrorContext::new("handle_token", self.oid))),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.handle.read().unwrap().is_some()
+    }
+}
+
+#[cfg(feature = "libdaos")]
 pub struct DaosObject {
     pub oid: DaosObjectId,
     handle: Option<DaosHandle>,
-    event_que: Option<DaosHandle>,
+    shared_handle: Arc<RwLock<Option<DaosHandle>>>,
+    // of the container the object was opened in, for verify_async and
+    // fetch_hedged_async
+    #[cfg_attr(
+        not(any(feature = "obj-verify", feature = "hedged-fetch")),
+        allow(dead_code)
+    )]
+    cont_handle: Option<DaosHandle>,
+    event_que: Option<DaosEventQueue>,
+    deadlines: DaosDeadlines,
+    cache: Option<Arc<DaosObjCache>>,
+    default_flags: DaosDefaultFlags,
+    read_only: bool,
+    compression: Option<DaosCompression>,
+    #[cfg(feature = "bytes")]
+    fetch_buf: Mutex<BytesMut>,
 }
 
+#[cfg(feature = "libdaos")]
 impl DaosObject {
-    fn new(id: DaosObjectId, hdl: DaosHandle, evt_que: Option<DaosHandle>) -> Self {
+    fn new(
+        id: DaosObjectId,
+        hdl: DaosHandle,
+        evt_que: Option<DaosEventQueue>,
+        deadlines: DaosDeadlines,
+    ) -> Self {
         DaosObject {
             oid: id,
             handle: Some(hdl),
+            shared_handle: Arc::new(RwLock::new(Some(hdl))),
+            cont_handle: None,
             event_que: evt_que,
+            deadlines,
+            cache: None,
+            default_flags: DaosDefaultFlags::default(),
+            read_only: false,
+            compression: None,
+            #[cfg(feature = "bytes")]
+            fetch_buf: Mutex::new(BytesMut::new()),
         }
     }
 
-    pub fn get_handle(&self) -> Option<DaosHandle> {
-        self.handle.clone()
+    fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
     }
 
-    pub fn get_event_queue(&self) -> Option<DaosHandle> {
-        self.event_que.clone()
+    fn with_container(mut self, cont_hdl: Option<DaosHandle>) -> Self {
+        self.cont_handle = cont_hdl;
+        self
     }
 
-    fn close(&mut self) -> Result<()> {
-        if self.handle.is_some() {
-            let res = unsafe { daos_obj_close(self.handle.unwrap(), ptr::null_mut()) };
-            if res == 0 {
-                self.handle.take();
-                Ok(())
-            } else {
-                Err(Error::new(ErrorKind::Other, "Failed to close DAOS object"))
-            }
-        } else {
-            Ok(())
+    pub fn set_deadlines(&mut self, deadlines: DaosDeadlines) {
+        self.deadlines = deadlines;
+    }
+
+    pub fn get_deadlines(&self) -> DaosDeadlines {
+        self.deadlines
+    }
+
+    // Whether the object was opened with read_only, in which case updates
+    // and punches fail with DaosError::ReadOnly without reaching DAOS.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn check_writable(&self, op: &str) -> Result<()> {
+        if self.read_only {
+            return Err(DaosError::read_only(format!("{} read-only object", op)));
         }
+        Ok(())
     }
-}
 
-impl Drop for DaosObject {
-    fn drop(&mut self) {
-        let res = self.close();
-        match res {
-            Ok(_) => {}
-            Err(e) => {
-                eprintln!("Failed to drop DAOS object: {:?}", e);
+    pub fn set_default_flags(&mut self, default_flags: DaosDefaultFlags) -> Result<()> {
+        check_update_flags(default_flags.update.bits())?;
+        check_fetch_flags(default_flags.fetch.bits())?;
+        self.default_flags = default_flags;
+        Ok(())
+    }
+
+    pub fn get_default_flags(&self) -> DaosDefaultFlags {
+        self.default_flags
+    }
+
+    // Compresses the single values written by update, update_async,
+    // update_vectored_async, update_many_async, update_akeys,
+    // update_akeys_async and set_meta_async, and decompresses the ones read
+    // by fetch, fetch_with_size, fetch_async, fetch_uninit_async,
+    // fetch_into_async, fetch_vectored_async, fetch_akeys,
+    // fetch_akeys_async, get_multi_async, get_map_async,
+    // fetch_hedged_async, fetch_with_epoch_async and get_meta_async.
+    // Array extents, listings and punches see the stored bytes as they are.
+    // Values stored without the compression header are fetched unchanged,
+    // so it can be turned on for objects holding older values.
+    pub fn set_compression(&mut self, compression: Option<DaosCompression>) {
+        self.compression = compression;
+    }
+
+    pub fn get_compression(&self) -> Option<DaosCompression> {
+        self.compression
+    }
+
+    // Caches up to `capacity` single values read by fetch/fetch_async.
+    pub fn enable_cache(&mut self, capacity: usize) {
+        self.cache = Some(Arc::new(DaosObjCache::new(capacity)));
+    }
+
+    pub fn disable_cache(&mut self) {
+        self.cache = None;
+    }
+
+    // Checks that the replicas or shards of the object agree at epoch,
+    // u64::MAX for the latest state, e.g. on suspect objects after a
+    // rebuild. Ok(false) when they differ. daos_obj_verify takes no event,
+    // so it runs on tokio's blocking pool.
+    #[cfg(feature = "obj-verify")]
+    pub async fn verify_async(&self, epoch: u64) -> Result<bool> {
+        let oid = self.oid;
+        let context = || DaosErrorContext::new("verify_async", oid);
+        let coh = match self.cont_handle {
+            Some(coh) => coh,
+            None => {
+                return Err(
+                    DaosError::no_handle("object has no container handle").with_context(context())
+                )
             }
+        };
+        let ret = tokio::task::spawn_blocking(move || unsafe { daos_obj_verify(coh, oid, epoch) })
+            .await
+            .map_err(|_| DaosError::canceled("verify task failed").with_context(context()))?;
+        match ret {
+            0 => Ok(true),
+            ret if ret == -(crate::bindings::daos_errno_DER_MISMATCH as i32) => Ok(false),
+            ret => Err(
+                DaosError::from_call("daos_obj_verify", ret, "Failed to verify object")
+                    .with_context(context()),
+            ),
         }
     }
-}
 
-#[derive(Debug)]
-pub struct DaosKeyList {
-    anchor: Box<daos_anchor_t>,
-    ndesc: Box<u32>,
-    key_descs: Vec<daos_key_desc_t>,
-    out_buf: Vec<u8>,
-}
+    // The replica count of the object, which fetch_hedged_async pins its
+    // fetches to. Only replicated classes with a single redundancy group
+    // qualify: libdaos doesn't tell which group a dkey hashes to, and the
+    // shards of group 0 are its replicas.
+    #[cfg(feature = "hedged-fetch")]
+    fn hedge_replicas(&self) -> Result<u32> {
+        let class = ObjectClass::from_id(self.oid.class_id());
+        if !class.name()?.starts_with("RP_") {
+            return Err(DaosError::inval(
+                "hedged fetches need a replicated object class",
+            ));
+        }
+        let coh = self
+            .cont_handle
+            .ok_or_else(|| DaosError::no_handle("object has no container handle"))?;
 
-impl DaosKeyList {
-    pub fn new() -> Box<Self> {
-        let vec = vec![0u8; KEY_BUF_SIZE];
-        Box::new(DaosKeyList {
-            anchor: Box::new(daos_anchor_t {
-                da_type: 0,
-                da_shard: 0,
-                da_flags: 0,
-                da_sub_anchors: 0,
-                da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
-            }),
-            ndesc: Box::new(0),
-            key_descs: vec![
-                daos_key_desc_t {
-                    kd_key_len: 0,
-                    kd_val_type: 0,
-                };
-                MAX_KEY_DESCS as usize
-            ],
-            out_buf: vec,
-        })
+        let mut layout: *mut ObjLayout = ptr::null_mut();
+        let ret = unsafe { daos_obj_layout_get(coh, self.oid, &mut layout) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_obj_layout_get",
+                ret,
+                "Failed to query object layout",
+            ));
+        }
+        let (groups, replicas) = unsafe {
+            let groups = (*layout).ol_nr;
+            let replicas = match groups {
+                0 => 0,
+                _ => (*(*layout).ol_shards.as_ptr().read()).os_replica_nr,
+            };
+            daos_obj_layout_free(layout);
+            (groups, replicas)
+        };
+        if groups != 1 {
+            return Err(DaosError::no_sys(
+                "hedged fetches need a single redundancy group",
+            ));
+        }
+        if replicas < 2 {
+            return Err(DaosError::inval("object has a single replica"));
+        }
+        Ok(replicas)
     }
 
-    fn prepare_next_query(&mut self) {
-        *(self.ndesc) = MAX_KEY_DESCS;
+    #[cfg(not(feature = "hedged-fetch"))]
+    fn hedge_replicas(&self) -> Result<u32> {
+        Err(DaosError::no_sys(
+            "hedged fetches need the hedged-fetch feature",
+        ))
     }
 
-    pub fn get_key_num(&self) -> u32 {
-        *self.ndesc
+    pub fn cache_stats(&self) -> Option<DaosObjCacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
     }
 
-    pub fn reach_end(&self) -> bool {
-        daos_anchor_is_eof(self.anchor.as_ref())
+    pub fn invalidate_cache(&self) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.clear();
+        }
     }
 
-    // use (0, 0) as start position
-    pub fn get_key(&self, start_and_idx: (u32, u32)) -> Result<(&[u8], (u32, u32))> {
-        let (start, idx) = start_and_idx;
-        if idx >= *self.ndesc {
-            return Err(Error::new(ErrorKind::Other, "index out of range"));
+    // Drops every cached value when the container epoch moves, e.g. when a
+    // new snapshot shows up or other writers are known to have committed.
+    pub fn observe_epoch(&self, epoch: u64) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.observe_epoch(epoch);
         }
-        let key_desc = &self.key_descs[idx as usize];
-        let end = start as usize + key_desc.kd_key_len as usize;
-        let key = &self.out_buf[start as usize..end];
-        Ok((key, (end as u32, idx + 1)))
     }
-}
 
-pub trait DaosObjSyncOps {
-    fn create(
-        cont: &DaosContainer,
-        oid_allocator: Arc<DaosSyncOidAllocator>,
-        otype: daos_otype_t,
-        cid: daos_oclass_id_t,
-        hints: daos_oclass_hints_t,
-        args: u32,
-    ) -> Result<Box<DaosObject>>;
-    fn open(cont: &DaosContainer, oid: DaosObjectId, read_only: bool) -> Result<Box<DaosObject>>;
-    fn punch(&self, txn: &DaosTxn) -> Result<()>;
-    fn fetch(
-        &self,
-        txn: &DaosTxn,
-        flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        max_size: u32,
-    ) -> Result<Vec<u8>>;
-    fn update(
+    // The cache only serves reads of the latest committed state, so fetches
+    // inside a transaction or with cond flags always go to DAOS.
+    fn cache_for(&self, txn: &DaosTxn, flags: u64) -> Option<&DaosObjCache> {
+        if txn.get_handle().is_none() && flags == 0 {
+            self.cache.as_deref()
+        } else {
+            None
+        }
+    }
+
+    // fetch_async of the bytes as stored, before decompression.
+    async fn fetch_stored_async(
         &self,
         txn: &DaosTxn,
-        flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        data: &[u8],
-    ) -> Result<()>;
-}
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        out_buf: &mut [MaybeUninit<u8>],
+    ) -> Result<usize> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let res: Result<usize> = async {
+            let flags = self.default_flags.apply_fetch(flags);
+            check_fetch_flags(flags)?;
 
-pub trait DaosObjAsyncOps {
-    fn create_async(
-        cont: &DaosContainer,
-        oid_allocator: Arc<DaosAsyncOidAllocator>,
-        otype: daos_otype_t,
-        cid: daos_oclass_id_t,
-        hints: daos_oclass_hints_t,
-        args: u32,
-    ) -> impl Future<Output = Result<Box<DaosObject>>> + Send + 'static;
-    fn open_async(
-        cont: &DaosContainer,
-        oid: DaosObjectId,
-        read_only: bool,
-    ) -> impl Future<Output = Result<Box<DaosObject>>> + Send + 'static;
-    fn punch_async(&self, txn: &DaosTxn) -> impl Future<Output = Result<()>> + Send + 'static;
-    async fn fetch_async(
-        &self,
-        txn: &DaosTxn,
-        flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        out_buf: &mut [u8],
-    ) -> Result<usize>;
-    async fn update_async(
-        &self,
-        txn: &DaosTxn,
-        flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        data: &[u8],
-    ) -> Result<()>;
-    async fn fetch_recx_async(
-        &self,
-        txn: &DaosTxn,
-        flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        offset: u64,
-        out_buf: &mut [u8],
-    ) -> Result<usize>;
-    async fn update_recx_async(
-        &self,
-        txn: &DaosTxn,
-        flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        offset: u64,
-        data: &[u8],
-    ) -> Result<()>;
-    fn list_dkey_async(
-        &self,
-        txn: &DaosTxn,
-        key_lst: Box<DaosKeyList>,
-    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static;
-}
-
-impl DaosObjSyncOps for DaosObject {
-    fn create(
-        cont: &DaosContainer,
-        oid_allocator: Arc<DaosSyncOidAllocator>,
-        otype: daos_otype_t,
-        cid: daos_oclass_id_t,
-        hints: daos_oclass_hints_t,
-        args: u32,
-    ) -> Result<Box<DaosObject>> {
-        let cont_hdl = cont.get_handle();
-        let eq = cont.get_event_queue();
-        let eqh = eq.map(|eq| eq.get_handle().unwrap());
+            let eq = self.event_que.clone();
+            let deadline = self.deadlines.bulk;
+            let obj_hdl = self.get_handle();
+            let tx_hdl = txn.get_handle();
 
-        let mut oid = oid_allocator.allocate()?;
-        let ret =
-            unsafe { daos_obj_generate_oid2(cont_hdl.unwrap(), &mut oid, otype, cid, hints, args) };
+            if eq.is_none() {
+                return Err(DaosError::no_handle("event queue is nil"));
+            }
+            if obj_hdl.is_none() {
+                return Err(DaosError::no_handle("fetch uninitialized object"));
+            }
 
-        if ret != 0 {
-            return Err(Error::new(ErrorKind::Other, "can't generate object id"));
-        }
+            let cache = self.cache_for(txn, flags);
+            let generation = match cache.map(|cache| cache.get(dkey, akey)) {
+                Some(Ok(value)) if value.len() > out_buf.len() => {
+                    let written = out_buf.len();
+                    write_uninit(out_buf, &value[..written]);
+                    return Err(DaosError::rec2big(
+                        "record is larger than the buffer",
+                        value.len(),
+                    )
+                    .into_truncated(written));
+                }
+                Some(Ok(value)) => {
+                    write_uninit(&mut out_buf[..value.len()], &value);
+                    return Ok(value.len());
+                }
+                Some(Err(generation)) => generation,
+                None => 0,
+            };
 
-        let mut obj_hdl = DaosHandle { cookie: 0u64 };
-        let ret = unsafe {
-            daos_obj_open(
-                cont_hdl.unwrap(),
-                oid,
-                DAOS_OO_RW,
-                &mut obj_hdl,
-                std::ptr::null_mut(),
-            )
-        };
+            let mut event = eq.unwrap().create_event()?;
+            let rx = event.register_callback()?;
 
-        if ret != 0 {
-            return Err(Error::new(ErrorKind::Other, "can't open object"));
-        } else {
-            Ok(Box::new(DaosObject::new(oid, obj_hdl, eqh)))
-        }
-    }
+            let txn = match tx_hdl {
+                Some(tx) => tx,
+                None => DAOS_TXN_NONE,
+            };
 
-    fn open(cont: &DaosContainer, oid: DaosObjectId, read_only: bool) -> Result<Box<DaosObject>> {
-        let cont_hdl = cont.get_handle();
-        let eq = cont.get_event_queue();
-        let eqh = eq.map(|eq| eq.get_handle().unwrap());
+            let mut dkey_wrapper = Box::new(daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            });
+            let mut iod = Box::new(daos_iod_t {
+                iod_name: daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                },
+                iod_type: daos_iod_type_t_DAOS_IOD_SINGLE,
+                iod_size: DAOS_REC_ANY as u64,
+                iod_flags: 0,
+                iod_nr: 1,
+                iod_recxs: std::ptr::null_mut(),
+            });
 
-        let mut obj_hdl = DaosHandle { cookie: 0u64 };
-        let ret = unsafe {
-            daos_obj_open(
-                cont_hdl.unwrap(),
-                oid,
-                if read_only { DAOS_OO_RO } else { DAOS_OO_RW },
-                &mut obj_hdl,
-                std::ptr::null_mut(),
-            )
-        };
+            let mut sg_iov = Box::new(d_iov_t {
+                iov_buf: out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
+                iov_buf_len: out_buf.len(),
+                iov_len: out_buf.len(),
+            });
+            let mut sgl = Box::new(d_sg_list_t {
+                sg_nr: 1,
+                sg_nr_out: 0,
+                sg_iovs: sg_iov.as_mut(),
+            });
+            let ret = unsafe {
+                daos_obj_fetch(
+                    obj_hdl.unwrap(),
+                    txn,
+                    flags,
+                    dkey_wrapper.as_mut(),
+                    1,
+                    iod.as_mut(),
+                    sgl.as_mut(),
+                    ptr::null_mut(),
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_obj_fetch",
+                    ret,
+                    "can't fetch object",
+                ));
+            }
 
-        if ret != 0 {
-            Err(Error::new(
-                ErrorKind::Other,
-                format!("can't open object, ret={}", ret),
-            ))
-        } else {
-            Ok(Box::new(DaosObject::new(oid, obj_hdl, eqh)))
+            match event.wait(rx, deadline).await {
+                Ok(ret) => {
+                    if ret != 0 {
+                        // libdaos copies nothing of a value too large
+                        Err(DaosError::from_call(
+                            "daos_obj_fetch",
+                            ret,
+                            "async fetch operation fail",
+                        )
+                        .with_required_size(iod.iod_size as usize)
+                        .into_truncated(0))
+                    } else {
+                        let size = iod.iod_size as usize;
+                        if size > out_buf.len() {
+                            let written = if sgl.sg_nr_out > 0 {
+                                sg_iov.iov_len.min(out_buf.len())
+                            } else {
+                                0
+                            };
+                            return Err(DaosError::rec2big(
+                                "record is larger than the buffer",
+                                size,
+                            )
+                            .into_truncated(written));
+                        }
+                        if let Some(cache) = cache {
+                            // daos_obj_fetch wrote the first size bytes
+                            let value = unsafe { assume_init(out_buf, size) };
+                            cache.put(generation, dkey.to_vec(), akey.to_vec(), value.to_vec());
+                        }
+                        Ok(size)
+                    }
+                }
+                Err(e) => Err(e),
+            }
         }
+        .await;
+        res.map_err(|e| e.with_context(self.op_context("fetch_async", dkey, akey)))
     }
 
-    fn punch(&self, _txn: &DaosTxn) -> Result<()> {
-        Err(Error::new(ErrorKind::Other, "Not implemented"))
-    }
-
-    fn fetch(
+    // Blocking fetch of the bytes as stored, into a buffer of buf_size.
+    fn fetch_stored(
         &self,
         txn: &DaosTxn,
-        flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        max_size: u32,
+        flags: CondFlags,
+        dkey: &[u8],
+        akey: &[u8],
+        buf_size: usize,
     ) -> Result<Vec<u8>> {
+        let flags = self.default_flags.apply_fetch(flags);
+        check_fetch_flags(flags)?;
+
         let obj_hdl = self.get_handle();
         if obj_hdl.is_none() {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "fetch uninitialized object",
-            ));
+            return Err(DaosError::no_handle("fetch uninitialized object")
+                .with_context(self.op_context("fetch", dkey, akey)));
         }
 
+        let cache = self.cache_for(txn, flags);
+        let generation = match cache.map(|cache| cache.get(dkey, akey)) {
+            Some(Ok(value)) if value.len() > buf_size => {
+                let required = value.len();
+                return Err(
+                    DaosError::rec2big("record is larger than the buffer", required)
+                        .with_context(self.op_context("fetch", dkey, akey)),
+                );
+            }
+            Some(Ok(value)) => return Ok(value),
+            Some(Err(generation)) => generation,
+            None => 0,
+        };
+
         let txn_hdl = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
-        let mut dkey = dkey;
-        let mut akey = akey;
 
         let mut dkey_wrapper = daos_key_t {
-            iov_buf: dkey.as_mut_ptr() as *mut std::os::raw::c_void,
+            iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
             iov_buf_len: dkey.len(),
             iov_len: dkey.len(),
         };
 
         let mut iod = daos_iod_t {
             iod_name: daos_key_t {
-                iov_buf: akey.as_mut_ptr() as *mut std::os::raw::c_void,
+                iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
                 iov_buf_len: akey.len(),
                 iov_len: akey.len(),
             },
@@ -371,13 +1079,13 @@ impl DaosObjSyncOps for DaosObject {
             iod_recxs: std::ptr::null_mut(),
         };
 
-        let mut buf = Vec::with_capacity(max_size as usize);
-        buf.resize(max_size as usize, 0u8);
+        // fetched into the spare capacity, nothing is zeroed
+        let mut buf: Vec<u8> = Vec::with_capacity(buf_size);
 
         let mut sg_iov = d_iov_t {
             iov_buf: buf.as_mut_ptr() as *mut std::os::raw::c_void,
-            iov_buf_len: buf.len(),
-            iov_len: buf.len(),
+            iov_buf_len: buf_size,
+            iov_len: buf_size,
         };
 
         let mut sgl = d_sg_list_t {
@@ -401,38 +1109,178 @@ impl DaosObjSyncOps for DaosObject {
         };
 
         if ret != 0 {
-            return Err(Error::new(ErrorKind::Other, "Failed to fetch object"));
+            return Err(
+                DaosError::from_call("daos_obj_fetch", ret, "Failed to fetch object")
+                    .with_required_size(iod.iod_size as usize)
+                    .with_context(self.op_context("fetch", dkey, akey)),
+            );
         }
 
-        buf.resize(iod.iod_size as usize, 0xffu8);
+        // daos_obj_fetch wrote the first iod_size bytes
+        unsafe { buf.set_len((iod.iod_size as usize).min(buf_size)) };
+        if let Some(cache) = cache {
+            cache.put(generation, dkey.to_vec(), akey.to_vec(), buf.clone());
+        }
         Ok(buf)
     }
 
-    fn update(
+    // Submits a single value fetch, served by the given shard if any.
+    fn submit_fetch(
         &self,
         txn: &DaosTxn,
         flags: u64,
         dkey: Vec<u8>,
         akey: Vec<u8>,
-        data: &[u8],
-    ) -> Result<()> {
+        buf_size: usize,
+        shard: Option<u32>,
+    ) -> Result<PendingFetch> {
+        let eq = self.event_que.as_ref();
+        if eq.is_none() {
+            return Err(DaosError::no_handle("event queue is nil"));
+        }
         let obj_hdl = self.get_handle();
         if obj_hdl.is_none() {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "update uninitialized object",
-            ));
+            return Err(DaosError::no_handle("fetch uninitialized object"));
         }
 
-        let txn_hdl = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+        let mut event = eq.unwrap().create_event()?;
+        let rx = event.register_callback()?;
+        let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
 
-        let mut dkey_wrapper = daos_key_t {
+        let mut buf = vec![0u8; buf_size];
+        let mut dkey_wrapper = Box::new(daos_key_t {
             iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
             iov_buf_len: dkey.len(),
             iov_len: dkey.len(),
+        });
+        let mut iod = Box::new(daos_iod_t {
+            iod_name: daos_key_t {
+                iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: akey.len(),
+                iov_len: akey.len(),
+            },
+            iod_type: daos_iod_type_t_DAOS_IOD_SINGLE,
+            iod_size: DAOS_REC_ANY as u64,
+            iod_flags: 0,
+            iod_nr: 1,
+            iod_recxs: std::ptr::null_mut(),
+        });
+        let mut sg_iov = Box::new(d_iov_t {
+            iov_buf: buf.as_mut_ptr() as *mut std::os::raw::c_void,
+            iov_buf_len: buf.len(),
+            iov_len: buf.len(),
+        });
+        let mut sgl = Box::new(d_sg_list_t {
+            sg_nr: 1,
+            sg_nr_out: 0,
+            sg_iovs: sg_iov.as_mut(),
+        });
+        let mut shard = shard.map(Box::new);
+        let (func, ret) = match shard.as_mut() {
+            // daos_obj_fetch can't pick the shard, the task it creates can
+            #[cfg(feature = "hedged-fetch")]
+            Some(shard) => {
+                let mut task = ptr::null_mut();
+                let ret = unsafe {
+                    dc_obj_fetch_task_create(
+                        obj_hdl.unwrap(),
+                        txn,
+                        flags,
+                        dkey_wrapper.as_mut(),
+                        1,
+                        DIOF_TO_SPEC_SHARD,
+                        iod.as_mut(),
+                        sgl.as_mut(),
+                        ptr::null_mut(),
+                        shard.as_mut() as *mut u32 as *mut std::os::raw::c_void,
+                        ptr::null_mut(),
+                        event.as_mut(),
+                        ptr::null_mut(),
+                        &mut task,
+                    )
+                };
+                match ret {
+                    0 => ("dc_task_schedule", unsafe { dc_task_schedule(task, true) }),
+                    ret => ("dc_obj_fetch_task_create", ret),
+                }
+            }
+            #[cfg(not(feature = "hedged-fetch"))]
+            Some(_) => {
+                return Err(DaosError::no_sys(
+                    "fetching from a shard needs the hedged-fetch feature",
+                ))
+            }
+            None => {
+                let ret = unsafe {
+                    daos_obj_fetch(
+                        obj_hdl.unwrap(),
+                        txn,
+                        flags,
+                        dkey_wrapper.as_mut(),
+                        1,
+                        iod.as_mut(),
+                        sgl.as_mut(),
+                        ptr::null_mut(),
+                        event.as_mut(),
+                    )
+                };
+                ("daos_obj_fetch", ret)
+            }
         };
+        if ret != 0 {
+            return Err(DaosError::from_call(func, ret, "can't fetch object"));
+        }
 
-        let mut iod = daos_iod_t {
+        Ok(PendingFetch {
+            event,
+            rx,
+            _dkey: dkey,
+            _dkey_wrapper: dkey_wrapper,
+            iod,
+            _sg_iov: sg_iov,
+            _sgl: sgl,
+            buf,
+            _akey: akey,
+            _shard: shard,
+        })
+    }
+
+    fn submit_update(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akey: Vec<u8>,
+        data: Vec<u8>,
+    ) -> Result<PendingUpdate> {
+        self.check_writable("update")?;
+        check_user_akey(&akey)?;
+        let flags = self.default_flags.apply_update(flags);
+        check_update_flags(flags)?;
+        let data = match self.compression {
+            Some(codec) => daos_compress::encode(codec, &data),
+            None => data,
+        };
+
+        let eq = self.event_que.as_ref();
+        if eq.is_none() {
+            return Err(DaosError::no_handle("event queue is nil"));
+        }
+        let obj_hdl = self.get_handle();
+        if obj_hdl.is_none() {
+            return Err(DaosError::no_handle("update uninitialized object"));
+        }
+
+        let mut event = eq.unwrap().create_event()?;
+        let rx = event.register_callback()?;
+        let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+        let mut dkey_wrapper = Box::new(daos_key_t {
+            iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+            iov_buf_len: dkey.len(),
+            iov_len: dkey.len(),
+        });
+        let mut iod = Box::new(daos_iod_t {
             iod_name: daos_key_t {
                 iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
                 iov_buf_len: akey.len(),
@@ -443,176 +1291,365 @@ impl DaosObjSyncOps for DaosObject {
             iod_flags: 0,
             iod_nr: 1,
             iod_recxs: std::ptr::null_mut(),
-        };
-
-        let mut sg_iov = d_iov_t {
+        });
+        let mut sg_iov = Box::new(d_iov_t {
             iov_buf: data.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
             iov_buf_len: data.len(),
             iov_len: data.len(),
-        };
-
-        let mut sgl = d_sg_list_t {
+        });
+        let mut sgl = Box::new(d_sg_list_t {
             sg_nr: 1,
             sg_nr_out: 0,
-            sg_iovs: &mut sg_iov,
-        };
+            sg_iovs: sg_iov.as_mut(),
+        });
 
         let ret = unsafe {
             daos_obj_update(
                 obj_hdl.unwrap(),
-                txn_hdl,
+                txn,
                 flags,
-                &mut dkey_wrapper,
+                dkey_wrapper.as_mut(),
                 1,
-                &mut iod,
-                &mut sgl,
-                std::ptr::null_mut(),
+                iod.as_mut(),
+                sgl.as_mut(),
+                event.as_mut(),
             )
         };
-
         if ret != 0 {
-            return Err(Error::new(ErrorKind::Other, "Failed to update object"));
+            return Err(DaosError::from_call(
+                "daos_obj_update",
+                ret,
+                "can't update object",
+            ));
         }
 
-        Ok(())
+        Ok(PendingUpdate {
+            event,
+            rx,
+            dkey,
+            _dkey_wrapper: dkey_wrapper,
+            _iod: iod,
+            _sg_iov: sg_iov,
+            _sgl: sgl,
+            _data: data,
+            akey,
+        })
     }
-}
 
-impl DaosObjAsyncOps for DaosObject {
-    fn create_async(
-        cont: &DaosContainer,
-        oid_allocator: Arc<DaosAsyncOidAllocator>,
-        otype: daos_otype_t,
-        cid: daos_oclass_id_t,
-        hints: daos_oclass_hints_t,
-        args: u32,
-    ) -> impl Future<Output = Result<Box<DaosObject>>> + Send + 'static {
-        let eq = cont.get_event_queue();
-        let eqh = eq.map(|eq| eq.get_handle().unwrap());
-        let evt = eq.map(|e| e.create_event());
-        let cont_hdl = cont.get_handle();
-        async move {
-            if cont_hdl.is_none() {
-                return Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    "empty container handle",
-                ));
-            }
-            if evt.is_none() {
-                return Err(Error::new(ErrorKind::InvalidData, "event queue is nil"));
-            }
+    fn submit_punch(&self, txn: &DaosTxn) -> Result<(DaosEvent, oneshot::Receiver<i32>)> {
+        self.check_writable("punch")?;
+        let eq = self.event_que.as_ref();
+        if eq.is_none() {
+            return Err(DaosError::no_handle("event queue is nil"));
+        }
+        let obj_hdl = self.get_handle();
+        if obj_hdl.is_none() {
+            return Err(DaosError::no_handle("punch uninitialized object"));
+        }
 
-            let mut oid = oid_allocator.allocate().await?;
-            let ret = unsafe {
-                daos_obj_generate_oid2(cont_hdl.unwrap(), &mut oid, otype, cid, hints, args)
-            };
-            if ret != 0 {
-                return Err(Error::new(ErrorKind::Other, "can't generate object id"));
-            }
+        let mut event = eq.unwrap().create_event()?;
+        let rx = event.register_callback()?;
+        let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
 
-            let mut event = evt.unwrap()?;
-            let rx = event.register_callback()?;
+        let ret = unsafe { daos_obj_punch(obj_hdl.unwrap(), txn, 0, event.as_mut()) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_obj_punch",
+                ret,
+                "can't punch object",
+            ));
+        }
+        Ok((event, rx))
+    }
 
-            let mut obj_hdl = Box::new(DaosHandle { cookie: 0u64 });
-            let ret = unsafe {
-                daos_obj_open(
-                    cont_hdl.unwrap(),
-                    oid,
-                    DAOS_OO_RW,
-                    obj_hdl.as_mut(),
-                    event.as_mut() as *mut daos_event_t,
-                )
-            };
+    // Fetches the records of data, rec_size bytes each, from record offset
+    // along with the I/O map of the extents that hold data, growing the map
+    // until it has all of them.
+    #[allow(clippy::too_many_arguments)]
+    async fn fetch_recx_mapped(
+        &self,
+        op: &'static str,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        rec_size: u64,
+        offset: u64,
+        data: &mut [u8],
+    ) -> Result<RecxFetch> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let res: Result<RecxFetch> = async {
+            let nr = records_in(data.len(), rec_size)?;
+            let flags = self.default_flags.apply_fetch(flags);
+            check_fetch_flags(flags)?;
+
+            let eq = self.event_que.clone();
+            let deadline = self.deadlines.bulk;
+            let obj_hdl = self.get_handle();
+            let tx_hdl = txn.get_handle();
 
-            if ret != 0 {
-                return Err(Error::new(ErrorKind::Other, "can't open object"));
+            if eq.is_none() {
+                return Err(DaosError::no_handle("event queue is nil"));
+            }
+            if obj_hdl.is_none() {
+                return Err(DaosError::no_handle("fetch uninitialized object"));
             }
 
-            match rx.await {
-                Ok(ret) => {
-                    if ret != 0 {
-                        return Err(Error::new(ErrorKind::Other, "async open operation fail"));
-                    }
+            let eq = eq.unwrap();
+            let txn = match tx_hdl {
+                Some(tx) => tx,
+                None => DAOS_TXN_NONE,
+            };
+
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut recx = daos_recx_t {
+                rx_idx: offset,
+                rx_nr: nr,
+            };
+            let mut iod = daos_iod_t {
+                iod_name: daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                },
+                iod_type: daos_iod_type_t_DAOS_IOD_ARRAY,
+                iod_size: DAOS_REC_ANY as u64,
+                iod_flags: 0,
+                iod_nr: 1,
+                iod_recxs: &mut recx,
+            };
+            let mut sg_iov = d_iov_t {
+                iov_buf: data.as_mut_ptr() as *mut std::os::raw::c_void,
+                iov_buf_len: data.len(),
+                iov_len: data.len(),
+            };
+            let mut sgl = d_sg_list_t {
+                sg_nr: 1,
+                sg_nr_out: 0,
+                sg_iovs: &mut sg_iov,
+            };
+            let mut map_recxs = vec![
+                daos_recx_t {
+                    rx_idx: 0,
+                    rx_nr: 0
+                };
+                IOM_RECXS
+            ];
+            loop {
+                let mut iom = daos_iom_t {
+                    iom_type: daos_iod_type_t_DAOS_IOD_ARRAY,
+                    iom_nr: map_recxs.len() as u32,
+                    iom_nr_out: 0,
+                    iom_flags: IOMF_DETAIL,
+                    iom_size: 0,
+                    iom_recx_lo: daos_recx_t {
+                        rx_idx: 0,
+                        rx_nr: 0,
+                    },
+                    iom_recx_hi: daos_recx_t {
+                        rx_idx: 0,
+                        rx_nr: 0,
+                    },
+                    iom_recxs: map_recxs.as_mut_ptr(),
+                };
+                let mut event = eq.create_event()?;
+                let rx = event.register_callback()?;
+                let ret = unsafe {
+                    daos_obj_fetch(
+                        obj_hdl.unwrap(),
+                        txn,
+                        flags,
+                        &mut dkey_wrapper,
+                        1,
+                        &mut iod,
+                        &mut sgl,
+                        &mut iom,
+                        event.as_mut(),
+                    )
+                };
+                if ret != 0 {
+                    return Err(DaosError::from_call(
+                        "daos_obj_fetch",
+                        ret,
+                        "can't fetch recx",
+                    ));
                 }
-                Err(_) => {
-                    return Err(Error::new(ErrorKind::ConnectionReset, "rx is closed early"));
+
+                let ret = event.wait(rx, deadline).await?;
+                if ret != 0 {
+                    return Err(DaosError::from_call(
+                        "daos_obj_fetch",
+                        ret,
+                        "async fetch recx fail",
+                    ));
+                }
+                let nr_out = iom.iom_nr_out as usize;
+                if nr_out > map_recxs.len() {
+                    map_recxs.resize(
+                        nr_out,
+                        daos_recx_t {
+                            rx_idx: 0,
+                            rx_nr: 0,
+                        },
+                    );
+                    continue;
                 }
+                return Ok(RecxFetch::new(offset, nr, &map_recxs[..nr_out]));
             }
-
-            Ok(Box::new(DaosObject::new(oid, *obj_hdl, eqh)))
         }
+        .await;
+        res.map_err(|e| e.with_context(self.op_context(op, dkey, akey)))
     }
 
-    fn open_async(
-        cont: &DaosContainer,
-        oid: DaosObjectId,
-        read_only: bool,
-    ) -> impl Future<Output = Result<Box<DaosObject>>> + Send + 'static {
-        let eq = cont.get_event_queue();
-        let eqh = eq.map(|eq| eq.get_handle().unwrap());
-        let evt = eq.map(|e| e.create_event());
-        let cont_hdl = cont.get_handle();
-        async move {
-            if cont_hdl.is_none() {
-                return Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    "empty container handle",
-                ));
+    // Writes data as the single value of akey, reserved akeys included.
+    pub(crate) async fn update_single(
+        &self,
+        op: &'static str,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: &[u8],
+        akey: &[u8],
+        data: &[u8],
+    ) -> Result<()> {
+        let encoded;
+        let data = match self.compression {
+            Some(codec) => {
+                encoded = daos_compress::encode(codec, data);
+                encoded.as_slice()
             }
-            if evt.is_none() {
-                return Err(Error::new(ErrorKind::InvalidData, "event queue is nil"));
+            None => data,
+        };
+        let res: Result<()> = async {
+            self.check_writable("update")?;
+            let flags = self.default_flags.apply_update(flags);
+            check_update_flags(flags)?;
+
+            let eq = self.event_que.clone();
+            let deadline = self.deadlines.bulk;
+            let obj_hdl = self.get_handle();
+            let tx_hdl = txn.get_handle();
+
+            if eq.is_none() {
+                return Err(DaosError::no_handle("event queue is nil"));
+            }
+            if obj_hdl.is_none() {
+                return Err(DaosError::no_handle("update uninitialized object"));
             }
 
-            let mut event = evt.unwrap()?;
+            let mut event = eq.unwrap().create_event()?;
             let rx = event.register_callback()?;
 
-            let mut obj_hdl = Box::new(DaosHandle { cookie: 0u64 });
+            let txn = match tx_hdl {
+                Some(tx) => tx,
+                None => DAOS_TXN_NONE,
+            };
+
+            let mut dkey_wrapper = Box::new(daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            });
+            let mut iod = Box::new(daos_iod_t {
+                iod_name: daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                },
+                iod_type: daos_iod_type_t_DAOS_IOD_SINGLE,
+                iod_size: data.len() as u64,
+                iod_flags: 0,
+                iod_nr: 1,
+                iod_recxs: std::ptr::null_mut(),
+            });
+            let mut sg_iov = Box::new(d_iov_t {
+                iov_buf: data.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: data.len(),
+                iov_len: data.len(),
+            });
+            let mut sgl = Box::new(d_sg_list_t {
+                sg_nr: 1,
+                sg_nr_out: 0,
+                sg_iovs: sg_iov.as_mut(),
+            });
             let ret = unsafe {
-                daos_obj_open(
-                    cont_hdl.unwrap(),
-                    oid,
-                    if read_only { DAOS_OO_RO } else { DAOS_OO_RW },
-                    obj_hdl.as_mut(),
-                    event.as_mut() as *mut daos_event_t,
+                daos_obj_update(
+                    obj_hdl.unwrap(),
+                    txn,
+                    flags,
+                    dkey_wrapper.as_mut(),
+                    1,
+                    iod.as_mut(),
+                    sgl.as_mut(),
+                    event.as_mut(),
                 )
             };
-
             if ret != 0 {
-                return Err(Error::new(ErrorKind::Other, "can't open object"));
+                return Err(DaosError::from_call(
+                    "daos_obj_update",
+                    ret,
+                    "can't update object",
+                ));
             }
 
-            match rx.await {
+            let res = match event.wait(rx, deadline).await {
                 Ok(ret) => {
                     if ret != 0 {
-                        Err(Error::new(
-                            ErrorKind::Other,
-                            format!("async open object fail, ret: {}", ret),
+                        Err(DaosError::from_call(
+                            "daos_obj_update",
+                            ret,
+                            "async update operation fail",
                         ))
                     } else {
-                        Ok(Box::new(DaosObject::new(oid, *obj_hdl, eqh)))
+                        Ok(())
                     }
                 }
-                Err(_) => Err(Error::new(ErrorKind::ConnectionReset, "rx is closed early")),
-            }
+                Err(e) => Err(e),
+            };
+            self.invalidate_key(dkey, akey);
+            res
         }
+        .await;
+        res.map_err(|e| e.with_context(self.op_context(op, dkey, akey)))
     }
 
-    fn punch_async(&self, txn: &DaosTxn) -> impl Future<Output = Result<()>> + Send + 'static {
-        let eq = self.get_event_queue();
-        let obj_hdl = self.get_handle();
-        let tx_hdl = txn.get_handle();
-        async move {
+    // Writes the records of data, rec_size bytes each, from record offset.
+    #[allow(clippy::too_many_arguments)]
+    async fn update_recx_sized(
+        &self,
+        op: &'static str,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        rec_size: u64,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let res: Result<()> = async {
+            self.check_writable("update")?;
+            check_user_akey(akey)?;
+            let nr = records_in(data.len(), rec_size)?;
+            let flags = self.default_flags.apply_update(flags);
+            check_update_flags(flags)?;
+
+            let eq = self.event_que.clone();
+            let deadline = self.deadlines.bulk;
+            let obj_hdl = self.get_handle();
+            let tx_hdl = txn.get_handle();
+
             if eq.is_none() {
-                return Err(Error::new(ErrorKind::InvalidData, "event queue is nil"));
+                return Err(DaosError::no_handle("event queue is nil"));
             }
             if obj_hdl.is_none() {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "punch uninitialized object",
-                ));
+                return Err(DaosError::no_handle("update uninitialized object"));
             }
 
-            let mut event = DaosEvent::new(eq.unwrap())?;
+            let mut event = eq.unwrap().create_event()?;
             let rx = event.register_callback()?;
 
             let txn = match tx_hdl {
@@ -620,421 +1657,446 @@ impl DaosObjAsyncOps for DaosObject {
                 None => DAOS_TXN_NONE,
             };
 
-            let ret = unsafe { daos_obj_punch(obj_hdl.unwrap(), txn, 0, event.as_mut()) };
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut recx = daos_recx_t {
+                rx_idx: offset,
+                rx_nr: nr,
+            };
+            let mut iod = daos_iod_t {
+                iod_name: daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                },
+                iod_type: daos_iod_type_t_DAOS_IOD_ARRAY,
+                iod_size: rec_size,
+                iod_flags: 0,
+                iod_nr: 1,
+                iod_recxs: &mut recx,
+            };
+            let mut sg_iov = d_iov_t {
+                iov_buf: data.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: data.len(),
+                iov_len: data.len(),
+            };
+            let mut sgl = d_sg_list_t {
+                sg_nr: 1,
+                sg_nr_out: 0,
+                sg_iovs: &mut sg_iov,
+            };
+            let ret = unsafe {
+                daos_obj_update(
+                    obj_hdl.unwrap(),
+                    txn,
+                    flags,
+                    &mut dkey_wrapper,
+                    1,
+                    &mut iod,
+                    &mut sgl,
+                    event.as_mut(),
+                )
+            };
             if ret != 0 {
-                return Err(Error::new(ErrorKind::Other, "can't punch object"));
+                return Err(DaosError::from_call(
+                    "daos_obj_update",
+                    ret,
+                    "can't update recx",
+                ));
             }
 
-            match rx.await {
+            let res = match event.wait(rx, deadline).await {
                 Ok(ret) => {
                     if ret != 0 {
-                        Err(Error::new(ErrorKind::Other, "async punch operation fail"))
+                        Err(DaosError::from_call(
+                            "daos_obj_update",
+                            ret,
+                            "async update recx operation fail",
+                        ))
                     } else {
                         Ok(())
                     }
                 }
-                Err(_) => Err(Error::new(ErrorKind::ConnectionReset, "rx is closed early")),
-            }
+                Err(e) => Err(e),
+            };
+            self.invalidate_key(dkey, akey);
+            res
         }
+        .await;
+        res.map_err(|e| e.with_context(self.op_context(op, dkey, akey)))
     }
 
-    async fn fetch_async(
+    // Punches the akeys under dkey, or the dkeys in keys when dkey is None.
+    // keys and dkey must outlive the completion of event.
+    fn punch_keys_raw(
         &self,
         txn: &DaosTxn,
         flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        out_buf: &mut [u8],
-    ) -> Result<usize> {
-        let eq = self.get_event_queue();
-        let obj_hdl = self.get_handle();
-        let tx_hdl = txn.get_handle();
+        dkey: Option<&mut daos_key_t>,
+        keys: &mut [daos_key_t],
+        event: *mut daos_event_t,
+    ) -> Result<&'static str> {
+        let obj_hdl = self
+            .get_handle()
+            .ok_or_else(|| DaosError::no_handle("punch uninitialized object"))?;
+        if keys.is_empty() {
+            return Err(DaosError::inval("no keys to punch"));
+        }
+        let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+        let (func, ret) = match dkey {
+            Some(dkey) => {
+                let ret = unsafe {
+                    daos_obj_punch_akeys(
+                        obj_hdl,
+                        txn,
+                        flags,
+                        dkey,
+                        keys.len() as u32,
+                        keys.as_mut_ptr(),
+                        event,
+                    )
+                };
+                ("daos_obj_punch_akeys", ret)
+            }
+            None => {
+                let ret = unsafe {
+                    daos_obj_punch_dkeys(
+                        obj_hdl,
+                        txn,
+                        flags,
+                        keys.len() as u32,
+                        keys.as_mut_ptr(),
+                        event,
+                    )
+                };
+                ("daos_obj_punch_dkeys", ret)
+            }
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(func, ret, "can't punch keys"));
+        }
+        Ok(func)
+    }
 
-        if eq.is_none() {
-            return Err(Error::new(ErrorKind::InvalidData, "event queue is nil"));
+    // Punched values leave the cache, all of it for dkeys since it isn't
+    // indexed by dkey alone.
+    fn invalidate_punched(&self, dkey: Option<&[u8]>, keys: &[Vec<u8>]) {
+        match (dkey, self.cache.as_ref()) {
+            (Some(dkey), _) => keys.iter().for_each(|akey| self.invalidate_key(dkey, akey)),
+            (None, Some(cache)) => cache.clear(),
+            (None, None) => {}
         }
-        if obj_hdl.is_none() {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "fetch uninitialized object",
-            ));
+    }
+
+    // Resolves the flags of update_akeys and encodes the values.
+    fn prepare_update_akeys<'a>(
+        &self,
+        flags: CondFlags,
+        akeys: &'a [DaosAkeyUpdate],
+    ) -> Result<(u64, Vec<u64>, Vec<Cow<'a, [u8]>>)> {
+        self.check_writable("update")?;
+        if akeys.is_empty() {
+            return Err(DaosError::inval("no akeys to update"));
         }
+        akeys
+            .iter()
+            .try_for_each(|(akey, _, _)| check_user_akey(akey))?;
+        let flags = self.default_flags.apply_update(flags);
+        check_update_flags(flags)?;
+        let akey_flags: Vec<CondFlags> = akeys.iter().map(|(_, flags, _)| *flags).collect();
+        let (flags, iod_flags) = per_akey_flags(flags, &akey_flags, AKEY_UPDATE_FLAGS)?;
+        iod_flags
+            .iter()
+            .try_for_each(|iod_flags| check_update_flags(*iod_flags))?;
+
+        let values = akeys
+            .iter()
+            .map(|(_, _, data)| match self.compression {
+                Some(codec) => Cow::Owned(daos_compress::encode(codec, data)),
+                None => Cow::Borrowed(data.as_slice()),
+            })
+            .collect();
+        Ok((flags, iod_flags, values))
+    }
 
-        let mut event = DaosEvent::new(eq.unwrap())?;
-        let rx = event.register_callback()?;
+    fn prepare_fetch_akeys(
+        &self,
+        flags: CondFlags,
+        akeys: &[DaosAkeyFetch],
+    ) -> Result<(u64, Vec<u64>)> {
+        if akeys.is_empty() {
+            return Err(DaosError::inval("no akeys to fetch"));
+        }
+        let flags = self.default_flags.apply_fetch(flags);
+        check_fetch_flags(flags)?;
+        let akey_flags: Vec<CondFlags> = akeys.iter().map(|(_, flags)| *flags).collect();
+        per_akey_flags(flags, &akey_flags, AKEY_FETCH_FLAGS)
+    }
 
-        let txn = match tx_hdl {
-            Some(tx) => tx,
-            None => DAOS_TXN_NONE,
-        };
+    // Buffer size that holds a value of max_size bytes as stored.
+    fn stored_size(&self, max_size: u32) -> usize {
+        // stored values are at most HEADER_LEN larger than the original
+        match self.compression {
+            Some(_) => max_size as usize + daos_compress::HEADER_LEN,
+            None => max_size as usize,
+        }
+    }
 
-        let mut dkey_wrapper = Box::new(daos_key_t {
-            iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-            iov_buf_len: dkey.len(),
-            iov_len: dkey.len(),
-        });
-        let mut iod = Box::new(daos_iod_t {
-            iod_name: daos_key_t {
-                iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-                iov_buf_len: akey.len(),
-                iov_len: akey.len(),
-            },
-            iod_type: daos_iod_type_t_DAOS_IOD_SINGLE,
-            iod_size: DAOS_REC_ANY as u64,
-            iod_flags: 0,
-            iod_nr: 1,
-            iod_recxs: std::ptr::null_mut(),
-        });
+    // The original value of stored, fetched into a buffer of
+    // stored_size(max_size), Truncated when it is larger than max_size.
+    fn decode_stored(&self, stored: Vec<u8>, max_size: u32) -> Result<Vec<u8>> {
+        if self.compression.is_none() {
+            return Ok(stored);
+        }
+        let mut value = vec![0u8; max_size as usize];
+        let len = daos_compress::decode(&stored, &mut value).map_err(|e| e.into_truncated(0))?;
+        value.truncate(len);
+        Ok(value)
+    }
 
-        let mut sg_iov = Box::new(d_iov_t {
-            iov_buf: out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
-            iov_buf_len: out_buf.len(),
-            iov_len: out_buf.len(),
-        });
-        let mut sgl = Box::new(d_sg_list_t {
-            sg_nr: 1,
-            sg_nr_out: 0,
-            sg_iovs: sg_iov.as_mut(),
-        });
+    // io and dkey must outlive the completion of event.
+    fn update_akeys_raw(
+        &self,
+        txn: &DaosTxn,
+        flags: u64,
+        dkey: &mut daos_key_t,
+        io: &mut AkeysIo,
+        event: *mut daos_event_t,
+    ) -> Result<()> {
+        let obj_hdl = self
+            .get_handle()
+            .ok_or_else(|| DaosError::no_handle("update uninitialized object"))?;
+        let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
         let ret = unsafe {
-            daos_obj_fetch(
-                obj_hdl.unwrap(),
+            daos_obj_update(
+                obj_hdl,
                 txn,
                 flags,
-                dkey_wrapper.as_mut(),
-                1,
-                iod.as_mut(),
-                sgl.as_mut(),
-                ptr::null_mut(),
-                event.as_mut(),
+                dkey,
+                io.iods.len() as u32,
+                io.iods.as_mut_ptr(),
+                io.sgls.as_mut_ptr(),
+                event,
             )
         };
         if ret != 0 {
-            return Err(Error::new(ErrorKind::Other, "can't fetch object"));
-        }
-
-        match rx.await {
-            Ok(ret) => {
-                if ret != 0 {
-                    Err(Error::new(
-                        ErrorKind::Other,
-                        format!("async fetch operation fail, ret={}", ret),
-                    ))
-                } else {
-                    Ok(iod.iod_size as usize)
-                }
-            }
-            Err(_) => Err(Error::new(ErrorKind::ConnectionReset, "rx is closed early")),
+            return Err(DaosError::from_call(
+                "daos_obj_update",
+                ret,
+                "can't update object",
+            ));
         }
+        Ok(())
     }
 
-    async fn update_async(
+    // io and dkey must outlive the completion of event.
+    fn fetch_akeys_raw(
         &self,
         txn: &DaosTxn,
         flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        data: &[u8],
+        dkey: &mut daos_key_t,
+        io: &mut AkeysIo,
+        event: *mut daos_event_t,
     ) -> Result<()> {
-        let eq = self.get_event_queue();
-        let obj_hdl = self.get_handle();
-        let tx_hdl = txn.get_handle();
+        let obj_hdl = self
+            .get_handle()
+            .ok_or_else(|| DaosError::no_handle("fetch uninitialized object"))?;
+        let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+        let ret = unsafe {
+            daos_obj_fetch(
+                obj_hdl,
+                txn,
+                flags,
+                dkey,
+                io.iods.len() as u32,
+                io.iods.as_mut_ptr(),
+                io.sgls.as_mut_ptr(),
+                ptr::null_mut(),
+                event,
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_obj_fetch",
+                ret,
+                "can't fetch object",
+            ));
+        }
+        Ok(())
+    }
 
-        if eq.is_none() {
-            return Err(Error::new(ErrorKind::InvalidData, "event queue is nil"));
+    // Values of a completed fetch_akeys_raw that returned ret.
+    fn finish_fetch_akeys(
+        &self,
+        ret: i32,
+        io: AkeysIo,
+        bufs: Vec<Vec<u8>>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        if ret != 0 {
+            let required = io.iods.iter().map(|iod| iod.iod_size as usize).max();
+            return Err(
+                DaosError::from_call("daos_obj_fetch", ret, "fetch operation fail")
+                    .with_required_size(required.unwrap_or(0)),
+            );
         }
-        if obj_hdl.is_none() {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "update uninitialized object",
-            ));
+
+        let mut values = Vec::with_capacity(bufs.len());
+        for (mut buf, iod) in bufs.into_iter().zip(io.iods.iter()) {
+            let size = iod.iod_size as usize;
+            if size == 0 {
+                values.push(None);
+                continue;
+            }
+            buf.truncate(size);
+            if self.compression.is_some() {
+                let mut value = vec![0u8; max_size as usize];
+                let len = daos_compress::decode(&buf, &mut value)?;
+                value.truncate(len);
+                buf = value;
+            }
+            values.push(Some(buf));
         }
+        Ok(values)
+    }
 
-        let mut event = DaosEvent::new(eq.unwrap())?;
-        let rx = event.register_callback()?;
+    fn dkey_context(&self, op: &'static str, dkey: &[u8]) -> DaosErrorContext {
+        DaosErrorContext {
+            dkey: Some(dkey.to_vec()),
+            ..DaosErrorContext::new(op, self.oid)
+        }
+    }
 
-        let txn = match tx_hdl {
-            Some(tx) => tx,
-            None => DAOS_TXN_NONE,
-        };
+    async fn finish_batch_update(&self, op: &'static str, update: PendingUpdate) -> Result<()> {
+        let (res, dkey, akey) = update.wait(self.deadlines.bulk).await;
+        self.invalidate_key(&dkey, &akey);
+        res.map_err(|e| e.with_context(self.op_context(op, &dkey, &akey)))
+    }
 
-        let mut dkey_wrapper = Box::new(daos_key_t {
-            iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-            iov_buf_len: dkey.len(),
-            iov_len: dkey.len(),
-        });
-        let mut iod = Box::new(daos_iod_t {
-            iod_name: daos_key_t {
-                iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-                iov_buf_len: akey.len(),
-                iov_len: akey.len(),
-            },
-            iod_type: daos_iod_type_t_DAOS_IOD_SINGLE,
-            iod_size: data.len() as u64,
-            iod_flags: 0,
-            iod_nr: 1,
-            iod_recxs: std::ptr::null_mut(),
-        });
-        let mut sg_iov = Box::new(d_iov_t {
-            iov_buf: data.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-            iov_buf_len: data.len(),
-            iov_len: data.len(),
-        });
-        let mut sgl = Box::new(d_sg_list_t {
-            sg_nr: 1,
-            sg_nr_out: 0,
-            sg_iovs: sg_iov.as_mut(),
-        });
-        let ret = unsafe {
-            daos_obj_update(
-                obj_hdl.unwrap(),
-                txn,
-                flags,
-                dkey_wrapper.as_mut(),
-                1,
-                iod.as_mut(),
-                sgl.as_mut(),
-                event.as_mut(),
-            )
-        };
-        if ret != 0 {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("can't update object, ret={}", ret),
-            ));
-        }
-
-        match rx.await {
-            Ok(ret) => {
-                if ret != 0 {
-                    Err(Error::new(
-                        ErrorKind::Other,
-                        format!("async update operation fail, ret={}", ret),
-                    ))
-                } else {
-                    Ok(())
-                }
-            }
-            Err(_) => Err(Error::new(ErrorKind::ConnectionReset, "rx is closed early")),
-        }
+    fn op_context(&self, op: &'static str, dkey: &[u8], akey: &[u8]) -> DaosErrorContext {
+        DaosErrorContext::new(op, self.oid).with_keys(dkey, akey)
     }
 
-    async fn fetch_recx_async(
-        &self,
-        txn: &DaosTxn,
-        flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        offset: u64,
-        data: &mut [u8],
-    ) -> Result<usize> {
-        let eq = self.get_event_queue();
-        let obj_hdl = self.get_handle();
-        let tx_hdl = txn.get_handle();
-
-        if eq.is_none() {
-            return Err(Error::new(ErrorKind::InvalidData, "event queue is nil"));
-        }
-        if obj_hdl.is_none() {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "fetch uninitialized object",
-            ));
+    fn invalidate_key(&self, dkey: &[u8], akey: &[u8]) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.invalidate(dkey, akey);
         }
+    }
 
-        let mut event = DaosEvent::new(eq.unwrap())?;
-        let rx = event.register_callback()?;
+    pub fn get_handle(&self) -> Option<DaosHandle> {
+        self.handle.clone()
+    }
 
-        let txn = match tx_hdl {
-            Some(tx) => tx,
-            None => DAOS_TXN_NONE,
-        };
+    pub fn get_event_queue(&self) -> Option<DaosHandle> {
+        self.event_que.as_ref().and_then(|eq| eq.get_handle())
+    }
 
-        let mut dkey_wrapper = daos_key_t {
-            iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-            iov_buf_len: dkey.len(),
-            iov_len: dkey.len(),
-        };
-        let mut recx = daos_recx_t {
-            rx_idx: offset,
-            rx_nr: data.len() as u64,
-        };
-        let mut iod = daos_iod_t {
-            iod_name: daos_key_t {
-                iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-                iov_buf_len: akey.len(),
-                iov_len: akey.len(),
-            },
-            iod_type: daos_iod_type_t_DAOS_IOD_ARRAY,
-            iod_size: DAOS_REC_ANY as u64,
-            iod_flags: 0,
-            iod_nr: 1,
-            iod_recxs: &mut recx,
-        };
-        let mut sg_iov = d_iov_t {
-            iov_buf: data.as_mut_ptr() as *mut std::os::raw::c_void,
-            iov_buf_len: data.len(),
-            iov_len: data.len(),
-        };
-        let mut sgl = d_sg_list_t {
-            sg_nr: 1,
-            sg_nr_out: 0,
-            sg_iovs: &mut sg_iov,
-        };
-        let ret = unsafe {
-            daos_obj_fetch(
-                obj_hdl.unwrap(),
-                txn,
-                flags,
-                &mut dkey_wrapper,
-                1,
-                &mut iod,
-                &mut sgl,
-                std::ptr::null_mut(),
-                event.as_mut(),
-            )
-        };
-        if ret != 0 {
-            return Err(Error::new(ErrorKind::Other, "can't fetch recx"));
+    pub fn handle_token(&self) -> DaosHandleToken {
+        DaosHandleToken {
+            oid: self.oid,
+            handle: self.shared_handle.clone(),
         }
+    }
 
-        match rx.await {
-            Ok(ret) => {
-                if ret != 0 {
-                    Err(Error::new(
-                        ErrorKind::Other,
-                        format!("async fetch recx fail, ret={}", ret),
-                    ))
-                } else {
-                    Ok(data.len())
-                }
-            }
-            Err(_) => Err(Error::new(ErrorKind::ConnectionReset, "rx is closed early")),
+    // Streams the byte array under dkey and akey from offset 0 to the end of
+    // its last extent, chunk_size bytes per fetch_recx_async. Only the chunk
+    // being read is held in memory.
+    pub fn fetch_stream(
+        self: &Arc<Self>,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        chunk_size: usize,
+    ) -> Result<DaosRecxReader> {
+        if chunk_size == 0 {
+            return Err(DaosError::inval("chunk size must not be 0"));
         }
+        Ok(DaosRecxReader {
+            obj: self.clone(),
+            dkey: dkey.as_ref().to_vec(),
+            akey: akey.as_ref().to_vec(),
+            chunk_size,
+            size: None,
+            offset: 0,
+            chunk: Vec::new(),
+            pos: 0,
+            pending: None,
+        })
     }
 
-    async fn update_recx_async(
-        &self,
-        txn: &DaosTxn,
-        flags: u64,
-        dkey: Vec<u8>,
-        akey: Vec<u8>,
-        offset: u64,
-        data: &[u8],
-    ) -> Result<()> {
-        let eq = self.get_event_queue();
-        let obj_hdl = self.get_handle();
-        let tx_hdl = txn.get_handle();
-
-        if eq.is_none() {
-            return Err(Error::new(ErrorKind::InvalidData, "event queue is nil"));
+    // Stores value under the metadata name, replacing the previous one.
+    // DAOS keeps no empty single values, an empty value is refused.
+    pub async fn set_meta_async(&self, txn: &DaosTxn, name: &str, value: &[u8]) -> Result<()> {
+        let akey = meta_akey(name)?;
+        if value.is_empty() {
+            return Err(DaosError::inval("metadata value must not be empty"));
         }
-        if obj_hdl.is_none() {
-            return Err(Error::new(
-                ErrorKind::InvalidData,
-                "update uninitialized object",
-            ));
+        if value.len() > META_MAX_SIZE {
+            return Err(DaosError::inval(format!(
+                "metadata value of {} bytes exceeds {} bytes",
+                value.len(),
+                META_MAX_SIZE
+            )));
         }
+        self.update_single(
+            "set_meta_async",
+            txn,
+            CondFlags::empty(),
+            META_DKEY,
+            &akey,
+            value,
+        )
+        .await
+    }
 
-        let mut event = DaosEvent::new(eq.unwrap())?;
-        let rx = event.register_callback()?;
-
-        let txn = match tx_hdl {
-            Some(tx) => tx,
-            None => DAOS_TXN_NONE,
-        };
-
-        let mut dkey_wrapper = daos_key_t {
-            iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-            iov_buf_len: dkey.len(),
-            iov_len: dkey.len(),
-        };
-        let mut recx = daos_recx_t {
-            rx_idx: offset,
-            rx_nr: data.len() as u64,
-        };
-        let mut iod = daos_iod_t {
-            iod_name: daos_key_t {
-                iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-                iov_buf_len: akey.len(),
-                iov_len: akey.len(),
-            },
-            iod_type: daos_iod_type_t_DAOS_IOD_ARRAY,
-            iod_size: 1u64,
-            iod_flags: 0,
-            iod_nr: 1,
-            iod_recxs: &mut recx,
-        };
-        let mut sg_iov = d_iov_t {
-            iov_buf: data.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
-            iov_buf_len: data.len(),
-            iov_len: data.len(),
-        };
-        let mut sgl = d_sg_list_t {
-            sg_nr: 1,
-            sg_nr_out: 0,
-            sg_iovs: &mut sg_iov,
-        };
-        let ret = unsafe {
-            daos_obj_update(
-                obj_hdl.unwrap(),
-                txn,
-                flags,
-                &mut dkey_wrapper,
-                1,
-                &mut iod,
-                &mut sgl,
-                event.as_mut(),
-            )
-        };
-        if ret != 0 {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("can't update recx, ret={}", ret),
-            ));
+    pub async fn get_meta_async(&self, txn: &DaosTxn, name: &str) -> Result<Option<Vec<u8>>> {
+        let akey = meta_akey(name)?;
+        let mut value = vec![0u8; META_MAX_SIZE];
+        let size = self
+            .fetch_async(txn, CondFlags::empty(), META_DKEY, &akey, &mut value)
+            .await?;
+        if size == 0 {
+            return Ok(None);
         }
+        value.truncate(size);
+        Ok(Some(value))
+    }
 
-        match rx.await {
-            Ok(ret) => {
-                if ret != 0 {
-                    Err(Error::new(
-                        ErrorKind::Other,
-                        format!("async update recx operation fail, ret={}", ret),
-                    ))
-                } else {
-                    Ok(())
-                }
-            }
-            Err(_) => Err(Error::new(ErrorKind::ConnectionReset, "rx is closed early")),
-        }
+    // Tells whether the name was set.
+    pub async fn remove_meta_async(&self, txn: &DaosTxn, name: &str) -> Result<bool> {
+        let akey = meta_akey(name)?;
+        self.remove_key("remove_meta_async", txn, META_DKEY.to_vec(), Some(akey))
+            .await
     }
 
-    fn list_dkey_async(
+    // Removes akey, or dkey when akey is None, reserved keys included. A
+    // conditional punch fails with NONEXIST instead of punching nothing,
+    // which gives the existence result without a probe round trip.
+    async fn remove_key(
         &self,
+        op: &'static str,
         txn: &DaosTxn,
-        key_lst: Box<DaosKeyList>,
-    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static {
-        let eq = self.get_event_queue();
+        dkey: Vec<u8>,
+        akey: Option<Vec<u8>>,
+    ) -> Result<bool> {
+        let eq = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
         let obj_hdl = self.get_handle();
         let tx_hdl = txn.get_handle();
-        async move {
+
+        let res: Result<bool> = async {
+            self.check_writable("remove")?;
             if eq.is_none() {
-                return Err(Error::new(ErrorKind::InvalidData, "event queue is nil"));
+                return Err(DaosError::no_handle("event queue is nil"));
             }
             if obj_hdl.is_none() {
-                return Err(Error::new(
-                    ErrorKind::InvalidData,
-                    "list uninitialized object",
-                ));
-            }
-
-            let mut key_lst: Box<DaosKeyList> = key_lst;
-            if key_lst.reach_end() {
-                *key_lst.ndesc = 0;
-                return Ok(key_lst);
+                return Err(DaosError::no_handle("punch uninitialized object"));
             }
 
-            let mut event = DaosEvent::new(eq.unwrap())?;
+            let mut event = eq.unwrap().create_event()?;
             let rx = event.register_callback()?;
 
             let txn = match tx_hdl {
@@ -1042,66 +2104,6091 @@ impl DaosObjAsyncOps for DaosObject {
                 None => DAOS_TXN_NONE,
             };
 
-            key_lst.prepare_next_query();
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let (func, ret) = match akey.as_ref() {
+                Some(akey) => {
+                    let mut akey_wrapper = daos_key_t {
+                        iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                        iov_buf_len: akey.len(),
+                        iov_len: akey.len(),
+                    };
+                    let ret = unsafe {
+                        daos_obj_punch_akeys(
+                            obj_hdl.unwrap(),
+                            txn,
+                            DAOS_COND_PUNCH as u64,
+                            &mut dkey_wrapper,
+                            1,
+                            &mut akey_wrapper,
+                            event.as_mut(),
+                        )
+                    };
+                    ("daos_obj_punch_akeys", ret)
+                }
+                None => {
+                    let ret = unsafe {
+                        daos_obj_punch_dkeys(
+                            obj_hdl.unwrap(),
+                            txn,
+                            DAOS_COND_PUNCH as u64,
+                            1,
+                            &mut dkey_wrapper,
+                            event.as_mut(),
+                        )
+                    };
+                    ("daos_obj_punch_dkeys", ret)
+                }
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(func, ret, "can't punch key"));
+            }
 
-            let mut sg_iov = Box::new(d_iov_t {
-                iov_buf: key_lst.out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
-                iov_buf_len: key_lst.out_buf.len(),
-                iov_len: key_lst.out_buf.len(),
-            });
-            let mut sgl = Box::new(d_sg_list_t {
-                sg_nr: 1,
-                sg_nr_out: 0,
-                sg_iovs: sg_iov.as_mut(),
-            });
+            let res = match event.wait(rx, deadline).await {
+                Ok(0) => Ok(true),
+                Ok(ret) if ret == -(daos_errno_DER_NONEXIST as i32) => Ok(false),
+                Ok(ret) => Err(DaosError::from_call(func, ret, "async punch key fail")),
+                Err(e) => Err(e),
+            };
+            match (akey.as_ref(), self.cache.as_ref()) {
+                (Some(akey), _) => self.invalidate_key(&dkey, akey),
+                (None, Some(cache)) => cache.clear(),
+                (None, None) => {}
+            }
+            res
+        }
+        .await;
+        res.map_err(|e| {
+            let context = DaosErrorContext::new(op, self.oid);
+            e.with_context(match akey.as_ref() {
+                Some(akey) => context.with_keys(&dkey, akey),
+                None => DaosErrorContext {
+                    dkey: Some(dkey.clone()),
+                    ..context
+                },
+            })
+        })
+    }
 
-            let res = unsafe {
-                daos_obj_list_dkey(
-                    obj_hdl.unwrap(),
-                    txn,
-                    key_lst.ndesc.as_mut(),
-                    key_lst.key_descs.as_mut_ptr(),
-                    sgl.as_mut(),
-                    key_lst.anchor.as_mut(),
-                    event.as_mut(),
+    fn close(&mut self) -> Result<()> {
+        if self.handle.is_some() {
+            // blocks until no token is using the handle
+            let mut shared = self.shared_handle.write().unwrap();
+            let res = unsafe { daos_obj_close(self.handle.unwrap(), ptr::null_mut()) };
+            if res == 0 {
+                self.handle.take();
+                shared.take();
+                Ok(())
+            } else {
+                Err(
+                    DaosError::from_call("daos_obj_close", res, "Failed to close DAOS object")
+                        .with_context(DaosErrorContext::new("close", self.oid)),
                 )
-            };
-            if res != 0 {
-                return Err(Error::new(
-                    ErrorKind::Other,
-                    format!("list dkey fail, err={}", res),
-                ));
             }
+        } else {
+            Ok(())
+        }
+    }
+}
 
-            match rx.await {
-                Ok(ret) => {
-                    if ret != 0 {
-                        Err(Error::new(
-                            ErrorKind::Other,
-                            format!("async list dkey fail, ret={}", ret),
-                        ))
-                    } else {
-                        Ok(key_lst)
-                    }
-                }
-                Err(_) => Err(Error::new(ErrorKind::ConnectionReset, "rx is closed early")),
+#[cfg(feature = "libdaos")]
+impl fmt::Debug for DaosObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DaosObject")
+            .field("oid", &format_args!("{}", self.oid))
+            .field("open", &self.handle.is_some())
+            .field("handle", &HandleFmt(self.handle))
+            .field("event_que", &HandleFmt(self.get_event_queue()))
+            .field("deadlines", &self.deadlines)
+            .field("cache", &self.cache_stats())
+            .finish()
+    }
+}
+
+#[cfg(feature = "libdaos")]
+impl Drop for DaosObject {
+    fn drop(&mut self) {
+        let res = self.close();
+        match res {
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Failed to drop DAOS object: {:?}", e);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "libdaos")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DaosObjCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    pub entries: usize,
+}
+
+#[cfg(feature = "libdaos")]
+struct DaosObjCacheEntries {
+    values: HashMap<(Vec<u8>, Vec<u8>), Vec<u8>>,
+    // bumped by every invalidation, a fetch that raced with one must not
+    // fill the cache with what it read
+    generation: u64,
+    epoch: Option<u64>,
+}
+
+// Read-through cache of single values. Updates and punches issued through
+// the owning DaosObject invalidate it whether or not their txn commits.
+#[cfg(feature = "libdaos")]
+struct DaosObjCache {
+    entries: Mutex<DaosObjCacheEntries>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+#[cfg(feature = "libdaos")]
+impl DaosObjCache {
+    fn new(capacity: usize) -> Self {
+        DaosObjCache {
+            entries: Mutex::new(DaosObjCacheEntries {
+                values: HashMap::new(),
+                generation: 0,
+                epoch: None,
+            }),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    // Returns the cached value, or the generation to pass to put() on a miss.
+    fn get(&self, dkey: &[u8], akey: &[u8]) -> std::result::Result<Vec<u8>, u64> {
+        let entries = self.entries.lock().unwrap();
+        match entries.values.get(&(dkey.to_vec(), akey.to_vec())) {
+            Some(value) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Ok(value.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                Err(entries.generation)
             }
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn put(&self, generation: u64, dkey: Vec<u8>, akey: Vec<u8>, value: Vec<u8>) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.generation != generation || self.capacity == 0 {
+            return;
+        }
+        let key = (dkey, akey);
+        if entries.values.len() >= self.capacity && !entries.values.contains_key(&key) {
+            let victim = entries.values.keys().next().cloned().unwrap();
+            entries.values.remove(&victim);
+        }
+        entries.values.insert(key, value);
+    }
+
+    fn invalidate(&self, dkey: &[u8], akey: &[u8]) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.values.remove(&(dkey.to_vec(), akey.to_vec()));
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.generation += 1;
+        entries.values.clear();
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn observe_epoch(&self, epoch: u64) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.epoch == Some(epoch) {
+            return;
+        }
+        if entries.epoch.is_some() {
+            entries.generation += 1;
+            entries.values.clear();
+            self.invalidations.fetch_add(1, Ordering::Relaxed);
+        }
+        entries.epoch = Some(epoch);
+    }
+
+    fn stats(&self) -> DaosObjCacheStats {
+        DaosObjCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().values.len(),
+        }
+    }
+}
+
+// A single value fetch in flight. It owns everything libdaos points at, so
+// it must not be dropped before its event completed, see cancel().
+#[cfg(feature = "libdaos")]
+struct PendingFetch {
+    event: DaosEvent,
+    rx: oneshot::Receiver<i32>,
+    _dkey: Vec<u8>,
+    _dkey_wrapper: Box<daos_key_t>,
+    iod: Box<daos_iod_t>,
+    _sg_iov: Box<d_iov_t>,
+    _sgl: Box<d_sg_list_t>,
+    buf: Vec<u8>,
+    _akey: Vec<u8>,
+    _shard: Option<Box<u32>>,
+}
+
+#[cfg(feature = "libdaos")]
+impl PendingFetch {
+    fn finish(self, res: std::result::Result<i32, oneshot::error::RecvError>) -> Result<Vec<u8>> {
+        match res {
+            Ok(ret) => self.complete(ret),
+            Err(_) => Err(DaosError::canceled("rx is closed early")),
+        }
+    }
+
+    async fn wait(mut self, deadline: Option<Duration>) -> Result<Vec<u8>> {
+        let rx = std::mem::replace(&mut self.rx, oneshot::channel().1);
+        let ret = self.event.wait(rx, deadline).await?;
+        self.complete(ret)
+    }
+
+    // The stored value of a fetch that completed with ret, its length is the
+    // iod size, so an empty buffer means the akey doesn't exist.
+    fn complete(self, ret: i32) -> Result<Vec<u8>> {
+        if ret != 0 {
+            // the buffer is handed out whole or not at all
+            return Err(
+                DaosError::from_call("daos_obj_fetch", ret, "async fetch operation fail")
+                    .with_required_size(self.iod.iod_size as usize)
+                    .into_truncated(0),
+            );
+        }
+        let mut buf = self.buf;
+        buf.truncate(self.iod.iod_size as usize);
+        Ok(buf)
+    }
+
+    async fn cancel(mut self) {
+        self.event.abort();
+        let _ = (&mut self.rx).await;
+    }
+}
+
+// A single value update in flight, see PendingFetch.
+#[cfg(feature = "libdaos")]
+struct PendingUpdate {
+    event: DaosEvent,
+    rx: oneshot::Receiver<i32>,
+    dkey: Vec<u8>,
+    _dkey_wrapper: Box<daos_key_t>,
+    _iod: Box<daos_iod_t>,
+    _sg_iov: Box<d_iov_t>,
+    _sgl: Box<d_sg_list_t>,
+    _data: Vec<u8>,
+    akey: Vec<u8>,
+}
+
+#[cfg(feature = "libdaos")]
+impl PendingUpdate {
+    async fn wait(mut self, deadline: Option<Duration>) -> (Result<()>, Vec<u8>, Vec<u8>) {
+        let res = match self.event.wait(self.rx, deadline).await {
+            Ok(0) => Ok(()),
+            Ok(ret) => Err(DaosError::from_call(
+                "daos_obj_update",
+                ret,
+                "async update operation fail",
+            )),
+            Err(e) => Err(e),
+        };
+        (res, self.dkey, self.akey)
+    }
+}
+
+// How update_many_objects_async resolves when some of the updates fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaosFanOutPolicy {
+    // fail with the error of the first failed update
+    AllSuccess,
+    // succeed and report every failed update by its index
+    BestEffort,
+}
+
+// Outcome of fetch_with_size. DAOS never returns part of a single value,
+// so a truncated result has no data and total_size tells how large a
+// buffer the value needs. total_size is 0 when the akey doesn't exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchResult {
+    pub data: Vec<u8>,
+    pub total_size: usize,
+    pub truncated: bool,
+}
+
+// A value returned by fetch_with_epoch_async with the epoch of the read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpochValue {
+    pub data: Vec<u8>,
+    pub epoch: u64,
+}
+
+// One entry of update_many_objects_async: object, dkey, akey and value.
+#[cfg(feature = "libdaos")]
+pub type DaosFanOutUpdate = (Arc<DaosObject>, Vec<u8>, Vec<u8>, Vec<u8>);
+
+// One entry of update_many_async: dkey, akey and value.
+pub type DaosBatchUpdate = (Vec<u8>, Vec<u8>, Vec<u8>);
+
+// One akey of update_akeys_async: akey, its own condition and value.
+pub type DaosAkeyUpdate = (Vec<u8>, CondFlags, Vec<u8>);
+
+// One akey of fetch_akeys_async: akey and its own condition.
+pub type DaosAkeyFetch = (Vec<u8>, CondFlags);
+
+// A run of array records, offset and len counted in records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DaosExtent {
+    pub offset: u64,
+    pub len: u64,
+}
+
+// Number of rec_size byte records in a buffer of len bytes.
+pub(crate) fn records_in(len: usize, rec_size: u64) -> Result<u64> {
+    if rec_size == 0 {
+        return Err(DaosError::inval("record size must not be 0"));
+    }
+    if !(len as u64).is_multiple_of(rec_size) {
+        return Err(DaosError::inval(format!(
+            "buffer of {} bytes doesn't hold whole {} byte records",
+            len, rec_size
+        )));
+    }
+    Ok(len as u64 / rec_size)
+}
+
+// What a recx fetch found from offset on, both counted in records:
+// extents are the runs that hold data, in increasing offset order, and len
+// reaches the end of the last one. The buffer is left as it was in the
+// holes between extents and past len.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecxFetch {
+    pub offset: u64,
+    pub len: usize,
+    pub extents: Vec<DaosExtent>,
+}
+
+impl RecxFetch {
+    #[cfg(feature = "libdaos")]
+    fn new(offset: u64, nr: u64, recxs: &[daos_recx_t]) -> Self {
+        let end = offset + nr;
+        let mut extents: Vec<DaosExtent> = recxs
+            .iter()
+            .filter_map(|recx| {
+                let lo = recx.rx_idx.max(offset);
+                let hi = (recx.rx_idx + recx.rx_nr).min(end);
+                (lo < hi).then_some(DaosExtent {
+                    offset: lo,
+                    len: hi - lo,
+                })
+            })
+            .collect();
+        extents.sort_by_key(|extent| extent.offset);
+        let len = extents
+            .last()
+            .map_or(0, |last| (last.offset + last.len - offset) as usize);
+        RecxFetch {
+            offset,
+            len,
+            extents,
+        }
+    }
+
+    // The runs before len that hold no data.
+    pub fn holes(&self) -> Vec<DaosExtent> {
+        let mut holes = Vec::new();
+        let mut next = self.offset;
+        for extent in &self.extents {
+            if extent.offset > next {
+                holes.push(DaosExtent {
+                    offset: next,
+                    len: extent.offset - next,
+                });
+            }
+            next = next.max(extent.offset + extent.len);
+        }
+        holes
+    }
+}
+
+// The extents written under an array akey, in increasing offset order, and
+// their record size.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecxList {
+    pub rec_size: u64,
+    pub extents: Vec<DaosExtent>,
+}
+
+#[cfg(feature = "list-obj")]
+// One item of a full object enumeration, in the order libdaos walks the
+// object: every dkey is followed by its akeys and every akey by its values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DaosObjEntry {
+    Dkey(Vec<u8>),
+    Akey(Vec<u8>),
+    // a single value of size bytes
+    Single { size: u64 },
+    // an extent of an array akey, counted in records of rec_size bytes
+    Recx { extent: DaosExtent, rec_size: u64 },
+}
+
+#[cfg(feature = "list-obj")]
+fn read_u64(buf: &[u8], at: usize) -> u64 {
+    u64::from_ne_bytes(buf[at..at + 8].try_into().unwrap())
+}
+
+#[cfg(feature = "list-obj")]
+fn read_u32(buf: &[u8], at: usize) -> u32 {
+    u32::from_ne_bytes(buf[at..at + 4].try_into().unwrap())
+}
+
+#[cfg(feature = "list-obj")]
+// Decodes the descriptors and packed buffer of a daos_obj_list_obj call.
+// Records of the same type can share a descriptor.
+fn parse_obj_entries(kds: &[daos_key_desc_t], buf: &[u8]) -> Result<Vec<DaosObjEntry>> {
+    let mut entries = Vec::new();
+    let mut start = 0usize;
+    for kd in kds {
+        let end = start + kd.kd_key_len as usize;
+        if end > buf.len() {
+            return Err(DaosError::inval("object listing overruns its buffer"));
+        }
+        let item = &buf[start..end];
+        match kd.kd_val_type {
+            OBJ_ITER_DKEY => entries.push(DaosObjEntry::Dkey(item.to_vec())),
+            OBJ_ITER_AKEY => entries.push(DaosObjEntry::Akey(item.to_vec())),
+            OBJ_ITER_SINGLE | OBJ_ITER_RECX => {
+                let mut at = 0usize;
+                while at < item.len() {
+                    if at + OBJ_ENUM_REC_SIZE > item.len() {
+                        return Err(DaosError::inval("truncated record in object listing"));
+                    }
+                    let rx_idx = read_u64(item, at);
+                    let rx_nr = read_u64(item, at + 8);
+                    let rec_size = read_u64(item, at + 32);
+                    let rec_flags = read_u32(item, at + 44);
+                    at += OBJ_ENUM_REC_SIZE;
+                    if rec_flags & RECX_INLINE != 0 {
+                        at += (rec_size * rx_nr) as usize;
+                    }
+                    entries.push(if kd.kd_val_type == OBJ_ITER_SINGLE {
+                        DaosObjEntry::Single { size: rec_size }
+                    } else {
+                        DaosObjEntry::Recx {
+                            extent: DaosExtent {
+                                offset: rx_idx,
+                                len: rx_nr,
+                            },
+                            rec_size,
+                        }
+                    });
+                }
+            }
+            _ => {}
+        }
+        start = end;
+    }
+    Ok(entries)
+}
+
+#[cfg(feature = "list-obj")]
+// Entries of a full object enumeration, filled one page per call to
+// DaosObjAsyncOps::list_obj_async and resumable like DaosKeyList.
+#[derive(Debug)]
+pub struct DaosObjList {
+    anchor: Box<daos_anchor_t>,
+    dkey_anchor: Box<daos_anchor_t>,
+    akey_anchor: Box<daos_anchor_t>,
+    nr: Box<u32>,
+    key_descs: Vec<daos_key_desc_t>,
+    eprs: Vec<daos_epoch_range_t>,
+    out_buf: Vec<u8>,
+    entries: Vec<DaosObjEntry>,
+}
+
+fn empty_anchor() -> Box<daos_anchor_t> {
+    Box::new(daos_anchor_t {
+        da_type: 0,
+        da_shard: 0,
+        da_flags: 0,
+        da_sub_anchors: 0,
+        da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
+    })
+}
+
+#[cfg(feature = "list-obj")]
+impl DaosObjList {
+    pub fn new() -> Box<Self> {
+        Self::with_capacity(OBJ_LIST_DESCS, OBJ_LIST_BUF_SIZE)
+    }
+
+    // descs bounds the entries of a page, buf_size the bytes of their keys
+    // and record descriptions. A page that doesn't fit grows the buffer.
+    pub fn with_capacity(descs: usize, buf_size: usize) -> Box<Self> {
+        let descs = descs.max(1);
+        Box::new(DaosObjList {
+            anchor: empty_anchor(),
+            dkey_anchor: empty_anchor(),
+            akey_anchor: empty_anchor(),
+            nr: Box::new(0),
+            key_descs: vec![
+                daos_key_desc_t {
+                    kd_key_len: 0,
+                    kd_val_type: 0,
+                };
+                descs
+            ],
+            eprs: vec![
+                daos_epoch_range_t {
+                    epr_lo: 0,
+                    epr_hi: 0,
+                };
+                descs
+            ],
+            out_buf: vec![0u8; buf_size.max(OBJ_ENUM_REC_SIZE)],
+            entries: Vec::new(),
+        })
+    }
+
+    // Rewind to the start of the object so the list can be reused.
+    pub fn reset(&mut self) {
+        self.anchor = empty_anchor();
+        self.dkey_anchor = empty_anchor();
+        self.akey_anchor = empty_anchor();
+        *self.nr = 0;
+        self.entries.clear();
+    }
+
+    // After KEY2BIG grows the buffer to the size libdaos asked for, at
+    // least doubling it, and tells whether the page is worth listing again.
+    // A page that needs more than MAX_OBJ_LIST_BUF_SIZE is an error.
+    fn grow_to_fit(&mut self, err: &DaosError) -> bool {
+        let len = self.out_buf.len();
+        match err {
+            DaosError::Key2Big { required, .. } if len < MAX_OBJ_LIST_BUF_SIZE => {
+                let required = required.unwrap_or(0);
+                if required > MAX_OBJ_LIST_BUF_SIZE {
+                    return false;
+                }
+                let grown = required.max(len * 2).min(MAX_OBJ_LIST_BUF_SIZE);
+                self.out_buf.resize(grown, 0);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn reach_end(&self) -> bool {
+        daos_anchor_is_eof(self.anchor.as_ref())
+    }
+
+    // Entries of the last page.
+    pub fn entries(&self) -> &[DaosObjEntry] {
+        &self.entries
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DaosObjectOpenOpts {
+    pub read_only: bool,
+}
+
+// Bounds for the per-round descriptor count and key buffer of a
+// DaosKeyList. Each round starts from the current sizes and doubles them,
+// up to the maximum, whenever the previous round filled them up. A key
+// longer than the buffer makes the listing grow it to fit and retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DaosKeyListConfig {
+    pub min_descs: u32,
+    pub max_descs: u32,
+    pub min_buf_size: usize,
+    pub max_buf_size: usize,
+}
+
+impl Default for DaosKeyListConfig {
+    fn default() -> Self {
+        DaosKeyListConfig {
+            min_descs: MIN_KEY_DESCS,
+            max_descs: MAX_KEY_DESCS,
+            min_buf_size: KEY_BUF_SIZE,
+            max_buf_size: MAX_KEY_BUF_SIZE,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DaosKeyList {
+    pub(crate) anchor: Box<daos_anchor_t>,
+    pub(crate) ndesc: Box<u32>,
+    pub(crate) key_descs: Vec<daos_key_desc_t>,
+    pub(crate) out_buf: Vec<u8>,
+    config: DaosKeyListConfig,
+}
+
+impl DaosKeyList {
+    pub fn new() -> Box<Self> {
+        Self::with_config(DaosKeyListConfig::default())
+    }
+
+    pub fn with_config(config: DaosKeyListConfig) -> Box<Self> {
+        let config = DaosKeyListConfig {
+            min_descs: config.min_descs.max(1),
+            max_descs: config.max_descs.max(config.min_descs.max(1)),
+            min_buf_size: config.min_buf_size.max(1),
+            max_buf_size: config.max_buf_size.max(config.min_buf_size.max(1)),
+        };
+        let vec = vec![0u8; config.min_buf_size];
+        Box::new(DaosKeyList {
+            anchor: Box::new(daos_anchor_t {
+                da_type: 0,
+                da_shard: 0,
+                da_flags: 0,
+                da_sub_anchors: 0,
+                da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
+            }),
+            ndesc: Box::new(0),
+            key_descs: vec![
+                daos_key_desc_t {
+                    kd_key_len: 0,
+                    kd_val_type: 0,
+                };
+                config.min_descs as usize
+            ],
+            out_buf: vec,
+            config,
+        })
+    }
+
+    pub(crate) fn prepare_next_query(&mut self) {
+        *(self.ndesc) = self.key_descs.len() as u32;
+    }
+
+    // Grow the descriptors and buffer for the next round if the last one
+    // came back full, so large objects need fewer round trips.
+    pub(crate) fn adapt_to_last_query(&mut self) {
+        let nr = *self.ndesc as usize;
+        if nr >= self.key_descs.len() && self.key_descs.len() < self.config.max_descs as usize {
+            let grown = (self.key_descs.len() * 2).min(self.config.max_descs as usize);
+            self.key_descs.resize(
+                grown,
+                daos_key_desc_t {
+                    kd_key_len: 0,
+                    kd_val_type: 0,
+                },
+            );
+        }
+
+        let used: usize = self.key_descs[..nr]
+            .iter()
+            .map(|kd| kd.kd_key_len as usize)
+            .sum();
+        if used * 4 >= self.out_buf.len() * 3 && self.out_buf.len() < self.config.max_buf_size {
+            let grown = (self.out_buf.len() * 2).min(self.config.max_buf_size);
+            self.out_buf.resize(grown, 0);
+        }
+    }
+
+    // After KEY2BIG grows the buffer to fit the key that didn't, and tells
+    // whether the query is worth retrying. Unlike the growth above this can
+    // go past max_buf_size, the key couldn't be listed otherwise.
+    pub(crate) fn grow_to_fit(&mut self, err: &DaosError) -> bool {
+        match err {
+            DaosError::Key2Big {
+                required: Some(required),
+                ..
+            } if *required > self.out_buf.len() => {
+                let grown = (*required).max(self.out_buf.len() * 2);
+                self.out_buf.resize(grown, 0);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn get_config(&self) -> DaosKeyListConfig {
+        self.config
+    }
+
+    // Rewind to the start of the enumeration so the list can be reused.
+    // Descriptors and buffer grown by earlier rounds shrink back to the
+    // minimum of the config.
+    pub fn reset(&mut self) {
+        *self.anchor = daos_anchor_t {
+            da_type: 0,
+            da_shard: 0,
+            da_flags: 0,
+            da_sub_anchors: 0,
+            da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
+        };
+        *self.ndesc = 0;
+        if self.key_descs.len() > self.config.min_descs as usize {
+            self.key_descs.truncate(self.config.min_descs as usize);
+            self.key_descs.shrink_to_fit();
+        }
+        if self.out_buf.len() > self.config.min_buf_size {
+            self.out_buf.truncate(self.config.min_buf_size);
+            self.out_buf.shrink_to_fit();
+        }
+    }
+
+    // Snapshot of the enumeration position of this list over object oid.
+    pub fn cursor(&self, oid: DaosObjectId) -> ScanCursor {
+        ScanCursor {
+            oid,
+            anchor: *self.anchor,
+            dkey: None,
+            akey: None,
+        }
+    }
+
+    pub fn get_key_num(&self) -> u32 {
+        *self.ndesc
+    }
+
+    pub fn reach_end(&self) -> bool {
+        daos_anchor_is_eof(self.anchor.as_ref())
+    }
+
+    // The keys of the last batch, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &[u8]> {
+        let mut start = 0usize;
+        self.key_descs[..*self.ndesc as usize]
+            .iter()
+            .map(move |kd| {
+                let key = &self.out_buf[start..start + kd.kd_key_len as usize];
+                start += kd.kd_key_len as usize;
+                key
+            })
+    }
+
+    // use (0, 0) as start position
+    pub fn get_key(&self, start_and_idx: (u32, u32)) -> Result<(&[u8], (u32, u32))> {
+        let (start, idx) = start_and_idx;
+        if idx >= *self.ndesc {
+            return Err(DaosError::inval("index out of range"));
+        }
+        let key_desc = &self.key_descs[idx as usize];
+        let end = start as usize + key_desc.kd_key_len as usize;
+        let key = &self.out_buf[start as usize..end];
+        Ok((key, (end as u32, idx + 1)))
+    }
+}
+
+const SCAN_CURSOR_VERSION: u8 = 1;
+
+// fetch_bytes_async carves its buffers out of chunks of at least this size.
+// A chunk is reused once every Bytes taken from it has been dropped.
+#[cfg(feature = "bytes")]
+const FETCH_BUF_CHUNK: usize = 64 * 1024;
+
+// Where a scan over an object stopped, in a form that outlives the process.
+// The anchor is the dkey enumeration position after the last batch; dkey
+// and akey are optional positions inside a dkey for scans that descend into
+// its akeys and values. Take the cursor once the current batch of keys has
+// been fully processed, DAOS can't resume in the middle of a batch.
+#[derive(Debug, Clone)]
+pub struct ScanCursor {
+    pub oid: DaosObjectId,
+    anchor: daos_anchor_t,
+    pub dkey: Option<Vec<u8>>,
+    pub akey: Option<Vec<u8>>,
+}
+
+impl ScanCursor {
+    pub fn with_position(mut self, dkey: Option<Vec<u8>>, akey: Option<Vec<u8>>) -> Self {
+        self.dkey = dkey;
+        self.akey = akey;
+        self
+    }
+
+    pub fn reach_end(&self) -> bool {
+        daos_anchor_is_eof(&self.anchor)
+    }
+
+    // Layout: version, oid lo/hi, anchor fields, then dkey and akey as a
+    // u32 length (u32::MAX for none) followed by the bytes, little endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + 16 + 16 + self.anchor.da_buf.len() + 8);
+        out.push(SCAN_CURSOR_VERSION);
+        out.extend_from_slice(&self.oid.lo.to_le_bytes());
+        out.extend_from_slice(&self.oid.hi.to_le_bytes());
+        out.extend_from_slice(&self.anchor.da_type.to_le_bytes());
+        out.extend_from_slice(&self.anchor.da_shard.to_le_bytes());
+        out.extend_from_slice(&self.anchor.da_flags.to_le_bytes());
+        out.extend_from_slice(&self.anchor.da_sub_anchors.to_le_bytes());
+        out.extend_from_slice(&self.anchor.da_buf);
+        for key in [&self.dkey, &self.akey] {
+            match key {
+                Some(key) => {
+                    out.extend_from_slice(&(key.len() as u32).to_le_bytes());
+                    out.extend_from_slice(key);
+                }
+                None => out.extend_from_slice(&u32::MAX.to_le_bytes()),
+            }
+        }
+        out
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> Result<ScanCursor> {
+        let mut rest = buf;
+        let mut take = |n: usize| -> Result<&[u8]> {
+            if rest.len() < n {
+                return Err(DaosError::inval("truncated scan cursor"));
+            }
+            let (head, tail) = rest.split_at(n);
+            rest = tail;
+            Ok(head)
+        };
+
+        if take(1)?[0] != SCAN_CURSOR_VERSION {
+            return Err(DaosError::inval("unknown scan cursor version"));
+        }
+        let oid = DaosObjectId {
+            lo: u64::from_le_bytes(take(8)?.try_into().unwrap()),
+            hi: u64::from_le_bytes(take(8)?.try_into().unwrap()),
+        };
+        let mut anchor = daos_anchor_t {
+            da_type: u16::from_le_bytes(take(2)?.try_into().unwrap()),
+            da_shard: u16::from_le_bytes(take(2)?.try_into().unwrap()),
+            da_flags: u32::from_le_bytes(take(4)?.try_into().unwrap()),
+            da_sub_anchors: u64::from_le_bytes(take(8)?.try_into().unwrap()),
+            da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
+        };
+        anchor
+            .da_buf
+            .copy_from_slice(take(DAOS_ANCHOR_BUF_MAX as usize)?);
+
+        let mut keys = [None, None];
+        for key in keys.iter_mut() {
+            let len = u32::from_le_bytes(take(4)?.try_into().unwrap());
+            if len != u32::MAX {
+                *key = Some(take(len as usize)?.to_vec());
+            }
+        }
+        if !rest.is_empty() {
+            return Err(DaosError::inval("trailing bytes after scan cursor"));
+        }
+
+        let [dkey, akey] = keys;
+        Ok(ScanCursor {
+            oid,
+            anchor,
+            dkey,
+            akey,
+        })
+    }
+
+    // Reopens the scanned object read-only in cont and returns it with a key
+    // list positioned where the cursor was taken.
+    #[cfg(feature = "libdaos")]
+    pub fn resume(
+        cont: &DaosContainer,
+        cursor: &ScanCursor,
+    ) -> Result<(Box<DaosObject>, Box<DaosKeyList>)> {
+        let obj = DaosObject::open(cont, cursor.oid, true)?;
+        let mut key_lst = DaosKeyList::new();
+        *key_lst.anchor = cursor.anchor;
+        Ok((obj, key_lst))
+    }
+}
+
+// Recycles key lists between scans instead of allocating a fresh set of
+// descriptors and key buffer each time. Safe to share between tasks.
+#[derive(Debug)]
+pub struct KeyListPool {
+    // kept boxed, that is how lists are handed out
+    #[allow(clippy::vec_box)]
+    lists: Mutex<Vec<Box<DaosKeyList>>>,
+    max_idle: usize,
+}
+
+// Lists kept idle by KeyListPool::shared().
+const SHARED_IDLE_KEY_LISTS: usize = 16;
+
+static SHARED_KEY_LISTS: KeyListPool = KeyListPool::new(SHARED_IDLE_KEY_LISTS);
+
+impl KeyListPool {
+    pub const fn new(max_idle: usize) -> Self {
+        KeyListPool {
+            lists: Mutex::new(Vec::new()),
+            max_idle,
+        }
+    }
+
+    // The pool the key streams of this crate check their lists out of.
+    pub fn shared() -> &'static KeyListPool {
+        &SHARED_KEY_LISTS
+    }
+
+    // The returned list is positioned at the start of an enumeration.
+    pub fn checkout(&self) -> Box<DaosKeyList> {
+        let list = self.lists.lock().unwrap().pop();
+        list.unwrap_or_else(DaosKeyList::new)
+    }
+
+    pub fn checkin(&self, mut key_lst: Box<DaosKeyList>) {
+        key_lst.reset();
+        let mut lists = self.lists.lock().unwrap();
+        if lists.len() < self.max_idle {
+            lists.push(key_lst);
+        }
+    }
+
+    pub fn idle(&self) -> usize {
+        self.lists.lock().unwrap().len()
+    }
+}
+
+pub trait DaosObjSyncOps {
+    fn punch(&self, txn: &DaosTxn) -> Result<()>;
+    // Blocking punch_with_flags_async.
+    fn punch_with_flags(&self, txn: &DaosTxn, flags: CondFlags) -> Result<bool>;
+    fn fetch(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<Vec<u8>>;
+    fn fetch_with_size(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<FetchResult>;
+    fn update(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        data: &[u8],
+    ) -> Result<()>;
+    // Punches every dkey in dkeys with all their akeys.
+    fn punch_dkeys(&self, txn: &DaosTxn, dkeys: &[Vec<u8>]) -> Result<()>;
+    // Punches the akeys under dkey, dkey itself stays.
+    fn punch_akeys(&self, txn: &DaosTxn, dkey: Vec<u8>, akeys: &[Vec<u8>]) -> Result<()>;
+    // Blocking fetch_recx_async, returns how far from offset data was found.
+    fn fetch_recx(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        out_buf: &mut [u8],
+    ) -> Result<usize>;
+    fn update_recx(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()>;
+    // Blocking list_dkey_async, neither needs an event queue.
+    fn list_dkey(&self, txn: &DaosTxn, key_lst: Box<DaosKeyList>) -> Result<Box<DaosKeyList>>;
+    fn list_akey(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        key_lst: Box<DaosKeyList>,
+    ) -> Result<Box<DaosKeyList>>;
+    fn list_recx(&self, txn: &DaosTxn, dkey: Vec<u8>, akey: Vec<u8>) -> Result<RecxList>;
+    #[cfg(feature = "list-obj")]
+    fn list_obj(&self, txn: &DaosTxn, obj_lst: Box<DaosObjList>) -> Result<Box<DaosObjList>>;
+    fn punch_akeys_with_flags(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: &[Vec<u8>],
+    ) -> Result<bool>;
+    fn remove(&self, txn: &DaosTxn, dkey: Vec<u8>, akey: Option<Vec<u8>>) -> Result<bool>;
+    fn update_akeys(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyUpdate>,
+    ) -> Result<()>;
+    fn fetch_akeys(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyFetch>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>>;
+}
+
+// Single value and array I/O borrow dkey and akey for the duration of the
+// call, so a Vec, slice or byte string literal works without a copy. The
+// methods returning 'static futures take their keys by value instead.
+#[allow(async_fn_in_trait)]
+pub trait DaosObjAsyncOps {
+    fn punch_async(&self, txn: &DaosTxn) -> impl Future<Output = Result<()>> + Send + 'static;
+    // punch_async with daos_obj_punch flags. With DAOS_COND_PUNCH a missing
+    // object gives Ok(false) instead of NonExist, otherwise it is Ok(true).
+    fn punch_with_flags_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+    ) -> impl Future<Output = Result<bool>> + Send + 'static;
+    // Fetches the single value into out_buf and returns its size. A value
+    // larger than out_buf fails with DaosError::Truncated, whose actual_size
+    // is the buffer size to fetch again with.
+    async fn fetch_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        out_buf: &mut [u8],
+    ) -> Result<usize>;
+    // fetch_async into memory that needn't be initialized, which saves
+    // zeroing large buffers. The first returned bytes of out_buf hold the
+    // value afterwards.
+    async fn fetch_uninit_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        out_buf: &mut [MaybeUninit<u8>],
+    ) -> Result<usize>;
+    // Appends the value, of at most max_size bytes, to buf and returns its
+    // size. Only reserves space in buf, nothing is zeroed.
+    #[cfg(feature = "bytes")]
+    async fn fetch_into_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        buf: &mut BytesMut,
+        max_size: u32,
+    ) -> Result<usize>;
+    async fn update_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        data: &[u8],
+    ) -> Result<()>;
+    // update_async of the concatenation of bufs, each passed to DAOS as an
+    // iovec of its own so nothing is copied.
+    async fn update_vectored_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        bufs: &[IoSlice<'_>],
+    ) -> Result<()>;
+    // fetch_async scattering the value over bufs in order, returns its size.
+    // Like fetch_async, values larger than all bufs together fail with
+    // Truncated and leave bufs untouched.
+    async fn fetch_vectored_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Result<usize>;
+    // Writes the single values of several akeys under dkey in one update.
+    // Each akey may carry CondFlags::AKEY_INSERT or AKEY_UPDATE of its own,
+    // the others take the akey condition of flags. A failed condition fails
+    // the whole update.
+    async fn update_akeys_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyUpdate>,
+    ) -> Result<()>;
+    // Fetches the single values of several akeys under dkey in one fetch,
+    // each of at most max_size bytes. Akeys may carry CondFlags::AKEY_FETCH
+    // of their own, a missing record is otherwise reported as None.
+    async fn fetch_akeys_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyFetch>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>>;
+    async fn fetch_recx_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        out_buf: &mut [u8],
+    ) -> Result<usize>;
+    // fetch_recx_async that also tells which parts of out_buf hold data,
+    // see RecxFetch.
+    async fn fetch_recx_map_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        out_buf: &mut [u8],
+    ) -> Result<RecxFetch>;
+    async fn update_recx_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()>;
+    // fetch_recx_async for arrays of rec_size byte records, e.g. 8 byte
+    // samples, addressed by record index. out_buf holds whole records and
+    // the returned length is counted in records.
+    #[allow(clippy::too_many_arguments)]
+    async fn fetch_recs_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        rec_size: u64,
+        index: u64,
+        out_buf: &mut [u8],
+    ) -> Result<usize>;
+    // update_recx_async of the whole rec_size byte records in data, written
+    // from record index on.
+    #[allow(clippy::too_many_arguments)]
+    async fn update_recs_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        rec_size: u64,
+        index: u64,
+        data: &[u8],
+    ) -> Result<()>;
+    // Punches len records from offset of the array value under dkey and
+    // akey, bytes for the arrays of update_recx_async, leaving a hole that
+    // fetch_recx_map_async reports as such. Done as an update of the extent
+    // with zero sized records.
+    async fn punch_recx_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        len: u64,
+    ) -> Result<()>;
+    fn list_dkey_async(
+        &self,
+        txn: &DaosTxn,
+        key_lst: Box<DaosKeyList>,
+    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static;
+    // list_dkey_async for the akeys under dkey.
+    fn list_akey_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        key_lst: Box<DaosKeyList>,
+    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static;
+    // Moves key_lst to dkey so that list_dkey_async goes on from there, or
+    // with akey to akey so that list_akey_async under dkey does. On objects
+    // with lexical keys this makes listing a range scan.
+    fn key2anchor_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akey: Option<Vec<u8>>,
+        key_lst: Box<DaosKeyList>,
+    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static;
+    // Every extent of the array value under dkey and akey, empty when the
+    // akey holds a single value or nothing.
+    fn list_recx_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akey: Vec<u8>,
+    ) -> impl Future<Output = Result<RecxList>> + Send + 'static;
+    // Fetches the next page of the dkeys, akeys and values of the whole
+    // object into obj_lst. Call until obj_lst.reach_end().
+    #[cfg(feature = "list-obj")]
+    fn list_obj_async(
+        &self,
+        txn: &DaosTxn,
+        obj_lst: Box<DaosObjList>,
+    ) -> impl Future<Output = Result<Box<DaosObjList>>> + Send + 'static;
+    // Fetch `akey` under each dkey, a missing record is reported as None.
+    // DAOS keeps no empty single values, so one written empty is None as
+    // well, unless compression stored it behind a header, then it comes back
+    // as an empty value. The fetches are submitted together, fails with
+    // Truncated when a value is larger than max_size.
+    async fn get_multi_async(
+        &self,
+        txn: &DaosTxn,
+        dkeys: &[Vec<u8>],
+        akey: Vec<u8>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>>;
+    // Writes every single value of updates with at most max_in_flight of
+    // them submitted at once, and returns the result of each in the order
+    // of updates. A failed update doesn't stop the others.
+    async fn update_many_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        updates: Vec<DaosBatchUpdate>,
+        max_in_flight: usize,
+    ) -> Result<Vec<Result<()>>>;
+    async fn get_map_async(
+        &self,
+        txn: &DaosTxn,
+        dkeys: Vec<Vec<u8>>,
+        akey: Vec<u8>,
+        max_size: u32,
+    ) -> Result<HashMap<Vec<u8>, Option<Vec<u8>>>>;
+    // Single value fetch pinned to one replica that sends a second fetch to
+    // another replica when the first one hasn't completed after
+    // `hedge_delay`, and returns whichever answers first. Fails with Inval
+    // for classes that aren't replicated, and like fetch_async with
+    // Truncated when the value is larger than max_size. DaosObject fails
+    // with NoSys unless built with the hedged-fetch feature.
+    async fn fetch_hedged_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+        hedge_delay: Duration,
+    ) -> Result<Vec<u8>>;
+    // Single value fetch inside txn that also returns the epoch txn reads
+    // at, for read-validate-write flows and versioned caches.
+    async fn fetch_with_epoch_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<EpochValue>;
+    // Map-style delete: punches akey under dkey, or the whole dkey when akey
+    // is None, and tells whether there was anything to remove.
+    async fn remove_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akey: Option<Vec<u8>>,
+    ) -> Result<bool>;
+    async fn punch_dkeys_async(&self, txn: &DaosTxn, dkeys: &[Vec<u8>]) -> Result<()>;
+    async fn punch_akeys_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akeys: &[Vec<u8>],
+    ) -> Result<()>;
+    // punch_akeys_async taking CondFlags::PUNCH, with which a missing akey
+    // punches nothing and returns false.
+    async fn punch_akeys_with_flags_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: &[Vec<u8>],
+    ) -> Result<bool>;
+    #[cfg(feature = "bytes")]
+    async fn update_bytes_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        data: Bytes,
+    ) -> Result<()>;
+    // The returned Bytes share larger pooled allocations, see FETCH_BUF_CHUNK.
+    #[cfg(feature = "bytes")]
+    async fn fetch_bytes_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<Bytes>;
+}
+
+// The get_multi_async contract, run by the tests of every backend on a new
+// object.
+#[cfg(test)]
+pub(crate) async fn check_get_multi_async(obj: &(impl DaosObjSyncOps + DaosObjAsyncOps)) {
+    let txn = DaosTxn::txn_none();
+    obj.update(&txn, CondFlags::empty(), b"d1", b"a", b"value")
+        .unwrap();
+    obj.update(&txn, CondFlags::empty(), b"d2", b"a", b"")
+        .unwrap();
+
+    let dkeys = vec![b"d1".to_vec(), b"d2".to_vec(), b"d3".to_vec()];
+    let values = obj.get_multi_async(&txn, &dkeys, b"a".to_vec(), 8).await;
+    assert_eq!(values.unwrap(), vec![Some(b"value".to_vec()), None, None]);
+
+    let values = obj.get_multi_async(&txn, &dkeys, b"a".to_vec(), 2).await;
+    assert!(matches!(values, Err(DaosError::Truncated { .. })));
+}
+
+// Constructors, kept out of DaosObjSyncOps and DaosObjAsyncOps so that
+// other backends can implement the object operations.
+#[cfg(feature = "libdaos")]
+impl DaosObject {
+    pub fn create(
+        cont: &DaosContainer,
+        oid_allocator: Arc<DaosSyncOidAllocator>,
+        otype: daos_otype_t,
+        cid: daos_oclass_id_t,
+        hints: daos_oclass_hints_t,
+        args: u32,
+    ) -> Result<Box<DaosObject>> {
+        let cont_hdl = cont.get_handle();
+        let eq = cont.get_event_queue();
+        let eqh = eq.cloned();
+
+        let mut oid = oid_allocator.allocate()?;
+        let ret =
+            unsafe { daos_obj_generate_oid2(cont_hdl.unwrap(), &mut oid, otype, cid, hints, args) };
+
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_obj_generate_oid2",
+                ret,
+                "can't generate object id",
+            ));
+        }
+
+        let mut obj_hdl = DaosHandle { cookie: 0u64 };
+        let ret = unsafe {
+            daos_obj_open(
+                cont_hdl.unwrap(),
+                oid,
+                DAOS_OO_RW,
+                &mut obj_hdl,
+                std::ptr::null_mut(),
+            )
+        };
+
+        if ret != 0 {
+            return Err(
+                DaosError::from_call("daos_obj_open", ret, "can't open object")
+                    .with_context(DaosErrorContext::new("create", oid)),
+            );
+        } else {
+            Ok(Box::new(
+                DaosObject::new(oid, obj_hdl, eqh, cont.get_deadlines()).with_container(cont_hdl),
+            ))
+        }
+    }
+
+    pub fn open(
+        cont: &DaosContainer,
+        oid: DaosObjectId,
+        read_only: bool,
+    ) -> Result<Box<DaosObject>> {
+        let cont_hdl = cont.get_handle();
+        let eq = cont.get_event_queue();
+        let eqh = eq.cloned();
+
+        let mut obj_hdl = DaosHandle { cookie: 0u64 };
+        let ret = unsafe {
+            daos_obj_open(
+                cont_hdl.unwrap(),
+                oid,
+                if read_only { DAOS_OO_RO } else { DAOS_OO_RW },
+                &mut obj_hdl,
+                std::ptr::null_mut(),
+            )
+        };
+
+        if ret != 0 {
+            Err(
+                DaosError::from_call("daos_obj_open", ret, "can't open object")
+                    .with_context(DaosErrorContext::new("open", oid)),
+            )
+        } else {
+            Ok(Box::new(
+                DaosObject::new(oid, obj_hdl, eqh, cont.get_deadlines())
+                    .with_container(cont_hdl)
+                    .with_read_only(read_only),
+            ))
+        }
+    }
+
+    pub fn create_async(
+        cont: &DaosContainer,
+        oid_allocator: Arc<DaosAsyncOidAllocator>,
+        otype: daos_otype_t,
+        cid: daos_oclass_id_t,
+        hints: daos_oclass_hints_t,
+        args: u32,
+    ) -> impl Future<Output = Result<Box<DaosObject>>> + Send + 'static {
+        let eq = cont.get_event_queue();
+        let eqh = eq.cloned();
+        let evt = eq.map(|e| e.create_event());
+        let cont_hdl = cont.get_handle();
+        let deadlines = cont.get_deadlines();
+        async move {
+            if cont_hdl.is_none() {
+                return Err(DaosError::no_handle("empty container handle"));
+            }
+            if evt.is_none() {
+                return Err(DaosError::no_handle("event queue is nil"));
+            }
+
+            let mut oid = oid_allocator.allocate().await?;
+            let ret = unsafe {
+                daos_obj_generate_oid2(cont_hdl.unwrap(), &mut oid, otype, cid, hints, args)
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_obj_generate_oid2",
+                    ret,
+                    "can't generate object id",
+                ));
+            }
+
+            let mut event = evt.unwrap()?;
+            let rx = event.register_callback()?;
+
+            let mut obj_hdl = Box::new(DaosHandle { cookie: 0u64 });
+            let ret = unsafe {
+                daos_obj_open(
+                    cont_hdl.unwrap(),
+                    oid,
+                    DAOS_OO_RW,
+                    obj_hdl.as_mut(),
+                    event.as_mut() as *mut daos_event_t,
+                )
+            };
+
+            if ret != 0 {
+                return Err(
+                    DaosError::from_call("daos_obj_open", ret, "can't open object")
+                        .with_context(DaosErrorContext::new("create_async", oid)),
+                );
+            }
+
+            match event.wait(rx, deadlines.metadata).await {
+                Ok(ret) => {
+                    if ret != 0 {
+                        return Err(DaosError::from_call(
+                            "daos_obj_open",
+                            ret,
+                            "async open operation fail",
+                        )
+                        .with_context(DaosErrorContext::new("create_async", oid)));
+                    }
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+            }
+
+            Ok(Box::new(
+                DaosObject::new(oid, *obj_hdl, eqh, deadlines).with_container(cont_hdl),
+            ))
+        }
+    }
+
+    pub fn create_many_async(
+        cont: &DaosContainer,
+        oid_allocator: Arc<DaosAsyncOidAllocator>,
+        n: usize,
+        otype: daos_otype_t,
+        cid: daos_oclass_id_t,
+        hints: daos_oclass_hints_t,
+        args: u32,
+    ) -> impl Future<Output = Result<Vec<Box<DaosObject>>>> + Send + 'static {
+        let eq = cont.get_event_queue().cloned();
+        let cont_hdl = cont.get_handle();
+        let deadlines = cont.get_deadlines();
+        async move {
+            if cont_hdl.is_none() {
+                return Err(DaosError::no_handle("empty container handle"));
+            }
+            if eq.is_none() {
+                return Err(DaosError::no_handle("event queue is nil"));
+            }
+            let eq = eq.unwrap();
+
+            let mut oids = Vec::with_capacity(n);
+            for _ in 0..n {
+                let mut oid = oid_allocator.allocate().await?;
+                let ret = unsafe {
+                    daos_obj_generate_oid2(cont_hdl.unwrap(), &mut oid, otype, cid, hints, args)
+                };
+                if ret != 0 {
+                    return Err(DaosError::from_call(
+                        "daos_obj_generate_oid2",
+                        ret,
+                        "can't generate object id",
+                    ));
+                }
+                oids.push(oid);
+            }
+
+            // Submit every open before waiting on any of them, so the
+            // round trips overlap on the event queue.
+            let mut obj_hdls = vec![DaosHandle { cookie: 0u64 }; n];
+            let mut pending = Vec::with_capacity(n);
+            let mut first_err = None;
+            for (oid, obj_hdl) in oids.iter().zip(obj_hdls.iter_mut()) {
+                let mut event = match eq.create_event() {
+                    Ok(event) => event,
+                    Err(e) => {
+                        first_err = Some(e);
+                        break;
+                    }
+                };
+                let rx = match event.register_callback() {
+                    Ok(rx) => rx,
+                    Err(e) => {
+                        first_err = Some(e);
+                        break;
+                    }
+                };
+                let ret = unsafe {
+                    daos_obj_open(
+                        cont_hdl.unwrap(),
+                        *oid,
+                        DAOS_OO_RW,
+                        obj_hdl,
+                        event.as_mut() as *mut daos_event_t,
+                    )
+                };
+                if ret != 0 {
+                    first_err = Some(
+                        DaosError::from_call("daos_obj_open", ret, "can't open object")
+                            .with_context(DaosErrorContext::new("create_many_async", *oid)),
+                    );
+                    break;
+                }
+                pending.push((event, rx));
+            }
+
+            // Opens already in flight must complete before their handles
+            // and events go away, even if a later submission failed.
+            let mut objs = Vec::with_capacity(pending.len());
+            for (idx, (mut event, rx)) in pending.into_iter().enumerate() {
+                let res = match event.wait(rx, deadlines.metadata).await {
+                    Ok(0) => Ok(()),
+                    Ok(ret) => Err(DaosError::from_call(
+                        "daos_obj_open",
+                        ret,
+                        "async open object fail",
+                    )
+                    .with_context(DaosErrorContext::new("create_many_async", oids[idx]))),
+                    Err(e) => Err(e),
+                };
+                drop(event);
+                match res {
+                    Ok(()) => objs.push(Box::new(
+                        DaosObject::new(oids[idx], obj_hdls[idx], Some(eq.clone()), deadlines)
+                            .with_container(cont_hdl),
+                    )),
+                    Err(e) => {
+                        first_err.get_or_insert(e);
+                    }
+                }
+            }
+
+            match first_err {
+                Some(e) => Err(e),
+                None => Ok(objs),
+            }
+        }
+    }
+
+    pub fn open_async(
+        cont: &DaosContainer,
+        oid: DaosObjectId,
+        read_only: bool,
+    ) -> impl Future<Output = Result<Box<DaosObject>>> + Send + 'static {
+        let eq = cont.get_event_queue();
+        let eqh = eq.cloned();
+        let evt = eq.map(|e| e.create_event());
+        let cont_hdl = cont.get_handle();
+        let deadlines = cont.get_deadlines();
+        async move {
+            if cont_hdl.is_none() {
+                return Err(DaosError::no_handle("empty container handle"));
+            }
+            if evt.is_none() {
+                return Err(DaosError::no_handle("event queue is nil"));
+            }
+
+            let mut event = evt.unwrap()?;
+            let rx = event.register_callback()?;
+
+            let mut obj_hdl = Box::new(DaosHandle { cookie: 0u64 });
+            let ret = unsafe {
+                daos_obj_open(
+                    cont_hdl.unwrap(),
+                    oid,
+                    if read_only { DAOS_OO_RO } else { DAOS_OO_RW },
+                    obj_hdl.as_mut(),
+                    event.as_mut() as *mut daos_event_t,
+                )
+            };
+
+            if ret != 0 {
+                return Err(
+                    DaosError::from_call("daos_obj_open", ret, "can't open object")
+                        .with_context(DaosErrorContext::new("open_async", oid)),
+                );
+            }
+
+            match event.wait(rx, deadlines.metadata).await {
+                Ok(ret) => {
+                    if ret != 0 {
+                        Err(
+                            DaosError::from_call("daos_obj_open", ret, "async open object fail")
+                                .with_context(DaosErrorContext::new("open_async", oid)),
+                        )
+                    } else {
+                        Ok(Box::new(
+                            DaosObject::new(oid, *obj_hdl, eqh, deadlines)
+                                .with_container(cont_hdl)
+                                .with_read_only(read_only),
+                        ))
+                    }
+                }
+                Err(e) => Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "libdaos")]
+impl DaosObjSyncOps for DaosObject {
+    fn punch(&self, txn: &DaosTxn) -> Result<()> {
+        self.punch_with_flags(txn, CondFlags::empty()).map(|_| ())
+    }
+
+    fn punch_with_flags(&self, txn: &DaosTxn, flags: CondFlags) -> Result<bool> {
+        let flags = flags.bits();
+        let res = (|| {
+            self.check_writable("punch")?;
+            check_punch_flags(flags)?;
+            let obj_hdl = self
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("punch uninitialized object"))?;
+            let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+            let ret = unsafe { daos_obj_punch(obj_hdl, txn, flags, ptr::null_mut()) };
+            if let Some(cache) = self.cache.as_ref() {
+                cache.clear();
+            }
+            let cond = flags & DAOS_COND_PUNCH as u64 != 0;
+            match ret {
+                0 => Ok(true),
+                ret if cond && ret == -(daos_errno_DER_NONEXIST as i32) => Ok(false),
+                ret => Err(DaosError::from_call(
+                    "daos_obj_punch",
+                    ret,
+                    "can't punch object",
+                )),
+            }
+        })();
+        res.map_err(|e| e.with_context(DaosErrorContext::new("punch", self.oid)))
+    }
+
+    fn fetch(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<Vec<u8>> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        if self.compression.is_none() {
+            return self.fetch_stored(txn, flags, dkey, akey, max_size as usize);
+        }
+
+        let buf_size = self.stored_size(max_size);
+        let stored = match self.fetch_stored(txn, flags, dkey, akey, buf_size) {
+            Ok(stored) => stored,
+            Err(DaosError::Rec2Big {
+                required: Some(size),
+                ..
+            }) => {
+                // fetch again to learn the original size from the header
+                self.fetch_stored(txn, flags, dkey, akey, size)?
+            }
+            Err(e) => return Err(e),
+        };
+        let mut value = vec![0u8; max_size as usize];
+        let len = daos_compress::decode(&stored, &mut value)
+            .map_err(|e| e.with_context(self.op_context("fetch", dkey, akey)))?;
+        value.truncate(len);
+        Ok(value)
+    }
+
+    fn fetch_with_size(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<FetchResult> {
+        match self.fetch(txn, flags, dkey, akey, max_size) {
+            Ok(data) => Ok(FetchResult {
+                total_size: data.len(),
+                data,
+                truncated: false,
+            }),
+            Err(DaosError::Rec2Big {
+                required: Some(total_size),
+                ..
+            }) => Ok(FetchResult {
+                data: Vec::new(),
+                total_size,
+                truncated: true,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn update(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        data: &[u8],
+    ) -> Result<()> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        self.check_writable("update")
+            .and_then(|_| check_user_akey(akey))
+            .map_err(|e| e.with_context(self.op_context("update", dkey, akey)))?;
+        let encoded;
+        let data = match self.compression {
+            Some(codec) => {
+                encoded = daos_compress::encode(codec, data);
+                encoded.as_slice()
+            }
+            None => data,
+        };
+        let flags = self.default_flags.apply_update(flags);
+        check_update_flags(flags)?;
+
+        let obj_hdl = self.get_handle();
+        if obj_hdl.is_none() {
+            return Err(DaosError::no_handle("update uninitialized object")
+                .with_context(self.op_context("update", dkey, akey)));
+        }
+
+        let txn_hdl = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+        let mut dkey_wrapper = daos_key_t {
+            iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+            iov_buf_len: dkey.len(),
+            iov_len: dkey.len(),
+        };
+
+        let mut iod = daos_iod_t {
+            iod_name: daos_key_t {
+                iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: akey.len(),
+                iov_len: akey.len(),
+            },
+            iod_type: daos_iod_type_t_DAOS_IOD_SINGLE,
+            iod_size: data.len() as u64,
+            iod_flags: 0,
+            iod_nr: 1,
+            iod_recxs: std::ptr::null_mut(),
+        };
+
+        let mut sg_iov = d_iov_t {
+            iov_buf: data.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+            iov_buf_len: data.len(),
+            iov_len: data.len(),
+        };
+
+        let mut sgl = d_sg_list_t {
+            sg_nr: 1,
+            sg_nr_out: 0,
+            sg_iovs: &mut sg_iov,
+        };
+
+        let ret = unsafe {
+            daos_obj_update(
+                obj_hdl.unwrap(),
+                txn_hdl,
+                flags,
+                &mut dkey_wrapper,
+                1,
+                &mut iod,
+                &mut sgl,
+                std::ptr::null_mut(),
+            )
+        };
+
+        if ret != 0 {
+            self.invalidate_key(dkey, akey);
+            return Err(
+                DaosError::from_call("daos_obj_update", ret, "Failed to update object")
+                    .with_context(self.op_context("update", dkey, akey)),
+            );
+        }
+
+        self.invalidate_key(dkey, akey);
+        Ok(())
+    }
+
+    fn punch_dkeys(&self, txn: &DaosTxn, dkeys: &[Vec<u8>]) -> Result<()> {
+        let res = self.check_writable("punch_dkeys").and_then(|_| {
+            dkeys.iter().try_for_each(|dkey| check_user_dkey(dkey))?;
+            let mut iovs = key_iovs(dkeys);
+            let res = self.punch_keys_raw(txn, 0, None, &mut iovs, ptr::null_mut());
+            self.invalidate_punched(None, dkeys);
+            res.map(|_| ())
+        });
+        res.map_err(|e| e.with_context(DaosErrorContext::new("punch_dkeys", self.oid)))
+    }
+
+    fn punch_akeys(&self, txn: &DaosTxn, dkey: Vec<u8>, akeys: &[Vec<u8>]) -> Result<()> {
+        self.punch_akeys_with_flags(txn, CondFlags::empty(), dkey, akeys)
+            .map(|_| ())
+    }
+
+    fn fetch_recx(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        data: &mut [u8],
+    ) -> Result<usize> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let res: Result<usize> = (|| {
+            let flags = self.default_flags.apply_fetch(flags);
+            check_fetch_flags(flags)?;
+
+            let obj_hdl = self
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("fetch uninitialized object"))?;
+            let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut recx = daos_recx_t {
+                rx_idx: offset,
+                rx_nr: data.len() as u64,
+            };
+            let mut iod = daos_iod_t {
+                iod_name: daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                },
+                iod_type: daos_iod_type_t_DAOS_IOD_ARRAY,
+                iod_size: DAOS_REC_ANY as u64,
+                iod_flags: 0,
+                iod_nr: 1,
+                iod_recxs: &mut recx,
+            };
+            let mut sg_iov = d_iov_t {
+                iov_buf: data.as_mut_ptr() as *mut std::os::raw::c_void,
+                iov_buf_len: data.len(),
+                iov_len: data.len(),
+            };
+            let mut sgl = d_sg_list_t {
+                sg_nr: 1,
+                sg_nr_out: 0,
+                sg_iovs: &mut sg_iov,
+            };
+            let mut map_recxs = vec![
+                daos_recx_t {
+                    rx_idx: 0,
+                    rx_nr: 0
+                };
+                IOM_RECXS
+            ];
+            loop {
+                let mut iom = daos_iom_t {
+                    iom_type: daos_iod_type_t_DAOS_IOD_ARRAY,
+                    iom_nr: map_recxs.len() as u32,
+                    iom_nr_out: 0,
+                    iom_flags: IOMF_DETAIL,
+                    iom_size: 0,
+                    iom_recx_lo: daos_recx_t {
+                        rx_idx: 0,
+                        rx_nr: 0,
+                    },
+                    iom_recx_hi: daos_recx_t {
+                        rx_idx: 0,
+                        rx_nr: 0,
+                    },
+                    iom_recxs: map_recxs.as_mut_ptr(),
+                };
+                let ret = unsafe {
+                    daos_obj_fetch(
+                        obj_hdl,
+                        txn,
+                        flags,
+                        &mut dkey_wrapper,
+                        1,
+                        &mut iod,
+                        &mut sgl,
+                        &mut iom,
+                        ptr::null_mut(),
+                    )
+                };
+                if ret != 0 {
+                    return Err(DaosError::from_call(
+                        "daos_obj_fetch",
+                        ret,
+                        "can't fetch recx",
+                    ));
+                }
+                let nr_out = iom.iom_nr_out as usize;
+                if nr_out > map_recxs.len() {
+                    map_recxs.resize(
+                        nr_out,
+                        daos_recx_t {
+                            rx_idx: 0,
+                            rx_nr: 0,
+                        },
+                    );
+                    continue;
+                }
+                return Ok(RecxFetch::new(offset, data.len() as u64, &map_recxs[..nr_out]).len);
+            }
+        })();
+        res.map_err(|e| e.with_context(self.op_context("fetch_recx", dkey, akey)))
+    }
+
+    fn update_recx(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let res: Result<()> = (|| {
+            self.check_writable("update")?;
+            check_user_akey(akey)?;
+            let flags = self.default_flags.apply_update(flags);
+            check_update_flags(flags)?;
+
+            let obj_hdl = self
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("update uninitialized object"))?;
+            let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut recx = daos_recx_t {
+                rx_idx: offset,
+                rx_nr: data.len() as u64,
+            };
+            let mut iod = daos_iod_t {
+                iod_name: daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                },
+                iod_type: daos_iod_type_t_DAOS_IOD_ARRAY,
+                iod_size: 1u64,
+                iod_flags: 0,
+                iod_nr: 1,
+                iod_recxs: &mut recx,
+            };
+            let mut sg_iov = d_iov_t {
+                iov_buf: data.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: data.len(),
+                iov_len: data.len(),
+            };
+            let mut sgl = d_sg_list_t {
+                sg_nr: 1,
+                sg_nr_out: 0,
+                sg_iovs: &mut sg_iov,
+            };
+            let ret = unsafe {
+                daos_obj_update(
+                    obj_hdl,
+                    txn,
+                    flags,
+                    &mut dkey_wrapper,
+                    1,
+                    &mut iod,
+                    &mut sgl,
+                    ptr::null_mut(),
+                )
+            };
+            self.invalidate_key(dkey, akey);
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_obj_update",
+                    ret,
+                    "can't update recx",
+                ));
+            }
+            Ok(())
+        })();
+        res.map_err(|e| e.with_context(self.op_context("update_recx", dkey, akey)))
+    }
+
+    fn list_dkey(&self, txn: &DaosTxn, key_lst: Box<DaosKeyList>) -> Result<Box<DaosKeyList>> {
+        let res: Result<Box<DaosKeyList>> = (|| {
+            let obj_hdl = self
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("list uninitialized object"))?;
+            let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+            let mut key_lst = key_lst;
+            if key_lst.reach_end() {
+                *key_lst.ndesc = 0;
+                return Ok(key_lst);
+            }
+
+            loop {
+                key_lst.prepare_next_query();
+
+                let mut sg_iov = d_iov_t {
+                    iov_buf: key_lst.out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
+                    iov_buf_len: key_lst.out_buf.len(),
+                    iov_len: key_lst.out_buf.len(),
+                };
+                let mut sgl = d_sg_list_t {
+                    sg_nr: 1,
+                    sg_nr_out: 0,
+                    sg_iovs: &mut sg_iov,
+                };
+
+                let ret = unsafe {
+                    daos_obj_list_dkey(
+                        obj_hdl,
+                        txn,
+                        key_lst.ndesc.as_mut(),
+                        key_lst.key_descs.as_mut_ptr(),
+                        &mut sgl,
+                        key_lst.anchor.as_mut(),
+                        ptr::null_mut(),
+                    )
+                };
+                if ret != 0 {
+                    let required = key_lst.key_descs[0].kd_key_len as usize;
+                    let err = DaosError::from_call("daos_obj_list_dkey", ret, "list dkey fail")
+                        .with_required_size(required);
+                    if key_lst.grow_to_fit(&err) {
+                        continue;
+                    }
+                    return Err(err);
+                }
+                key_lst.adapt_to_last_query();
+                return Ok(key_lst);
+            }
+        })();
+        res.map_err(|e| e.with_context(DaosErrorContext::new("list_dkey", self.oid)))
+    }
+
+    fn list_akey(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        key_lst: Box<DaosKeyList>,
+    ) -> Result<Box<DaosKeyList>> {
+        let res: Result<Box<DaosKeyList>> = (|| {
+            let obj_hdl = self
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("list uninitialized object"))?;
+            let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+            let mut key_lst = key_lst;
+            if key_lst.reach_end() {
+                *key_lst.ndesc = 0;
+                return Ok(key_lst);
+            }
+
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            loop {
+                key_lst.prepare_next_query();
+
+                let mut sg_iov = d_iov_t {
+                    iov_buf: key_lst.out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
+                    iov_buf_len: key_lst.out_buf.len(),
+                    iov_len: key_lst.out_buf.len(),
+                };
+                let mut sgl = d_sg_list_t {
+                    sg_nr: 1,
+                    sg_nr_out: 0,
+                    sg_iovs: &mut sg_iov,
+                };
+
+                let ret = unsafe {
+                    daos_obj_list_akey(
+                        obj_hdl,
+                        txn,
+                        &mut dkey_wrapper,
+                        key_lst.ndesc.as_mut(),
+                        key_lst.key_descs.as_mut_ptr(),
+                        &mut sgl,
+                        key_lst.anchor.as_mut(),
+                        ptr::null_mut(),
+                    )
+                };
+                if ret != 0 {
+                    let required = key_lst.key_descs[0].kd_key_len as usize;
+                    let err = DaosError::from_call("daos_obj_list_akey", ret, "list akey fail")
+                        .with_required_size(required);
+                    if key_lst.grow_to_fit(&err) {
+                        continue;
+                    }
+                    return Err(err);
+                }
+                key_lst.adapt_to_last_query();
+                return Ok(key_lst);
+            }
+        })();
+        res.map_err(|e| e.with_context(self.op_context("list_akey", &dkey, &[])))
+    }
+
+    fn list_recx(&self, txn: &DaosTxn, dkey: Vec<u8>, akey: Vec<u8>) -> Result<RecxList> {
+        let res: Result<RecxList> = (|| {
+            let obj_hdl = self
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("list uninitialized object"))?;
+            let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut akey_wrapper = daos_key_t {
+                iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: akey.len(),
+                iov_len: akey.len(),
+            };
+            let mut anchor = empty_anchor();
+            let mut recxs = vec![
+                daos_recx_t {
+                    rx_idx: 0,
+                    rx_nr: 0
+                };
+                RECX_BATCH
+            ];
+            let mut eprs = vec![
+                daos_epoch_range_t {
+                    epr_lo: 0,
+                    epr_hi: 0,
+                };
+                RECX_BATCH
+            ];
+            let mut rec_size = 0u64;
+
+            let mut list = RecxList::default();
+            while !daos_anchor_is_eof(anchor.as_ref()) {
+                let mut nr = RECX_BATCH as u32;
+                let ret = unsafe {
+                    daos_obj_list_recx(
+                        obj_hdl,
+                        txn,
+                        &mut dkey_wrapper,
+                        &mut akey_wrapper,
+                        &mut rec_size,
+                        &mut nr,
+                        recxs.as_mut_ptr(),
+                        eprs.as_mut_ptr(),
+                        anchor.as_mut(),
+                        true,
+                        ptr::null_mut(),
+                    )
+                };
+                if ret != 0 {
+                    return Err(DaosError::from_call(
+                        "daos_obj_list_recx",
+                        ret,
+                        "list recx fail",
+                    ));
+                }
+
+                list.extents
+                    .extend(recxs[..nr as usize].iter().map(|recx| DaosExtent {
+                        offset: recx.rx_idx,
+                        len: recx.rx_nr,
+                    }));
+            }
+            list.rec_size = rec_size;
+            Ok(list)
+        })();
+        res.map_err(|e| e.with_context(self.op_context("list_recx", &dkey, &akey)))
+    }
+
+    #[cfg(feature = "list-obj")]
+    fn list_obj(&self, txn: &DaosTxn, obj_lst: Box<DaosObjList>) -> Result<Box<DaosObjList>> {
+        let res: Result<Box<DaosObjList>> = (|| {
+            let obj_hdl = self
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("list uninitialized object"))?;
+            let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+            let mut obj_lst = obj_lst;
+            obj_lst.entries.clear();
+            if obj_lst.reach_end() {
+                *obj_lst.nr = 0;
+                return Ok(obj_lst);
+            }
+
+            loop {
+                *obj_lst.nr = obj_lst.key_descs.len() as u32;
+                let mut sg_iov = d_iov_t {
+                    iov_buf: obj_lst.out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
+                    iov_buf_len: obj_lst.out_buf.len(),
+                    iov_len: 0,
+                };
+                let mut sgl = d_sg_list_t {
+                    sg_nr: 1,
+                    sg_nr_out: 0,
+                    sg_iovs: &mut sg_iov,
+                };
+
+                let ret = unsafe {
+                    daos_obj_list_obj(
+                        obj_hdl,
+                        txn,
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                        obj_lst.nr.as_mut(),
+                        obj_lst.key_descs.as_mut_ptr(),
+                        obj_lst.eprs.as_mut_ptr(),
+                        &mut sgl,
+                        obj_lst.anchor.as_mut(),
+                        obj_lst.dkey_anchor.as_mut(),
+                        obj_lst.akey_anchor.as_mut(),
+                        true,
+                        false,
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                    )
+                };
+                if ret != 0 {
+                    let err = DaosError::from_call("daos_obj_list_obj", ret, "list object fail")
+                        .with_required_size(obj_lst.key_descs[0].kd_key_len as usize);
+                    if obj_lst.grow_to_fit(&err) {
+                        continue;
+                    }
+                    return Err(err);
+                }
+
+                let nr = (*obj_lst.nr as usize).min(obj_lst.key_descs.len());
+                obj_lst.entries = parse_obj_entries(&obj_lst.key_descs[..nr], &obj_lst.out_buf)?;
+                return Ok(obj_lst);
+            }
+        })();
+        res.map_err(|e| e.with_context(DaosErrorContext::new("list_obj", self.oid)))
+    }
+
+    fn punch_akeys_with_flags(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: &[Vec<u8>],
+    ) -> Result<bool> {
+        let flags = flags.bits();
+        let res = (|| {
+            self.check_writable("punch_akeys")?;
+            check_punch_flags(flags)?;
+            akeys.iter().try_for_each(|akey| check_user_akey(akey))?;
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut iovs = key_iovs(akeys);
+            let res = self.punch_keys_raw(
+                txn,
+                flags,
+                Some(&mut dkey_wrapper),
+                &mut iovs,
+                ptr::null_mut(),
+            );
+            self.invalidate_punched(Some(&dkey), akeys);
+            match res {
+                Ok(_) => Ok(true),
+                Err(DaosError::NonExist(_)) if flags & DAOS_COND_PUNCH as u64 != 0 => Ok(false),
+                Err(e) => Err(e),
+            }
+        })();
+        res.map_err(|e| e.with_context(self.dkey_context("punch_akeys", &dkey)))
+    }
+
+    fn remove(&self, txn: &DaosTxn, dkey: Vec<u8>, akey: Option<Vec<u8>>) -> Result<bool> {
+        let res = (|| {
+            self.check_writable("remove")?;
+            match akey.as_ref() {
+                Some(akey) => check_user_akey(akey)?,
+                None => check_user_dkey(&dkey)?,
+            }
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let flags = DAOS_COND_PUNCH as u64;
+            let res = match akey.as_ref() {
+                Some(akey) => {
+                    let mut iovs = key_iovs(std::slice::from_ref(akey));
+                    let res = self.punch_keys_raw(
+                        txn,
+                        flags,
+                        Some(&mut dkey_wrapper),
+                        &mut iovs,
+                        ptr::null_mut(),
+                    );
+                    self.invalidate_key(&dkey, akey);
+                    res
+                }
+                None => {
+                    let res = self.punch_keys_raw(
+                        txn,
+                        flags,
+                        None,
+                        std::slice::from_mut(&mut dkey_wrapper),
+                        ptr::null_mut(),
+                    );
+                    self.invalidate_punched(None, &[]);
+                    res
+                }
+            };
+            match res {
+                Ok(_) => Ok(true),
+                Err(DaosError::NonExist(_)) => Ok(false),
+                Err(e) => Err(e),
+            }
+        })();
+        res.map_err(|e| {
+            let context = DaosErrorContext::new("remove", self.oid);
+            e.with_context(match akey.as_ref() {
+                Some(akey) => context.with_keys(&dkey, akey),
+                None => DaosErrorContext {
+                    dkey: Some(dkey.clone()),
+                    ..context
+                },
+            })
+        })
+    }
+
+    fn update_akeys(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyUpdate>,
+    ) -> Result<()> {
+        let res = (|| {
+            let (flags, iod_flags, values) = self.prepare_update_akeys(flags, &akeys)?;
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let sg_iovs = values.iter().map(|value| buf_iov(value)).collect();
+            let akey_names = akeys.iter().map(|(akey, _, _)| akey.as_slice());
+            let mut io = AkeysIo::new(akey_names, &iod_flags, sg_iovs, None);
+            let res =
+                self.update_akeys_raw(txn, flags, &mut dkey_wrapper, &mut io, ptr::null_mut());
+            for (akey, _, _) in akeys.iter() {
+                self.invalidate_key(&dkey, akey);
+            }
+            res
+        })();
+        res.map_err(|e| e.with_context(self.dkey_context("update_akeys", &dkey)))
+    }
+
+    fn fetch_akeys(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyFetch>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        let res = (|| {
+            let (flags, iod_flags) = self.prepare_fetch_akeys(flags, &akeys)?;
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut bufs = vec![vec![0u8; self.stored_size(max_size)]; akeys.len()];
+            let sg_iovs = bufs.iter_mut().map(|buf| buf_iov(buf)).collect();
+            let akey_names = akeys.iter().map(|(akey, _)| akey.as_slice());
+            let rec_any = Some(DAOS_REC_ANY as u64);
+            let mut io = AkeysIo::new(akey_names, &iod_flags, sg_iovs, rec_any);
+            let ret =
+                match self.fetch_akeys_raw(txn, flags, &mut dkey_wrapper, &mut io, ptr::null_mut())
+                {
+                    Ok(()) => 0,
+                    // the call didn't reach libdaos
+                    Err(e) if e.rc() == 0 => return Err(e),
+                    // iods hold the sizes needed for Rec2Big
+                    Err(e) => e.rc(),
+                };
+            self.finish_fetch_akeys(ret, io, bufs, max_size)
+        })();
+        res.map_err(|e| e.with_context(self.dkey_context("fetch_akeys", &dkey)))
+    }
+}
+
+#[cfg(feature = "libdaos")]
+impl DaosObjAsyncOps for DaosObject {
+    fn punch_async(&self, txn: &DaosTxn) -> impl Future<Output = Result<()>> + Send + 'static {
+        let punch = self.punch_with_flags_async(txn, CondFlags::empty());
+        async move { punch.await.map(|_| ()) }
+    }
+
+    fn punch_with_flags_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+    ) -> impl Future<Output = Result<bool>> + Send + 'static {
+        let flags = flags.bits();
+        let checked = self
+            .check_writable("punch")
+            .and_then(|_| check_punch_flags(flags));
+        let eq = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
+        let obj_hdl = self.get_handle();
+        let tx_hdl = txn.get_handle();
+        let cache = self.cache.clone();
+        let oid = self.oid;
+        async move {
+            let res: Result<bool> = async {
+                checked?;
+                if eq.is_none() {
+                    return Err(DaosError::no_handle("event queue is nil"));
+                }
+                if obj_hdl.is_none() {
+                    return Err(DaosError::no_handle("punch uninitialized object"));
+                }
+
+                let mut event = eq.unwrap().create_event()?;
+                let rx = event.register_callback()?;
+
+                let txn = match tx_hdl {
+                    Some(tx) => tx,
+                    None => DAOS_TXN_NONE,
+                };
+
+                let ret = unsafe { daos_obj_punch(obj_hdl.unwrap(), txn, flags, event.as_mut()) };
+                if ret != 0 {
+                    return Err(DaosError::from_call(
+                        "daos_obj_punch",
+                        ret,
+                        "can't punch object",
+                    ));
+                }
+
+                let cond = flags & DAOS_COND_PUNCH as u64 != 0;
+                let res = match event.wait(rx, deadline).await {
+                    Ok(0) => Ok(true),
+                    Ok(ret) if cond && ret == -(daos_errno_DER_NONEXIST as i32) => Ok(false),
+                    Ok(ret) => Err(DaosError::from_call(
+                        "daos_obj_punch",
+                        ret,
+                        "async punch operation fail",
+                    )),
+                    Err(e) => Err(e),
+                };
+                if let Some(cache) = cache {
+                    cache.clear();
+                }
+                res
+            }
+            .await;
+            res.map_err(|e| e.with_context(DaosErrorContext::new("punch_async", oid)))
+        }
+    }
+
+    async fn fetch_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        out_buf: &mut [u8],
+    ) -> Result<usize> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        if self.compression.is_none() {
+            return self
+                .fetch_stored_async(txn, flags, dkey, akey, as_uninit(out_buf))
+                .await;
+        }
+
+        // stored values are at most HEADER_LEN larger than the original
+        let mut stored = vec![0u8; out_buf.len() + daos_compress::HEADER_LEN];
+        let size = match self
+            .fetch_stored_async(txn, flags, dkey, akey, as_uninit(&mut stored))
+            .await
+        {
+            Ok(size) => size,
+            Err(DaosError::Truncated { actual_size, .. }) => {
+                // fetch again to learn the original size from the header
+                stored.resize(actual_size, 0);
+                self.fetch_stored_async(txn, flags, dkey, akey, as_uninit(&mut stored))
+                    .await?
+            }
+            Err(e) => return Err(e),
+        };
+        // decode writes nothing when out_buf is too small
+        daos_compress::decode(&stored[..size], out_buf).map_err(|e| {
+            e.into_truncated(0)
+                .with_context(self.op_context("fetch_async", dkey, akey))
+        })
+    }
+
+    async fn fetch_uninit_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        out_buf: &mut [MaybeUninit<u8>],
+    ) -> Result<usize> {
+        if self.compression.is_none() {
+            return self
+                .fetch_stored_async(txn, flags, dkey, akey, out_buf)
+                .await;
+        }
+        // decoding writes through a plain slice
+        self.fetch_async(txn, flags, dkey, akey, zero_init(out_buf))
+            .await
+    }
+
+    #[cfg(feature = "bytes")]
+    async fn fetch_into_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        buf: &mut BytesMut,
+        max_size: u32,
+    ) -> Result<usize> {
+        let max_size = max_size as usize;
+        buf.reserve(max_size);
+        let spare = &mut buf.spare_capacity_mut()[..max_size];
+        let size = self
+            .fetch_uninit_async(txn, flags, dkey, akey, spare)
+            .await?;
+        // the fetch wrote the first size bytes of the spare capacity
+        unsafe { buf.set_len(buf.len() + size) };
+        Ok(size)
+    }
+
+    async fn update_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        data: &[u8],
+    ) -> Result<()> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        check_user_akey(akey)
+            .map_err(|e| e.with_context(self.op_context("update_async", dkey, akey)))?;
+        self.update_single("update_async", txn, flags, dkey, akey, data)
+            .await
+    }
+
+    async fn update_vectored_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        bufs: &[IoSlice<'_>],
+    ) -> Result<()> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        // compression needs the value in one piece
+        if self.compression.is_some() {
+            let data: Vec<u8> = bufs.iter().flat_map(|buf| buf.iter().copied()).collect();
+            return self.update_async(txn, flags, dkey, akey, &data).await;
+        }
+
+        let res: Result<()> = async {
+            self.check_writable("update")?;
+            check_user_akey(akey)?;
+            let flags = self.default_flags.apply_update(flags);
+            check_update_flags(flags)?;
+
+            let eq = self
+                .event_que
+                .as_ref()
+                .ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+            let obj_hdl = self
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("update uninitialized object"))?;
+            let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut dkey_wrapper = Box::new(daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            });
+            let mut sg_iovs: Vec<d_iov_t> = bufs
+                .iter()
+                .map(|buf| d_iov_t {
+                    iov_buf: buf.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: buf.len(),
+                    iov_len: buf.len(),
+                })
+                .collect();
+            let mut iod = Box::new(daos_iod_t {
+                iod_name: daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                },
+                iod_type: daos_iod_type_t_DAOS_IOD_SINGLE,
+                iod_size: bufs.iter().map(|buf| buf.len() as u64).sum(),
+                iod_flags: 0,
+                iod_nr: 1,
+                iod_recxs: std::ptr::null_mut(),
+            });
+            let mut sgl = Box::new(d_sg_list_t {
+                sg_nr: sg_iovs.len() as u32,
+                sg_nr_out: 0,
+                sg_iovs: sg_iovs.as_mut_ptr(),
+            });
+            let ret = unsafe {
+                daos_obj_update(
+                    obj_hdl,
+                    txn,
+                    flags,
+                    dkey_wrapper.as_mut(),
+                    1,
+                    iod.as_mut(),
+                    sgl.as_mut(),
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_obj_update",
+                    ret,
+                    "can't update object",
+                ));
+            }
+
+            let res = match event.wait(rx, self.deadlines.bulk).await {
+                Ok(0) => Ok(()),
+                Ok(ret) => Err(DaosError::from_call(
+                    "daos_obj_update",
+                    ret,
+                    "async update operation fail",
+                )),
+                Err(e) => Err(e),
+            };
+            self.invalidate_key(dkey, akey);
+            res
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.op_context("update_vectored_async", dkey, akey)))
+    }
+
+    async fn fetch_vectored_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Result<usize> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        // decoding needs the value in one piece
+        if self.compression.is_some() {
+            let mut data = vec![0u8; bufs.iter().map(|buf| buf.len()).sum()];
+            let size = self.fetch_async(txn, flags, dkey, akey, &mut data).await?;
+            let mut rest = &data[..size];
+            for buf in bufs.iter_mut() {
+                let len = buf.len().min(rest.len());
+                buf[..len].copy_from_slice(&rest[..len]);
+                rest = &rest[len..];
+            }
+            return Ok(size);
+        }
+
+        // the value isn't contiguous in bufs so it bypasses the cache
+        let res: Result<usize> = async {
+            let flags = self.default_flags.apply_fetch(flags);
+            check_fetch_flags(flags)?;
+
+            let eq = self
+                .event_que
+                .as_ref()
+                .ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+            let obj_hdl = self
+                .get_handle()
+                .ok_or_else(|| DaosError::no_handle("fetch uninitialized object"))?;
+            let txn = txn.get_handle().unwrap_or(DAOS_TXN_NONE);
+
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut dkey_wrapper = Box::new(daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            });
+            let mut iod = Box::new(daos_iod_t {
+                iod_name: daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                },
+                iod_type: daos_iod_type_t_DAOS_IOD_SINGLE,
+                iod_size: DAOS_REC_ANY as u64,
+                iod_flags: 0,
+                iod_nr: 1,
+                iod_recxs: std::ptr::null_mut(),
+            });
+            let mut sg_iovs: Vec<d_iov_t> = bufs
+                .iter_mut()
+                .map(|buf| d_iov_t {
+                    iov_buf: buf.as_mut_ptr() as *mut std::os::raw::c_void,
+                    iov_buf_len: buf.len(),
+                    iov_len: buf.len(),
+                })
+                .collect();
+            let mut sgl = Box::new(d_sg_list_t {
+                sg_nr: sg_iovs.len() as u32,
+                sg_nr_out: 0,
+                sg_iovs: sg_iovs.as_mut_ptr(),
+            });
+            let ret = unsafe {
+                daos_obj_fetch(
+                    obj_hdl,
+                    txn,
+                    flags,
+                    dkey_wrapper.as_mut(),
+                    1,
+                    iod.as_mut(),
+                    sgl.as_mut(),
+                    ptr::null_mut(),
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_obj_fetch",
+                    ret,
+                    "can't fetch object",
+                ));
+            }
+
+            match event.wait(rx, self.deadlines.bulk).await {
+                Ok(0) => Ok(iod.iod_size as usize),
+                Ok(ret) => {
+                    Err(
+                        DaosError::from_call("daos_obj_fetch", ret, "async fetch operation fail")
+                            .with_required_size(iod.iod_size as usize)
+                            .into_truncated(0),
+                    )
+                }
+                Err(e) => Err(e),
+            }
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.op_context("fetch_vectored_async", dkey, akey)))
+    }
+
+    async fn update_akeys_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyUpdate>,
+    ) -> Result<()> {
+        let res: Result<()> = async {
+            let (flags, iod_flags, values) = self.prepare_update_akeys(flags, &akeys)?;
+            let eq = self
+                .event_que
+                .as_ref()
+                .ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let sg_iovs = values.iter().map(|value| buf_iov(value)).collect();
+            let akey_names = akeys.iter().map(|(akey, _, _)| akey.as_slice());
+            let mut io = AkeysIo::new(akey_names, &iod_flags, sg_iovs, None);
+            self.update_akeys_raw(txn, flags, &mut dkey_wrapper, &mut io, event.as_mut())?;
+
+            let res = match event.wait(rx, self.deadlines.bulk).await {
+                Ok(0) => Ok(()),
+                Ok(ret) => Err(DaosError::from_call(
+                    "daos_obj_update",
+                    ret,
+                    "async update operation fail",
+                )),
+                Err(e) => Err(e),
+            };
+            for (akey, _, _) in akeys.iter() {
+                self.invalidate_key(&dkey, akey);
+            }
+            res
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.dkey_context("update_akeys_async", &dkey)))
+    }
+
+    async fn fetch_akeys_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: Vec<DaosAkeyFetch>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        // the values are fetched together so this bypasses the cache
+        let res: Result<Vec<Option<Vec<u8>>>> = async {
+            let (flags, iod_flags) = self.prepare_fetch_akeys(flags, &akeys)?;
+            let eq = self
+                .event_que
+                .as_ref()
+                .ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut bufs = vec![vec![0u8; self.stored_size(max_size)]; akeys.len()];
+            let sg_iovs = bufs.iter_mut().map(|buf| buf_iov(buf)).collect();
+            let akey_names = akeys.iter().map(|(akey, _)| akey.as_slice());
+            let rec_any = Some(DAOS_REC_ANY as u64);
+            let mut io = AkeysIo::new(akey_names, &iod_flags, sg_iovs, rec_any);
+            self.fetch_akeys_raw(txn, flags, &mut dkey_wrapper, &mut io, event.as_mut())?;
+
+            let ret = event.wait(rx, self.deadlines.bulk).await?;
+            self.finish_fetch_akeys(ret, io, bufs, max_size)
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.dkey_context("fetch_akeys_async", &dkey)))
+    }
+
+    async fn fetch_recx_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        data: &mut [u8],
+    ) -> Result<usize> {
+        self.fetch_recx_mapped("fetch_recx_async", txn, flags, dkey, akey, 1, offset, data)
+            .await
+            .map(|fetched| fetched.len)
+    }
+
+    async fn fetch_recx_map_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        data: &mut [u8],
+    ) -> Result<RecxFetch> {
+        self.fetch_recx_mapped(
+            "fetch_recx_map_async",
+            txn,
+            flags,
+            dkey,
+            akey,
+            1,
+            offset,
+            data,
+        )
+        .await
+    }
+
+    async fn update_recx_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        self.update_recx_sized("update_recx_async", txn, flags, dkey, akey, 1, offset, data)
+            .await
+    }
+
+    async fn fetch_recs_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        rec_size: u64,
+        index: u64,
+        out_buf: &mut [u8],
+    ) -> Result<usize> {
+        self.fetch_recx_mapped(
+            "fetch_recs_async",
+            txn,
+            flags,
+            dkey,
+            akey,
+            rec_size,
+            index,
+            out_buf,
+        )
+        .await
+        .map(|fetched| fetched.len)
+    }
+
+    async fn update_recs_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        rec_size: u64,
+        index: u64,
+        data: &[u8],
+    ) -> Result<()> {
+        self.update_recx_sized(
+            "update_recs_async",
+            txn,
+            flags,
+            dkey,
+            akey,
+            rec_size,
+            index,
+            data,
+        )
+        .await
+    }
+
+    async fn punch_recx_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        offset: u64,
+        len: u64,
+    ) -> Result<()> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let res: Result<()> = async {
+            self.check_writable("punch")?;
+            if len == 0 {
+                return Ok(());
+            }
+
+            let eq = self.event_que.clone();
+            let deadline = self.deadlines.metadata;
+            let obj_hdl = self.get_handle();
+            let tx_hdl = txn.get_handle();
+
+            if eq.is_none() {
+                return Err(DaosError::no_handle("event queue is nil"));
+            }
+            if obj_hdl.is_none() {
+                return Err(DaosError::no_handle("punch uninitialized object"));
+            }
+
+            let mut event = eq.unwrap().create_event()?;
+            let rx = event.register_callback()?;
+
+            let txn = match tx_hdl {
+                Some(tx) => tx,
+                None => DAOS_TXN_NONE,
+            };
+
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut recx = daos_recx_t {
+                rx_idx: offset,
+                rx_nr: len,
+            };
+            // a zero iod_size punches the extent, no data goes with it
+            let mut iod = daos_iod_t {
+                iod_name: daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                },
+                iod_type: daos_iod_type_t_DAOS_IOD_ARRAY,
+                iod_size: 0,
+                iod_flags: 0,
+                iod_nr: 1,
+                iod_recxs: &mut recx,
+            };
+            let ret = unsafe {
+                daos_obj_update(
+                    obj_hdl.unwrap(),
+                    txn,
+                    0,
+                    &mut dkey_wrapper,
+                    1,
+                    &mut iod,
+                    ptr::null_mut(),
+                    event.as_mut(),
+                )
+            };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_obj_update",
+                    ret,
+                    "can't punch recx",
+                ));
+            }
+
+            let res = match event.wait(rx, deadline).await {
+                Ok(0) => Ok(()),
+                Ok(ret) => Err(DaosError::from_call(
+                    "daos_obj_update",
+                    ret,
+                    "async punch recx operation fail",
+                )),
+                Err(e) => Err(e),
+            };
+            self.invalidate_key(dkey, akey);
+            res
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.op_context("punch_recx_async", dkey, akey)))
+    }
+
+    fn list_dkey_async(
+        &self,
+        txn: &DaosTxn,
+        key_lst: Box<DaosKeyList>,
+    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static {
+        let eq = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
+        let obj_hdl = self.get_handle();
+        let tx_hdl = txn.get_handle();
+        let oid = self.oid;
+        async move {
+            let res: Result<Box<DaosKeyList>> = async {
+                if eq.is_none() {
+                    return Err(DaosError::no_handle("event queue is nil"));
+                }
+                if obj_hdl.is_none() {
+                    return Err(DaosError::no_handle("list uninitialized object"));
+                }
+
+                let mut key_lst: Box<DaosKeyList> = key_lst;
+                if key_lst.reach_end() {
+                    *key_lst.ndesc = 0;
+                    return Ok(key_lst);
+                }
+
+                let eq = eq.unwrap();
+                let txn = match tx_hdl {
+                    Some(tx) => tx,
+                    None => DAOS_TXN_NONE,
+                };
+
+                loop {
+                    let mut event = eq.create_event()?;
+                    let rx = event.register_callback()?;
+
+                    key_lst.prepare_next_query();
+
+                    let mut sg_iov = Box::new(d_iov_t {
+                        iov_buf: key_lst.out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
+                        iov_buf_len: key_lst.out_buf.len(),
+                        iov_len: key_lst.out_buf.len(),
+                    });
+                    let mut sgl = Box::new(d_sg_list_t {
+                        sg_nr: 1,
+                        sg_nr_out: 0,
+                        sg_iovs: sg_iov.as_mut(),
+                    });
+
+                    let res = unsafe {
+                        daos_obj_list_dkey(
+                            obj_hdl.unwrap(),
+                            txn,
+                            key_lst.ndesc.as_mut(),
+                            key_lst.key_descs.as_mut_ptr(),
+                            sgl.as_mut(),
+                            key_lst.anchor.as_mut(),
+                            event.as_mut(),
+                        )
+                    };
+                    if res != 0 {
+                        return Err(DaosError::from_call(
+                            "daos_obj_list_dkey",
+                            res,
+                            "list dkey fail",
+                        ));
+                    }
+
+                    let ret = event.wait(rx, deadline).await?;
+                    if ret != 0 {
+                        // on KEY2BIG the first descriptor holds the size of
+                        // the key that didn't fit
+                        let required = key_lst.key_descs[0].kd_key_len as usize;
+                        let err =
+                            DaosError::from_call("daos_obj_list_dkey", ret, "async list dkey fail")
+                                .with_required_size(required);
+                        if key_lst.grow_to_fit(&err) {
+                            continue;
+                        }
+                        return Err(err);
+                    }
+                    key_lst.adapt_to_last_query();
+                    return Ok(key_lst);
+                }
+            }
+            .await;
+            res.map_err(|e| e.with_context(DaosErrorContext::new("list_dkey_async", oid)))
+        }
+    }
+
+    fn list_akey_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        key_lst: Box<DaosKeyList>,
+    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static {
+        let eq = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
+        let obj_hdl = self.get_handle();
+        let tx_hdl = txn.get_handle();
+        let context = self.op_context("list_akey_async", &dkey, &[]);
+        async move {
+            let res: Result<Box<DaosKeyList>> = async {
+                if eq.is_none() {
+                    return Err(DaosError::no_handle("event queue is nil"));
+                }
+                if obj_hdl.is_none() {
+                    return Err(DaosError::no_handle("list uninitialized object"));
+                }
+
+                let mut key_lst: Box<DaosKeyList> = key_lst;
+                if key_lst.reach_end() {
+                    *key_lst.ndesc = 0;
+                    return Ok(key_lst);
+                }
+
+                let eq = eq.unwrap();
+                let txn = match tx_hdl {
+                    Some(tx) => tx,
+                    None => DAOS_TXN_NONE,
+                };
+
+                let mut dkey_wrapper = Box::new(daos_key_t {
+                    iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: dkey.len(),
+                    iov_len: dkey.len(),
+                });
+                loop {
+                    let mut event = eq.create_event()?;
+                    let rx = event.register_callback()?;
+
+                    key_lst.prepare_next_query();
+
+                    let mut sg_iov = Box::new(d_iov_t {
+                        iov_buf: key_lst.out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
+                        iov_buf_len: key_lst.out_buf.len(),
+                        iov_len: key_lst.out_buf.len(),
+                    });
+                    let mut sgl = Box::new(d_sg_list_t {
+                        sg_nr: 1,
+                        sg_nr_out: 0,
+                        sg_iovs: sg_iov.as_mut(),
+                    });
+
+                    let res = unsafe {
+                        daos_obj_list_akey(
+                            obj_hdl.unwrap(),
+                            txn,
+                            dkey_wrapper.as_mut(),
+                            key_lst.ndesc.as_mut(),
+                            key_lst.key_descs.as_mut_ptr(),
+                            sgl.as_mut(),
+                            key_lst.anchor.as_mut(),
+                            event.as_mut(),
+                        )
+                    };
+                    if res != 0 {
+                        return Err(DaosError::from_call(
+                            "daos_obj_list_akey",
+                            res,
+                            "list akey fail",
+                        ));
+                    }
+
+                    let ret = event.wait(rx, deadline).await?;
+                    if ret != 0 {
+                        let required = key_lst.key_descs[0].kd_key_len as usize;
+                        let err =
+                            DaosError::from_call("daos_obj_list_akey", ret, "async list akey fail")
+                                .with_required_size(required);
+                        if key_lst.grow_to_fit(&err) {
+                            continue;
+                        }
+                        return Err(err);
+                    }
+                    key_lst.adapt_to_last_query();
+                    return Ok(key_lst);
+                }
+            }
+            .await;
+            res.map_err(|e| e.with_context(context))
+        }
+    }
+
+    fn key2anchor_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akey: Option<Vec<u8>>,
+        key_lst: Box<DaosKeyList>,
+    ) -> impl Future<Output = Result<Box<DaosKeyList>>> + Send + 'static {
+        let eq = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
+        let obj_hdl = self.get_handle();
+        let tx_hdl = txn.get_handle();
+        let context = self.op_context("key2anchor_async", &dkey, akey.as_deref().unwrap_or(&[]));
+        async move {
+            let res: Result<Box<DaosKeyList>> = async {
+                let eq = eq.ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+                let obj_hdl =
+                    obj_hdl.ok_or_else(|| DaosError::no_handle("list uninitialized object"))?;
+                let txn = tx_hdl.unwrap_or(DAOS_TXN_NONE);
+
+                let mut key_lst = key_lst;
+                key_lst.reset();
+
+                let mut dkey_wrapper = Box::new(daos_key_t {
+                    iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: dkey.len(),
+                    iov_len: dkey.len(),
+                });
+                let mut akey_wrapper = akey.as_ref().map(|akey| {
+                    Box::new(daos_key_t {
+                        iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                        iov_buf_len: akey.len(),
+                        iov_len: akey.len(),
+                    })
+                });
+
+                let mut event = eq.create_event()?;
+                let rx = event.register_callback()?;
+                let res = unsafe {
+                    daos_obj_key2anchor(
+                        obj_hdl,
+                        txn,
+                        dkey_wrapper.as_mut(),
+                        akey_wrapper
+                            .as_mut()
+                            .map_or(ptr::null_mut(), |akey| akey.as_mut() as *mut daos_key_t),
+                        key_lst.anchor.as_mut(),
+                        event.as_mut(),
+                    )
+                };
+                if res != 0 {
+                    return Err(DaosError::from_call(
+                        "daos_obj_key2anchor",
+                        res,
+                        "key to anchor fail",
+                    ));
+                }
+                let ret = event.wait(rx, deadline).await?;
+                if ret != 0 {
+                    return Err(DaosError::from_call(
+                        "daos_obj_key2anchor",
+                        ret,
+                        "async key to anchor fail",
+                    ));
+                }
+                Ok(key_lst)
+            }
+            .await;
+            res.map_err(|e| e.with_context(context))
+        }
+    }
+
+    fn list_recx_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akey: Vec<u8>,
+    ) -> impl Future<Output = Result<RecxList>> + Send + 'static {
+        let eq = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
+        let obj_hdl = self.get_handle();
+        let tx_hdl = txn.get_handle();
+        let context = self.op_context("list_recx_async", &dkey, &akey);
+        async move {
+            let res: Result<RecxList> = async {
+                let eq = eq.ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+                let obj_hdl =
+                    obj_hdl.ok_or_else(|| DaosError::no_handle("list uninitialized object"))?;
+                let txn = tx_hdl.unwrap_or(DAOS_TXN_NONE);
+
+                let mut dkey_wrapper = Box::new(daos_key_t {
+                    iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: dkey.len(),
+                    iov_len: dkey.len(),
+                });
+                let mut akey_wrapper = Box::new(daos_key_t {
+                    iov_buf: akey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                    iov_buf_len: akey.len(),
+                    iov_len: akey.len(),
+                });
+                let mut anchor = Box::new(daos_anchor_t {
+                    da_type: 0,
+                    da_shard: 0,
+                    da_flags: 0,
+                    da_sub_anchors: 0,
+                    da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
+                });
+                let mut recxs = vec![
+                    daos_recx_t {
+                        rx_idx: 0,
+                        rx_nr: 0
+                    };
+                    RECX_BATCH
+                ];
+                let mut eprs = vec![
+                    daos_epoch_range_t {
+                        epr_lo: 0,
+                        epr_hi: 0,
+                    };
+                    RECX_BATCH
+                ];
+                let mut rec_size = Box::new(0u64);
+                let mut nr = Box::new(0u32);
+
+                let mut list = RecxList::default();
+                while !daos_anchor_is_eof(anchor.as_ref()) {
+                    let mut event = eq.create_event()?;
+                    let rx = event.register_callback()?;
+
+                    *nr = RECX_BATCH as u32;
+                    let res = unsafe {
+                        daos_obj_list_recx(
+                            obj_hdl,
+                            txn,
+                            dkey_wrapper.as_mut(),
+                            akey_wrapper.as_mut(),
+                            rec_size.as_mut(),
+                            nr.as_mut(),
+                            recxs.as_mut_ptr(),
+                            eprs.as_mut_ptr(),
+                            anchor.as_mut(),
+                            true,
+                            event.as_mut(),
+                        )
+                    };
+                    if res != 0 {
+                        return Err(DaosError::from_call(
+                            "daos_obj_list_recx",
+                            res,
+                            "list recx fail",
+                        ));
+                    }
+                    let ret = event.wait(rx, deadline).await?;
+                    if ret != 0 {
+                        return Err(DaosError::from_call(
+                            "daos_obj_list_recx",
+                            ret,
+                            "async list recx fail",
+                        ));
+                    }
+
+                    list.extents
+                        .extend(recxs[..*nr as usize].iter().map(|recx| DaosExtent {
+                            offset: recx.rx_idx,
+                            len: recx.rx_nr,
+                        }));
+                }
+                list.rec_size = *rec_size;
+                Ok(list)
+            }
+            .await;
+            res.map_err(|e| e.with_context(context))
+        }
+    }
+
+    #[cfg(feature = "list-obj")]
+    fn list_obj_async(
+        &self,
+        txn: &DaosTxn,
+        obj_lst: Box<DaosObjList>,
+    ) -> impl Future<Output = Result<Box<DaosObjList>>> + Send + 'static {
+        let eq = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
+        let obj_hdl = self.get_handle();
+        let tx_hdl = txn.get_handle();
+        let oid = self.oid;
+        async move {
+            let res: Result<Box<DaosObjList>> = async {
+                let eq = eq.ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+                let obj_hdl =
+                    obj_hdl.ok_or_else(|| DaosError::no_handle("list uninitialized object"))?;
+                let txn = tx_hdl.unwrap_or(DAOS_TXN_NONE);
+
+                let mut obj_lst = obj_lst;
+                obj_lst.entries.clear();
+                if obj_lst.reach_end() {
+                    *obj_lst.nr = 0;
+                    return Ok(obj_lst);
+                }
+
+                loop {
+                    let mut event = eq.create_event()?;
+                    let rx = event.register_callback()?;
+
+                    *obj_lst.nr = obj_lst.key_descs.len() as u32;
+                    let mut sg_iov = Box::new(d_iov_t {
+                        iov_buf: obj_lst.out_buf.as_mut_ptr() as *mut std::os::raw::c_void,
+                        iov_buf_len: obj_lst.out_buf.len(),
+                        iov_len: 0,
+                    });
+                    let mut sgl = Box::new(d_sg_list_t {
+                        sg_nr: 1,
+                        sg_nr_out: 0,
+                        sg_iovs: sg_iov.as_mut(),
+                    });
+
+                    let res = unsafe {
+                        daos_obj_list_obj(
+                            obj_hdl,
+                            txn,
+                            ptr::null_mut(),
+                            ptr::null_mut(),
+                            ptr::null_mut(),
+                            obj_lst.nr.as_mut(),
+                            obj_lst.key_descs.as_mut_ptr(),
+                            obj_lst.eprs.as_mut_ptr(),
+                            sgl.as_mut(),
+                            obj_lst.anchor.as_mut(),
+                            obj_lst.dkey_anchor.as_mut(),
+                            obj_lst.akey_anchor.as_mut(),
+                            true,
+                            false,
+                            ptr::null_mut(),
+                            event.as_mut(),
+                        )
+                    };
+                    if res != 0 {
+                        return Err(DaosError::from_call(
+                            "daos_obj_list_obj",
+                            res,
+                            "list object fail",
+                        ));
+                    }
+
+                    let ret = event.wait(rx, deadline).await?;
+                    if ret != 0 {
+                        let err = DaosError::from_call(
+                            "daos_obj_list_obj",
+                            ret,
+                            "async list object fail",
+                        )
+                        .with_required_size(obj_lst.key_descs[0].kd_key_len as usize);
+                        if obj_lst.grow_to_fit(&err) {
+                            continue;
+                        }
+                        return Err(err);
+                    }
+
+                    let nr = (*obj_lst.nr as usize).min(obj_lst.key_descs.len());
+                    obj_lst.entries =
+                        parse_obj_entries(&obj_lst.key_descs[..nr], &obj_lst.out_buf)?;
+                    return Ok(obj_lst);
+                }
+            }
+            .await;
+            res.map_err(|e| e.with_context(DaosErrorContext::new("list_obj_async", oid)))
+        }
+    }
+
+    async fn get_multi_async(
+        &self,
+        txn: &DaosTxn,
+        dkeys: &[Vec<u8>],
+        akey: Vec<u8>,
+        max_size: u32,
+    ) -> Result<Vec<Option<Vec<u8>>>> {
+        const OP: &str = "get_multi_async";
+        let flags = self.default_flags.apply_fetch(CondFlags::empty());
+        check_fetch_flags(flags)
+            .map_err(|e| e.with_context(DaosErrorContext::new(OP, self.oid)))?;
+
+        // every dkey is in flight at once, a pending fetch must be waited
+        // for even when an earlier one failed
+        let buf_size = self.stored_size(max_size);
+        let mut pending = Vec::with_capacity(dkeys.len());
+        for dkey in dkeys {
+            match self.submit_fetch(txn, flags, dkey.clone(), akey.clone(), buf_size, None) {
+                Ok(fetch) => pending.push(fetch),
+                Err(e) => {
+                    for fetch in pending {
+                        fetch.cancel().await;
+                    }
+                    return Err(e.with_context(self.op_context(OP, dkey, &akey)));
+                }
+            }
+        }
+        let mut stored = Vec::with_capacity(pending.len());
+        for fetch in pending {
+            stored.push(fetch.wait(self.deadlines.bulk).await);
+        }
+
+        let mut values = Vec::with_capacity(stored.len());
+        for (dkey, res) in dkeys.iter().zip(stored) {
+            let res: Result<Option<Vec<u8>>> = match res {
+                // DAOS keeps no empty single values, so only a compressed
+                // value can be present but empty
+                Ok(buf) if buf.is_empty() => Ok(None),
+                Ok(buf) if self.compression.is_some() => {
+                    let mut value = vec![0u8; max_size as usize];
+                    daos_compress::decode(&buf, &mut value).map(|len| {
+                        value.truncate(len);
+                        Some(value)
+                    })
+                }
+                Ok(buf) => Ok(Some(buf)),
+                Err(e) => Err(e),
+            };
+            values.push(res.map_err(|e| {
+                e.into_truncated(0)
+                    .with_context(self.op_context(OP, dkey, &akey))
+            })?);
+        }
+        Ok(values)
+    }
+
+    async fn update_many_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        updates: Vec<DaosBatchUpdate>,
+        max_in_flight: usize,
+    ) -> Result<Vec<Result<()>>> {
+        const OP: &str = "update_many_async";
+        if max_in_flight == 0 {
+            return Err(DaosError::inval("max_in_flight must not be zero"));
+        }
+
+        let mut results: Vec<Result<()>> = Vec::with_capacity(updates.len());
+        let mut pending = VecDeque::with_capacity(max_in_flight.min(updates.len()));
+        for (idx, (dkey, akey, data)) in updates.into_iter().enumerate() {
+            if pending.len() == max_in_flight {
+                let (idx, update) = pending.pop_front().unwrap();
+                results[idx] = self.finish_batch_update(OP, update).await;
+            }
+            let context = self.op_context(OP, &dkey, &akey);
+            match self.submit_update(txn, flags, dkey, akey, data) {
+                Ok(update) => {
+                    results.push(Ok(()));
+                    pending.push_back((idx, update));
+                }
+                Err(e) => results.push(Err(e.with_context(context))),
+            }
+        }
+        for (idx, update) in pending {
+            results[idx] = self.finish_batch_update(OP, update).await;
+        }
+        Ok(results)
+    }
+
+    async fn get_map_async(
+        &self,
+        txn: &DaosTxn,
+        dkeys: Vec<Vec<u8>>,
+        akey: Vec<u8>,
+        max_size: u32,
+    ) -> Result<HashMap<Vec<u8>, Option<Vec<u8>>>> {
+        let values = self
+            .get_multi_async(txn, dkeys.as_slice(), akey, max_size)
+            .await?;
+        Ok(dkeys.into_iter().zip(values).collect())
+    }
+
+    async fn fetch_with_epoch_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<EpochValue> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let context = self.op_context("fetch_with_epoch_async", dkey, akey);
+        if txn.get_handle().is_none() {
+            return Err(
+                DaosError::inval("reading an epoch needs a transaction").with_context(context)
+            );
+        }
+
+        let mut data = vec![0u8; max_size as usize];
+        let size = self.fetch_async(txn, flags, dkey, akey, &mut data).await?;
+        data.truncate(size);
+        // the epoch is only known once the fetch has it chosen
+        let epoch = txn.epoch().map_err(|e| e.with_context(context))?;
+        Ok(EpochValue { data, epoch })
+    }
+
+    // Both fetches go to a replica of their own with DIOF_TO_SPEC_SHARD.
+    // The fetch that loses is aborted and waited for before returning.
+    async fn fetch_hedged_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+        hedge_delay: Duration,
+    ) -> Result<Vec<u8>> {
+        let (dkey, akey) = (dkey.as_ref(), akey.as_ref());
+        let res: Result<Vec<u8>> = async {
+            let flags = self.default_flags.apply_fetch(flags);
+            check_fetch_flags(flags)?;
+            let replicas = self.hedge_replicas()? as u64;
+            let primary = (NEXT_REPLICA.fetch_add(1, Ordering::Relaxed) % replicas) as u32;
+            let backup = (primary + 1) % replicas as u32;
+
+            // the cache and both fetches hold values as stored
+            let buf_size = self.stored_size(max_size);
+            let cache = self.cache_for(txn, flags);
+            let generation = match cache.map(|cache| cache.get(dkey, akey)) {
+                Some(Ok(value)) if value.len() > buf_size => {
+                    let required = value.len();
+                    return Err(
+                        DaosError::rec2big("record is larger than max_size", required)
+                            .into_truncated(0),
+                    );
+                }
+                Some(Ok(value)) => return self.decode_stored(value, max_size),
+                Some(Err(generation)) => generation,
+                None => 0,
+            };
+
+            let mut first = self.submit_fetch(
+                txn,
+                flags,
+                dkey.to_vec(),
+                akey.to_vec(),
+                buf_size,
+                Some(primary),
+            )?;
+            let res = match tokio::time::timeout(hedge_delay, &mut first.rx).await {
+                Ok(res) => first.finish(res),
+                Err(_) => {
+                    let second = self.submit_fetch(
+                        txn,
+                        flags,
+                        dkey.to_vec(),
+                        akey.to_vec(),
+                        buf_size,
+                        Some(backup),
+                    );
+                    match second {
+                        Ok(mut second) => {
+                            let (first_won, res) = tokio::select! {
+                                res = &mut first.rx => (true, res),
+                                res = &mut second.rx => (false, res),
+                            };
+                            if first_won {
+                                second.cancel().await;
+                                first.finish(res)
+                            } else {
+                                first.cancel().await;
+                                second.finish(res)
+                            }
+                        }
+                        Err(_) => {
+                            let res = (&mut first.rx).await;
+                            first.finish(res)
+                        }
+                    }
+                }
+            };
+
+            if let (Ok(value), Some(cache)) = (&res, cache) {
+                cache.put(generation, dkey.to_vec(), akey.to_vec(), value.clone());
+            }
+            self.decode_stored(res?, max_size)
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.op_context("fetch_hedged_async", dkey, akey)))
+    }
+
+    async fn remove_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akey: Option<Vec<u8>>,
+    ) -> Result<bool> {
+        let check = match akey.as_ref() {
+            Some(akey) => check_user_akey(akey),
+            None => check_user_dkey(&dkey),
+        };
+        if let Err(e) = check {
+            return Err(e.with_context(DaosErrorContext::new("remove_async", self.oid)));
+        }
+        self.remove_key("remove_async", txn, dkey, akey).await
+    }
+
+    async fn punch_dkeys_async(&self, txn: &DaosTxn, dkeys: &[Vec<u8>]) -> Result<()> {
+        let res: Result<()> = async {
+            self.check_writable("punch_dkeys")?;
+            dkeys.iter().try_for_each(|dkey| check_user_dkey(dkey))?;
+            let eq = self
+                .event_que
+                .as_ref()
+                .ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut iovs = key_iovs(dkeys);
+            let func = self.punch_keys_raw(txn, 0, None, &mut iovs, event.as_mut())?;
+            let ret = event.wait(rx, self.deadlines.metadata).await;
+            self.invalidate_punched(None, dkeys);
+            match ret? {
+                0 => Ok(()),
+                ret => Err(DaosError::from_call(func, ret, "async punch keys fail")),
+            }
+        }
+        .await;
+        res.map_err(|e| e.with_context(DaosErrorContext::new("punch_dkeys_async", self.oid)))
+    }
+
+    async fn punch_akeys_async(
+        &self,
+        txn: &DaosTxn,
+        dkey: Vec<u8>,
+        akeys: &[Vec<u8>],
+    ) -> Result<()> {
+        self.punch_akeys_with_flags_async(txn, CondFlags::empty(), dkey, akeys)
+            .await
+            .map(|_| ())
+    }
+
+    async fn punch_akeys_with_flags_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: Vec<u8>,
+        akeys: &[Vec<u8>],
+    ) -> Result<bool> {
+        let flags = flags.bits();
+        let res: Result<bool> = async {
+            self.check_writable("punch_akeys")?;
+            check_punch_flags(flags)?;
+            akeys.iter().try_for_each(|akey| check_user_akey(akey))?;
+            let eq = self
+                .event_que
+                .as_ref()
+                .ok_or_else(|| DaosError::no_handle("event queue is nil"))?;
+            let mut event = eq.create_event()?;
+            let rx = event.register_callback()?;
+
+            let mut dkey_wrapper = daos_key_t {
+                iov_buf: dkey.as_ptr() as *mut u8 as *mut std::os::raw::c_void,
+                iov_buf_len: dkey.len(),
+                iov_len: dkey.len(),
+            };
+            let mut iovs = key_iovs(akeys);
+            let func = self.punch_keys_raw(
+                txn,
+                flags,
+                Some(&mut dkey_wrapper),
+                &mut iovs,
+                event.as_mut(),
+            )?;
+            let ret = event.wait(rx, self.deadlines.metadata).await;
+            self.invalidate_punched(Some(&dkey), akeys);
+            let cond = flags & DAOS_COND_PUNCH as u64 != 0;
+            match ret? {
+                0 => Ok(true),
+                ret if cond && ret == -(daos_errno_DER_NONEXIST as i32) => Ok(false),
+                ret => Err(DaosError::from_call(func, ret, "async punch keys fail")),
+            }
+        }
+        .await;
+        res.map_err(|e| e.with_context(self.dkey_context("punch_akeys_async", &dkey)))
+    }
+
+    #[cfg(feature = "bytes")]
+    async fn update_bytes_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        data: Bytes,
+    ) -> Result<()> {
+        self.update_async(txn, flags, dkey, akey, &data).await
+    }
+
+    #[cfg(feature = "bytes")]
+    async fn fetch_bytes_async(
+        &self,
+        txn: &DaosTxn,
+        flags: CondFlags,
+        dkey: impl AsRef<[u8]>,
+        akey: impl AsRef<[u8]>,
+        max_size: u32,
+    ) -> Result<Bytes> {
+        // the next max_size bytes of the pool's capacity, left uninitialized
+        let mut buf = {
+            let mut pool = self.fetch_buf.lock().unwrap();
+            if pool.capacity() < max_size as usize {
+                pool.reserve((max_size as usize).max(FETCH_BUF_CHUNK));
+            }
+            let rest = pool.split_off(max_size as usize);
+            std::mem::replace(&mut *pool, rest)
+        };
+        self.fetch_into_async(txn, flags, dkey, akey, &mut buf, max_size)
+            .await?;
+        Ok(buf.freeze())
+    }
+}
+
+// Writes one single value per entry of updates, outside of any transaction.
+// All updates are submitted before any of them is waited for, so they run
+// concurrently even when they target different objects. Failures are
+// returned with the index of their entry in updates. With AllSuccess the
+// remaining submissions stop at the first one that fails, but updates
+// already in flight are still waited for.
+#[cfg(feature = "libdaos")]
+pub async fn update_many_objects_async(
+    updates: Vec<DaosFanOutUpdate>,
+    policy: DaosFanOutPolicy,
+) -> Result<Vec<(usize, DaosError)>> {
+    const OP: &str = "update_many_objects_async";
+    let txn = DaosTxn::txn_none();
+
+    let mut failures = Vec::new();
+    let mut pending = Vec::with_capacity(updates.len());
+    for (idx, (obj, dkey, akey, data)) in updates.into_iter().enumerate() {
+        let context = obj.op_context(OP, &dkey, &akey);
+        match obj.submit_update(&txn, CondFlags::empty(), dkey, akey, data) {
+            Ok(update) => pending.push((idx, obj, update)),
+            Err(e) => {
+                failures.push((idx, e.with_context(context)));
+                if policy == DaosFanOutPolicy::AllSuccess {
+                    break;
+                }
+            }
+        }
+    }
+
+    for (idx, obj, update) in pending {
+        let (res, dkey, akey) = update.wait(obj.deadlines.bulk).await;
+        obj.invalidate_key(&dkey, &akey);
+        if let Err(e) = res {
+            failures.push((idx, e.with_context(obj.op_context(OP, &dkey, &akey))));
+        }
+    }
+    failures.sort_by_key(|(idx, _)| *idx);
+
+    match policy {
+        DaosFanOutPolicy::AllSuccess => match failures.into_iter().next() {
+            Some((_, e)) => Err(e),
+            None => Ok(Vec::new()),
+        },
+        DaosFanOutPolicy::BestEffort => Ok(failures),
+    }
+}
+
+// Writes every entry of updates in one transaction of cont, the container
+// all the objects were opened in, so that either all of them are committed
+// or none is. On a conflict such as DER_TX_RESTART the transaction is
+// dropped and the updates are reapplied in a new one, for up to
+// MAX_TXN_ATTEMPTS attempts.
+#[cfg(feature = "libdaos")]
+pub async fn update_objects_atomic_async(
+    cont: &DaosContainer,
+    updates: &[DaosFanOutUpdate],
+) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let txn = DaosTxn::open_async(cont, 0).await?;
+        let res = match apply_updates_in(&txn, updates).await {
+            Ok(()) => txn.commit_async().await,
+            Err(e) => {
+                let _ = txn.abort_async().await;
+                Err(e)
+            }
+        };
+        let _ = txn.close_async().await;
+        match res {
+            Err(e) if e.is_tx_conflict() && attempt < MAX_TXN_ATTEMPTS => continue,
+            res => return res,
+        }
+    }
+}
+
+// Submits every update under txn before waiting for any of them, like
+// update_many_objects_async, and stops submitting at the first failure.
+#[cfg(feature = "libdaos")]
+async fn apply_updates_in(txn: &DaosTxn, updates: &[DaosFanOutUpdate]) -> Result<()> {
+    const OP: &str = "update_objects_atomic_async";
+
+    let mut first_err = None;
+    let mut pending = Vec::with_capacity(updates.len());
+    for (obj, dkey, akey, data) in updates {
+        match obj.submit_update(
+            txn,
+            CondFlags::empty(),
+            dkey.clone(),
+            akey.clone(),
+            data.clone(),
+        ) {
+            Ok(update) => pending.push((obj, update)),
+            Err(e) => {
+                first_err = Some(e.with_context(obj.op_context(OP, dkey, akey)));
+                break;
+            }
+        }
+    }
+
+    for (obj, update) in pending {
+        let (res, dkey, akey) = update.wait(obj.deadlines.bulk).await;
+        obj.invalidate_key(&dkey, &akey);
+        if let Err(e) = res {
+            first_err.get_or_insert(e.with_context(obj.op_context(OP, &dkey, &akey)));
+        }
+    }
+    match first_err {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+// Punches every object in objs. All punches are submitted before any of them
+// is waited for, and every one runs regardless of the others failing. The
+// failed punches are returned with the index of their object in objs.
+#[cfg(feature = "libdaos")]
+pub async fn punch_objects_async(
+    objs: &[Arc<DaosObject>],
+    txn: &DaosTxn,
+) -> Vec<(usize, DaosError)> {
+    const OP: &str = "punch_objects_async";
+
+    let mut failures = Vec::new();
+    let mut pending = Vec::with_capacity(objs.len());
+    for (idx, obj) in objs.iter().enumerate() {
+        match obj.submit_punch(txn) {
+            Ok(punch) => pending.push((idx, punch)),
+            Err(e) => failures.push((idx, e.with_context(DaosErrorContext::new(OP, obj.oid)))),
+        }
+    }
+
+    for (idx, (mut event, rx)) in pending {
+        let obj = &objs[idx];
+        let res = match event.wait(rx, obj.deadlines.metadata).await {
+            Ok(0) => Ok(()),
+            Ok(ret) => Err(DaosError::from_call(
+                "daos_obj_punch",
+                ret,
+                "async punch operation fail",
+            )),
+            Err(e) => Err(e),
+        };
+        obj.invalidate_cache();
+        if let Err(e) = res {
+            failures.push((idx, e.with_context(DaosErrorContext::new(OP, obj.oid))));
+        }
+    }
+    failures.sort_by_key(|(idx, _)| *idx);
+    failures
+}
+
+#[cfg(feature = "libdaos")]
+type ChunkFuture = Pin<Box<dyn Future<Output = Result<(u64, Vec<u8>)>> + Send>>;
+
+// Chunks of an array value, see DaosObject::fetch_stream. Holes read as
+// zeros. The end is taken from a listing of the extents on the first read,
+// data written past it afterwards isn't read.
+#[cfg(feature = "libdaos")]
+pub struct DaosRecxReader {
+    obj: Arc<DaosObject>,
+    dkey: Vec<u8>,
+    akey: Vec<u8>,
+    chunk_size: usize,
+    // end of the value, None until the extents are listed
+    size: Option<u64>,
+    // offset of the next chunk to fetch
+    offset: u64,
+    chunk: Vec<u8>,
+    // bytes of chunk already handed out by poll_read
+    pos: usize,
+    pending: Option<ChunkFuture>,
+}
+
+#[cfg(feature = "libdaos")]
+impl DaosRecxReader {
+    pub fn offset(&self) -> u64 {
+        self.offset - (self.chunk.len() - self.pos) as u64
+    }
+
+    // The size of the value, known once the first chunk is read.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    // The next chunk, None at the end. Every chunk but the last is
+    // chunk_size bytes, the rest of a chunk poll_read began is returned
+    // first. Dropping the future doesn't lose the chunk being fetched.
+    pub async fn next_chunk_async(&mut self) -> Result<Option<Vec<u8>>> {
+        if self.pos == self.chunk.len() {
+            if self.at_end() {
+                return Ok(None);
+            }
+            let pending = self.pending.get_or_insert_with(|| {
+                Box::pin(fetch_chunk(
+                    self.obj.clone(),
+                    self.dkey.clone(),
+                    self.akey.clone(),
+                    self.offset,
+                    self.size,
+                    self.chunk_size,
+                ))
+            });
+            let res = pending.await;
+            self.pending = None;
+            self.set_chunk(res?);
+            if self.chunk.is_empty() {
+                return Ok(None);
+            }
+        }
+        let mut chunk = std::mem::take(&mut self.chunk);
+        if self.pos > 0 {
+            chunk.drain(..self.pos);
+            self.pos = 0;
+        }
+        Ok(Some(chunk))
+    }
+
+    fn at_end(&self) -> bool {
+        self.size.is_some_and(|size| self.offset >= size)
+    }
+
+    fn set_chunk(&mut self, (size, chunk): (u64, Vec<u8>)) {
+        self.size = Some(size);
+        self.offset += chunk.len() as u64;
+        self.chunk = chunk;
+        self.pos = 0;
+    }
+}
+
+#[cfg(feature = "libdaos")]
+impl AsyncRead for DaosRecxReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+        loop {
+            if this.pos < this.chunk.len() {
+                let len = buf.remaining().min(this.chunk.len() - this.pos);
+                buf.put_slice(&this.chunk[this.pos..this.pos + len]);
+                this.pos += len;
+                return Poll::Ready(Ok(()));
+            }
+            if this.pending.is_none() {
+                if this.at_end() {
+                    return Poll::Ready(Ok(()));
+                }
+                this.pending = Some(Box::pin(fetch_chunk(
+                    this.obj.clone(),
+                    this.dkey.clone(),
+                    this.akey.clone(),
+                    this.offset,
+                    this.size,
+                    this.chunk_size,
+                )));
+            }
+            let res = ready!(this.pending.as_mut().unwrap().as_mut().poll(cx));
+            this.pending = None;
+            match res {
+                Ok(chunk) => {
+                    this.set_chunk(chunk);
+                    // an empty value
+                    if this.chunk.is_empty() {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+                Err(e) => return Poll::Ready(Err(std::io::Error::other(e))),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "libdaos")]
+impl fmt::Debug for DaosRecxReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DaosRecxReader")
+            .field("oid", &self.obj.oid)
+            .field("chunk_size", &self.chunk_size)
+            .field("size", &self.size)
+            .field("offset", &self.offset())
+            .finish()
+    }
+}
+
+// The chunk at offset, and the end of the value, listed first when size is
+// still unknown.
+#[cfg(feature = "libdaos")]
+async fn fetch_chunk(
+    obj: Arc<DaosObject>,
+    dkey: Vec<u8>,
+    akey: Vec<u8>,
+    offset: u64,
+    size: Option<u64>,
+    chunk_size: usize,
+) -> Result<(u64, Vec<u8>)> {
+    let txn = DaosTxn::txn_none();
+    let size = match size {
+        Some(size) => size,
+        None => {
+            let recxs = obj
+                .list_recx_async(&txn, dkey.clone(), akey.clone())
+                .await?;
+            recxs
+                .extents
+                .iter()
+                .map(|extent| extent.offset + extent.len)
+                .max()
+                .unwrap_or(0)
+        }
+    };
+    let len = size.saturating_sub(offset).min(chunk_size as u64) as usize;
+    // zeroed, fetch_recx_async leaves the holes untouched
+    let mut chunk = vec![0u8; len];
+    if len > 0 {
+        obj.fetch_recx_async(&txn, CondFlags::empty(), &dkey, &akey, offset, &mut chunk)
+            .await?;
+    }
+    Ok((size, chunk))
+}
+
+#[cfg(all(test, feature = "libdaos"))]
+mod tests {
+    use super::*;
+
+    use crate::bindings::{
+        daos_anchor_type_t_DAOS_ANCHOR_TYPE_EOF, daos_otype_t_DAOS_OT_MULTI_HASHED,
+        daos_otype_t_DAOS_OT_MULTI_LEXICAL, OC_UNKNOWN,
+    };
+    use crate::daos_pool::DaosPool;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[test]
+    fn test_oid_text() {
+        let oid = DaosObjectId {
+            lo: 0x2a,
+            hi: (0x10 << 56) | (2 << 48) | (3 << 32),
+        };
+        assert_eq!(oid.to_string(), "1002000300000000.2a");
+        assert_eq!(oid.to_string().parse::<DaosObjectId>().unwrap(), oid);
+        assert_eq!(
+            "0x10.0x2".parse::<DaosObjectId>().unwrap(),
+            DaosObjectId { lo: 2, hi: 16 }
+        );
+        assert!("10".parse::<DaosObjectId>().is_err());
+        assert!("10.zz".parse::<DaosObjectId>().is_err());
+
+        assert_eq!(oid.otype(), 0x10);
+        assert_eq!(oid.class_id(), (2 << crate::bindings::OC_REDUN_SHIFT) | 3);
+        assert_eq!(DaosObjectId::from_bytes(&oid.to_bytes()).unwrap(), oid);
+        assert!(DaosObjectId::from_bytes(&[0u8; 8]).is_err());
+
+        let raw = u128::from(oid);
+        assert_eq!(raw, (0x1002000300000000u128 << 64) | 0x2a);
+        assert_eq!(DaosObjectId::from(raw), oid);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_oid_serde() {
+        use serde::de::value::{Error, StrDeserializer};
+        use serde::de::IntoDeserializer;
+        use serde::Deserialize;
+
+        let oid = DaosObjectId { lo: 0x2a, hi: 0x10 };
+        let de: StrDeserializer<Error> = "10.2a".into_deserializer();
+        assert_eq!(DaosObjectId::deserialize(de).unwrap(), oid);
+        let de: StrDeserializer<Error> = "2a".into_deserializer();
+        assert!(DaosObjectId::deserialize(de).is_err());
+    }
+
+    #[test]
+    fn test_key_list_pool() {
+        let pool = KeyListPool::new(1);
+        let mut lst = pool.checkout();
+        lst.anchor.da_type = daos_anchor_type_t_DAOS_ANCHOR_TYPE_EOF as u16;
+        *lst.ndesc = 3;
+        assert!(lst.reach_end());
+
+        pool.checkin(lst);
+        assert_eq!(pool.idle(), 1);
+        pool.checkin(DaosKeyList::new());
+        assert_eq!(pool.idle(), 1);
+
+        let lst = pool.checkout();
+        assert!(!lst.reach_end());
+        assert_eq!(lst.get_key_num(), 0);
+        assert_eq!(pool.idle(), 0);
+    }
+
+    #[test]
+    fn test_key_list_reset_shrinks() {
+        let mut lst = DaosKeyList::new();
+        let config = lst.get_config();
+        lst.out_buf.resize(config.min_buf_size * 8, 0);
+        *lst.ndesc = lst.key_descs.len() as u32;
+        lst.adapt_to_last_query();
+        assert!(lst.key_descs.len() > config.min_descs as usize);
+
+        let pool = KeyListPool::new(1);
+        pool.checkin(lst);
+        let lst = pool.checkout();
+        assert_eq!(lst.key_descs.len(), config.min_descs as usize);
+        assert_eq!(lst.out_buf.len(), config.min_buf_size);
+    }
+
+    #[test]
+    fn test_scan_cursor() {
+        let mut lst = DaosKeyList::new();
+        lst.anchor.da_shard = 2;
+        lst.anchor.da_buf[0] = 0xab;
+        let oid = DaosObjectId { lo: 5, hi: 6 };
+        let cursor = lst.cursor(oid).with_position(Some(b"dkey".to_vec()), None);
+
+        let bytes = cursor.to_bytes();
+        let restored = ScanCursor::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.oid, oid);
+        assert_eq!(restored.anchor.da_shard, 2);
+        assert_eq!(restored.anchor.da_buf[0], 0xab);
+        assert_eq!(restored.dkey.as_deref(), Some(&b"dkey"[..]));
+        assert_eq!(restored.akey, None);
+        assert!(!restored.reach_end());
+
+        assert!(ScanCursor::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut bad_version = bytes.clone();
+        bad_version[0] = 0;
+        assert!(ScanCursor::from_bytes(&bad_version).is_err());
+
+        lst.anchor.da_type = daos_anchor_type_t_DAOS_ANCHOR_TYPE_EOF as u16;
+        assert!(lst.cursor(oid).reach_end());
+    }
+
+    #[tokio::test]
+    async fn test_error_context() {
+        let oid = DaosObjectId { lo: 3, hi: 4 };
+        let mut obj = DaosObject::new(oid, DaosHandle { cookie: 0 }, None, Default::default());
+        obj.handle.take();
+
+        let txn = DaosTxn::txn_none();
+        let res = obj
+            .update_async(
+                &txn,
+                CondFlags::empty(),
+                b"dkey".to_vec(),
+                b"akey".to_vec(),
+                b"v",
+            )
+            .await;
+        let err = res.unwrap_err();
+        assert!(matches!(err, DaosError::NoHandle(_)));
+        let context = err.context().unwrap();
+        assert_eq!(context.op, "update_async");
+        assert_eq!(context.oid, oid);
+        assert_eq!(context.dkey.as_deref(), Some(&b"dkey"[..]));
+        assert_eq!(context.akey.as_deref(), Some(&b"akey"[..]));
+
+        let err = obj.punch_async(&txn).await.unwrap_err();
+        assert_eq!(err.context().unwrap().op, "punch_async");
+        assert_eq!(err.context().unwrap().dkey, None);
+    }
+
+    #[test]
+    fn test_default_flags() {
+        let mut obj = DaosObject::new(
+            DaosObjectId { lo: 1, hi: 0 },
+            DaosHandle { cookie: 0 },
+            None,
+            Default::default(),
+        );
+        obj.handle.take();
+
+        let bad = DaosDefaultFlags {
+            update: CondFlags::DKEY_FETCH,
+            fetch: CondFlags::empty(),
+        };
+        assert!(matches!(
+            obj.set_default_flags(bad),
+            Err(DaosError::InvalidFlags(_))
+        ));
+        assert_eq!(obj.get_default_flags(), DaosDefaultFlags::default());
+
+        let defaults = DaosDefaultFlags {
+            update: CondFlags::DKEY_UPDATE,
+            fetch: CondFlags::DKEY_FETCH,
+        };
+        assert!(obj.set_default_flags(defaults).is_ok());
+        assert_eq!(
+            obj.default_flags.apply_update(CondFlags::empty()),
+            DAOS_COND_DKEY_UPDATE as u64
+        );
+        assert_eq!(
+            obj.default_flags.apply_update(CondFlags::DKEY_INSERT),
+            DAOS_COND_DKEY_INSERT as u64
+        );
+        assert_eq!(
+            obj.default_flags.apply_fetch(CondFlags::empty()),
+            DAOS_COND_DKEY_FETCH as u64
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_only() {
+        let mut obj = DaosObject::new(
+            DaosObjectId { lo: 1, hi: 0 },
+            DaosHandle { cookie: 0 },
+            None,
+            Default::default(),
+        )
+        .with_read_only(true);
+        obj.handle.take();
+        assert!(obj.is_read_only());
+
+        let txn = DaosTxn::txn_none();
+        let res = obj.update(
+            &txn,
+            CondFlags::empty(),
+            b"dkey".to_vec(),
+            b"akey".to_vec(),
+            &[1u8],
+        );
+        let err = res.unwrap_err();
+        assert!(matches!(err, DaosError::ReadOnly(_)));
+        assert_eq!(err.context().unwrap().op, "update");
+
+        let res = obj
+            .update_async(
+                &txn,
+                CondFlags::empty(),
+                b"dkey".to_vec(),
+                b"akey".to_vec(),
+                &[1u8],
+            )
+            .await;
+        assert!(matches!(res, Err(DaosError::ReadOnly(_))));
+        let res = obj.punch_async(&txn).await;
+        assert!(matches!(res, Err(DaosError::ReadOnly(_))));
+        let res = obj.remove_async(&txn, b"dkey".to_vec(), None).await;
+        assert!(matches!(res, Err(DaosError::ReadOnly(_))));
+    }
+
+    #[test]
+    fn test_obj_cache() {
+        let cache = DaosObjCache::new(2);
+        let gen = cache.get(b"d1", b"a").unwrap_err();
+        cache.put(gen, b"d1".to_vec(), b"a".to_vec(), b"v1".to_vec());
+        assert_eq!(cache.get(b"d1", b"a").unwrap(), b"v1".to_vec());
+
+        // a fill that raced with an invalidation is dropped
+        let gen = cache.get(b"d2", b"a").unwrap_err();
+        cache.invalidate(b"d1", b"a");
+        cache.put(gen, b"d2".to_vec(), b"a".to_vec(), b"stale".to_vec());
+        assert!(cache.get(b"d2", b"a").is_err());
+        assert!(cache.get(b"d1", b"a").is_err());
+
+        let gen = cache.get(b"d1", b"a").unwrap_err();
+        for dkey in [b"d1", b"d2", b"d3"] {
+            cache.put(gen, dkey.to_vec(), b"a".to_vec(), b"v".to_vec());
+        }
+        assert_eq!(cache.stats().entries, 2);
+
+        cache.observe_epoch(10);
+        assert_eq!(cache.stats().entries, 2);
+        cache.observe_epoch(11);
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 0);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 5);
+        assert_eq!(stats.invalidations, 2);
+    }
+
+    #[test]
+    fn test_key_list_adaptive_growth() {
+        let config = DaosKeyListConfig {
+            min_descs: 2,
+            max_descs: 4,
+            min_buf_size: 8,
+            max_buf_size: 12,
+        };
+        let mut lst = DaosKeyList::with_config(config);
+        assert_eq!(lst.key_descs.len(), 2);
+        assert_eq!(lst.out_buf.len(), 8);
+
+        // a full round of short keys only grows the descriptors
+        lst.prepare_next_query();
+        lst.key_descs[0].kd_key_len = 1;
+        lst.key_descs[1].kd_key_len = 1;
+        lst.adapt_to_last_query();
+        assert_eq!(lst.key_descs.len(), 4);
+        assert_eq!(lst.out_buf.len(), 8);
+
+        // a nearly full buffer grows the buffer, capped at the maximum
+        *lst.ndesc = 1;
+        lst.key_descs[0].kd_key_len = 7;
+        lst.adapt_to_last_query();
+        assert_eq!(lst.key_descs.len(), 4);
+        assert_eq!(lst.out_buf.len(), 12);
+
+        lst.prepare_next_query();
+        lst.key_descs.iter_mut().for_each(|kd| kd.kd_key_len = 3);
+        lst.adapt_to_last_query();
+        assert_eq!(lst.key_descs.len(), 4);
+        assert_eq!(lst.out_buf.len(), 12);
+    }
+
+    #[test]
+    fn test_key_list_grow_to_fit() {
+        let config = DaosKeyListConfig {
+            min_descs: 2,
+            max_descs: 4,
+            min_buf_size: 8,
+            max_buf_size: 12,
+        };
+        let mut lst = DaosKeyList::with_config(config);
+        let key2big = DaosError::from_call(
+            "daos_obj_list_dkey",
+            -(crate::bindings::daos_errno_DER_KEY2BIG as i32),
+            "list dkey fail",
+        );
+
+        // goes past max_buf_size for a key that needs it
+        assert!(lst.grow_to_fit(&key2big.clone().with_required_size(100)));
+        assert_eq!(lst.out_buf.len(), 100);
+        assert!(lst.grow_to_fit(&key2big.clone().with_required_size(120)));
+        assert_eq!(lst.out_buf.len(), 200);
+
+        assert!(!lst.grow_to_fit(&key2big.clone().with_required_size(50)));
+        assert!(!lst.grow_to_fit(&key2big));
+        assert!(!lst.grow_to_fit(&DaosError::inval("not a key size error")));
+        assert_eq!(lst.out_buf.len(), 200);
+    }
+
+    #[test]
+    fn test_cond_flags() {
+        let flags = CondFlags::DKEY_INSERT | CondFlags::AKEY_UPDATE;
+        assert_eq!(
+            u64::from(flags),
+            (DAOS_COND_DKEY_INSERT | DAOS_COND_AKEY_UPDATE) as u64
+        );
+        assert_eq!(CondFlags::from(DAOS_COND_PUNCH as u64), CondFlags::PUNCH);
+        // bits without a name survive the round trip
+        let raw = CondFlags::from(1u64 << 40 | DAOS_COND_DKEY_FETCH as u64);
+        assert!(raw.contains(CondFlags::DKEY_FETCH));
+        assert_eq!(raw.bits(), 1u64 << 40 | DAOS_COND_DKEY_FETCH as u64);
+    }
+
+    #[test]
+    fn test_per_akey_flags() {
+        let none = [CondFlags::empty(), CondFlags::empty()];
+        let (flags, iod_flags) =
+            per_akey_flags(CondFlags::AKEY_UPDATE.bits(), &none, AKEY_UPDATE_FLAGS).unwrap();
+        assert_eq!(flags, CondFlags::AKEY_UPDATE.bits());
+        assert_eq!(iod_flags, vec![0, 0]);
+
+        let mixed = [CondFlags::AKEY_INSERT, CondFlags::empty()];
+        let (flags, iod_flags) = per_akey_flags(
+            (CondFlags::DKEY_UPDATE | CondFlags::AKEY_UPDATE).bits(),
+            &mixed,
+            AKEY_UPDATE_FLAGS,
+        )
+        .unwrap();
+        assert_eq!(flags, (CondFlags::DKEY_UPDATE | CondFlags::PER_AKEY).bits());
+        assert_eq!(
+            iod_flags,
+            vec![CondFlags::AKEY_INSERT.bits(), CondFlags::AKEY_UPDATE.bits()]
+        );
+
+        let err = per_akey_flags(0, &[CondFlags::DKEY_INSERT], AKEY_UPDATE_FLAGS).unwrap_err();
+        assert!(matches!(err, DaosError::InvalidFlags(_)));
+        assert!(per_akey_flags(0, &[CondFlags::AKEY_UPDATE], AKEY_FETCH_FLAGS).is_err());
+    }
+
+    #[test]
+    fn test_check_flags() {
+        assert!(check_update_flags(0).is_ok());
+        assert!(check_update_flags(DAOS_COND_DKEY_INSERT as u64).is_ok());
+        assert!(check_fetch_flags(DAOS_COND_DKEY_FETCH as u64).is_ok());
+
+        let err = check_update_flags(DAOS_COND_DKEY_FETCH as u64).unwrap_err();
+        assert!(matches!(err, DaosError::InvalidFlags(_)));
+
+        let flags = (DAOS_COND_DKEY_INSERT | DAOS_COND_DKEY_UPDATE) as u64;
+        assert!(check_update_flags(flags).is_err());
+        let flags = (DAOS_COND_AKEY_INSERT | DAOS_COND_AKEY_UPDATE) as u64;
+        assert!(check_update_flags(flags).is_err());
+        assert!(check_fetch_flags(DAOS_COND_DKEY_UPDATE as u64).is_err());
+        assert!(check_punch_flags(DAOS_COND_PUNCH as u64).is_ok());
+        assert!(check_punch_flags(DAOS_COND_AKEY_INSERT as u64).is_err());
+    }
+
+    #[test]
+    fn test_create_sync() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosSyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result = DaosObject::create(cont.as_ref(), allocator, otype, cid, hints, args);
+
+        assert!(result.is_ok());
+        let _obj_box = result.unwrap();
+        // Assert obj_box is created correctly
+    }
+
+    #[test]
+    fn test_update_sync() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosSyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result = DaosObject::create(cont.as_ref(), allocator, otype, cid, hints, args);
+
+        assert!(result.is_ok());
+        let obj_box = result.unwrap();
+
+        let txn = DaosTxn::txn_none();
+        let dkey = vec![0u8, 1u8, 2u8, 3u8];
+        let akey = vec![0u8];
+        let data = "something".as_bytes();
+        let result = obj_box.update(
+            &txn,
+            CondFlags::DKEY_INSERT,
+            dkey.clone(),
+            akey.clone(),
+            data,
+        );
+        assert!(result.is_ok());
+        // Assert update operation is successful
+
+        let res = obj_box.fetch(&txn, CondFlags::DKEY_FETCH, dkey.clone(), akey.clone(), 16);
+        assert!(res.is_ok());
+        let read = res.unwrap();
+        assert_eq!(String::from_utf8(read).unwrap(), "something");
+
+        let res = obj_box.fetch_with_size(&txn, CondFlags::empty(), dkey.clone(), akey.clone(), 16);
+        let res = res.unwrap();
+        assert!(!res.truncated);
+        assert_eq!(res.total_size, data.len());
+        assert_eq!(res.data, data);
+
+        let res = obj_box.fetch_with_size(&txn, CondFlags::empty(), dkey, akey, 4);
+        assert_eq!(
+            res.unwrap(),
+            FetchResult {
+                data: Vec::new(),
+                total_size: data.len(),
+                truncated: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_recx_sync() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::without_event_queue(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosSyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let obj_box = DaosObject::create(cont.as_ref(), allocator, otype, OC_UNKNOWN, 0, 0)
+            .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        let akey = b"data".to_vec();
+        for dkey in [b"first".to_vec(), b"second".to_vec()] {
+            obj_box
+                .update_recx(&txn, CondFlags::empty(), dkey, akey.clone(), 4, b"extent")
+                .expect("Failed to update extent");
+        }
+
+        let mut buf = vec![0u8; 16];
+        let len = obj_box
+            .fetch_recx(
+                &txn,
+                CondFlags::empty(),
+                b"first".to_vec(),
+                akey.clone(),
+                0,
+                &mut buf,
+            )
+            .unwrap();
+        assert_eq!(len, 10);
+        assert_eq!(&buf[4..10], b"extent");
+
+        let mut key_lst = DaosKeyList::new();
+        let mut dkeys = Vec::new();
+        while !key_lst.reach_end() {
+            key_lst = obj_box.list_dkey(&txn, key_lst).unwrap();
+            dkeys.extend(key_lst.keys().map(|key| key.to_vec()));
+        }
+        dkeys.sort();
+        assert_eq!(dkeys, vec![b"first".to_vec(), b"second".to_vec()]);
+    }
+
+    #[test]
+    fn test_sync_ops() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::without_event_queue(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosSyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let obj_box = DaosObject::create(cont.as_ref(), allocator, otype, OC_UNKNOWN, 0, 0)
+            .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        let dkey = b"row".to_vec();
+        let akeys: Vec<DaosAkeyUpdate> = vec![
+            (b"a".to_vec(), CondFlags::AKEY_INSERT, b"1".to_vec()),
+            (b"b".to_vec(), CondFlags::AKEY_INSERT, b"2".to_vec()),
+        ];
+        obj_box
+            .update_akeys(&txn, CondFlags::empty(), dkey.clone(), akeys)
+            .expect("Failed to update akeys");
+        obj_box
+            .update_recx(
+                &txn,
+                CondFlags::empty(),
+                dkey.clone(),
+                b"c".to_vec(),
+                0,
+                b"xyz",
+            )
+            .expect("Failed to update extent");
+
+        let akeys: Vec<DaosAkeyFetch> = vec![
+            (b"a".to_vec(), CondFlags::AKEY_FETCH),
+            (b"z".to_vec(), CondFlags::empty()),
+        ];
+        let values = obj_box
+            .fetch_akeys(&txn, CondFlags::empty(), dkey.clone(), akeys, 16)
+            .expect("Failed to fetch akeys");
+        assert_eq!(values, vec![Some(b"1".to_vec()), None]);
+
+        let mut key_lst = DaosKeyList::new();
+        let mut akeys = Vec::new();
+        while !key_lst.reach_end() {
+            key_lst = obj_box.list_akey(&txn, dkey.clone(), key_lst).unwrap();
+            akeys.extend(key_lst.keys().map(|key| key.to_vec()));
+        }
+        akeys.sort();
+        assert_eq!(akeys, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+
+        let recxs = obj_box
+            .list_recx(&txn, dkey.clone(), b"c".to_vec())
+            .unwrap();
+        assert_eq!(recxs.extents, vec![DaosExtent { offset: 0, len: 3 }]);
+
+        #[cfg(feature = "list-obj")]
+        {
+            let mut obj_lst = DaosObjList::new();
+            let mut entries = Vec::new();
+            while !obj_lst.reach_end() {
+                obj_lst = obj_box.list_obj(&txn, obj_lst).unwrap();
+                entries.extend(obj_lst.entries().iter().cloned());
+            }
+            assert!(entries.contains(&DaosObjEntry::Dkey(dkey.clone())));
+        }
+
+        assert!(obj_box
+            .remove(&txn, dkey.clone(), Some(b"a".to_vec()))
+            .unwrap());
+        assert!(!obj_box
+            .remove(&txn, dkey.clone(), Some(b"a".to_vec()))
+            .unwrap());
+        assert!(!obj_box
+            .punch_akeys_with_flags(&txn, CondFlags::PUNCH, dkey.clone(), &[b"a".to_vec()])
+            .unwrap());
+
+        assert!(obj_box.punch_with_flags(&txn, CondFlags::PUNCH).unwrap());
+        let res = obj_box.punch_with_flags(&txn, CondFlags::DKEY_INSERT);
+        assert!(matches!(res, Err(DaosError::InvalidFlags(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result =
+            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
+
+        assert!(result.is_ok());
+        let _obj_box = result.unwrap();
+        // Assert obj_box is created correctly
+    }
+
+    #[tokio::test]
+    async fn test_create_many_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result =
+            DaosObject::create_many_async(cont.as_ref(), allocator, 8, otype, cid, hints, args)
+                .await;
+        assert!(result.is_ok());
+        let objs = result.unwrap();
+        assert_eq!(objs.len(), 8);
+        for (idx, obj) in objs.iter().enumerate() {
+            assert!(obj.get_handle().is_some());
+            assert!(objs[idx + 1..].iter().all(|other| other.oid != obj.oid));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result =
+            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
+        assert!(result.is_ok());
+        let obj_box = result.unwrap();
+
+        let oid = obj_box.oid;
+
+        let result = DaosObject::open_async(&cont, oid, /* read_only */ true).await;
+        assert!(result.is_ok());
+        let obj = result.unwrap();
+        assert!(obj.is_read_only());
+        assert!(!obj_box.is_read_only());
+
+        let txn = DaosTxn::txn_none();
+        let res = obj
+            .update_async(&txn, CondFlags::empty(), vec![1u8], vec![0u8], &[1u8])
+            .await;
+        assert!(matches!(res, Err(DaosError::ReadOnly(_))));
+    }
+
+    #[cfg(feature = "lz4")]
+    #[tokio::test]
+    async fn test_update_fetch_compressed() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let mut obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        obj.update_async(
+            &txn,
+            CondFlags::empty(),
+            b"d".to_vec(),
+            b"plain".to_vec(),
+            b"old value",
+        )
+        .await
+        .unwrap();
+        obj.set_compression(Some(DaosCompression::Lz4));
+        let value = b"compressible ".repeat(100);
+        obj.update_async(
+            &txn,
+            CondFlags::empty(),
+            b"d".to_vec(),
+            b"a".to_vec(),
+            &value,
+        )
+        .await
+        .unwrap();
+
+        let mut buf = vec![0u8; value.len()];
+        let size = obj
+            .fetch_async(
+                &txn,
+                CondFlags::empty(),
+                b"d".to_vec(),
+                b"a".to_vec(),
+                &mut buf,
+            )
+            .await
+            .unwrap();
+        assert_eq!(&buf[..size], value.as_slice());
+        let res = obj
+            .fetch_async(
+                &txn,
+                CondFlags::empty(),
+                b"d".to_vec(),
+                b"a".to_vec(),
+                &mut buf[..16],
+            )
+            .await;
+        let err = res.unwrap_err();
+        assert!(matches!(err, DaosError::Truncated { written: 0, .. }));
+        assert_eq!(err.required_size(), Some(value.len()));
+        let size = obj
+            .fetch_async(
+                &txn,
+                CondFlags::empty(),
+                b"d".to_vec(),
+                b"plain".to_vec(),
+                &mut buf,
+            )
+            .await
+            .unwrap();
+        assert_eq!(&buf[..size], b"old value");
+
+        obj.update(&txn, CondFlags::empty(), b"d", b"sync", &value)
+            .unwrap();
+        for akey in [&b"a"[..], b"sync"] {
+            let fetched = obj
+                .fetch(&txn, CondFlags::empty(), b"d", akey, value.len() as u32)
+                .unwrap();
+            assert_eq!(fetched, value);
+        }
+        let res = obj
+            .fetch_with_size(&txn, CondFlags::empty(), b"d", b"sync", 16)
+            .unwrap();
+        assert!(res.truncated);
+        assert_eq!(res.total_size, value.len());
+
+        obj.set_compression(None);
+        let stored = obj
+            .fetch_async(
+                &txn,
+                CondFlags::empty(),
+                b"d".to_vec(),
+                b"a".to_vec(),
+                &mut buf,
+            )
+            .await
+            .unwrap();
+        assert!(stored < value.len());
+    }
+
+    #[tokio::test]
+    async fn test_handle_token() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .expect("Failed to create object");
+
+        let token = obj.handle_token();
+        let cookie = obj.get_handle().unwrap().cookie;
+        let res = tokio::task::spawn_blocking({
+            let token = token.clone();
+            move || token.with_handle(|hdl| hdl.cookie)
+        })
+        .await
+        .unwrap();
+        assert_eq!(res.unwrap(), cookie);
+        assert!(token.is_open());
+
+        drop(obj);
+        assert!(!token.is_open());
+        let res = token.with_handle(|hdl| hdl.cookie);
+        assert!(matches!(res, Err(DaosError::NoHandle(_))));
+    }
+
+    #[cfg(feature = "obj-verify")]
+    #[tokio::test]
+    async fn test_verify_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .unwrap();
+
+        let txn = DaosTxn::txn_none();
+        obj.update_async(&txn, CondFlags::empty(), [1u8], [0u8], b"verify")
+            .await
+            .unwrap();
+        assert!(obj.verify_async(u64::MAX).await.unwrap());
+
+        let reopened = DaosObject::open_async(cont.as_ref(), obj.oid, true)
+            .await
+            .unwrap();
+        assert!(reopened.verify_async(u64::MAX).await.unwrap());
+
+        let mut detached =
+            DaosObject::new(obj.oid, DaosHandle { cookie: 0 }, None, Default::default());
+        detached.handle.take();
+        let res = detached.verify_async(u64::MAX).await;
+        assert!(matches!(res, Err(DaosError::NoHandle(_))));
+    }
+
+    #[tokio::test]
+    async fn test_punch_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result =
+            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
+        assert!(result.is_ok());
+        let obj_box = result.unwrap();
+
+        let txn = DaosTxn::txn_none();
+        let result = obj_box.punch_async(&txn).await;
+        assert!(result.is_ok());
+        // Assert punch operation is successful
+
+        let cond = CondFlags::PUNCH;
+        let res = obj_box
+            .update_async(&txn, CondFlags::empty(), vec![1u8], vec![0u8], &[1u8])
+            .await;
+        assert!(res.is_ok());
+        let res = obj_box.punch_with_flags_async(&txn, cond).await;
+        assert!(res.unwrap());
+        let res = obj_box.punch_with_flags_async(&txn, cond).await;
+        assert!(!res.unwrap());
+
+        let res = obj_box
+            .punch_with_flags_async(&txn, CondFlags::DKEY_FETCH)
+            .await;
+        assert!(matches!(res, Err(DaosError::InvalidFlags(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result =
+            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
+        assert!(result.is_ok());
+        let obj_box = result.unwrap();
+
+        let txn = DaosTxn::txn_none();
+        let flags = CondFlags::empty();
+        let dkey = vec![0u8, 1u8, 2u8, 3u8];
+        let akey = vec![0u8];
+        let mut buf = vec![0u8; 1024];
+        let result = obj_box
+            .fetch_async(&txn, flags, dkey, akey, buf.as_mut_slice())
+            .await;
+        assert!(result.is_ok());
+        // Assert fetched data is correct
+    }
+
+    #[tokio::test]
+    async fn test_update_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result =
+            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
+        assert!(result.is_ok());
+        let obj_box = result.unwrap();
+
+        let txn = DaosTxn::txn_none();
+        let dkey = "async_update".as_bytes().to_vec();
+        let akey = vec![0u8];
+        let data = "some_something".as_bytes();
+        let result = obj_box
+            .update_async(
+                &txn,
+                CondFlags::DKEY_INSERT,
+                dkey.clone(),
+                akey.clone(),
+                data,
+            )
+            .await;
+        assert!(result.is_ok());
+
+        let mut buf = vec![0u8, 32];
+        let res = obj_box
+            .fetch_async(&txn, CondFlags::DKEY_FETCH, dkey, akey, buf.as_mut_slice())
+            .await;
+        // Assert update operation is successful
+        assert!(res.is_ok());
+        let out_size = res.unwrap();
+        buf.resize(out_size, 0);
+        assert_eq!(String::from_utf8(buf).unwrap(), "some_something");
+    }
+
+    #[tokio::test]
+    async fn test_list_dkey_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result =
+            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
+        assert!(result.is_ok());
+        let obj_box = result.unwrap();
+
+        let txn = DaosTxn::txn_none();
+        let dkey = "string1".as_bytes().to_vec();
+        let akey = vec![0u8];
+        let data = vec![1u8; 256];
+        let res = obj_box
+            .update_async(&txn, CondFlags::DKEY_INSERT, dkey, akey, data.as_slice())
+            .await;
+        assert!(res.is_ok());
+
+        let dkey = "very_long_string2".as_bytes().to_vec();
+        let akey = vec![0u8];
+        let data = vec![2u8; 256];
+        let res = obj_box
+            .update_async(&txn, CondFlags::DKEY_INSERT, dkey, akey, data.as_slice())
+            .await;
+        assert!(res.is_ok());
+
+        let key_lst = DaosKeyList::new();
+        let result = obj_box.list_dkey_async(&txn, key_lst).await;
+        assert!(result.is_ok());
+        // Assert list dkey operation is successful
+        let key_lst = result.unwrap();
+
+        let off = (0u32, 0u32);
+        let res = key_lst.get_key(off);
+        let off = match res {
+            Ok((key, off)) => {
+                assert_eq!(key, "string1".as_bytes());
+                off
+            }
+            Err(_) => {
+                assert!(false);
+                (0u32, 0u32)
+            }
+        };
+
+        let res = key_lst.get_key(off);
+        let off = match res {
+            Ok((key, off)) => {
+                assert_eq!(key, "very_long_string2".as_bytes());
+                off
+            }
+            Err(_) => {
+                assert!(false);
+                (0u32, 0u32)
+            }
+        };
+
+        let res = key_lst.get_key(off);
+        assert!(res.is_err());
+
+        // a buffer too small for the longest key grows on KEY2BIG
+        let config = DaosKeyListConfig {
+            min_buf_size: 4,
+            max_buf_size: 4,
+            ..DaosKeyListConfig::default()
+        };
+        let mut key_lst = DaosKeyList::with_config(config);
+        let mut dkeys = Vec::new();
+        while !key_lst.reach_end() {
+            key_lst = obj_box.list_dkey_async(&txn, key_lst).await.unwrap();
+            dkeys.extend(key_lst.keys().map(|key| key.to_vec()));
+        }
+        dkeys.sort();
+        assert_eq!(
+            dkeys,
+            vec![b"string1".to_vec(), b"very_long_string2".to_vec()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_key2anchor_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_LEXICAL;
+        let obj_box = DaosObject::create_async(cont.as_ref(), allocator, otype, OC_UNKNOWN, 0, 0)
+            .await
+            .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        for dkey in [b"a", b"b", b"c", b"d"] {
+            for akey in [b"x", b"y", b"z"] {
+                obj_box
+                    .update_async(&txn, CondFlags::empty(), dkey.to_vec(), akey.to_vec(), b"v")
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let key_lst = obj_box
+            .key2anchor_async(&txn, b"c".to_vec(), None, DaosKeyList::new())
+            .await
+            .unwrap();
+        let mut key_lst = key_lst;
+        let mut dkeys = Vec::new();
+        while !key_lst.reach_end() {
+            key_lst = obj_box.list_dkey_async(&txn, key_lst).await.unwrap();
+            dkeys.extend(key_lst.keys().map(|key| key.to_vec()));
+        }
+        assert!(!dkeys.contains(&b"a".to_vec()));
+        assert!(!dkeys.contains(&b"b".to_vec()));
+        assert!(dkeys.contains(&b"d".to_vec()));
+
+        let mut key_lst = obj_box
+            .key2anchor_async(&txn, b"b".to_vec(), Some(b"y".to_vec()), key_lst)
+            .await
+            .unwrap();
+        let mut akeys = Vec::new();
+        while !key_lst.reach_end() {
+            key_lst = obj_box
+                .list_akey_async(&txn, b"b".to_vec(), key_lst)
+                .await
+                .unwrap();
+            akeys.extend(key_lst.keys().map(|key| key.to_vec()));
+        }
+        assert!(!akeys.contains(&b"x".to_vec()));
+        assert!(akeys.contains(&b"z".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_list_akey_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let obj_box = DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args)
+            .await
+            .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        let dkey = b"dkey".to_vec();
+        let akeys = [
+            b"akey1".to_vec(),
+            b"a_longer_akey2".to_vec(),
+            b"a3".to_vec(),
+        ];
+        for akey in akeys.iter() {
+            obj_box
+                .update_async(
+                    &txn,
+                    CondFlags::empty(),
+                    dkey.clone(),
+                    akey.clone(),
+                    b"value",
+                )
+                .await
+                .expect("Failed to update object");
+        }
+
+        // a one-key page pages through every akey
+        let mut listed = Vec::new();
+        let mut key_lst = DaosKeyList::with_config(DaosKeyListConfig {
+            min_descs: 1,
+            max_descs: 1,
+            ..Default::default()
+        });
+        while !key_lst.reach_end() {
+            key_lst = obj_box
+                .list_akey_async(&txn, dkey.clone(), key_lst)
+                .await
+                .expect("Failed to list akeys");
+            listed.extend(key_lst.keys().map(|key| key.to_vec()));
+        }
+        listed.sort();
+        let mut expected = akeys.to_vec();
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        let key_lst = obj_box
+            .list_akey_async(&txn, b"no_such_dkey".to_vec(), DaosKeyList::new())
+            .await
+            .expect("Failed to list akeys");
+        assert_eq!(key_lst.get_key_num(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_list_recx_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let obj_box = DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args)
+            .await
+            .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        let dkey = b"sparse".to_vec();
+        let akey = b"data".to_vec();
+        // two runs with a hole between them
+        for (offset, len) in [(0u64, 16usize), (4096, 100)] {
+            obj_box
+                .update_recx_async(
+                    &txn,
+                    CondFlags::empty(),
+                    dkey.clone(),
+                    akey.clone(),
+                    offset,
+                    &vec![7u8; len],
+                )
+                .await
+                .expect("Failed to update extent");
+        }
+
+        let recxs = obj_box
+            .list_recx_async(&txn, dkey.clone(), akey.clone())
+            .await
+            .expect("Failed to list extents");
+        assert_eq!(recxs.rec_size, 1);
+        assert_eq!(
+            recxs.extents,
+            vec![
+                DaosExtent { offset: 0, len: 16 },
+                DaosExtent {
+                    offset: 4096,
+                    len: 100
+                },
+            ]
+        );
+
+        obj_box
+            .update_async(
+                &txn,
+                CondFlags::empty(),
+                dkey.clone(),
+                b"single".to_vec(),
+                b"value",
+            )
+            .await
+            .expect("Failed to update object");
+        let recxs = obj_box
+            .list_recx_async(&txn, dkey, b"single".to_vec())
+            .await
+            .expect("Failed to list extents");
+        assert!(recxs.extents.is_empty());
+    }
+
+    #[test]
+    fn test_recx_fetch_holes() {
+        let recxs = [
+            daos_recx_t {
+                rx_idx: 12,
+                rx_nr: 4,
+            },
+            daos_recx_t {
+                rx_idx: 0,
+                rx_nr: 6,
+            },
+        ];
+        let fetched = RecxFetch::new(2, 20, &recxs);
+        assert_eq!(fetched.len, 14);
+        assert_eq!(
+            fetched.extents,
+            vec![
+                DaosExtent { offset: 2, len: 4 },
+                DaosExtent { offset: 12, len: 4 },
+            ]
+        );
+        assert_eq!(fetched.holes(), vec![DaosExtent { offset: 6, len: 6 }]);
+
+        let empty = RecxFetch::new(2, 20, &[]);
+        assert_eq!(empty.len, 0);
+        assert!(empty.holes().is_empty());
+    }
+
+    #[cfg(feature = "list-obj")]
+    #[test]
+    fn test_parse_obj_entries() {
+        let rec = |idx: u64, nr: u64, size: u64, flags: u32| {
+            let mut rec = Vec::new();
+            for v in [idx, nr, 0, u64::MAX, size] {
+                rec.extend_from_slice(&v.to_ne_bytes());
+            }
+            rec.extend_from_slice(&0u32.to_ne_bytes());
+            rec.extend_from_slice(&flags.to_ne_bytes());
+            rec
+        };
+        let kd = |len: usize, val_type: u32| daos_key_desc_t {
+            kd_key_len: len as u64,
+            kd_val_type: val_type,
+        };
+
+        let mut buf = b"d1a1".to_vec();
+        buf.extend(rec(0, 1, 9, 0));
+        buf.extend_from_slice(b"a2");
+        // two extents in one descriptor, the first with its data inline
+        buf.extend(rec(0, 4, 1, RECX_INLINE));
+        buf.extend_from_slice(b"data");
+        buf.extend(rec(16, 8, 1, 0));
+        let kds = [
+            kd(2, OBJ_ITER_DKEY),
+            kd(2, OBJ_ITER_AKEY),
+            kd(OBJ_ENUM_REC_SIZE, OBJ_ITER_SINGLE),
+            kd(2, OBJ_ITER_AKEY),
+            kd(2 * OBJ_ENUM_REC_SIZE + 4, OBJ_ITER_RECX),
+        ];
+        assert_eq!(
+            parse_obj_entries(&kds, &buf).unwrap(),
+            vec![
+                DaosObjEntry::Dkey(b"d1".to_vec()),
+                DaosObjEntry::Akey(b"a1".to_vec()),
+                DaosObjEntry::Single { size: 9 },
+                DaosObjEntry::Akey(b"a2".to_vec()),
+                DaosObjEntry::Recx {
+                    extent: DaosExtent { offset: 0, len: 4 },
+                    rec_size: 1,
+                },
+                DaosObjEntry::Recx {
+                    extent: DaosExtent { offset: 16, len: 8 },
+                    rec_size: 1,
+                },
+            ]
+        );
+
+        assert!(parse_obj_entries(&[kd(8, OBJ_ITER_DKEY)], b"d1").is_err());
+        assert!(parse_obj_entries(&[kd(2, OBJ_ITER_RECX)], b"d1").is_err());
+    }
+
+    #[cfg(feature = "list-obj")]
+    #[tokio::test]
+    async fn test_list_obj_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let obj_box = DaosObject::create_async(cont.as_ref(), allocator, otype, OC_UNKNOWN, 0, 0)
+            .await
+            .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        obj_box
+            .update_async(
+                &txn,
+                CondFlags::empty(),
+                b"d1".to_vec(),
+                b"single".to_vec(),
+                b"value",
+            )
+            .await
+            .unwrap();
+        obj_box
+            .update_recx_async(
+                &txn,
+                CondFlags::empty(),
+                b"d2".to_vec(),
+                b"array".to_vec(),
+                8,
+                b"extent",
+            )
+            .await
+            .unwrap();
+
+        // small pages to go through more than one
+        let mut obj_lst = DaosObjList::with_capacity(2, 64);
+        let mut entries = Vec::new();
+        while !obj_lst.reach_end() {
+            obj_lst = obj_box.list_obj_async(&txn, obj_lst).await.unwrap();
+            entries.extend_from_slice(obj_lst.entries());
+        }
+        assert!(entries.contains(&DaosObjEntry::Dkey(b"d1".to_vec())));
+        assert!(entries.contains(&DaosObjEntry::Akey(b"single".to_vec())));
+        assert!(entries.contains(&DaosObjEntry::Single { size: 5 }));
+        assert!(entries.contains(&DaosObjEntry::Dkey(b"d2".to_vec())));
+        assert!(entries.contains(&DaosObjEntry::Akey(b"array".to_vec())));
+        assert!(entries.contains(&DaosObjEntry::Recx {
+            extent: DaosExtent { offset: 8, len: 6 },
+            rec_size: 1,
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_recx_map_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let obj_box = DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args)
+            .await
+            .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        let (dkey, akey) = (b"sparse".to_vec(), b"data".to_vec());
+        for offset in [0u64, 32] {
+            obj_box
+                .update_recx_async(
+                    &txn,
+                    CondFlags::empty(),
+                    dkey.clone(),
+                    akey.clone(),
+                    offset,
+                    &[1u8; 8],
+                )
+                .await
+                .expect("Failed to update extent");
+        }
+
+        // stale bytes stay where nothing was written
+        let mut buf = vec![0xeeu8; 64];
+        let fetched = obj_box
+            .fetch_recx_map_async(
+                &txn,
+                CondFlags::empty(),
+                dkey.clone(),
+                akey.clone(),
+                0,
+                &mut buf,
+            )
+            .await
+            .expect("Failed to fetch extents");
+        assert_eq!(fetched.len, 40);
+        assert_eq!(fetched.holes(), vec![DaosExtent { offset: 8, len: 24 }]);
+        assert_eq!(&buf[..8], &[1u8; 8]);
+        assert_eq!(&buf[32..40], &[1u8; 8]);
+
+        let mut buf = vec![0u8; 64];
+        let len = obj_box
+            .fetch_recx_async(
+                &txn,
+                CondFlags::empty(),
+                dkey.clone(),
+                akey.clone(),
+                4,
+                &mut buf,
+            )
+            .await
+            .unwrap();
+        assert_eq!(len, 36);
+        let len = obj_box
+            .fetch_recx_async(&txn, CondFlags::empty(), &dkey, &akey, 100, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(len, 0);
+
+        obj_box
+            .punch_recx_async(&txn, &dkey, &akey, 2, 4)
+            .await
+            .expect("Failed to punch extent");
+        let mut buf = vec![0u8; 64];
+        let fetched = obj_box
+            .fetch_recx_map_async(&txn, CondFlags::empty(), &dkey, &akey, 0, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(
+            fetched.holes(),
+            vec![
+                DaosExtent { offset: 2, len: 4 },
+                DaosExtent { offset: 8, len: 24 }
+            ]
+        );
+        assert_eq!(&buf[..2], &[1u8; 2]);
+        assert_eq!(&buf[6..8], &[1u8; 2]);
+    }
+
+    #[tokio::test]
+    async fn test_recs_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .unwrap();
+
+        let txn = DaosTxn::txn_none();
+        let samples: Vec<u8> = [7u64, 8, 9].iter().flat_map(|v| v.to_le_bytes()).collect();
+        obj.update_recs_async(&txn, CondFlags::empty(), b"s", b"a", 8, 10, &samples)
+            .await
+            .unwrap();
+
+        let mut buf = vec![0u8; 16];
+        let nr = obj
+            .fetch_recs_async(&txn, CondFlags::empty(), b"s", b"a", 8, 11, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(nr, 2);
+        assert_eq!(buf, samples[8..]);
+
+        let res = obj
+            .update_recs_async(&txn, CondFlags::empty(), b"s", b"a", 8, 0, &[0u8; 12])
+            .await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        let res = obj
+            .fetch_recs_async(&txn, CondFlags::empty(), b"s", b"a", 0, 0, &mut buf)
+            .await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_stream() {
+        use tokio::io::AsyncReadExt;
+
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj: Arc<DaosObject> = Arc::from(
+            DaosObject::create_async(
+                cont.as_ref(),
+                allocator,
+                daos_otype_t_DAOS_OT_MULTI_HASHED,
+                OC_UNKNOWN,
+                0,
+                0,
+            )
+            .await
+            .unwrap(),
+        );
+
+        // two extents with a hole in between
+        let txn = DaosTxn::txn_none();
+        let head: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        obj.update_recx_async(&txn, CondFlags::empty(), b"d", b"a", 0, &head)
+            .await
+            .unwrap();
+        obj.update_recx_async(&txn, CondFlags::empty(), b"d", b"a", 2000, &[9u8; 500])
+            .await
+            .unwrap();
+        let mut expected = head.clone();
+        expected.resize(2000, 0);
+        expected.extend_from_slice(&[9u8; 500]);
+
+        let mut reader = obj.fetch_stream(b"d", b"a", 1024).unwrap();
+        let mut value = Vec::new();
+        reader.read_to_end(&mut value).await.unwrap();
+        assert_eq!(value, expected);
+        assert_eq!(reader.size(), Some(2500));
+
+        let mut reader = obj.fetch_stream(b"d", b"a", 1024).unwrap();
+        let mut lens = Vec::new();
+        while let Some(chunk) = reader.next_chunk_async().await.unwrap() {
+            lens.push(chunk.len());
+        }
+        assert_eq!(lens, vec![1024, 1024, 452]);
+
+        let mut reader = obj.fetch_stream(b"d", b"missing", 1024).unwrap();
+        assert_eq!(reader.next_chunk_async().await.unwrap(), None);
+        assert!(obj.fetch_stream(b"d", b"a", 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_meta_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .unwrap();
+
+        let txn = DaosTxn::txn_none();
+        obj.set_meta_async(&txn, META_CONTENT_TYPE, b"application/json")
+            .await
+            .unwrap();
+        obj.set_meta_async(&txn, META_SCHEMA_VERSION, b"2")
+            .await
+            .unwrap();
+        assert_eq!(
+            obj.get_meta_async(&txn, META_CONTENT_TYPE).await.unwrap(),
+            Some(b"application/json".to_vec())
+        );
+        assert_eq!(obj.get_meta_async(&txn, "missing").await.unwrap(), None);
+        assert!(obj.set_meta_async(&txn, "empty", b"").await.is_err());
+
+        // user akeys can't reach into the metadata namespace
+        let akey = [META_PREFIX, META_CONTENT_TYPE.as_bytes()].concat();
+        let res = obj
+            .update_async(&txn, CondFlags::empty(), META_DKEY, &akey, b"text/plain")
+            .await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        let res = obj.update(&txn, CondFlags::empty(), b"d", &akey, b"text/plain");
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        let res = obj.punch_dkeys_async(&txn, &[META_DKEY.to_vec()]).await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        let res = obj.punch_akeys(&txn, META_DKEY.to_vec(), &[akey.clone()]);
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        let res = obj.remove_async(&txn, META_DKEY.to_vec(), None).await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        let res = obj.remove(&txn, META_DKEY.to_vec(), Some(akey));
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+
+        assert!(obj
+            .remove_meta_async(&txn, META_SCHEMA_VERSION)
+            .await
+            .unwrap());
+        assert_eq!(
+            obj.get_meta_async(&txn, META_SCHEMA_VERSION).await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_map_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result =
+            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
+        assert!(result.is_ok());
+        let obj_box = result.unwrap();
+
+        let txn = DaosTxn::txn_none();
+        let akey = vec![0u8];
+        let res = obj_box
+            .update_async(
+                &txn,
+                CondFlags::DKEY_INSERT,
+                "present".as_bytes().to_vec(),
+                akey.clone(),
+                "value".as_bytes(),
+            )
+            .await;
+        assert!(res.is_ok());
+
+        let keys = vec!["present".as_bytes().to_vec(), "missing".as_bytes().to_vec()];
+        let res = obj_box.get_map_async(&txn, keys, akey, 32).await;
+        assert!(res.is_ok());
+        let map = res.unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            map.get("present".as_bytes()).unwrap().as_deref(),
+            Some("value".as_bytes())
+        );
+        assert_eq!(map.get("missing".as_bytes()).unwrap(), &None);
+    }
+
+    #[tokio::test]
+    async fn test_get_multi_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .expect("Failed to create object");
+
+        check_get_multi_async(obj.as_ref()).await;
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cache() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let result =
+            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
+        assert!(result.is_ok());
+        let mut obj_box = result.unwrap();
+        obj_box.enable_cache(16);
+
+        let txn = DaosTxn::txn_none();
+        let dkey = vec![1u8];
+        let akey = vec![0u8];
+        let res = obj_box
+            .update_async(
+                &txn,
+                CondFlags::empty(),
+                dkey.clone(),
+                akey.clone(),
+                "v1".as_bytes(),
+            )
+            .await;
+        assert!(res.is_ok());
+
+        let mut buf = vec![0u8; 16];
+        for _ in 0..2 {
+            let res = obj_box
+                .fetch_async(
+                    &txn,
+                    CondFlags::empty(),
+                    dkey.clone(),
+                    akey.clone(),
+                    &mut buf,
+                )
+                .await;
+            assert_eq!(res.unwrap(), 2);
+            assert_eq!(&buf[..2], "v1".as_bytes());
+        }
+
+        let res = obj_box
+            .update_async(
+                &txn,
+                CondFlags::empty(),
+                dkey.clone(),
+                akey.clone(),
+                "v22".as_bytes(),
+            )
+            .await;
+        assert!(res.is_ok());
+        let res = obj_box
+            .fetch_async(
+                &txn,
+                CondFlags::empty(),
+                dkey.clone(),
+                akey.clone(),
+                &mut buf,
+            )
+            .await;
+        assert_eq!(res.unwrap(), 3);
+        assert_eq!(&buf[..3], "v22".as_bytes());
+
+        let stats = obj_box.cache_stats().unwrap();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_epoch_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
 
-    use crate::bindings::{daos_otype_t_DAOS_OT_MULTI_HASHED, OC_UNKNOWN};
-    use crate::daos_pool::DaosPool;
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
 
-    const TEST_POOL_NAME: &str = "pool1";
-    const TEST_CONT_NAME: &str = "cont1";
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .expect("Failed to create object");
+
+        let dkey = vec![1u8];
+        let akey = vec![0u8];
+        let none = DaosTxn::txn_none();
+        obj.update_async(&none, CondFlags::empty(), dkey.clone(), akey.clone(), b"v1")
+            .await
+            .unwrap();
+        let res = obj
+            .fetch_with_epoch_async(&none, CondFlags::empty(), dkey.clone(), akey.clone(), 16)
+            .await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+
+        let txn = DaosTxn::open_async(cont.as_ref(), 0).await.unwrap();
+        let value = obj
+            .fetch_with_epoch_async(&txn, CondFlags::empty(), dkey.clone(), akey.clone(), 16)
+            .await
+            .unwrap();
+        assert_eq!(value.data, b"v1".to_vec());
+        assert!(value.epoch > 0);
+        assert_eq!(txn.epoch().unwrap(), value.epoch);
+        txn.close_async().await.unwrap();
+    }
 
-    #[test]
-    fn test_create_sync() {
+    #[cfg(feature = "hedged-fetch")]
+    #[tokio::test]
+    async fn test_fetch_hedged() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
         pool.connect().expect("Failed to connect to pool");
 
@@ -1109,22 +8196,87 @@ mod tests {
         cont.connect(&pool).expect("Failed to connect to container");
 
         let cont: Arc<DaosContainer> = Arc::from(cont);
-        let allocator = Arc::from(DaosSyncOidAllocator::new(cont.clone()).unwrap());
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
 
         let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
-        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let cid: daos_oclass_id_t = crate::bindings::OC_RP_2G1;
         let hints: daos_oclass_hints_t = 0;
         let args = 0;
 
-        let result = DaosObject::create(cont.as_ref(), allocator, otype, cid, hints, args);
-
+        let result =
+            DaosObject::create_async(cont.as_ref(), allocator.clone(), otype, cid, hints, args)
+                .await;
         assert!(result.is_ok());
-        let _obj_box = result.unwrap();
-        // Assert obj_box is created correctly
+        let obj_box = result.unwrap();
+
+        let txn = DaosTxn::txn_none();
+        let dkey = vec![1u8];
+        let akey = vec![0u8];
+        let res = obj_box
+            .update_async(
+                &txn,
+                CondFlags::empty(),
+                dkey.clone(),
+                akey.clone(),
+                "hedged".as_bytes(),
+            )
+            .await;
+        assert!(res.is_ok());
+
+        // a zero delay always sends the second fetch, and the first one
+        // rotates over both replicas
+        for delay in [Duration::ZERO, Duration::ZERO, Duration::from_secs(5)] {
+            let res = obj_box
+                .fetch_hedged_async(
+                    &txn,
+                    CondFlags::empty(),
+                    dkey.clone(),
+                    akey.clone(),
+                    64,
+                    delay,
+                )
+                .await;
+            assert_eq!(res.unwrap(), "hedged".as_bytes());
+        }
+
+        let res = obj_box
+            .fetch_hedged_async(
+                &txn,
+                CondFlags::empty(),
+                dkey.clone(),
+                akey.clone(),
+                2,
+                Duration::ZERO,
+            )
+            .await;
+        assert!(matches!(
+            res,
+            Err(DaosError::Truncated {
+                actual_size: 6,
+                written: 0,
+                ..
+            })
+        ));
+
+        // a single copy has nothing to hedge against
+        let plain = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            otype,
+            crate::bindings::OC_S1,
+            hints,
+            args,
+        )
+        .await
+        .unwrap();
+        let res = plain
+            .fetch_hedged_async(&txn, CondFlags::empty(), dkey, akey, 64, Duration::ZERO)
+            .await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
     }
 
-    #[test]
-    fn test_update_sync() {
+    #[tokio::test]
+    async fn test_update_many_objects() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
         pool.connect().expect("Failed to connect to pool");
 
@@ -1132,40 +8284,68 @@ mod tests {
         cont.connect(&pool).expect("Failed to connect to container");
 
         let cont: Arc<DaosContainer> = Arc::from(cont);
-        let allocator = Arc::from(DaosSyncOidAllocator::new(cont.clone()).unwrap());
+        let allocator: Arc<DaosAsyncOidAllocator> =
+            Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
 
         let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
         let cid: daos_oclass_id_t = OC_UNKNOWN;
         let hints: daos_oclass_hints_t = 0;
         let args = 0;
 
-        let result = DaosObject::create(cont.as_ref(), allocator, otype, cid, hints, args);
+        let mut objs = Vec::new();
+        for _ in 0..2 {
+            let result =
+                DaosObject::create_async(cont.as_ref(), allocator.clone(), otype, cid, hints, args)
+                    .await;
+            assert!(result.is_ok());
+            objs.push(Arc::<DaosObject>::from(result.unwrap()));
+        }
 
-        assert!(result.is_ok());
-        let obj_box = result.unwrap();
+        let mut closed = DaosObject::new(
+            DaosObjectId { lo: 0, hi: 0 },
+            DaosHandle { cookie: 0 },
+            None,
+            Default::default(),
+        );
+        closed.handle.take();
+        let closed = Arc::new(closed);
+
+        let updates = vec![
+            (objs[0].clone(), vec![1u8], vec![0u8], b"first".to_vec()),
+            (closed.clone(), vec![1u8], vec![0u8], b"lost".to_vec()),
+            (objs[1].clone(), vec![1u8], vec![0u8], b"second".to_vec()),
+        ];
+        let failures = update_many_objects_async(updates, DaosFanOutPolicy::BestEffort)
+            .await
+            .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, 1);
+        assert_eq!(
+            failures[0].1.context().unwrap().op,
+            "update_many_objects_async"
+        );
 
         let txn = DaosTxn::txn_none();
-        let dkey = vec![0u8, 1u8, 2u8, 3u8];
-        let akey = vec![0u8];
-        let data = "something".as_bytes();
-        let result = obj_box.update(
-            &txn,
-            DAOS_COND_DKEY_INSERT as u64,
-            dkey.clone(),
-            akey.clone(),
-            data,
-        );
-        assert!(result.is_ok());
-        // Assert update operation is successful
+        for (obj, expected) in objs.iter().zip(["first", "second"]) {
+            let res = obj
+                .fetch_async(
+                    &txn,
+                    CondFlags::empty(),
+                    vec![1u8],
+                    vec![0u8],
+                    &mut [0u8; 16],
+                )
+                .await;
+            assert_eq!(res.unwrap(), expected.len());
+        }
 
-        let res = obj_box.fetch(&txn, DAOS_COND_DKEY_FETCH as u64, dkey, akey, 16);
-        assert!(res.is_ok());
-        let read = res.unwrap();
-        assert_eq!(String::from_utf8(read).unwrap(), "something");
+        let updates = vec![(closed.clone(), vec![1u8], vec![0u8], b"lost".to_vec())];
+        let res = update_many_objects_async(updates, DaosFanOutPolicy::AllSuccess).await;
+        assert!(matches!(res, Err(DaosError::NoHandle(_))));
     }
 
     #[tokio::test]
-    async fn test_create_async() {
+    async fn test_update_objects_atomic() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
         pool.connect().expect("Failed to connect to pool");
 
@@ -1173,23 +8353,62 @@ mod tests {
         cont.connect(&pool).expect("Failed to connect to container");
 
         let cont: Arc<DaosContainer> = Arc::from(cont);
-        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let allocator: Arc<DaosAsyncOidAllocator> =
+            Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
 
         let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
         let cid: daos_oclass_id_t = OC_UNKNOWN;
         let hints: daos_oclass_hints_t = 0;
-        let args = 0;
+        let objs: Vec<Arc<DaosObject>> =
+            DaosObject::create_many_async(cont.as_ref(), allocator, 2, otype, cid, hints, 0)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(Arc::from)
+                .collect();
+
+        let updates = vec![
+            (objs[0].clone(), vec![1u8], vec![0u8], b"debit".to_vec()),
+            (objs[1].clone(), vec![1u8], vec![0u8], b"credit".to_vec()),
+        ];
+        update_objects_atomic_async(cont.as_ref(), &updates)
+            .await
+            .unwrap();
 
-        let result =
-            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
+        let txn = DaosTxn::txn_none();
+        for (obj, expected) in objs.iter().zip(["debit", "credit"]) {
+            let mut buf = [0u8; 16];
+            let size = obj
+                .fetch_async(&txn, CondFlags::empty(), [1u8], [0u8], &mut buf)
+                .await
+                .unwrap();
+            assert_eq!(&buf[..size], expected.as_bytes());
+        }
 
-        assert!(result.is_ok());
-        let _obj_box = result.unwrap();
-        // Assert obj_box is created correctly
+        // a failing update leaves every object as it was
+        let mut closed = DaosObject::new(
+            DaosObjectId { lo: 0, hi: 0 },
+            DaosHandle { cookie: 0 },
+            None,
+            Default::default(),
+        );
+        closed.handle.take();
+        let updates = vec![
+            (objs[0].clone(), vec![1u8], vec![0u8], b"lost".to_vec()),
+            (Arc::new(closed), vec![1u8], vec![0u8], b"lost".to_vec()),
+        ];
+        let res = update_objects_atomic_async(cont.as_ref(), &updates).await;
+        assert!(matches!(res, Err(DaosError::NoHandle(_))));
+        let mut buf = [0u8; 16];
+        let size = objs[0]
+            .fetch_async(&txn, CondFlags::empty(), [1u8], [0u8], &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(&buf[..size], b"debit");
     }
 
     #[tokio::test]
-    async fn test_open_async() {
+    async fn test_remove_async() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
         pool.connect().expect("Failed to connect to pool");
 
@@ -1209,16 +8428,28 @@ mod tests {
         assert!(result.is_ok());
         let obj_box = result.unwrap();
 
-        let oid = obj_box.oid;
+        let txn = DaosTxn::txn_none();
+        let res = obj_box
+            .update_async(
+                &txn,
+                CondFlags::empty(),
+                vec![1u8],
+                vec![0u8],
+                "value".as_bytes(),
+            )
+            .await;
+        assert!(res.is_ok());
 
-        let result = DaosObject::open_async(&cont, oid, /* read_only */ true).await;
-        assert!(result.is_ok());
-        let _obj = result.unwrap();
-        // Assert obj is opened correctly
+        let res = obj_box.remove_async(&txn, vec![1u8], Some(vec![0u8])).await;
+        assert!(res.unwrap());
+        let res = obj_box.remove_async(&txn, vec![1u8], Some(vec![0u8])).await;
+        assert!(!res.unwrap());
+        let res = obj_box.remove_async(&txn, vec![2u8], None).await;
+        assert!(!res.unwrap());
     }
 
     #[tokio::test]
-    async fn test_punch_async() {
+    async fn test_vectored_async() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
         pool.connect().expect("Failed to connect to pool");
 
@@ -1233,19 +8464,75 @@ mod tests {
         let hints: daos_oclass_hints_t = 0;
         let args = 0;
 
-        let result =
-            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
-        assert!(result.is_ok());
-        let obj_box = result.unwrap();
+        let obj_box = DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args)
+            .await
+            .expect("Failed to create object");
 
         let txn = DaosTxn::txn_none();
-        let result = obj_box.punch_async(&txn).await;
-        assert!(result.is_ok());
-        // Assert punch operation is successful
+        let header = b"hdr:".to_vec();
+        let body = vec![5u8; 100];
+        obj_box
+            .update_vectored_async(
+                &txn,
+                CondFlags::empty(),
+                b"dkey".to_vec(),
+                b"akey".to_vec(),
+                &[IoSlice::new(&header), IoSlice::new(&body)],
+            )
+            .await
+            .expect("Failed to update object");
+
+        let mut buf = vec![0u8; 128];
+        let size = obj_box
+            .fetch_async(
+                &txn,
+                CondFlags::empty(),
+                b"dkey".to_vec(),
+                b"akey".to_vec(),
+                &mut buf,
+            )
+            .await
+            .unwrap();
+        assert_eq!(&buf[..size], [header.as_slice(), body.as_slice()].concat());
+
+        let mut head = [0u8; 4];
+        let mut tail = vec![0u8; 200];
+        let size = obj_box
+            .fetch_vectored_async(
+                &txn,
+                CondFlags::empty(),
+                b"dkey".to_vec(),
+                b"akey".to_vec(),
+                &mut [IoSliceMut::new(&mut head), IoSliceMut::new(&mut tail)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(size, 104);
+        assert_eq!(&head, b"hdr:");
+        assert_eq!(&tail[..100], body.as_slice());
+
+        let mut small = [0u8; 8];
+        let res = obj_box
+            .fetch_vectored_async(
+                &txn,
+                CondFlags::empty(),
+                b"dkey".to_vec(),
+                b"akey".to_vec(),
+                &mut [IoSliceMut::new(&mut small)],
+            )
+            .await;
+        assert!(matches!(
+            res,
+            Err(DaosError::Truncated {
+                actual_size: 104,
+                written: 0,
+                ..
+            })
+        ));
     }
 
     #[tokio::test]
-    async fn test_fetch_async() {
+    async fn test_update_many_async() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
         pool.connect().expect("Failed to connect to pool");
 
@@ -1260,25 +8547,47 @@ mod tests {
         let hints: daos_oclass_hints_t = 0;
         let args = 0;
 
-        let result =
-            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
-        assert!(result.is_ok());
-        let obj_box = result.unwrap();
+        let obj_box = DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args)
+            .await
+            .expect("Failed to create object");
 
         let txn = DaosTxn::txn_none();
-        let flags = 0;
-        let dkey = vec![0u8, 1u8, 2u8, 3u8];
-        let akey = vec![0u8];
-        let mut buf = vec![0u8; 1024];
-        let result = obj_box
-            .fetch_async(&txn, flags, dkey, akey, buf.as_mut_slice())
+        let updates: Vec<DaosBatchUpdate> = (0u8..10)
+            .map(|i| (vec![i], vec![0u8], vec![i; 64]))
+            .collect();
+        let results = obj_box
+            .update_many_async(&txn, CondFlags::empty(), updates, 3)
+            .await
+            .expect("Failed to update object");
+        assert_eq!(results.len(), 10);
+        assert!(results.iter().all(|res| res.is_ok()));
+        for i in 0u8..10 {
+            let value = obj_box
+                .fetch(&txn, CondFlags::empty(), vec![i], vec![0u8], 64)
+                .unwrap();
+            assert_eq!(value, vec![i; 64]);
+        }
+
+        // the existing dkey fails the insert, the new one goes in
+        let updates = vec![
+            (vec![0u8], vec![0u8], b"again".to_vec()),
+            (vec![10u8], vec![0u8], b"new".to_vec()),
+        ];
+        let results = obj_box
+            .update_many_async(&txn, CondFlags::DKEY_INSERT, updates, 2)
+            .await
+            .expect("Failed to update object");
+        assert!(matches!(results[0], Err(DaosError::Exist(_))));
+        assert!(results[1].is_ok());
+
+        let res = obj_box
+            .update_many_async(&txn, CondFlags::empty(), Vec::new(), 0)
             .await;
-        assert!(result.is_ok());
-        // Assert fetched data is correct
+        assert!(matches!(res, Err(DaosError::Inval(_))));
     }
 
     #[tokio::test]
-    async fn test_update_async() {
+    async fn test_update_akeys_async() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
         pool.connect().expect("Failed to connect to pool");
 
@@ -1293,45 +8602,151 @@ mod tests {
         let hints: daos_oclass_hints_t = 0;
         let args = 0;
 
-        let result =
-            DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args).await;
-        assert!(result.is_ok());
-        let obj_box = result.unwrap();
+        let obj_box = DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args)
+            .await
+            .expect("Failed to create object");
 
         let txn = DaosTxn::txn_none();
-        let dkey = "async_update".as_bytes().to_vec();
-        let akey = vec![0u8];
-        let data = "some_something".as_bytes();
-        let result = obj_box
-            .update_async(
-                &txn,
-                DAOS_COND_DKEY_INSERT as u64,
-                dkey.clone(),
-                akey.clone(),
-                data,
-            )
+        let dkey = b"row".to_vec();
+        let akeys: Vec<DaosAkeyUpdate> = vec![
+            (b"a".to_vec(), CondFlags::empty(), b"1".to_vec()),
+            (b"b".to_vec(), CondFlags::empty(), b"2".to_vec()),
+        ];
+        obj_box
+            .update_akeys_async(&txn, CondFlags::empty(), dkey.clone(), akeys)
+            .await
+            .expect("Failed to update akeys");
+
+        // b exists and is updated, c is new and inserted, in one update
+        let akeys: Vec<DaosAkeyUpdate> = vec![
+            (b"b".to_vec(), CondFlags::AKEY_UPDATE, b"3".to_vec()),
+            (b"c".to_vec(), CondFlags::AKEY_INSERT, b"4".to_vec()),
+        ];
+        obj_box
+            .update_akeys_async(&txn, CondFlags::empty(), dkey.clone(), akeys)
+            .await
+            .expect("Failed to update akeys");
+
+        let akeys: Vec<DaosAkeyFetch> = vec![
+            (b"a".to_vec(), CondFlags::empty()),
+            (b"b".to_vec(), CondFlags::AKEY_FETCH),
+            (b"c".to_vec(), CondFlags::empty()),
+            (b"d".to_vec(), CondFlags::empty()),
+        ];
+        let values = obj_box
+            .fetch_akeys_async(&txn, CondFlags::empty(), dkey.clone(), akeys, 16)
+            .await
+            .expect("Failed to fetch akeys");
+        assert_eq!(
+            values,
+            vec![
+                Some(b"1".to_vec()),
+                Some(b"3".to_vec()),
+                Some(b"4".to_vec()),
+                None
+            ]
+        );
+
+        // inserting an existing akey fails the whole update
+        let akeys: Vec<DaosAkeyUpdate> = vec![
+            (b"a".to_vec(), CondFlags::AKEY_INSERT, b"5".to_vec()),
+            (b"e".to_vec(), CondFlags::AKEY_INSERT, b"6".to_vec()),
+        ];
+        let res = obj_box
+            .update_akeys_async(&txn, CondFlags::empty(), dkey.clone(), akeys)
             .await;
-        assert!(result.is_ok());
+        assert!(matches!(res, Err(DaosError::Exist(_))));
 
-        let mut buf = vec![0u8, 32];
+        let akeys: Vec<DaosAkeyFetch> = vec![(b"d".to_vec(), CondFlags::AKEY_FETCH)];
         let res = obj_box
-            .fetch_async(
-                &txn,
-                DAOS_COND_DKEY_FETCH as u64,
-                dkey,
-                akey,
-                buf.as_mut_slice(),
-            )
+            .fetch_akeys_async(&txn, CondFlags::empty(), dkey.clone(), akeys, 16)
             .await;
-        // Assert update operation is successful
-        assert!(res.is_ok());
-        let out_size = res.unwrap();
-        buf.resize(out_size, 0);
-        assert_eq!(String::from_utf8(buf).unwrap(), "some_something");
+        assert!(matches!(res, Err(DaosError::NonExist(_))));
+
+        let punched = obj_box
+            .punch_akeys_with_flags_async(&txn, CondFlags::PUNCH, dkey.clone(), &[b"d".to_vec()])
+            .await
+            .expect("Failed to punch akeys");
+        assert!(!punched);
+        let punched = obj_box
+            .punch_akeys_with_flags_async(&txn, CondFlags::PUNCH, dkey.clone(), &[b"a".to_vec()])
+            .await
+            .expect("Failed to punch akeys");
+        assert!(punched);
     }
 
     #[tokio::test]
-    async fn test_list_dkey_async() {
+    async fn test_punch_keys() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let obj_box = DaosObject::create_async(cont.as_ref(), allocator, otype, cid, hints, args)
+            .await
+            .expect("Failed to create object");
+
+        let txn = DaosTxn::txn_none();
+        for dkey in 1u8..5 {
+            for akey in 0u8..2 {
+                obj_box
+                    .update_async(&txn, CondFlags::empty(), vec![dkey], vec![akey], b"value")
+                    .await
+                    .expect("Failed to update object");
+            }
+        }
+
+        obj_box
+            .punch_dkeys_async(&txn, &[vec![1u8], vec![2u8]])
+            .await
+            .expect("Failed to punch dkeys");
+        obj_box
+            .punch_dkeys(&txn, &[vec![3u8]])
+            .expect("Failed to punch dkeys");
+        assert!(obj_box
+            .fetch(&txn, CondFlags::empty(), vec![2u8], vec![0u8], 16)
+            .unwrap()
+            .is_empty());
+
+        obj_box
+            .punch_akeys_async(&txn, vec![4u8], &[vec![0u8]])
+            .await
+            .expect("Failed to punch akeys");
+        assert!(obj_box
+            .fetch(&txn, CondFlags::empty(), vec![4u8], vec![0u8], 16)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            obj_box
+                .fetch(&txn, CondFlags::empty(), vec![4u8], vec![1u8], 16)
+                .unwrap(),
+            b"value"
+        );
+        obj_box
+            .punch_akeys(&txn, vec![4u8], &[vec![1u8]])
+            .expect("Failed to punch akeys");
+        assert!(obj_box
+            .fetch(&txn, CondFlags::empty(), vec![4u8], vec![1u8], 16)
+            .unwrap()
+            .is_empty());
+
+        let res = obj_box.punch_dkeys_async(&txn, &[]).await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        obj_box.punch(&txn).expect("Failed to punch object");
+    }
+
+    #[cfg(feature = "bytes")]
+    #[tokio::test]
+    async fn test_bytes_async() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
         pool.connect().expect("Failed to connect to pool");
 
@@ -1352,66 +8767,100 @@ mod tests {
         let obj_box = result.unwrap();
 
         let txn = DaosTxn::txn_none();
-        let dkey = "string1".as_bytes().to_vec();
-        let akey = vec![0u8];
-        let data = vec![1u8; 256];
+        let data = Bytes::from_static(b"shared");
         let res = obj_box
-            .update_async(
-                &txn,
-                DAOS_COND_DKEY_INSERT as u64,
-                dkey,
-                akey,
-                data.as_slice(),
-            )
+            .update_bytes_async(&txn, CondFlags::empty(), vec![1u8], vec![0u8], data.clone())
             .await;
         assert!(res.is_ok());
 
-        let dkey = "very_long_string2".as_bytes().to_vec();
-        let akey = vec![0u8];
-        let data = vec![2u8; 256];
-        let res = obj_box
-            .update_async(
-                &txn,
-                DAOS_COND_DKEY_INSERT as u64,
-                dkey,
-                akey,
-                data.as_slice(),
-            )
-            .await;
-        assert!(res.is_ok());
+        let first = obj_box
+            .fetch_bytes_async(&txn, CondFlags::empty(), vec![1u8], vec![0u8], 64)
+            .await
+            .unwrap();
+        let second = obj_box
+            .fetch_bytes_async(&txn, CondFlags::empty(), vec![1u8], vec![0u8], 64)
+            .await
+            .unwrap();
+        assert_eq!(first, data);
+        assert_eq!(second, data);
+
+        // appended after what buf already holds
+        let mut buf = BytesMut::from(&b"head:"[..]);
+        let size = obj_box
+            .fetch_into_async(&txn, CondFlags::empty(), [1u8], [0u8], &mut buf, 64)
+            .await
+            .unwrap();
+        assert_eq!(size, data.len());
+        assert_eq!(&buf[..], b"head:shared");
+
+        let mut out = vec![MaybeUninit::<u8>::uninit(); 64];
+        let size = obj_box
+            .fetch_uninit_async(&txn, CondFlags::empty(), [1u8], [0u8], &mut out)
+            .await
+            .unwrap();
+        assert_eq!(unsafe { assume_init(&out, size) }, b"shared");
+    }
 
-        let key_lst = DaosKeyList::new();
-        let result = obj_box.list_dkey_async(&txn, key_lst).await;
-        assert!(result.is_ok());
-        // Assert list dkey operation is successful
-        let key_lst = result.unwrap();
+    #[tokio::test]
+    async fn test_punch_objects_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
 
-        let off = (0u32, 0u32);
-        let res = key_lst.get_key(off);
-        let off = match res {
-            Ok((key, off)) => {
-                assert_eq!(key, "string1".as_bytes());
-                off
-            }
-            Err(_) => {
-                assert!(false);
-                (0u32, 0u32)
-            }
-        };
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
 
-        let res = key_lst.get_key(off);
-        let off = match res {
-            Ok((key, off)) => {
-                assert_eq!(key, "very_long_string2".as_bytes());
-                off
-            }
-            Err(_) => {
-                assert!(false);
-                (0u32, 0u32)
-            }
-        };
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator: Arc<DaosAsyncOidAllocator> =
+            Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
 
-        let res = key_lst.get_key(off);
-        assert!(res.is_err());
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let args = 0;
+
+        let res =
+            DaosObject::create_many_async(cont.as_ref(), allocator, 4, otype, cid, hints, args)
+                .await;
+        let mut objs: Vec<Arc<DaosObject>> = res.unwrap().into_iter().map(Arc::from).collect();
+
+        let txn = DaosTxn::txn_none();
+        for obj in objs.iter() {
+            let res = obj
+                .update_async(
+                    &txn,
+                    CondFlags::empty(),
+                    vec![1u8],
+                    vec![0u8],
+                    "value".as_bytes(),
+                )
+                .await;
+            assert!(res.is_ok());
+        }
+
+        let mut closed = DaosObject::new(
+            DaosObjectId { lo: 0, hi: 0 },
+            DaosHandle { cookie: 0 },
+            None,
+            Default::default(),
+        );
+        closed.handle.take();
+        objs.insert(2, Arc::new(closed));
+
+        let failures = punch_objects_async(&objs, &txn).await;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, 2);
+
+        for obj in objs.iter().filter(|obj| obj.get_handle().is_some()) {
+            let res = obj
+                .fetch_async(
+                    &txn,
+                    CondFlags::empty(),
+                    vec![1u8],
+                    vec![0u8],
+                    &mut [0u8; 16],
+                )
+                .await;
+            assert_eq!(res.unwrap(), 0);
+        }
     }
 }
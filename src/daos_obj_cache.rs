@@ -0,0 +1,282 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Open object handles of one container, kept around so a workload touching
+// the same objects over and over doesn't pay a daos_obj_open and
+// daos_obj_close per request. Up to capacity handles stay open, the least
+// recently used one is evicted when a miss opens one more. Handles are
+// shared: an evicted handle a caller still holds stays open until the last
+// clone is dropped, the cache only drops its own reference.
+
+use crate::daos_cont::DaosContainer;
+use crate::daos_obj::{DaosObject, DaosObjectOpenOpts};
+use crate::daos_pool::DaosObjectId;
+use crate::error::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ObjectCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+}
+
+struct ObjectCacheEntries {
+    handles: HashMap<DaosObjectId, (Arc<DaosObject>, u64)>,
+    // last use tick to oid, the first entry is the eviction candidate
+    lru: BTreeMap<u64, DaosObjectId>,
+    tick: u64,
+}
+
+impl ObjectCacheEntries {
+    fn touch(&mut self, oid: DaosObjectId) -> Option<Arc<DaosObject>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (obj, last) = self.handles.get_mut(&oid)?;
+        self.lru.remove(last);
+        *last = tick;
+        self.lru.insert(tick, oid);
+        Some(obj.clone())
+    }
+
+    fn remove(&mut self, oid: DaosObjectId) -> Option<Arc<DaosObject>> {
+        let (obj, last) = self.handles.remove(&oid)?;
+        self.lru.remove(&last);
+        Some(obj)
+    }
+}
+
+pub struct ObjectCache {
+    cont: Arc<DaosContainer>,
+    opts: DaosObjectOpenOpts,
+    capacity: usize,
+    entries: Mutex<ObjectCacheEntries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl ObjectCache {
+    // Every handle is opened with opts. A capacity of 0 keeps nothing open,
+    // each get opens a handle only the caller holds.
+    pub fn new(cont: Arc<DaosContainer>, capacity: usize, opts: DaosObjectOpenOpts) -> Self {
+        ObjectCache {
+            cont,
+            opts,
+            capacity,
+            entries: Mutex::new(ObjectCacheEntries {
+                handles: HashMap::new(),
+                lru: BTreeMap::new(),
+                tick: 0,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    pub fn container(&self) -> &Arc<DaosContainer> {
+        &self.cont
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // The open handle of oid, opened on a miss. Two tasks missing on the
+    // same oid at once both open it, the one inserting second closes its
+    // own handle and shares the first.
+    pub async fn get_async(&self, oid: DaosObjectId) -> Result<Arc<DaosObject>> {
+        if let Some(obj) = self.entries.lock().unwrap().touch(oid) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(obj);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let obj: Arc<DaosObject> =
+            Arc::from(DaosObject::open_async(self.cont.as_ref(), oid, self.opts.read_only).await?);
+        if self.capacity == 0 {
+            return Ok(obj);
+        }
+
+        let mut evicted = Vec::new();
+        let obj = {
+            let mut entries = self.entries.lock().unwrap();
+            match entries.touch(oid) {
+                Some(raced) => {
+                    evicted.push(obj);
+                    raced
+                }
+                None => {
+                    let tick = entries.tick;
+                    entries.handles.insert(oid, (obj.clone(), tick));
+                    entries.lru.insert(tick, oid);
+                    while entries.handles.len() > self.capacity {
+                        let (_, victim) = entries.lru.pop_first().unwrap();
+                        let (victim, _) = entries.handles.remove(&victim).unwrap();
+                        self.evictions.fetch_add(1, Ordering::Relaxed);
+                        evicted.push(victim);
+                    }
+                    obj
+                }
+            }
+        };
+        close_detached(evicted);
+        Ok(obj)
+    }
+
+    // Drop the cached handle of oid, e.g. after punching the object.
+    pub fn invalidate(&self, oid: DaosObjectId) -> bool {
+        let obj = self.entries.lock().unwrap().remove(oid);
+        let found = obj.is_some();
+        close_detached(obj.into_iter().collect());
+        found
+    }
+
+    pub fn clear(&self) {
+        let evicted = {
+            let mut entries = self.entries.lock().unwrap();
+            entries.lru.clear();
+            entries.handles.drain().map(|(_, (obj, _))| obj).collect()
+        };
+        close_detached(evicted);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().handles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn stats(&self) -> ObjectCacheStats {
+        ObjectCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: self.len(),
+        }
+    }
+}
+
+impl fmt::Debug for ObjectCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObjectCache")
+            .field("capacity", &self.capacity)
+            .field("read_only", &self.opts.read_only)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+// Closing a handle waits for the tokens still using it, keep that off the
+// caller's task. Outside a runtime the handles are closed right here.
+fn close_detached(objs: Vec<Arc<DaosObject>>) {
+    if objs.is_empty() {
+        return;
+    }
+    match tokio::runtime::Handle::try_current() {
+        Ok(rt) => {
+            rt.spawn_blocking(move || drop(objs));
+        }
+        Err(_) => drop(objs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::{
+        daos_oclass_hints_t, daos_oclass_id_t, daos_otype_t_DAOS_OT_MULTI_HASHED, OC_UNKNOWN,
+    };
+    use crate::daos_obj::{CondFlags, DaosObjAsyncOps, DaosObjSyncOps};
+    use crate::daos_oid_allocator::DaosAsyncOidAllocator;
+    use crate::daos_pool::DaosPool;
+    use crate::daos_txn::DaosTxn;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test]
+    async fn test_object_cache() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator: Arc<DaosAsyncOidAllocator> =
+            Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+
+        let otype = daos_otype_t_DAOS_OT_MULTI_HASHED;
+        let cid: daos_oclass_id_t = OC_UNKNOWN;
+        let hints: daos_oclass_hints_t = 0;
+        let objs = DaosObject::create_many_async(cont.as_ref(), allocator, 3, otype, cid, hints, 0)
+            .await
+            .unwrap();
+        let oids: Vec<DaosObjectId> = objs.iter().map(|obj| obj.oid).collect();
+        drop(objs);
+
+        let cache = ObjectCache::new(cont.clone(), 2, DaosObjectOpenOpts { read_only: false });
+        let first = cache.get_async(oids[0]).await.unwrap();
+        let again = cache.get_async(oids[0]).await.unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(first.oid, oids[0]);
+
+        // oids[0] is the most recently used when oids[2] comes in, so
+        // oids[1] is the one evicted
+        cache.get_async(oids[1]).await.unwrap();
+        cache.get_async(oids[0]).await.unwrap();
+        cache.get_async(oids[2]).await.unwrap();
+        assert_eq!(
+            cache.stats(),
+            ObjectCacheStats {
+                hits: 2,
+                misses: 3,
+                evictions: 1,
+                entries: 2,
+            }
+        );
+        cache.get_async(oids[0]).await.unwrap();
+        assert_eq!(cache.stats().hits, 3);
+
+        // an invalidated handle still held by a caller keeps working
+        assert!(cache.invalidate(oids[0]));
+        assert!(!cache.invalidate(oids[0]));
+        let txn = DaosTxn::txn_none();
+        first
+            .update(&txn, CondFlags::empty(), [1u8], [0u8], b"cached")
+            .unwrap();
+
+        cache.clear();
+        assert!(cache.is_empty());
+        let obj = cache.get_async(oids[0]).await.unwrap();
+        assert!(!Arc::ptr_eq(&obj, &first));
+        let mut buf = [0u8; 16];
+        let size = obj
+            .fetch_async(&txn, CondFlags::empty(), [1u8], [0u8], &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(&buf[..size], b"cached");
+    }
+}
@@ -0,0 +1,158 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Object classes by name, e.g. "EC_2P1GX", as the daos tool and config
+// files spell them. libdaos registers its class table in daos_init, so
+// name lookups need a live DaosRuntime.
+
+use crate::bindings::{
+    daos_oclass_id2name, daos_oclass_id_t, daos_oclass_name2id, daos_oclass_names_list,
+};
+use crate::error::{DaosError, Result};
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::str::FromStr;
+
+// MAX_OBJ_CLASS_NAME_LEN of daos_obj_class.h
+const CLASS_NAME_LEN: usize = 64;
+const CLASS_LIST_LEN: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectClass(daos_oclass_id_t);
+
+impl ObjectClass {
+    // let the container properties pick the class
+    pub const UNKNOWN: ObjectClass = ObjectClass(crate::bindings::OC_UNKNOWN);
+    pub const S1: ObjectClass = ObjectClass(crate::bindings::OC_S1);
+    pub const S2: ObjectClass = ObjectClass(crate::bindings::OC_S2);
+    pub const SX: ObjectClass = ObjectClass(crate::bindings::OC_SX);
+    pub const RP_2G1: ObjectClass = ObjectClass(crate::bindings::OC_RP_2G1);
+    pub const RP_2GX: ObjectClass = ObjectClass(crate::bindings::OC_RP_2GX);
+    pub const RP_3G1: ObjectClass = ObjectClass(crate::bindings::OC_RP_3G1);
+    pub const RP_3GX: ObjectClass = ObjectClass(crate::bindings::OC_RP_3GX);
+    pub const EC_2P1G1: ObjectClass = ObjectClass(crate::bindings::OC_EC_2P1G1);
+    pub const EC_2P1GX: ObjectClass = ObjectClass(crate::bindings::OC_EC_2P1GX);
+    pub const EC_4P2G1: ObjectClass = ObjectClass(crate::bindings::OC_EC_4P2G1);
+    pub const EC_4P2GX: ObjectClass = ObjectClass(crate::bindings::OC_EC_4P2GX);
+    pub const EC_8P2G1: ObjectClass = ObjectClass(crate::bindings::OC_EC_8P2G1);
+    pub const EC_8P2GX: ObjectClass = ObjectClass(crate::bindings::OC_EC_8P2GX);
+
+    pub fn from_id(id: daos_oclass_id_t) -> Self {
+        ObjectClass(id)
+    }
+
+    pub fn id(&self) -> daos_oclass_id_t {
+        self.0
+    }
+
+    // Fails with Inval for a name libdaos doesn't know.
+    pub fn from_name(name: &str) -> Result<Self> {
+        let c_name = CString::new(name)
+            .map_err(|_| DaosError::inval("object class name contains a nul byte"))?;
+        let id = unsafe { daos_oclass_name2id(c_name.as_ptr()) };
+        if id <= 0 {
+            return Err(DaosError::inval(format!("unknown object class {:?}", name)));
+        }
+        Ok(ObjectClass(id as daos_oclass_id_t))
+    }
+
+    pub fn name(&self) -> Result<String> {
+        let mut buf = vec![0u8; CLASS_NAME_LEN];
+        let ret = unsafe { daos_oclass_id2name(self.0, buf.as_mut_ptr() as *mut _) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_oclass_id2name",
+                ret,
+                format!("unknown object class id {:#x}", self.0),
+            ));
+        }
+        let name = CStr::from_bytes_until_nul(&buf)
+            .map_err(|_| DaosError::inval("object class name is not terminated"))?;
+        Ok(name.to_string_lossy().into_owned())
+    }
+
+    // Every class this libdaos supports.
+    pub fn list() -> Result<Vec<ObjectClass>> {
+        let mut buf = vec![0u8; CLASS_LIST_LEN];
+        loop {
+            let len = unsafe { daos_oclass_names_list(buf.len(), buf.as_mut_ptr() as *mut _) };
+            if len < 0 {
+                return Err(DaosError::from_call(
+                    "daos_oclass_names_list",
+                    len as i32,
+                    "Failed to list object classes",
+                ));
+            }
+            if len as usize >= buf.len() {
+                buf.resize(len as usize + 1, 0);
+                continue;
+            }
+            let names = String::from_utf8_lossy(&buf[..len as usize]).into_owned();
+            return names
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(ObjectClass::from_name)
+                .collect();
+        }
+    }
+}
+
+impl From<ObjectClass> for daos_oclass_id_t {
+    fn from(class: ObjectClass) -> Self {
+        class.0
+    }
+}
+
+// The class name, or its id when libdaos has none for it.
+impl fmt::Display for ObjectClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Ok(name) => write!(f, "{}", name),
+            Err(_) => write!(f, "{:#x}", self.0),
+        }
+    }
+}
+
+impl FromStr for ObjectClass {
+    type Err = DaosError;
+
+    fn from_str(s: &str) -> Result<ObjectClass> {
+        ObjectClass::from_name(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daos_runtime::DaosRuntime;
+
+    #[test]
+    fn test_oclass_names() {
+        let _rt = DaosRuntime::init().expect("Failed to init DAOS");
+
+        let class: ObjectClass = "EC_2P1GX".parse().unwrap();
+        assert_eq!(class, ObjectClass::EC_2P1GX);
+        assert_eq!(class.name().unwrap(), "EC_2P1GX");
+        assert_eq!(ObjectClass::SX.to_string(), "SX");
+        assert!(ObjectClass::from_name("NO_SUCH_CLASS").is_err());
+
+        let classes = ObjectClass::list().unwrap();
+        assert!(classes.contains(&ObjectClass::SX));
+        assert!(classes.contains(&ObjectClass::RP_2GX));
+    }
+}
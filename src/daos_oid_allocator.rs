@@ -15,27 +15,83 @@
 //  along with this program.  If not, see <https://www.gnu.org/licenses/>.
 //
 
-use crate::bindings::OID_FMT_INTR_BITS;
-use crate::daos_cont::{DaosContainer, DaosContainerSyncOps};
+use crate::bindings::{
+    daos_oclass_hints_t, daos_oclass_id_t, daos_otype_t, daos_otype_t_DAOS_OT_MULTI_HASHED,
+    OID_FMT_INTR_BITS,
+};
+use crate::daos_cont::DaosContainer;
+use crate::daos_meta::MetaObject;
 use crate::daos_obj::{
-    DaosObjAsyncOps, DaosObjSyncOps, DaosObject, DAOS_COND_DKEY_FETCH, DAOS_COND_DKEY_INSERT,
-    DAOS_COND_DKEY_UPDATE,
+    CondFlags, DaosObjAsyncOps, DaosObjSyncOps, DaosObject, DAOS_OC_HINTS_NONE, DAOS_OC_UNKNOWN,
 };
 use crate::daos_pool::DaosObjectId;
 use crate::daos_txn::{DaosTxn, DaosTxnAsyncOps, DaosTxnSyncOps};
-use std::io::{Error, ErrorKind, Result};
+use crate::error::{DaosError, Result};
 use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 const OID_BATCH_SIZE: u128 = 1u128 << 10;
 const OID_BATCH_CURSOR_KEY: &str = "OID_BATCH_CURSOR";
 const OID_BATCH_CURSOR_START: u128 = 1024;
 
+// Describes the CO_ROOTS objects created by DaosContainer::init_roots_async,
+// laid out as described by MetaObject.
+#[derive(Debug, Clone, Copy)]
+pub struct DaosOidAllocatorConfig {
+    pub otype: daos_otype_t,
+    pub cid: daos_oclass_id_t,
+    pub hints: daos_oclass_hints_t,
+    pub args: u32,
+    pub nr_roots: usize,
+}
+
+impl Default for DaosOidAllocatorConfig {
+    fn default() -> Self {
+        DaosOidAllocatorConfig {
+            otype: daos_otype_t_DAOS_OT_MULTI_HASHED,
+            cid: DAOS_OC_UNKNOWN,
+            hints: DAOS_OC_HINTS_NONE,
+            args: 0,
+            nr_roots: 1,
+        }
+    }
+}
+
+// Oids above this bound overflow the bits DAOS reserves for object class.
+const OID_LIMIT: u128 = 1u128 << (128 - OID_FMT_INTR_BITS);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaosOidAllocatorStats {
+    // oids cached locally and not handed out yet
+    pub range: Range<u128>,
+    pub remaining: u128,
+    // oids handed out and batches fetched by this allocator instance
+    pub allocated: u64,
+    pub refills: u64,
+    // oids left before the container wide space is exhausted
+    pub headroom: u128,
+}
+
+impl DaosOidAllocatorStats {
+    fn new(range: &Range<u128>, allocated: &AtomicU64, refills: &AtomicU64) -> Self {
+        DaosOidAllocatorStats {
+            range: range.clone(),
+            remaining: range.end.saturating_sub(range.start),
+            allocated: allocated.load(Ordering::Relaxed),
+            refills: refills.load(Ordering::Relaxed),
+            headroom: OID_LIMIT.saturating_sub(range.start),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct DaosAsyncOidAllocator {
     range: tokio::sync::Mutex<Range<u128>>,
     cont: Arc<DaosContainer>,
     meta_obj: Box<DaosObject>,
+    allocated: AtomicU64,
+    refills: AtomicU64,
 }
 
 #[derive(Debug)]
@@ -43,36 +99,43 @@ pub struct DaosSyncOidAllocator {
     range: std::sync::Mutex<Range<u128>>,
     cont: Arc<DaosContainer>,
     meta_obj: Box<DaosObject>,
+    allocated: AtomicU64,
+    refills: AtomicU64,
 }
 
 impl DaosAsyncOidAllocator {
     pub fn new(cont: Arc<DaosContainer>) -> Result<Box<Self>> {
-        let prop = cont.query_prop()?;
-        let co_roots = prop.get_co_roots()?;
-        let meta_oid = co_roots[0];
-
-        let obj = DaosObject::open(cont.as_ref(), meta_oid, false)?;
+        let obj = MetaObject::load(cont.as_ref())?.open_root(cont.as_ref(), false)?;
 
         Ok(Box::new(DaosAsyncOidAllocator {
             range: tokio::sync::Mutex::new(0..0),
             cont: cont,
             meta_obj: obj,
+            allocated: AtomicU64::new(0),
+            refills: AtomicU64::new(0),
         }))
     }
 
+    pub async fn stats(&self) -> DaosOidAllocatorStats {
+        let range = self.range.lock().await;
+        DaosOidAllocatorStats::new(&range, &self.allocated, &self.refills)
+    }
+
     pub async fn allocate(&self) -> Result<DaosObjectId> {
         let mut range = self.range.lock().await;
         if range.start >= range.end {
             drop(range);
             let new_range = self.allocate_oid_batch().await?;
+            self.refills.fetch_add(1, Ordering::Relaxed);
             let mut range = self.range.lock().await;
             *range = new_range;
             if (range.start >> (128 - OID_FMT_INTR_BITS)) != 0 {
-                Err(Error::new(ErrorKind::Other, "No more OIDs available"))
+                Err(DaosError::no_space("No more OIDs available"))
             } else {
                 let hi = range.start >> 64;
                 let lo = range.start & 0xFFFF_FFFF_FFFF_FFFF;
                 range.start += 1;
+                self.allocated.fetch_add(1, Ordering::Relaxed);
                 Ok(DaosObjectId {
                     hi: hi as u64,
                     lo: lo as u64,
@@ -80,11 +143,12 @@ impl DaosAsyncOidAllocator {
             }
         } else {
             if (range.start >> (128 - OID_FMT_INTR_BITS)) != 0 {
-                Err(Error::new(ErrorKind::Other, "No more OIDs available"))
+                Err(DaosError::no_space("No more OIDs available"))
             } else {
                 let hi = range.start >> 64;
                 let lo = range.start & 0xFFFF_FFFF_FFFF_FFFF;
                 range.start += 1;
+                self.allocated.fetch_add(1, Ordering::Relaxed);
                 Ok(DaosObjectId {
                     hi: hi as u64,
                     lo: lo as u64,
@@ -96,16 +160,16 @@ impl DaosAsyncOidAllocator {
     async fn allocate_oid_batch(&self) -> Result<Range<u128>> {
         let txn = DaosTxn::open_async(self.cont.as_ref(), 0).await?;
 
-        let dkey = OID_BATCH_CURSOR_KEY.as_bytes().to_vec();
-        let akey = vec![0u8];
+        let dkey = OID_BATCH_CURSOR_KEY.as_bytes();
+        let akey = [0u8];
         let mut data = vec![0u8; 32];
         let res = self
             .meta_obj
             .fetch_async(
                 &txn,
-                DAOS_COND_DKEY_FETCH as u64,
-                dkey.clone(),
-                akey.clone(),
+                CondFlags::DKEY_FETCH,
+                dkey,
+                akey,
                 data.as_mut_slice(),
             )
             .await;
@@ -121,9 +185,9 @@ impl DaosAsyncOidAllocator {
                 .meta_obj
                 .update_async(
                     &DaosTxn::txn_none(),
-                    DAOS_COND_DKEY_INSERT as u64,
-                    dkey.clone(),
-                    akey.clone(),
+                    CondFlags::DKEY_INSERT,
+                    dkey,
+                    akey,
                     &init_val,
                 )
                 .await;
@@ -139,9 +203,9 @@ impl DaosAsyncOidAllocator {
                 .meta_obj
                 .fetch_async(
                     txn.as_ref(),
-                    DAOS_COND_DKEY_FETCH as u64,
-                    dkey.clone(),
-                    akey.clone(),
+                    CondFlags::DKEY_FETCH,
+                    dkey,
+                    akey,
                     data.as_mut_slice(),
                 )
                 .await;
@@ -161,9 +225,9 @@ impl DaosAsyncOidAllocator {
         let res = self.meta_obj
             .update_async(
                 txn.as_ref(),
-                DAOS_COND_DKEY_UPDATE as u64,
-                dkey.clone(),
-                akey.clone(),
+                CondFlags::DKEY_UPDATE,
+                dkey,
+                akey,
                 bytes,
             )
             .await;
@@ -187,24 +251,28 @@ impl DaosAsyncOidAllocator {
 
 impl DaosSyncOidAllocator {
     pub fn new(cont: Arc<DaosContainer>) -> Result<Box<Self>> {
-        let prop = cont.query_prop()?;
-        let co_roots = prop.get_co_roots()?;
-        let meta_oid = co_roots[0];
-
-        let obj = DaosObject::open(cont.as_ref(), meta_oid, false)?;
+        let obj = MetaObject::load(cont.as_ref())?.open_root(cont.as_ref(), false)?;
 
         Ok(Box::new(DaosSyncOidAllocator {
             range: std::sync::Mutex::new(0..0),
             cont: cont,
             meta_obj: obj,
+            allocated: AtomicU64::new(0),
+            refills: AtomicU64::new(0),
         }))
     }
 
+    pub fn stats(&self) -> DaosOidAllocatorStats {
+        let range = self.range.lock().unwrap();
+        DaosOidAllocatorStats::new(&range, &self.allocated, &self.refills)
+    }
+
     pub fn allocate(&self) -> Result<DaosObjectId> {
         let range = self.range.lock().unwrap();
         let mut range = if range.start >= range.end {
             drop(range);
             let new_range = self.allocate_oid_batch()?;
+            self.refills.fetch_add(1, Ordering::Relaxed);
             let mut range = self.range.lock().unwrap();
             *range = new_range;
             range
@@ -213,11 +281,12 @@ impl DaosSyncOidAllocator {
         };
 
         if (range.start >> (128 - OID_FMT_INTR_BITS)) != 0 {
-            Err(Error::new(ErrorKind::Other, "No more OIDs available"))
+            Err(DaosError::no_space("No more OIDs available"))
         } else {
             let hi = range.start >> 64;
             let lo = range.start & 0xFFFF_FFFF_FFFF_FFFF;
             range.start += 1;
+            self.allocated.fetch_add(1, Ordering::Relaxed);
             Ok(DaosObjectId {
                 hi: hi as u64,
                 lo: lo as u64,
@@ -228,15 +297,15 @@ impl DaosSyncOidAllocator {
     fn allocate_oid_batch(&self) -> Result<Range<u128>> {
         let txn = DaosTxn::open(self.cont.as_ref(), 0)?;
 
-        let dkey = OID_BATCH_CURSOR_KEY.as_bytes().to_vec();
-        let akey = vec![0u8];
+        let dkey = OID_BATCH_CURSOR_KEY.as_bytes();
+        let akey = [0u8];
         let res = self
             .meta_obj
             .fetch(
                 &txn,
-                DAOS_COND_DKEY_FETCH as u64,
-                dkey.clone(),
-                akey.clone(),
+                CondFlags::DKEY_FETCH,
+                dkey,
+                akey,
                 32,
             );
 
@@ -248,9 +317,9 @@ impl DaosSyncOidAllocator {
                 .meta_obj
                 .update(
                     &DaosTxn::txn_none(),
-                    DAOS_COND_DKEY_INSERT as u64,
-                    dkey.clone(),
-                    akey.clone(),
+                    CondFlags::DKEY_INSERT,
+                    dkey,
+                    akey,
                     &data,
                 );
             if res.is_err() {
@@ -272,9 +341,9 @@ impl DaosSyncOidAllocator {
                 .meta_obj
                 .fetch(
                     txn.as_ref(),
-                    DAOS_COND_DKEY_FETCH as u64,
-                    dkey.clone(),
-                    akey.clone(),
+                    CondFlags::DKEY_FETCH,
+                    dkey,
+                    akey,
                     32,
                 )?;
             (txn, u128::from_le_bytes(res.try_into().unwrap()))
@@ -286,9 +355,9 @@ impl DaosSyncOidAllocator {
         self.meta_obj
             .update(
                 txn.as_ref(),
-                DAOS_COND_DKEY_UPDATE as u64,
-                dkey.clone(),
-                akey.clone(),
+                CondFlags::DKEY_UPDATE,
+                dkey,
+                akey,
                 bytes,
             )?;
 
@@ -300,3 +369,38 @@ impl DaosSyncOidAllocator {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daos_pool::DaosPool;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test]
+    async fn test_async_allocator_stats() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = DaosAsyncOidAllocator::new(cont.clone()).unwrap();
+
+        let stats = allocator.stats().await;
+        assert_eq!(stats.remaining, 0);
+        assert_eq!(stats.allocated, 0);
+        assert_eq!(stats.refills, 0);
+
+        allocator.allocate().await.unwrap();
+        allocator.allocate().await.unwrap();
+
+        let stats = allocator.stats().await;
+        assert_eq!(stats.allocated, 2);
+        assert_eq!(stats.refills, 1);
+        assert_eq!(stats.remaining, OID_BATCH_SIZE - 2);
+        assert!(stats.headroom > stats.remaining);
+    }
+}
@@ -0,0 +1,285 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Lists the objects of a container through its object index table (OIT),
+// which libdaos only keeps for snapshots taken with
+// DaosContainerAsyncOps::create_oit_snapshot_async.
+
+use crate::bindings::{
+    daos_anchor_is_eof, daos_anchor_t, daos_oit_close, daos_oit_list, daos_oit_open,
+    DAOS_ANCHOR_BUF_MAX,
+};
+use crate::daos_cont::DaosContainer;
+use crate::daos_event::DaosEventQueue;
+use crate::daos_pool::{DaosHandle, DaosObjectId};
+use crate::error::{DaosError, Result};
+use std::ptr;
+use std::time::Duration;
+
+const OIT_PAGE_OIDS: usize = 256;
+
+// Oids of the table, filled one page per call to DaosOit::list_async and
+// resumable from its anchor like DaosKeyList.
+#[derive(Debug)]
+pub struct DaosOitList {
+    anchor: Box<daos_anchor_t>,
+    oids: Vec<DaosObjectId>,
+    nr: usize,
+}
+
+impl DaosOitList {
+    pub fn new() -> Box<Self> {
+        Self::with_capacity(OIT_PAGE_OIDS)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Box<Self> {
+        Box::new(DaosOitList {
+            anchor: Box::new(daos_anchor_t {
+                da_type: 0,
+                da_shard: 0,
+                da_flags: 0,
+                da_sub_anchors: 0,
+                da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
+            }),
+            oids: vec![DaosObjectId::default(); capacity.max(1)],
+            nr: 0,
+        })
+    }
+
+    // Rewind to the first oid so the list can be reused.
+    pub fn reset(&mut self) {
+        *self.anchor = daos_anchor_t {
+            da_type: 0,
+            da_shard: 0,
+            da_flags: 0,
+            da_sub_anchors: 0,
+            da_buf: [0; DAOS_ANCHOR_BUF_MAX as usize],
+        };
+        self.nr = 0;
+    }
+
+    pub fn reach_end(&self) -> bool {
+        daos_anchor_is_eof(self.anchor.as_ref())
+    }
+
+    // Oids of the last page.
+    pub fn oids(&self) -> &[DaosObjectId] {
+        &self.oids[..self.nr]
+    }
+}
+
+pub struct DaosOit {
+    handle: Option<DaosHandle>,
+    event_queue: DaosEventQueue,
+    deadline: Option<Duration>,
+    epoch: u64,
+}
+
+impl DaosOit {
+    // Opens the table of the snapshot at epoch.
+    pub async fn open_async(cont: &DaosContainer, epoch: u64) -> Result<Self> {
+        let cont_hdl = cont
+            .get_handle()
+            .ok_or_else(|| DaosError::no_handle("empty container handle"))?;
+        let eq = cont
+            .get_event_queue()
+            .cloned()
+            .ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+        let deadline = cont.get_deadlines().metadata;
+
+        let mut event = eq.create_event()?;
+        let rx = event.register_callback()?;
+        let mut oh = Box::new(DaosHandle { cookie: 0u64 });
+        let ret = unsafe { daos_oit_open(cont_hdl, epoch, oh.as_mut(), event.as_mut()) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_oit_open",
+                ret,
+                "Failed to open DAOS object index table",
+            ));
+        }
+        let ret = event.wait(rx, deadline).await?;
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_oit_open",
+                ret,
+                "async open object index table failed",
+            ));
+        }
+        Ok(DaosOit {
+            handle: Some(*oh),
+            event_queue: eq,
+            deadline,
+            epoch,
+        })
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    // Fetches the next page of oids into oit_lst. Call until
+    // oit_lst.reach_end().
+    pub async fn list_async(&self, oit_lst: Box<DaosOitList>) -> Result<Box<DaosOitList>> {
+        let oh = self
+            .handle
+            .ok_or_else(|| DaosError::no_handle("object index table is closed"))?;
+        let mut oit_lst = oit_lst;
+        if oit_lst.reach_end() {
+            oit_lst.nr = 0;
+            return Ok(oit_lst);
+        }
+
+        let mut event = self.event_queue.create_event()?;
+        let rx = event.register_callback()?;
+        let mut nr = oit_lst.oids.len() as u32;
+        let ret = unsafe {
+            daos_oit_list(
+                oh,
+                oit_lst.oids.as_mut_ptr(),
+                &mut nr,
+                oit_lst.anchor.as_mut(),
+                event.as_mut(),
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_oit_list",
+                ret,
+                "Failed to list DAOS object index table",
+            ));
+        }
+        let ret = event.wait(rx, self.deadline).await?;
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_oit_list",
+                ret,
+                "async list object index table failed",
+            ));
+        }
+        oit_lst.nr = (nr as usize).min(oit_lst.oids.len());
+        Ok(oit_lst)
+    }
+
+    // Every oid of the table, for containers small enough to hold them at
+    // once. Larger ones are better paged through with list_async.
+    pub async fn list_all_async(&self) -> Result<Vec<DaosObjectId>> {
+        let mut oids = Vec::new();
+        let mut oit_lst = DaosOitList::new();
+        while !oit_lst.reach_end() {
+            oit_lst = self.list_async(oit_lst).await?;
+            oids.extend_from_slice(oit_lst.oids());
+        }
+        Ok(oids)
+    }
+
+    pub fn close(&mut self) -> Result<()> {
+        if let Some(oh) = self.handle {
+            let ret = unsafe { daos_oit_close(oh, ptr::null_mut()) };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_oit_close",
+                    ret,
+                    "Failed to close DAOS object index table",
+                ));
+            }
+            self.handle.take();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for DaosOit {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            eprintln!("Failed to drop DAOS object index table: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daos_cont::DaosContainerAsyncOps;
+    use crate::daos_obj::{
+        CondFlags, DaosObjAsyncOps, DaosObject, DAOS_OC_HINTS_NONE, DAOS_OC_UNKNOWN,
+        DAOS_OT_MULTI_HASHED,
+    };
+    use crate::daos_oid_allocator::DaosAsyncOidAllocator;
+    use crate::daos_pool::DaosPool;
+    use crate::daos_txn::DaosTxn;
+    use std::sync::Arc;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test]
+    async fn test_list_oit() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        cont.connect(&pool).expect("Failed to connect to container");
+        let cont = Arc::new(cont);
+
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            DAOS_OT_MULTI_HASHED,
+            DAOS_OC_UNKNOWN,
+            DAOS_OC_HINTS_NONE,
+            0,
+        )
+        .await
+        .expect("Failed to create object");
+        let txn = DaosTxn::txn_none();
+        obj.update_async(
+            &txn,
+            CondFlags::empty(),
+            b"dkey".to_vec(),
+            b"akey".to_vec(),
+            b"value",
+        )
+        .await
+        .expect("Failed to update object");
+
+        let epoch = cont.create_oit_snapshot_async().await.unwrap();
+        let oit = DaosOit::open_async(&cont, epoch)
+            .await
+            .expect("Failed to open OIT");
+        assert_eq!(oit.epoch(), epoch);
+        let oids = oit.list_all_async().await.expect("Failed to list OIT");
+        assert!(oids
+            .iter()
+            .any(|oid| oid.lo == obj.oid.lo && oid.hi == obj.oid.hi));
+
+        let mut paged = Vec::new();
+        let mut oit_lst = DaosOitList::with_capacity(1);
+        while !oit_lst.reach_end() {
+            oit_lst = oit.list_async(oit_lst).await.unwrap();
+            assert!(oit_lst.oids().len() <= 1);
+            paged.extend_from_slice(oit_lst.oids());
+        }
+        assert_eq!(paged.len(), oids.len());
+        oit_lst.reset();
+        assert!(!oit_lst.reach_end());
+
+        drop(oit);
+        cont.destroy_snapshot_async(epoch).await.unwrap();
+    }
+}
@@ -15,58 +15,332 @@
  *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+use crate::bindings;
+#[cfg(feature = "mgmt")]
 use crate::bindings::{
-    daos_handle_t, daos_init, daos_obj_id_t, daos_pool_connect2, daos_pool_disconnect, DAOS_PC_RW,
+    d_rank_list_free, d_rank_list_t, daos_mgmt_list_pools, daos_mgmt_pool_info_t,
+    daos_pool_props_DAOS_PROP_PO_LABEL, daos_prop_alloc, daos_prop_entry_set_str, daos_prop_free,
+    daos_prop_t,
 };
-use std::ffi::CString;
-use std::sync::Once;
-use std::{
-    io::{Error, ErrorKind, Result},
-    option::Option,
-    ptr,
+use crate::bindings::{daos_handle_t, daos_obj_id_t};
+#[cfg(feature = "libdaos")]
+use crate::bindings::{
+    daos_pool_connect2, daos_pool_disconnect, daos_pool_get_attr,
+    daos_pool_info_bit_DPI_REBUILD_STATUS, daos_pool_info_bit_DPI_SPACE, daos_pool_info_t,
+    daos_pool_list_attr, daos_pool_query, daos_pool_query_target, daos_pool_set_attr,
+    daos_rebuild_state_t_DRS_COMPLETED, daos_rebuild_state_t_DRS_IN_PROGRESS, daos_space,
+    daos_target_info_t, DAOS_MEDIA_NVME, DAOS_MEDIA_SCM,
 };
+#[cfg(feature = "libdaos")]
+use crate::daos_cont::DaosContainer;
+#[cfg(feature = "libdaos")]
+use crate::daos_event::{DaosEvent, DaosEventQueue, DaosEventQueueConfig};
+#[cfg(feature = "libdaos")]
+use crate::daos_meta::{MetaObject, META_USER_SLOTS};
+#[cfg(feature = "libdaos")]
+use crate::daos_obj::{CondFlags, DaosObjAsyncOps, DaosObject};
+#[cfg(feature = "libdaos")]
+use crate::daos_runtime::DaosRuntime;
+use crate::daos_txn::DaosTxn;
+use crate::error::{DaosError, Result};
+use std::ffi::CString;
+use std::fmt;
+use std::future::Future;
+use std::mem;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{option::Option, ptr};
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
 
 pub type DaosHandle = daos_handle_t;
 pub type DaosObjectId = daos_obj_id_t;
 
-static INIT_DAOS: Once = Once::new();
+#[cfg(feature = "libdaos")]
+pub const DAOS_PC_RO: u32 = crate::bindings::DAOS_PC_RO;
+#[cfg(feature = "libdaos")]
+pub const DAOS_PC_RW: u32 = crate::bindings::DAOS_PC_RW;
+#[cfg(feature = "libdaos")]
+pub const DAOS_PC_EX: u32 = crate::bindings::DAOS_PC_EX;
+
+// dkey and akey of the fetch warm_up_async sends to each root object
+#[cfg(feature = "libdaos")]
+const WARM_UP_KEY: &[u8] = b"__warm_up";
+
+// Formats an optional handle as its hex cookie, e.g. `0x1a2b` or `None`.
+pub(crate) struct HandleFmt(pub(crate) Option<DaosHandle>);
+
+impl fmt::Debug for HandleFmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(hdl) => write!(f, "{:#x}", hdl.cookie),
+            None => write!(f, "None"),
+        }
+    }
+}
+
+#[cfg(feature = "libdaos")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebuildState {
+    NotStarted,
+    InProgress,
+    Completed,
+}
+
+// Space is in bytes, summed over all targets of the pool.
+#[cfg(feature = "libdaos")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolInfo {
+    pub scm_total: u64,
+    pub scm_free: u64,
+    pub nvme_total: u64,
+    pub nvme_free: u64,
+    pub total_targets: u32,
+    pub disabled_targets: u32,
+    pub rebuild_state: RebuildState,
+    // errno of a failed rebuild, 0 otherwise
+    pub rebuild_errno: i32,
+    pub rebuild_objs: u64,
+    pub rebuild_recs: u64,
+}
+
+#[cfg(feature = "libdaos")]
+impl PoolInfo {
+    fn query_bits() -> u64 {
+        (daos_pool_info_bit_DPI_SPACE | daos_pool_info_bit_DPI_REBUILD_STATUS) as u64
+    }
+
+    fn from_raw(info: &daos_pool_info_t) -> Self {
+        let space = &info.pi_space.ps_space;
+        let rebuild = &info.pi_rebuild_st;
+        let state = unsafe { rebuild.__bindgen_anon_1.rs_state } as u32;
+        let rebuild_state = if state == daos_rebuild_state_t_DRS_IN_PROGRESS {
+            RebuildState::InProgress
+        } else if state == daos_rebuild_state_t_DRS_COMPLETED {
+            RebuildState::Completed
+        } else {
+            RebuildState::NotStarted
+        };
+        PoolInfo {
+            scm_total: space.s_total[DAOS_MEDIA_SCM as usize],
+            scm_free: space.s_free[DAOS_MEDIA_SCM as usize],
+            nvme_total: space.s_total[DAOS_MEDIA_NVME as usize],
+            nvme_free: space.s_free[DAOS_MEDIA_NVME as usize],
+            total_targets: info.pi_ntargets,
+            disabled_targets: info.pi_ndisabled,
+            rebuild_state,
+            rebuild_errno: rebuild.rs_errno,
+            rebuild_objs: rebuild.rs_obj_nr,
+            rebuild_recs: rebuild.rs_rec_nr,
+        }
+    }
+}
+
+// What the task started by DaosPool::watch_health last saw.
+#[cfg(feature = "libdaos")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolHealth {
+    // the last successful query, None until the first one
+    pub info: Option<PoolInfo>,
+    // why the last query failed, None when it succeeded
+    pub error: Option<DaosError>,
+}
+
+#[cfg(feature = "libdaos")]
+impl PoolHealth {
+    pub fn is_rebuilding(&self) -> bool {
+        self.info
+            .is_some_and(|info| info.rebuild_state == RebuildState::InProgress)
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.info.is_some_and(|info| info.disabled_targets > 0)
+    }
+
+    // Free share of SCM and NVMe space together, between 0 and 1.
+    pub fn free_ratio(&self) -> Option<f64> {
+        let info = self.info?;
+        let total = info.scm_total + info.nvme_total;
+        if total == 0 {
+            return None;
+        }
+        Some((info.scm_free + info.nvme_free) as f64 / total as f64)
+    }
+}
+
+#[cfg(feature = "libdaos")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetState {
+    Unknown,
+    // excluded from the pool, its data rebuilt elsewhere
+    DownOut,
+    // excluded, rebuild still running
+    Down,
+    // being reintegrated
+    Up,
+    UpIn,
+    New,
+    Drain,
+}
+
+#[cfg(feature = "libdaos")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetInfo {
+    pub state: TargetState,
+    pub scm_total: u64,
+    pub scm_free: u64,
+    pub nvme_total: u64,
+    pub nvme_free: u64,
+}
+
+#[cfg(feature = "libdaos")]
+impl TargetInfo {
+    fn from_raw(info: &daos_target_info_t) -> Self {
+        let state = match info.ta_state {
+            bindings::daos_target_state_t_DAOS_TS_DOWN_OUT => TargetState::DownOut,
+            bindings::daos_target_state_t_DAOS_TS_DOWN => TargetState::Down,
+            bindings::daos_target_state_t_DAOS_TS_UP => TargetState::Up,
+            bindings::daos_target_state_t_DAOS_TS_UP_IN => TargetState::UpIn,
+            bindings::daos_target_state_t_DAOS_TS_NEW => TargetState::New,
+            bindings::daos_target_state_t_DAOS_TS_DRAIN => TargetState::Drain,
+            _ => TargetState::Unknown,
+        };
+        let space: &daos_space = &info.ta_space;
+        TargetInfo {
+            state,
+            scm_total: space.s_total[DAOS_MEDIA_SCM as usize],
+            scm_free: space.s_free[DAOS_MEDIA_SCM as usize],
+            nvme_total: space.s_total[DAOS_MEDIA_NVME as usize],
+            nvme_free: space.s_free[DAOS_MEDIA_NVME as usize],
+        }
+    }
+
+    // Only an UPIN target serves I/O at full redundancy.
+    pub fn is_degraded(&self) -> bool {
+        self.state != TargetState::UpIn
+    }
+}
+
+#[cfg(feature = "libdaos")]
+pub trait DaosPoolSyncOps {
+    fn query(&self) -> Result<PoolInfo>;
+    // State and space of target tgt_idx on engine rank.
+    fn query_target(&self, rank: u32, tgt_idx: u32) -> Result<TargetInfo>;
+    // User attributes stored on the pool, a missing name fails with NonExist.
+    fn get_attr(&self, name: &str) -> Result<Vec<u8>>;
+    fn set_attr(&self, name: &str, value: &[u8]) -> Result<()>;
+    fn list_attrs(&self) -> Result<Vec<String>>;
+}
+
+#[cfg(feature = "libdaos")]
+pub trait DaosPoolAsyncOps {
+    fn query_async(&self) -> impl Future<Output = Result<PoolInfo>> + Send + 'static;
+}
 
+// A container connected by DaosPool::warm_up_async, with the objects of
+// its CO_ROOTS open read-only.
+#[cfg(feature = "libdaos")]
 #[derive(Debug)]
+pub struct WarmContainer {
+    pub cont: Arc<DaosContainer>,
+    // None for a container without CO_ROOTS, see init_roots_async
+    pub meta: Option<MetaObject>,
+    // the meta root first, then every user slot created
+    pub objects: Vec<Arc<DaosObject>>,
+}
+
+#[cfg(feature = "libdaos")]
 pub struct DaosPool {
     pub label: String,
+    // DAOS system to connect in, the default one when None
+    sys: Option<String>,
+    // one of DAOS_PC_RO, DAOS_PC_RW and DAOS_PC_EX
+    flags: u32,
     handle: Option<DaosHandle>,
+    event_queue: Option<DaosEventQueue>,
+    event_queue_config: DaosEventQueueConfig,
+    // keeps libdaos initialized while the pool is around, None until a
+    // failed daos_init is retried by connect()
+    runtime: Option<DaosRuntime>,
 }
 
+#[cfg(feature = "libdaos")]
 impl DaosPool {
     pub fn new(label: &str) -> Self {
-        INIT_DAOS.call_once(|| unsafe {
-            daos_init();
-        });
-
         DaosPool {
             label: label.to_string(),
+            sys: None,
+            flags: DAOS_PC_RW,
             handle: None,
+            event_queue: None,
+            event_queue_config: DaosEventQueueConfig::default(),
+            runtime: DaosRuntime::init().ok(),
         }
     }
 
+    pub(crate) fn get_runtime(&self) -> Option<&DaosRuntime> {
+        self.runtime.as_ref()
+    }
+
+    pub fn builder() -> DaosPoolBuilder {
+        DaosPoolBuilder::default()
+    }
+
+    // Takes effect on the next connect().
+    pub fn set_sys(&mut self, sys: Option<&str>) {
+        self.sys = sys.map(|s| s.to_string());
+    }
+
+    pub fn sys(&self) -> Option<&str> {
+        self.sys.as_deref()
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    // Takes effect for the event queue created by the next connect().
+    pub fn set_event_queue_config(&mut self, config: DaosEventQueueConfig) {
+        self.event_queue_config = config;
+    }
+
     pub(crate) fn get_handle(&self) -> Option<DaosHandle> {
         self.handle.clone()
     }
 
+    pub fn get_event_queue(&self) -> Option<&DaosEventQueue> {
+        self.event_queue.as_ref()
+    }
+
+    fn connected_handle(&self) -> Result<DaosHandle> {
+        self.handle
+            .ok_or_else(|| DaosError::no_handle("Pool is not connected"))
+    }
+
     // Should not be called in async executer like tokio.
     // Consider spawning a new thread to open/close pools.
     pub fn connect(&mut self) -> Result<()> {
         if self.handle.is_some() {
             return Ok(());
         }
+        if self.runtime.is_none() {
+            self.runtime = Some(DaosRuntime::init()?);
+        }
 
         let c_label = CString::new(self.label.clone()).unwrap();
+        let c_sys = match &self.sys {
+            Some(sys) => Some(
+                CString::new(sys.as_str())
+                    .map_err(|_| DaosError::inval("system name contains a NUL byte"))?,
+            ),
+            None => None,
+        };
         let mut poh: DaosHandle = DaosHandle { cookie: 0u64 };
         let res = unsafe {
             daos_pool_connect2(
                 c_label.as_ptr(),
-                ptr::null(),
-                DAOS_PC_RW,
+                c_sys.as_ref().map_or(ptr::null(), |s| s.as_ptr()),
+                self.flags,
                 &mut poh,
                 ptr::null_mut(),
                 ptr::null_mut(),
@@ -74,10 +348,11 @@ impl DaosPool {
         };
         if res == 0 {
             self.handle.replace(poh);
-            Ok(())
+            self.create_eq()
         } else {
-            Err(Error::new(
-                ErrorKind::Other,
+            Err(DaosError::from_call(
+                "daos_pool_connect2",
+                res,
                 "Failed to connect to DAOS pool",
             ))
         }
@@ -92,8 +367,9 @@ impl DaosPool {
                 self.handle.take();
                 Ok(())
             } else {
-                Err(Error::new(
-                    ErrorKind::Other,
+                Err(DaosError::from_call(
+                    "daos_pool_disconnect",
+                    res,
                     "Failed to disconnect from DAOS pool",
                 ))
             }
@@ -101,8 +377,777 @@ impl DaosPool {
             Ok(())
         }
     }
+
+    // Disconnects and connects again. libdaos accepts disconnecting a
+    // handle the engines already evicted; any other failure, like Busy
+    // while containers are still open, is returned with the handle kept.
+    // Blocks like connect().
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.disconnect()?;
+        self.connect()
+    }
+
+    // Spawns a task on the current tokio runtime that queries the pool
+    // every interval and publishes the result whenever it differs from the
+    // previous one. The task stops once every receiver is dropped. It keeps
+    // the handle it started with, so after disconnect() or reconnect() its
+    // queries fail and the error is published; watch again to follow the
+    // new handle.
+    pub fn watch_health(&self, interval: Duration) -> Result<watch::Receiver<PoolHealth>> {
+        let pool_hdl = self.connected_handle()?;
+        let eq = self
+            .get_event_queue()
+            .cloned()
+            .ok_or_else(|| DaosError::no_handle("empty event queue"))?;
+        let rt = tokio::runtime::Handle::try_current()
+            .map_err(|_| DaosError::no_sys("watch_health needs a tokio runtime"))?;
+        if interval.is_zero() {
+            return Err(DaosError::inval("health poll interval must not be zero"));
+        }
+
+        let (tx, rx) = watch::channel(PoolHealth::default());
+        rt.spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = tx.closed() => return,
+                }
+                let res = query_pool_async(Some(pool_hdl), Some(eq.create_event())).await;
+                tx.send_if_modified(|health| {
+                    let next = match res {
+                        Ok(info) => PoolHealth {
+                            info: Some(info),
+                            error: None,
+                        },
+                        Err(e) => PoolHealth {
+                            info: health.info,
+                            error: Some(e),
+                        },
+                    };
+                    if *health == next {
+                        return false;
+                    }
+                    *health = next;
+                    true
+                });
+            }
+        });
+        Ok(rx)
+    }
+
+    // Connects the containers labelled containers and opens their root
+    // objects read-only, reading from each once so that the container, its
+    // event queue and the placement of its roots are ready before the first
+    // request of a service needs them. Fails on the first container that
+    // can't be warmed up.
+    pub async fn warm_up_async(&self, containers: &[&str]) -> Result<Vec<WarmContainer>> {
+        self.connected_handle()?;
+        let mut warm = Vec::with_capacity(containers.len());
+        for label in containers {
+            let mut cont = DaosContainer::new(label);
+            cont.connect_async(self).await?;
+            let meta = match MetaObject::load_async(&cont).await {
+                Ok(meta) => Some(meta),
+                Err(DaosError::NonExist(_)) => None,
+                Err(e) => return Err(e),
+            };
+
+            let mut oids = Vec::new();
+            if let Some(meta) = &meta {
+                oids.extend(meta.root_oid().ok());
+                oids.extend((0..META_USER_SLOTS).filter_map(|slot| meta.user_oid(slot).ok()));
+            }
+            let mut objects = Vec::with_capacity(oids.len());
+            for oid in oids {
+                let obj = DaosObject::open_async(&cont, oid, true).await?;
+                // a miss is enough to fetch the layout and reach the targets
+                obj.fetch_async(
+                    &DaosTxn::txn_none(),
+                    CondFlags::empty(),
+                    WARM_UP_KEY.to_vec(),
+                    WARM_UP_KEY.to_vec(),
+                    &mut [],
+                )
+                .await?;
+                objects.push(Arc::from(obj));
+            }
+            warm.push(WarmContainer {
+                cont: Arc::new(cont),
+                meta,
+                objects,
+            });
+        }
+        Ok(warm)
+    }
+
+    fn create_eq(&mut self) -> Result<()> {
+        if self.event_queue.is_none() {
+            self.event_queue
+                .replace(DaosEventQueue::with_config(&self.event_queue_config)?);
+        }
+        Ok(())
+    }
+}
+
+// The uuid in the canonical form daos_pool_connect2 accepts in place of
+// a label.
+#[cfg(feature = "libdaos")]
+fn uuid_string(uuid: &[u8; 16]) -> String {
+    let hex: Vec<String> = uuid.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex[0..4].concat(),
+        hex[4..6].concat(),
+        hex[6..8].concat(),
+        hex[8..10].concat(),
+        hex[10..16].concat()
+    )
 }
 
+#[cfg(feature = "libdaos")]
+#[derive(Debug, Clone, Default)]
+pub struct DaosPoolBuilder {
+    // label or uuid string, whichever was set last
+    label: Option<String>,
+    sys: Option<String>,
+    flags: Option<u32>,
+    event_queue_config: DaosEventQueueConfig,
+}
+
+#[cfg(feature = "libdaos")]
+impl DaosPoolBuilder {
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = Some(label.to_string());
+        self
+    }
+
+    // Connects by uuid instead of label, e.g. the one DaosPool::create
+    // returned.
+    pub fn uuid(mut self, uuid: &[u8; 16]) -> Self {
+        self.label = Some(uuid_string(uuid));
+        self
+    }
+
+    // DAOS_PC_RW when not set.
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    // Name of the DAOS system the pool lives in, e.g. "daos_server2" on
+    // multi-system deployments.
+    pub fn sys(mut self, sys: &str) -> Self {
+        self.sys = Some(sys.to_string());
+        self
+    }
+
+    pub fn event_queue_config(mut self, config: DaosEventQueueConfig) -> Self {
+        self.event_queue_config = config;
+        self
+    }
+
+    // The pool is not connected yet, see DaosPool::connect.
+    pub fn build(self) -> Result<DaosPool> {
+        let label = self
+            .label
+            .ok_or_else(|| DaosError::inval("pool label is not set"))?;
+        let flags = self.flags.unwrap_or(DAOS_PC_RW);
+        if ![DAOS_PC_RO, DAOS_PC_RW, DAOS_PC_EX].contains(&flags) {
+            return Err(DaosError::inval(format!(
+                "invalid pool connect flags {:#x}",
+                flags
+            )));
+        }
+        let mut pool = DaosPool::new(&label);
+        pool.sys = self.sys;
+        pool.flags = flags;
+        pool.event_queue_config = self.event_queue_config;
+        Ok(pool)
+    }
+
+    // Builds and connects the pool on tokio's blocking pool, so it can be
+    // called from async code unlike DaosPool::connect.
+    pub async fn connect_blocking_offloaded(self) -> Result<DaosPool> {
+        tokio::task::spawn_blocking(move || {
+            let mut pool = self.build()?;
+            pool.connect()?;
+            Ok(pool)
+        })
+        .await
+        .map_err(|_| DaosError::canceled("pool connect task failed"))?
+    }
+}
+
+#[cfg(feature = "libdaos")]
+impl DaosPoolSyncOps for DaosPool {
+    fn query(&self) -> Result<PoolInfo> {
+        if self.handle.is_none() {
+            return Err(DaosError::no_handle("Pool is not connected"));
+        }
+
+        let mut info: daos_pool_info_t = unsafe { mem::zeroed() };
+        info.pi_bits = PoolInfo::query_bits();
+        let ret = unsafe {
+            daos_pool_query(
+                self.handle.unwrap(),
+                ptr::null_mut(),
+                &mut info,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_pool_query",
+                ret,
+                "Failed to query DAOS pool",
+            ));
+        }
+        Ok(PoolInfo::from_raw(&info))
+    }
+
+    fn query_target(&self, rank: u32, tgt_idx: u32) -> Result<TargetInfo> {
+        let poh = self.connected_handle()?;
+
+        let mut info: daos_target_info_t = unsafe { mem::zeroed() };
+        let ret = unsafe { daos_pool_query_target(poh, tgt_idx, rank, &mut info, ptr::null_mut()) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_pool_query_target",
+                ret,
+                "Failed to query DAOS pool target",
+            ));
+        }
+        Ok(TargetInfo::from_raw(&info))
+    }
+
+    fn get_attr(&self, name: &str) -> Result<Vec<u8>> {
+        let poh = self.connected_handle()?;
+        let c_name = attr_name(name)?;
+        let names = [c_name.as_ptr()];
+
+        // a null buffer only reports the value size
+        let mut size = 0usize;
+        let buffers = [ptr::null_mut()];
+        let ret = unsafe {
+            daos_pool_get_attr(
+                poh,
+                1,
+                names.as_ptr(),
+                buffers.as_ptr(),
+                &mut size,
+                ptr::null_mut(),
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_pool_get_attr",
+                ret,
+                "Failed to get pool attribute size",
+            ));
+        }
+
+        let mut value = vec![0u8; size];
+        let buffers = [value.as_mut_ptr() as *mut std::os::raw::c_void];
+        let ret = unsafe {
+            daos_pool_get_attr(
+                poh,
+                1,
+                names.as_ptr(),
+                buffers.as_ptr(),
+                &mut size,
+                ptr::null_mut(),
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_pool_get_attr",
+                ret,
+                "Failed to get pool attribute",
+            ));
+        }
+        value.truncate(size);
+        Ok(value)
+    }
+
+    fn set_attr(&self, name: &str, value: &[u8]) -> Result<()> {
+        let poh = self.connected_handle()?;
+        let c_name = attr_name(name)?;
+        let names = [c_name.as_ptr()];
+        let values = [value.as_ptr() as *const std::os::raw::c_void];
+        let sizes = [value.len()];
+        let ret = unsafe {
+            daos_pool_set_attr(
+                poh,
+                1,
+                names.as_ptr(),
+                values.as_ptr(),
+                sizes.as_ptr(),
+                ptr::null_mut(),
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_pool_set_attr",
+                ret,
+                "Failed to set pool attribute",
+            ));
+        }
+        Ok(())
+    }
+
+    fn list_attrs(&self) -> Result<Vec<String>> {
+        let poh = self.connected_handle()?;
+
+        // names come back NUL separated; retry if the list grew between
+        // the size probe and the actual listing
+        let mut buf: Vec<u8> = Vec::new();
+        loop {
+            let mut size = buf.len();
+            let buf_ptr = if buf.is_empty() {
+                ptr::null_mut()
+            } else {
+                buf.as_mut_ptr() as *mut std::os::raw::c_char
+            };
+            let ret = unsafe { daos_pool_list_attr(poh, buf_ptr, &mut size, ptr::null_mut()) };
+            if ret != 0 {
+                return Err(DaosError::from_call(
+                    "daos_pool_list_attr",
+                    ret,
+                    "Failed to list pool attributes",
+                ));
+            }
+            if size <= buf.len() || size == 0 {
+                buf.truncate(size);
+                break;
+            }
+            buf.resize(size, 0);
+        }
+
+        Ok(buf
+            .split(|b| *b == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect())
+    }
+}
+
+#[cfg(feature = "libdaos")]
+fn attr_name(name: &str) -> Result<CString> {
+    CString::new(name).map_err(|_| DaosError::inval("attribute name contains a NUL byte"))
+}
+
+#[cfg(feature = "libdaos")]
+impl DaosPoolAsyncOps for DaosPool {
+    fn query_async(&self) -> impl Future<Output = Result<PoolInfo>> + Send + 'static {
+        let pool_hdl = self.handle;
+        let ev = self.get_event_queue().map(|e| e.create_event());
+        query_pool_async(pool_hdl, ev)
+    }
+}
+
+#[cfg(feature = "libdaos")]
+async fn query_pool_async(
+    pool_hdl: Option<DaosHandle>,
+    ev: Option<Result<DaosEvent>>,
+) -> Result<PoolInfo> {
+    if pool_hdl.is_none() {
+        return Err(DaosError::no_handle("Pool is not connected"));
+    }
+    if ev.is_none() {
+        return Err(DaosError::no_handle("empty event queue"));
+    }
+    let mut event = ev.unwrap()?;
+
+    let rx = event.register_callback()?;
+
+    // boxed so the buffer stays put while libdaos fills it
+    let mut info: Box<daos_pool_info_t> = Box::new(unsafe { mem::zeroed() });
+    info.pi_bits = PoolInfo::query_bits();
+    let ret = unsafe {
+        daos_pool_query(
+            pool_hdl.unwrap(),
+            ptr::null_mut(),
+            info.as_mut(),
+            ptr::null_mut(),
+            event.as_mut(),
+        )
+    };
+    if ret != 0 {
+        return Err(DaosError::from_call(
+            "daos_pool_query",
+            ret,
+            "Failed to query DAOS pool",
+        ));
+    }
+
+    let ret = event.wait(rx, None).await?;
+    if ret != 0 {
+        return Err(DaosError::from_call(
+            "daos_pool_query",
+            ret,
+            "async query pool failed",
+        ));
+    }
+    Ok(PoolInfo::from_raw(info.as_ref()))
+}
+
+// Pool creation goes through the control plane, which libdaos doesn't
+// expose. libdmg, shipped with the DAOS test packages, wraps the dmg tool
+// for C callers; these are its entry points from daos/tests_lib.h.
+#[cfg(feature = "mgmt")]
+extern "C" {
+    fn dmg_pool_create(
+        dmg_config_file: *const std::os::raw::c_char,
+        uid: u32,
+        gid: u32,
+        grp: *const std::os::raw::c_char,
+        tgts: *const d_rank_list_t,
+        scm_size: u64,
+        nvme_size: u64,
+        prop: *mut daos_prop_t,
+        svc: *mut d_rank_list_t,
+        uuid: *mut u8,
+    ) -> std::os::raw::c_int;
+    fn dmg_pool_destroy(
+        dmg_config_file: *const std::os::raw::c_char,
+        uuid: *const u8,
+        grp: *const std::os::raw::c_char,
+        force: std::os::raw::c_int,
+    ) -> std::os::raw::c_int;
+    fn dmg_pool_exclude(
+        dmg_config_file: *const std::os::raw::c_char,
+        uuid: *const u8,
+        grp: *const std::os::raw::c_char,
+        rank: u32,
+        tgt_idx: std::os::raw::c_int,
+    ) -> std::os::raw::c_int;
+    fn dmg_pool_drain(
+        dmg_config_file: *const std::os::raw::c_char,
+        uuid: *const u8,
+        grp: *const std::os::raw::c_char,
+        rank: u32,
+        tgt_idx: std::os::raw::c_int,
+    ) -> std::os::raw::c_int;
+    fn dmg_pool_reintegrate(
+        dmg_config_file: *const std::os::raw::c_char,
+        uuid: *const u8,
+        grp: *const std::os::raw::c_char,
+        rank: u32,
+        tgt_idx: std::os::raw::c_int,
+    ) -> std::os::raw::c_int;
+    fn geteuid() -> u32;
+    fn getegid() -> u32;
+    fn free(ptr: *mut std::os::raw::c_void);
+}
+
+// Where to reach the control plane, None picks the dmg defaults.
+#[cfg(feature = "mgmt")]
+#[derive(Debug, Clone, Default)]
+pub struct DaosMgmtConfig {
+    pub dmg_config: Option<String>,
+    pub group: Option<String>,
+}
+
+#[cfg(feature = "mgmt")]
+#[derive(Debug, Clone, Default)]
+pub struct DaosPoolCreateOpts {
+    pub scm_size: u64,
+    pub nvme_size: u64,
+    // engines to place the pool on, empty lets the system choose
+    pub ranks: Vec<u32>,
+    pub mgmt: DaosMgmtConfig,
+}
+
+// What to do with a pool target, see DaosPool::update_target.
+#[cfg(feature = "mgmt")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetUpdate {
+    // takes the target out right away, its data is rebuilt elsewhere
+    Exclude,
+    // migrates the data off the target first, then takes it out
+    Drain,
+    // brings an excluded or drained target back in
+    Reintegrate,
+}
+
+// A pool of the DAOS system as reported by list_pools().
+#[cfg(feature = "mgmt")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolEntry {
+    pub uuid: [u8; 16],
+    pub label: Option<String>,
+    // engines running the pool service
+    pub svc_ranks: Vec<u32>,
+}
+
+#[cfg(feature = "mgmt")]
+impl PoolEntry {
+    fn from_raw(info: &daos_mgmt_pool_info_t) -> Self {
+        let label = if info.mgpi_label.is_null() {
+            None
+        } else {
+            let label = unsafe { std::ffi::CStr::from_ptr(info.mgpi_label) };
+            Some(label.to_string_lossy().into_owned())
+        };
+        let svc_ranks = if info.mgpi_svc.is_null() {
+            Vec::new()
+        } else {
+            let svc = unsafe { &*info.mgpi_svc };
+            if svc.rl_ranks.is_null() {
+                Vec::new()
+            } else {
+                unsafe { std::slice::from_raw_parts(svc.rl_ranks, svc.rl_nr as usize) }.to_vec()
+            }
+        };
+        PoolEntry {
+            uuid: info.mgpi_uuid,
+            label,
+            svc_ranks,
+        }
+    }
+
+    // The form DaosPoolBuilder::uuid connects with.
+    pub fn uuid_string(&self) -> String {
+        uuid_string(&self.uuid)
+    }
+}
+
+// Lists the pools of DAOS system sys, the default one when None, through
+// the management service. Blocks like DaosPool::connect.
+#[cfg(feature = "mgmt")]
+pub fn list_pools(sys: Option<&str>) -> Result<Vec<PoolEntry>> {
+    let _runtime = DaosRuntime::init()?;
+
+    let c_sys = opt_cstring(&sys.map(|s| s.to_string()))?;
+    let c_sys = c_sys.as_ref().map_or(ptr::null(), |c| c.as_ptr());
+    loop {
+        let mut npools = 0u64;
+        let ret =
+            unsafe { daos_mgmt_list_pools(c_sys, &mut npools, ptr::null_mut(), ptr::null_mut()) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_mgmt_list_pools",
+                ret,
+                "Failed to count DAOS pools",
+            ));
+        }
+        if npools == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut infos: Vec<daos_mgmt_pool_info_t> = vec![unsafe { mem::zeroed() }; npools as usize];
+        let ret = unsafe {
+            daos_mgmt_list_pools(c_sys, &mut npools, infos.as_mut_ptr(), ptr::null_mut())
+        };
+        // pools were created since they were counted
+        if ret == -(bindings::daos_errno_DER_TRUNC as i32) {
+            continue;
+        }
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_mgmt_list_pools",
+                ret,
+                "Failed to list DAOS pools",
+            ));
+        }
+
+        let infos = &infos[..(npools as usize).min(infos.len())];
+        let pools = infos.iter().map(PoolEntry::from_raw).collect();
+        for info in infos {
+            unsafe {
+                free(info.mgpi_label as *mut std::os::raw::c_void);
+                d_rank_list_free(info.mgpi_svc);
+            }
+        }
+        return Ok(pools);
+    }
+}
+
+#[cfg(feature = "mgmt")]
+fn opt_cstring(s: &Option<String>) -> Result<Option<CString>> {
+    match s {
+        Some(s) => CString::new(s.as_str())
+            .map(Some)
+            .map_err(|_| DaosError::inval("string contains a NUL byte")),
+        None => Ok(None),
+    }
+}
+
+#[cfg(feature = "mgmt")]
+impl DaosPool {
+    // Creates a pool owned by the current user and returns its uuid, the
+    // pool can then be connected to by label with DaosPool::new(label).
+    // Like connect(), this blocks for the whole dmg round trip.
+    pub fn create(label: &str, opts: &DaosPoolCreateOpts) -> Result<[u8; 16]> {
+        let c_label =
+            CString::new(label).map_err(|_| DaosError::inval("label contains a NUL byte"))?;
+        let c_config = opt_cstring(&opts.mgmt.dmg_config)?;
+        let c_group = opt_cstring(&opts.mgmt.group)?;
+
+        let prop = unsafe { daos_prop_alloc(1) };
+        if prop.is_null() {
+            return Err(DaosError::no_mem("Failed to allocate DAOS property"));
+        }
+        let ret = unsafe {
+            let entry = (*prop).dpp_entries;
+            (*entry).dpe_type = daos_pool_props_DAOS_PROP_PO_LABEL;
+            daos_prop_entry_set_str(entry, c_label.as_ptr(), label.len() as u64)
+        };
+        if ret != 0 {
+            unsafe { daos_prop_free(prop) };
+            return Err(DaosError::from_call(
+                "daos_prop_entry_set_str",
+                ret,
+                "Failed to set pool label prop entry",
+            ));
+        }
+
+        let mut ranks = opts.ranks.clone();
+        let tgts = d_rank_list_t {
+            rl_ranks: ranks.as_mut_ptr(),
+            rl_nr: ranks.len() as u32,
+        };
+        let mut uuid = [0u8; 16];
+        let ret = unsafe {
+            dmg_pool_create(
+                c_config.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                geteuid(),
+                getegid(),
+                c_group.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                if ranks.is_empty() { ptr::null() } else { &tgts },
+                opts.scm_size,
+                opts.nvme_size,
+                prop,
+                // the service ranks chosen by the system aren't reported
+                ptr::null_mut(),
+                uuid.as_mut_ptr(),
+            )
+        };
+        unsafe { daos_prop_free(prop) };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "dmg_pool_create",
+                ret,
+                "Failed to create DAOS pool",
+            ));
+        }
+        Ok(uuid)
+    }
+
+    // Changes the state of target tgt_idx on engine rank, None meaning all
+    // targets of the rank. The rebuild it starts runs in the background,
+    // see PoolInfo::rebuild_state. Blocks for the dmg round trip.
+    pub fn update_target(
+        uuid: &[u8; 16],
+        mgmt: &DaosMgmtConfig,
+        op: TargetUpdate,
+        rank: u32,
+        tgt_idx: Option<u32>,
+    ) -> Result<()> {
+        let c_config = opt_cstring(&mgmt.dmg_config)?;
+        let c_group = opt_cstring(&mgmt.group)?;
+        let tgt_idx = match tgt_idx {
+            Some(idx) => std::os::raw::c_int::try_from(idx)
+                .map_err(|_| DaosError::inval(format!("target {} is out of range", idx)))?,
+            None => -1,
+        };
+        let (func, update, msg): (_, unsafe extern "C" fn(_, _, _, _, _) -> _, _) = match op {
+            TargetUpdate::Exclude => (
+                "dmg_pool_exclude",
+                dmg_pool_exclude,
+                "Failed to exclude pool target",
+            ),
+            TargetUpdate::Drain => (
+                "dmg_pool_drain",
+                dmg_pool_drain,
+                "Failed to drain pool target",
+            ),
+            TargetUpdate::Reintegrate => (
+                "dmg_pool_reintegrate",
+                dmg_pool_reintegrate,
+                "Failed to reintegrate pool target",
+            ),
+        };
+        let ret = unsafe {
+            update(
+                c_config.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                uuid.as_ptr(),
+                c_group.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                rank,
+                tgt_idx,
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(func, ret, msg));
+        }
+        Ok(())
+    }
+
+    pub fn exclude_target(
+        uuid: &[u8; 16],
+        mgmt: &DaosMgmtConfig,
+        rank: u32,
+        tgt_idx: Option<u32>,
+    ) -> Result<()> {
+        Self::update_target(uuid, mgmt, TargetUpdate::Exclude, rank, tgt_idx)
+    }
+
+    pub fn drain_target(
+        uuid: &[u8; 16],
+        mgmt: &DaosMgmtConfig,
+        rank: u32,
+        tgt_idx: Option<u32>,
+    ) -> Result<()> {
+        Self::update_target(uuid, mgmt, TargetUpdate::Drain, rank, tgt_idx)
+    }
+
+    pub fn reintegrate_target(
+        uuid: &[u8; 16],
+        mgmt: &DaosMgmtConfig,
+        rank: u32,
+        tgt_idx: Option<u32>,
+    ) -> Result<()> {
+        Self::update_target(uuid, mgmt, TargetUpdate::Reintegrate, rank, tgt_idx)
+    }
+
+    // Destroys the pool, force also evicts connected handles.
+    pub fn destroy(uuid: &[u8; 16], mgmt: &DaosMgmtConfig, force: bool) -> Result<()> {
+        let c_config = opt_cstring(&mgmt.dmg_config)?;
+        let c_group = opt_cstring(&mgmt.group)?;
+        let ret = unsafe {
+            dmg_pool_destroy(
+                c_config.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                uuid.as_ptr(),
+                c_group.as_ref().map_or(ptr::null(), |c| c.as_ptr()),
+                force as std::os::raw::c_int,
+            )
+        };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "dmg_pool_destroy",
+                ret,
+                "Failed to destroy DAOS pool",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "libdaos")]
+impl fmt::Debug for DaosPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DaosPool")
+            .field("label", &self.label)
+            .field("connected", &self.handle.is_some())
+            .field("handle", &HandleFmt(self.handle))
+            .finish()
+    }
+}
+
+#[cfg(feature = "libdaos")]
 impl Drop for DaosPool {
     fn drop(&mut self) {
         let res = self.disconnect();
@@ -115,7 +1160,7 @@ impl Drop for DaosPool {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "libdaos"))]
 mod tests {
     use super::*;
     const TEST_POOL_NAME: &str = "pool1";
@@ -137,6 +1182,68 @@ mod tests {
         assert_eq!(result.is_ok(), true);
     }
 
+    #[test]
+    fn test_daos_pool_builder() {
+        let res = DaosPool::builder().sys("daos_server").build();
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+
+        let mut pool = DaosPool::builder()
+            .label(TEST_POOL_NAME)
+            .sys("daos_server")
+            .build()
+            .unwrap();
+        assert_eq!(pool.label, TEST_POOL_NAME);
+        assert_eq!(pool.sys(), Some("daos_server"));
+        pool.connect().expect("Failed to connect in daos_server");
+        assert!(pool.handle.is_some());
+        pool.disconnect().unwrap();
+
+        // a system that doesn't exist can't be joined
+        pool.set_sys(Some("no_such_system"));
+        assert!(pool.connect().is_err());
+        assert!(pool.handle.is_none());
+
+        let res = DaosPool::builder()
+            .label(TEST_POOL_NAME)
+            .flags(DAOS_PC_RO | DAOS_PC_RW)
+            .build();
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+
+        let mut pool = DaosPool::builder()
+            .label(TEST_POOL_NAME)
+            .flags(DAOS_PC_RO)
+            .build()
+            .unwrap();
+        assert_eq!(pool.flags(), DAOS_PC_RO);
+        pool.connect().expect("Failed to connect read-only");
+        pool.disconnect().unwrap();
+    }
+
+    #[test]
+    fn test_uuid_string() {
+        let uuid = [
+            0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+            0xcd, 0xef,
+        ];
+        assert_eq!(uuid_string(&uuid), "12345678-9abc-def0-0123-456789abcdef");
+        let pool = DaosPool::builder().uuid(&uuid).build().unwrap();
+        assert_eq!(pool.label, "12345678-9abc-def0-0123-456789abcdef");
+    }
+
+    #[tokio::test]
+    async fn test_connect_blocking_offloaded() {
+        let pool = DaosPool::builder()
+            .label(TEST_POOL_NAME)
+            .connect_blocking_offloaded()
+            .await
+            .expect("Failed to connect to pool");
+        assert!(pool.handle.is_some());
+        assert!(pool.query_async().await.is_ok());
+
+        let res = DaosPool::builder().connect_blocking_offloaded().await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+    }
+
     #[test]
     fn test_daos_pool_disconnect() {
         let mut pool = DaosPool::new(TEST_POOL_NAME);
@@ -150,4 +1257,218 @@ mod tests {
         assert_eq!(result.is_ok(), true);
         assert_eq!(pool.handle.is_some(), false);
     }
+
+    #[test]
+    fn test_daos_pool_query() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        assert!(pool.query().is_err());
+
+        pool.connect().expect("Failed to connect to pool");
+        let info = pool.query().expect("Failed to query pool");
+        assert!(info.total_targets > 0);
+        assert!(info.scm_total > 0);
+        assert!(info.scm_free <= info.scm_total);
+        assert!(info.nvme_free <= info.nvme_total);
+    }
+
+    #[test]
+    fn test_daos_pool_query_target() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let info = pool.query_target(0, 0).expect("Failed to query target");
+        assert_eq!(info.state, TargetState::UpIn);
+        assert!(!info.is_degraded());
+        assert!(info.scm_free <= info.scm_total);
+
+        let res = pool.query_target(u32::MAX - 1, 0);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_daos_pool_attrs() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        assert!(pool.get_attr("rust.deploy").is_err());
+
+        pool.connect().expect("Failed to connect to pool");
+        pool.set_attr("rust.deploy", b"rack-7")
+            .expect("Failed to set pool attribute");
+        assert_eq!(pool.get_attr("rust.deploy").unwrap(), b"rack-7".to_vec());
+
+        let names = pool.list_attrs().expect("Failed to list pool attributes");
+        assert!(names.iter().any(|name| name == "rust.deploy"));
+
+        let res = pool.get_attr("rust.missing");
+        assert!(matches!(res, Err(DaosError::NonExist(_))));
+        assert!(pool.set_attr("bad\0name", b"x").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_daos_pool_query_async() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let info = pool.query_async().await.expect("Failed to query pool");
+        let sync_info = pool.query().expect("Failed to query pool");
+        assert_eq!(info.total_targets, sync_info.total_targets);
+        assert_eq!(info.scm_total, sync_info.scm_total);
+    }
+
+    #[tokio::test]
+    async fn test_watch_health() {
+        let idle = DaosPool::new(TEST_POOL_NAME);
+        let res = idle.watch_health(Duration::from_millis(10));
+        assert!(matches!(res, Err(DaosError::NoHandle(_))));
+
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+        let res = pool.watch_health(Duration::ZERO);
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+
+        let mut rx = pool
+            .watch_health(Duration::from_millis(10))
+            .expect("Failed to watch pool");
+        rx.changed().await.expect("watcher stopped");
+        let health = rx.borrow_and_update().clone();
+        assert!(health.error.is_none());
+        let info = health.info.unwrap();
+        assert_eq!(info.total_targets, pool.query().unwrap().total_targets);
+        assert!(health
+            .free_ratio()
+            .is_some_and(|r| (0.0..=1.0).contains(&r)));
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_async() {
+        let idle = DaosPool::new(TEST_POOL_NAME);
+        let res = idle.warm_up_async(&["cont1"]).await;
+        assert!(matches!(res, Err(DaosError::NoHandle(_))));
+
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+        let warm = pool
+            .warm_up_async(&["cont1"])
+            .await
+            .expect("Failed to warm up container");
+        assert_eq!(warm.len(), 1);
+        assert!(warm[0].cont.get_handle().is_some());
+        assert!(warm[0].cont.get_event_queue().is_some());
+        if let Some(meta) = warm[0].meta {
+            let root = meta.root_oid().unwrap();
+            assert_eq!(warm[0].objects[0].oid.lo, root.lo);
+            assert_eq!(warm[0].objects[0].oid.hi, root.hi);
+        }
+        assert!(warm[0].objects.iter().all(|obj| obj.is_read_only()));
+
+        let res = pool.warm_up_async(&["cont1", "no-such-cont"]).await;
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_pool_health() {
+        let mut info = PoolInfo {
+            scm_total: 100,
+            scm_free: 50,
+            nvme_total: 300,
+            nvme_free: 50,
+            total_targets: 8,
+            disabled_targets: 0,
+            rebuild_state: RebuildState::Completed,
+            rebuild_errno: 0,
+            rebuild_objs: 0,
+            rebuild_recs: 0,
+        };
+        let health = PoolHealth {
+            info: Some(info),
+            error: None,
+        };
+        assert_eq!(health.free_ratio(), Some(0.25));
+        assert!(!health.is_rebuilding());
+        assert!(!health.is_degraded());
+
+        info.disabled_targets = 1;
+        info.rebuild_state = RebuildState::InProgress;
+        let health = PoolHealth {
+            info: Some(info),
+            error: None,
+        };
+        assert!(health.is_rebuilding());
+        assert!(health.is_degraded());
+        assert_eq!(PoolHealth::default().free_ratio(), None);
+    }
+
+    #[cfg(feature = "mgmt")]
+    #[test]
+    fn test_daos_pool_create_destroy() {
+        let opts = DaosPoolCreateOpts {
+            scm_size: 1 << 30,
+            ..Default::default()
+        };
+        let uuid = DaosPool::create("rust_mgmt_pool", &opts).expect("Failed to create pool");
+
+        let mut pool = DaosPool::new("rust_mgmt_pool");
+        assert!(pool.connect().is_ok());
+        assert!(pool.disconnect().is_ok());
+
+        let res = DaosPool::destroy(&uuid, &opts.mgmt, false);
+        assert!(res.is_ok());
+    }
+
+    #[cfg(feature = "mgmt")]
+    #[test]
+    fn test_list_pools() {
+        let pools = list_pools(None).expect("Failed to list pools");
+        let entry = pools
+            .iter()
+            .find(|p| p.label.as_deref() == Some(TEST_POOL_NAME))
+            .expect("test pool is not listed");
+        assert!(!entry.svc_ranks.is_empty());
+
+        let mut pool = DaosPool::builder().uuid(&entry.uuid).build().unwrap();
+        assert_eq!(pool.label, entry.uuid_string());
+        pool.connect().expect("Failed to connect by uuid");
+        pool.disconnect().unwrap();
+
+        assert!(list_pools(Some("no_such_system")).is_err());
+    }
+
+    #[cfg(feature = "mgmt")]
+    #[test]
+    fn test_daos_pool_update_target() {
+        let opts = DaosPoolCreateOpts {
+            scm_size: 1 << 30,
+            ..Default::default()
+        };
+        let uuid = DaosPool::create("rust_tgt_pool", &opts).expect("Failed to create pool");
+        let mut pool = DaosPool::new("rust_tgt_pool");
+        pool.connect().expect("Failed to connect to pool");
+
+        DaosPool::exclude_target(&uuid, &opts.mgmt, 0, Some(0)).expect("Failed to exclude");
+        let info = pool.query_target(0, 0).unwrap();
+        assert!(matches!(
+            info.state,
+            TargetState::Down | TargetState::DownOut
+        ));
+        assert!(info.is_degraded());
+
+        DaosPool::reintegrate_target(&uuid, &opts.mgmt, 0, Some(0)).expect("Failed to reintegrate");
+        let info = pool.query_target(0, 0).unwrap();
+        assert!(!matches!(
+            info.state,
+            TargetState::Down | TargetState::DownOut
+        ));
+
+        let res = DaosPool::drain_target(&uuid, &opts.mgmt, 0, Some(u32::MAX));
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+
+        pool.disconnect().unwrap();
+        DaosPool::destroy(&uuid, &opts.mgmt, true).unwrap();
+    }
+
+    #[test]
+    fn test_handle_fmt() {
+        let hdl = DaosHandle { cookie: 0x1a2bu64 };
+        assert_eq!(format!("{:?}", HandleFmt(Some(hdl))), "0x1a2b");
+        assert_eq!(format!("{:?}", HandleFmt(None)), "None");
+    }
 }
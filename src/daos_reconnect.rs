@@ -0,0 +1,317 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::daos_cont::{DaosContainer, DaosDeadlines};
+use crate::daos_pool::DaosPool;
+use crate::error::{DaosError, Result};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DaosReconnectPolicy {
+    // retries of a failed operation, each one after a reconnect
+    pub max_retries: u32,
+    // wait before the first reconnect, doubled for every later one
+    pub backoff: Duration,
+}
+
+impl Default for DaosReconnectPolicy {
+    fn default() -> Self {
+        DaosReconnectPolicy {
+            max_retries: 3,
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+// Opt-in reconnect layer owning a pool and the containers opened through
+// it. When an operation given to run() fails with is_connection_lost(),
+// the pool is connected again, every registered container reopened and the
+// operation retried. Objects opened from the old handles are stale after
+// that, so operations should look their container up with container() and
+// open objects inside the closure, without keeping either past it.
+pub struct DaosReconnectingPool {
+    pool: RwLock<DaosPool>,
+    containers: RwLock<HashMap<String, Arc<DaosContainer>>>,
+    policy: DaosReconnectPolicy,
+    // bumped by every reconnect, so concurrent failures reconnect once
+    generation: AtomicU64,
+    reconnecting: Mutex<()>,
+}
+
+impl DaosReconnectingPool {
+    // Blocks like DaosPool::connect.
+    pub fn connect(label: &str, policy: DaosReconnectPolicy) -> Result<Arc<Self>> {
+        let mut pool = DaosPool::new(label);
+        pool.connect()?;
+        Ok(Arc::new(DaosReconnectingPool {
+            pool: RwLock::new(pool),
+            containers: RwLock::new(HashMap::new()),
+            policy,
+            generation: AtomicU64::new(0),
+            reconnecting: Mutex::new(()),
+        }))
+    }
+
+    // Opens the container once and registers it to be reopened on
+    // reconnect. Blocks like DaosContainer::connect.
+    pub fn open_container(&self, label: &str) -> Result<Arc<DaosContainer>> {
+        if let Some(cont) = self.container(label) {
+            return Ok(cont);
+        }
+        let cont = Arc::new(self.connect_container(label, DaosDeadlines::default())?);
+        let mut containers = self.containers.write().unwrap();
+        Ok(containers.entry(label.to_string()).or_insert(cont).clone())
+    }
+
+    // The container as of the last reconnect.
+    pub fn container(&self, label: &str) -> Option<Arc<DaosContainer>> {
+        self.containers.read().unwrap().get(label).cloned()
+    }
+
+    pub fn with_pool<R>(&self, f: impl FnOnce(&DaosPool) -> R) -> R {
+        f(&self.pool.read().unwrap())
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    fn connect_container(&self, label: &str, deadlines: DaosDeadlines) -> Result<DaosContainer> {
+        let mut cont = DaosContainer::new(label);
+        cont.set_deadlines(deadlines);
+        cont.connect(&self.pool.read().unwrap())?;
+        Ok(cont)
+    }
+
+    // Closes the registered containers, connects the pool again and reopens
+    // them, unless another caller already did so since generation was read.
+    // The pool can't be disconnected while containers are open, so this
+    // fails with Busy while a container returned earlier is still held
+    // elsewhere. Once handles are torn down a failed step doesn't stop the
+    // others, the first error is returned and the generation moves on, and
+    // whatever is left without a handle is reopened by the next call.
+    // Blocks like DaosPool::connect.
+    pub fn reconnect(&self, generation: u64) -> Result<()> {
+        let _reconnecting = self.reconnecting.lock().unwrap();
+        if self.generation() != generation {
+            return Ok(());
+        }
+
+        // held throughout, so nobody picks up a container while it is closed
+        let mut containers = self.containers.write().unwrap();
+        if let Some(label) = containers
+            .iter()
+            .find(|(_, cont)| Arc::strong_count(cont) > 1)
+            .map(|(label, _)| label)
+        {
+            return Err(DaosError::busy(format!(
+                "container {} is still in use",
+                label
+            )));
+        }
+        let mut res = Ok(());
+        for cont in containers.values_mut() {
+            res = res.and(Arc::get_mut(cont).unwrap().disconnect());
+        }
+
+        res = res.and(self.pool.write().unwrap().reconnect());
+
+        for (label, cont) in containers.iter_mut() {
+            // a container that failed to close keeps its handle
+            if cont.get_handle().is_some() {
+                continue;
+            }
+            match self.connect_container(label, cont.get_deadlines()) {
+                Ok(reopened) => *cont = Arc::new(reopened),
+                Err(e) => res = res.and(Err(e)),
+            }
+        }
+
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        res
+    }
+
+    // A failed reconnect leaves the pool or containers without a handle,
+    // operations on them fail with a NoHandle of the bindings until the
+    // next reconnect.
+    fn is_disconnected(&self) -> bool {
+        self.pool.read().unwrap().get_handle().is_none()
+            || self
+                .containers
+                .read()
+                .unwrap()
+                .values()
+                .any(|cont| cont.get_handle().is_none())
+    }
+
+    fn is_recoverable(&self, err: &DaosError) -> bool {
+        err.is_connection_lost()
+            || (matches!(err, DaosError::NoHandle(_)) && self.is_disconnected())
+    }
+
+    // Runs op, reconnecting and retrying it as the policy allows while it
+    // fails with a lost connection, or with NoHandle while a failed
+    // reconnect left a handle missing. Other errors are returned right away.
+    pub async fn run<T, F, Fut>(self: &Arc<Self>, mut op: F) -> Result<T>
+    where
+        F: FnMut(Arc<Self>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut backoff = self.policy.backoff;
+        let mut retries = 0u32;
+        loop {
+            let generation = self.generation();
+            let err = match op(self.clone()).await {
+                Ok(val) => return Ok(val),
+                Err(err) => err,
+            };
+            if !self.is_recoverable(&err) || retries >= self.policy.max_retries {
+                return Err(err);
+            }
+            retries += 1;
+
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+
+            // connect calls must stay off the async executor
+            let this = self.clone();
+            let res = tokio::task::spawn_blocking(move || this.reconnect(generation))
+                .await
+                .map_err(|_| DaosError::canceled("reconnect task failed"))?;
+            match res {
+                // still unreachable, the next attempt tries again
+                Err(e) if !self.is_recoverable(&e) => return Err(e),
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings;
+    use std::sync::atomic::AtomicU32;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reconnect_and_retry() {
+        let policy = DaosReconnectPolicy {
+            max_retries: 2,
+            backoff: Duration::from_millis(1),
+        };
+        let pool = DaosReconnectingPool::connect(TEST_POOL_NAME, policy).unwrap();
+        let cont = pool.open_container(TEST_CONT_NAME).unwrap();
+        assert!(Arc::ptr_eq(
+            &cont,
+            &pool.open_container(TEST_CONT_NAME).unwrap()
+        ));
+
+        // a container still held elsewhere keeps the pool from reconnecting
+        let res = pool.reconnect(pool.generation());
+        assert!(matches!(res, Err(DaosError::Busy(_))));
+        assert_eq!(pool.generation(), 0);
+        let old_handle = cont.get_handle();
+        drop(cont);
+
+        let lost = -(bindings::daos_errno_DER_NO_HDL as i32);
+        let calls = AtomicU32::new(0);
+        let res = pool
+            .run(|pool| {
+                let call = calls.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    if call == 0 {
+                        return Err(DaosError::from_rc(lost, "stale handle"));
+                    }
+                    Ok(pool.container(TEST_CONT_NAME).unwrap())
+                }
+            })
+            .await;
+        let reopened = res.unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+        assert_eq!(pool.generation(), 1);
+        assert!(reopened.get_handle().is_some());
+        assert_ne!(
+            reopened.get_handle().map(|h| h.cookie),
+            old_handle.map(|h| h.cookie)
+        );
+        drop(reopened);
+
+        // other errors and exhausted retries are handed back
+        let calls = AtomicU32::new(0);
+        let res: Result<()> = pool
+            .run(|_| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                async { Err(DaosError::from_rc(lost, "stale handle")) }
+            })
+            .await;
+        assert!(res.unwrap_err().is_connection_lost());
+        assert_eq!(calls.load(Ordering::Relaxed), 3);
+
+        let res: Result<()> = pool
+            .run(|_| async { Err(DaosError::inval("bad argument")) })
+            .await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        assert_eq!(pool.generation(), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_reconnect_partial_failure() {
+        let policy = DaosReconnectPolicy {
+            max_retries: 2,
+            backoff: Duration::from_millis(1),
+        };
+        let pool = DaosReconnectingPool::connect(TEST_POOL_NAME, policy).unwrap();
+        drop(pool.open_container(TEST_CONT_NAME).unwrap());
+        // registered without ever being opened, so reopening it fails
+        pool.containers.write().unwrap().insert(
+            "missing".to_string(),
+            Arc::new(DaosContainer::new("missing")),
+        );
+
+        // the failure doesn't keep the other containers closed
+        assert!(pool.reconnect(pool.generation()).is_err());
+        assert_eq!(pool.generation(), 1);
+        let cont = pool.container(TEST_CONT_NAME).unwrap();
+        assert!(cont.get_handle().is_some());
+        drop(cont);
+
+        // a container left without a handle is reopened by run()
+        pool.containers.write().unwrap().remove("missing");
+        {
+            let mut containers = pool.containers.write().unwrap();
+            let cont = containers.get_mut(TEST_CONT_NAME).unwrap();
+            Arc::get_mut(cont).unwrap().disconnect().unwrap();
+        }
+        let res = pool
+            .run(|pool| async move {
+                let cont = pool.container(TEST_CONT_NAME).unwrap();
+                cont.get_handle()
+                    .map(|_| ())
+                    .ok_or_else(|| DaosError::no_handle("container is closed"))
+            })
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(pool.generation(), 2);
+    }
+}
@@ -0,0 +1,228 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::bindings::{daos_fini, daos_init};
+use crate::error::{DaosError, Result};
+use std::env;
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+
+// Client network settings for throughput oriented deployments. daos_init
+// takes no parameters and libdaos only reads these from the CaRT variables
+// of env_vars(). Changing the environment isn't sound once other threads
+// run, so the caller exports them, from a launcher or at the top of main
+// before any runtime starts, and DaosRuntime::init_with() checks them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DaosInitOptions {
+    // fabric provider, e.g. "ofi+tcp" (D_PROVIDER)
+    pub provider: Option<String>,
+    // NICs to spread traffic over, e.g. ["eth0", "eth1"] (D_INTERFACE)
+    pub interfaces: Vec<String>,
+    // fabric domains, one per interface when the provider needs them
+    // (D_DOMAIN)
+    pub domains: Vec<String>,
+    // network contexts opened by the client (CRT_CTX_NUM)
+    pub network_contexts: Option<u32>,
+}
+
+impl DaosInitOptions {
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = Vec::new();
+        if let Some(provider) = &self.provider {
+            vars.push(("D_PROVIDER", provider.clone()));
+        }
+        if !self.interfaces.is_empty() {
+            vars.push(("D_INTERFACE", self.interfaces.join(",")));
+        }
+        if !self.domains.is_empty() {
+            vars.push(("D_DOMAIN", self.domains.join(",")));
+        }
+        if let Some(n) = self.network_contexts {
+            vars.push(("CRT_CTX_NUM", n.to_string()));
+        }
+        vars
+    }
+}
+
+// The live runtime, if any. Its lock also orders daos_init against the
+// daos_fini of a runtime that is just going away.
+static RUNTIME: Mutex<Option<Weak<RuntimeInner>>> = Mutex::new(None);
+
+struct RuntimeInner {
+    finalized: bool,
+}
+
+impl RuntimeInner {
+    fn fini(&mut self) -> Result<()> {
+        if self.finalized {
+            return Ok(());
+        }
+        self.finalized = true;
+        let _lock = RUNTIME.lock().unwrap();
+        let ret = unsafe { daos_fini() };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_fini",
+                ret,
+                "Failed to finalize DAOS",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RuntimeInner {
+    fn drop(&mut self) {
+        if let Err(e) = self.fini() {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+// A reference to the initialized DAOS client library. The first one calls
+// daos_init, clones and later init() calls share it, and daos_fini runs
+// once the last one is dropped or shut down. Pools, containers and event
+// queues hold one each, so libdaos outlives every handle opened with it.
+#[derive(Clone)]
+pub struct DaosRuntime {
+    inner: Arc<RuntimeInner>,
+}
+
+impl DaosRuntime {
+    pub fn init() -> Result<Self> {
+        Self::init_with(&DaosInitOptions::default())
+    }
+
+    // Like init(), but fails with Busy if libdaos is already initialized
+    // and opts asks for settings, as they could no longer take effect, and
+    // with Inval if the environment doesn't carry them. It is never changed
+    // here, see DaosInitOptions.
+    pub fn init_with(opts: &DaosInitOptions) -> Result<Self> {
+        let mut live = RUNTIME.lock().unwrap();
+        let existing = live.as_ref().and_then(|weak| weak.upgrade());
+        if let Some(inner) = existing {
+            let busy = *opts != DaosInitOptions::default();
+            // dropping what may now be the last reference takes the lock
+            drop(live);
+            if busy {
+                drop(inner);
+                return Err(DaosError::busy("DAOS is already initialized"));
+            }
+            return Ok(DaosRuntime { inner });
+        }
+
+        for (key, value) in opts.env_vars() {
+            if env::var(key).ok().as_deref() != Some(value.as_str()) {
+                return Err(DaosError::inval(format!(
+                    "{}={} must be exported before the process starts threads",
+                    key, value
+                )));
+            }
+        }
+        let ret = unsafe { daos_init() };
+        if ret != 0 {
+            return Err(DaosError::from_call(
+                "daos_init",
+                ret,
+                "Failed to initialize DAOS",
+            ));
+        }
+        let inner = Arc::new(RuntimeInner { finalized: false });
+        *live = Some(Arc::downgrade(&inner));
+        Ok(DaosRuntime { inner })
+    }
+
+    // The live runtime, without initializing libdaos if there is none.
+    pub fn current() -> Option<Self> {
+        let live = RUNTIME.lock().unwrap();
+        let inner = live.as_ref().and_then(|weak| weak.upgrade());
+        drop(live);
+        inner.map(|inner| DaosRuntime { inner })
+    }
+
+    // Releases this reference and, if it was the last, finalizes libdaos
+    // and reports how that went. Fails with Busy while other references
+    // are left, the library then stays up until they are gone.
+    pub fn shutdown(self) -> Result<()> {
+        match Arc::try_unwrap(self.inner) {
+            Ok(mut inner) => inner.fini(),
+            Err(_) => Err(DaosError::busy("DAOS is still in use")),
+        }
+    }
+
+    // The number of references to the runtime, this one included.
+    pub fn handles(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    pub fn ptr_eq(&self, other: &DaosRuntime) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl fmt::Debug for DaosRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DaosRuntime")
+            .field("handles", &self.handles())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_shared() {
+        let rt = DaosRuntime::init().expect("Failed to init DAOS");
+        let rt2 = DaosRuntime::init().unwrap();
+        assert!(rt.ptr_eq(&rt2));
+        assert!(rt.handles() >= 2);
+        assert!(DaosRuntime::current().unwrap().ptr_eq(&rt));
+
+        let opts = DaosInitOptions {
+            provider: Some("ofi+tcp".to_string()),
+            ..Default::default()
+        };
+        let res = DaosRuntime::init_with(&opts);
+        assert!(matches!(res, Err(DaosError::Busy(_))));
+
+        // rt2 is still around
+        assert!(matches!(rt.shutdown(), Err(DaosError::Busy(_))));
+        assert!(rt2.handles() >= 1);
+    }
+
+    #[test]
+    fn test_init_options_env() {
+        assert!(DaosInitOptions::default().env_vars().is_empty());
+
+        let opts = DaosInitOptions {
+            provider: Some("ofi+tcp".to_string()),
+            interfaces: vec!["eth0".to_string(), "eth1".to_string()],
+            domains: vec![],
+            network_contexts: Some(4),
+        };
+        assert_eq!(
+            opts.env_vars(),
+            vec![
+                ("D_PROVIDER", "ofi+tcp".to_string()),
+                ("D_INTERFACE", "eth0,eth1".to_string()),
+                ("CRT_CTX_NUM", "4".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,398 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Records which schema the values of each namespace (see daos_namespace)
+// follow, so a reader built for another schema or a newer version is
+// turned away at open time instead of misreading the values:
+//   dkey = REGISTRY_DKEY, akey = namespace prefix
+//   value = version (u32), then name and format as a u32 length and bytes,
+//   little endian
+// The registry may share the object of the namespaces it describes, as
+// long as no prefix starts with REGISTRY_DKEY. Typed values are stored
+// under akey VALUE_AKEY as the version that wrote them (u32 little endian)
+// followed by the encoded value.
+
+use crate::daos_cont::DaosContainer;
+use crate::daos_namespace::DaosNamespace;
+use crate::daos_obj::{CondFlags, DaosObjAsyncOps, DaosObject};
+use crate::daos_txn::DaosTxn;
+use crate::error::{DaosError, Result};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+const REGISTRY_DKEY: &[u8] = b"__schemas";
+const SCHEMA_BUF_SIZE: u32 = 1024;
+// first buffer tried for typed values, grown when a value doesn't fit
+const VALUE_BUF_SIZE: u32 = 4 * 1024;
+const VALUE_AKEY: &[u8] = b"v";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaosSchema {
+    pub name: String,
+    pub version: u32,
+    // serialization of the values, e.g. "json" or "bincode"
+    pub format: String,
+}
+
+impl DaosSchema {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(12 + self.name.len() + self.format.len());
+        out.extend_from_slice(&self.version.to_le_bytes());
+        for field in [&self.name, &self.format] {
+            out.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            out.extend_from_slice(field.as_bytes());
+        }
+        out
+    }
+
+    fn from_bytes(buf: &[u8]) -> Result<Self> {
+        let mut rest = buf;
+        let mut take = |n: usize| -> Result<&[u8]> {
+            if rest.len() < n {
+                return Err(DaosError::inval("truncated schema record"));
+            }
+            let (head, tail) = rest.split_at(n);
+            rest = tail;
+            Ok(head)
+        };
+        let version = u32::from_le_bytes(take(4)?.try_into().unwrap());
+        let mut fields = [String::new(), String::new()];
+        for field in fields.iter_mut() {
+            let len = u32::from_le_bytes(take(4)?.try_into().unwrap());
+            *field = String::from_utf8(take(len as usize)?.to_vec())
+                .map_err(|_| DaosError::inval("schema record is not utf-8"))?;
+        }
+        if !rest.is_empty() {
+            return Err(DaosError::inval("trailing bytes after schema record"));
+        }
+        let [name, format] = fields;
+        Ok(DaosSchema {
+            name,
+            version,
+            format,
+        })
+    }
+}
+
+// A value type stored in a typed namespace.
+pub trait DaosTyped: Sized {
+    const SCHEMA: &'static str;
+    const VERSION: u32;
+    const FORMAT: &'static str;
+
+    fn encode(&self) -> Vec<u8>;
+    // Decodes a value written by version, VERSION or one accepted by
+    // reads_version.
+    fn decode(buf: &[u8], version: u32) -> Result<Self>;
+
+    // Older versions this type still reads. Opening a namespace recorded
+    // at one of them moves the record to VERSION, so readers of the old
+    // version are turned away from then on.
+    fn reads_version(version: u32) -> bool {
+        version == Self::VERSION
+    }
+
+    fn schema() -> DaosSchema {
+        DaosSchema {
+            name: Self::SCHEMA.to_string(),
+            version: Self::VERSION,
+            format: Self::FORMAT.to_string(),
+        }
+    }
+}
+
+pub struct DaosSchemaRegistry {
+    obj: Arc<DaosObject>,
+}
+
+impl DaosSchemaRegistry {
+    pub fn new(obj: Arc<DaosObject>) -> Self {
+        DaosSchemaRegistry { obj }
+    }
+
+    // The schema recorded for prefix, None for a namespace never opened
+    // typed.
+    pub async fn get_async(&self, prefix: &[u8]) -> Result<Option<DaosSchema>> {
+        let txn = DaosTxn::txn_none();
+        let mut buf = vec![0u8; SCHEMA_BUF_SIZE as usize];
+        let size = self
+            .obj
+            .fetch_async(
+                &txn,
+                CondFlags::empty(),
+                REGISTRY_DKEY.to_vec(),
+                prefix.to_vec(),
+                &mut buf,
+            )
+            .await?;
+        if size == 0 {
+            return Ok(None);
+        }
+        DaosSchema::from_bytes(&buf[..size]).map(Some)
+    }
+
+    async fn put_async(&self, prefix: &[u8], schema: &DaosSchema, flags: CondFlags) -> Result<()> {
+        let txn = DaosTxn::txn_none();
+        self.obj
+            .update_async(
+                &txn,
+                flags,
+                REGISTRY_DKEY.to_vec(),
+                prefix.to_vec(),
+                &schema.to_bytes(),
+            )
+            .await
+    }
+
+    // Opens the namespace with prefix in obj for values of type T. The
+    // first open records T's schema, later ones fail with Inval unless the
+    // recorded schema has T's name and format and a version T reads.
+    pub async fn open_typed_namespace<T: DaosTyped>(
+        &self,
+        cont: Arc<DaosContainer>,
+        obj: Arc<DaosObject>,
+        prefix: &[u8],
+    ) -> Result<DaosTypedNamespace<T>> {
+        let ns = DaosNamespace::new(cont, obj, prefix)?;
+        let wanted = T::schema();
+        let recorded = match self.get_async(prefix).await? {
+            Some(recorded) => recorded,
+            None => {
+                let flags = CondFlags::AKEY_INSERT;
+                match self.put_async(prefix, &wanted, flags).await {
+                    Ok(()) => wanted.clone(),
+                    // lost the race against another first open
+                    Err(DaosError::Exist(_)) => self
+                        .get_async(prefix)
+                        .await?
+                        .ok_or_else(|| DaosError::non_exist("schema record vanished"))?,
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+
+        let readable = recorded.version == wanted.version
+            || (recorded.version < wanted.version && T::reads_version(recorded.version));
+        if recorded.name != wanted.name || recorded.format != wanted.format || !readable {
+            return Err(DaosError::inval(format!(
+                "namespace holds {} v{} ({}), {} v{} ({}) can't read it",
+                recorded.name,
+                recorded.version,
+                recorded.format,
+                wanted.name,
+                wanted.version,
+                wanted.format
+            )));
+        }
+        if recorded.version < wanted.version {
+            self.put_async(prefix, &wanted, CondFlags::empty()).await?;
+        }
+
+        Ok(DaosTypedNamespace {
+            ns,
+            _marker: PhantomData,
+        })
+    }
+}
+
+// Values of one DaosTyped type, one per key, in a DaosNamespace.
+pub struct DaosTypedNamespace<T> {
+    ns: DaosNamespace,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: DaosTyped> DaosTypedNamespace<T> {
+    pub fn namespace(&self) -> &DaosNamespace {
+        &self.ns
+    }
+
+    pub async fn put_async(&self, key: &[u8], value: &T) -> Result<()> {
+        let mut buf = T::VERSION.to_le_bytes().to_vec();
+        buf.extend_from_slice(&value.encode());
+        self.ns.put_async(key, VALUE_AKEY, &buf).await.map(|_| ())
+    }
+
+    fn decode(buf: &[u8]) -> Result<T> {
+        if buf.len() < 4 {
+            return Err(DaosError::inval("typed value has no version"));
+        }
+        let (version, value) = buf.split_at(4);
+        let version = u32::from_le_bytes(version.try_into().unwrap());
+        if version != T::VERSION && !T::reads_version(version) {
+            return Err(DaosError::inval(format!(
+                "value written by {} v{}, which v{} can't read",
+                T::SCHEMA,
+                version,
+                T::VERSION
+            )));
+        }
+        T::decode(value, version)
+    }
+
+    // Values keep the version that wrote them until put_async rewrites
+    // them, so older ones are decoded as such.
+    pub async fn get_async(&self, key: &[u8]) -> Result<Option<T>> {
+        let mut max_size = VALUE_BUF_SIZE;
+        loop {
+            match self.ns.get_async(key, VALUE_AKEY, max_size).await {
+                Ok(Some(buf)) => return Self::decode(&buf).map(Some),
+                Ok(None) => return Ok(None),
+                Err(e) => match e.required_size() {
+                    Some(required) if required > max_size as usize => {
+                        max_size = u32::try_from(required)
+                            .map_err(|_| DaosError::inval("typed value is too large"))?;
+                    }
+                    _ => return Err(e),
+                },
+            }
+        }
+    }
+
+    pub async fn remove_async(&self, key: &[u8]) -> Result<()> {
+        self.ns.remove_async(key, VALUE_AKEY).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daos_obj::{DAOS_OC_HINTS_NONE, DAOS_OC_UNKNOWN, DAOS_OT_MULTI_HASHED};
+    use crate::daos_oid_allocator::DaosAsyncOidAllocator;
+    use crate::daos_pool::DaosPool;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[derive(Debug, PartialEq)]
+    struct CounterV1(u32);
+
+    impl DaosTyped for CounterV1 {
+        const SCHEMA: &'static str = "counter";
+        const VERSION: u32 = 1;
+        const FORMAT: &'static str = "le";
+
+        fn encode(&self) -> Vec<u8> {
+            self.0.to_le_bytes().to_vec()
+        }
+
+        fn decode(buf: &[u8], _version: u32) -> Result<Self> {
+            let raw = buf
+                .try_into()
+                .map_err(|_| DaosError::inval("bad counter"))?;
+            Ok(CounterV1(u32::from_le_bytes(raw)))
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct CounterV2(u64);
+
+    impl DaosTyped for CounterV2 {
+        const SCHEMA: &'static str = "counter";
+        const VERSION: u32 = 2;
+        const FORMAT: &'static str = "le";
+
+        fn encode(&self) -> Vec<u8> {
+            self.0.to_le_bytes().to_vec()
+        }
+
+        fn decode(buf: &[u8], version: u32) -> Result<Self> {
+            match version {
+                1 => CounterV1::decode(buf, version).map(|v| CounterV2(v.0 as u64)),
+                _ => {
+                    let raw = buf
+                        .try_into()
+                        .map_err(|_| DaosError::inval("bad counter"))?;
+                    Ok(CounterV2(u64::from_le_bytes(raw)))
+                }
+            }
+        }
+
+        fn reads_version(version: u32) -> bool {
+            version == 1 || version == 2
+        }
+    }
+
+    #[test]
+    fn test_schema_record() {
+        let schema = CounterV2::schema();
+        assert_eq!(DaosSchema::from_bytes(&schema.to_bytes()).unwrap(), schema);
+        let bytes = schema.to_bytes();
+        assert!(DaosSchema::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_open_typed_namespace() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        cont.connect(&pool).expect("Failed to connect to container");
+        let cont = Arc::new(cont);
+
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj: Arc<DaosObject> = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            DAOS_OT_MULTI_HASHED,
+            DAOS_OC_UNKNOWN,
+            DAOS_OC_HINTS_NONE,
+            0,
+        )
+        .await
+        .expect("Failed to create object")
+        .into();
+        let registry = DaosSchemaRegistry::new(obj.clone());
+
+        let v1 = registry
+            .open_typed_namespace::<CounterV1>(cont.clone(), obj.clone(), b"c/")
+            .await
+            .unwrap();
+        v1.put_async(b"hits", &CounterV1(7)).await.unwrap();
+        assert_eq!(
+            registry.get_async(b"c/").await.unwrap(),
+            Some(CounterV1::schema())
+        );
+
+        let v2 = registry
+            .open_typed_namespace::<CounterV2>(cont.clone(), obj.clone(), b"c/")
+            .await
+            .unwrap();
+        assert_eq!(v2.get_async(b"hits").await.unwrap(), Some(CounterV2(7)));
+        assert_eq!(
+            registry.get_async(b"c/").await.unwrap(),
+            Some(CounterV2::schema())
+        );
+
+        // readers of the old version are refused once the namespace moved
+        let res = registry
+            .open_typed_namespace::<CounterV1>(cont.clone(), obj.clone(), b"c/")
+            .await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+
+        let v2 = registry
+            .open_typed_namespace::<CounterV2>(cont.clone(), obj.clone(), b"c/")
+            .await
+            .unwrap();
+        v2.put_async(b"hits", &CounterV2(1 << 40)).await.unwrap();
+        assert_eq!(
+            v2.get_async(b"hits").await.unwrap(),
+            Some(CounterV2(1 << 40))
+        );
+        v2.remove_async(b"hits").await.unwrap();
+        assert_eq!(v2.get_async(b"hits").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,291 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+// Client driven integrity sweep: one pass reading back every value of every
+// object in a container. Objects are taken from the object index table of a
+// snapshot made for the pass and dropped at its end, values are read at the
+// latest epoch, so objects written after the snapshot are only checked by
+// the next pass. With checksums enabled on the container libdaos verifies
+// every value it returns, a value it can't read intact becomes a finding.
+
+use crate::daos_archive::{collect_keys, fetch_single};
+use crate::daos_cont::{DaosContainer, DaosContainerAsyncOps};
+use crate::daos_obj::{CondFlags, DaosObjAsyncOps, DaosObject};
+use crate::daos_oit::{DaosOit, DaosOitList};
+use crate::daos_pool::DaosObjectId;
+use crate::daos_txn::DaosTxn;
+use crate::error::{DaosError, Result};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+const FINDINGS_CHANNEL_SIZE: usize = 64;
+// largest piece of an array value read at once
+const EXTENT_CHUNK: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScrubFinding {
+    // a value failed checksum verification, the error context names the
+    // dkey and akey
+    Corrupt { oid: DaosObjectId, error: DaosError },
+    // the object or one of its values couldn't be read for another reason
+    Unreadable { oid: DaosObjectId, error: DaosError },
+}
+
+impl ScrubFinding {
+    fn new(oid: DaosObjectId, error: DaosError) -> Self {
+        if error.is_checksum_error() {
+            ScrubFinding::Corrupt { oid, error }
+        } else {
+            ScrubFinding::Unreadable { oid, error }
+        }
+    }
+
+    pub fn oid(&self) -> DaosObjectId {
+        match self {
+            ScrubFinding::Corrupt { oid, .. } | ScrubFinding::Unreadable { oid, .. } => *oid,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrubStats {
+    pub objects: u64,
+    pub values: u64,
+    pub bytes: u64,
+    pub findings: u64,
+    // array values with records larger than a byte, which aren't read back
+    pub skipped_arrays: u64,
+}
+
+pub struct Scrubber {
+    task: JoinHandle<Result<ScrubStats>>,
+}
+
+impl Scrubber {
+    // Starts one pass over cont reading at most bytes_per_sec, and returns
+    // the receiver its findings are sent to. Dropping the receiver stops the
+    // pass at the next finding.
+    pub fn spawn(
+        cont: Arc<DaosContainer>,
+        bytes_per_sec: u64,
+    ) -> Result<(Self, mpsc::Receiver<ScrubFinding>)> {
+        if cont.get_handle().is_none() {
+            return Err(DaosError::no_handle("empty container handle"));
+        }
+        if bytes_per_sec == 0 {
+            return Err(DaosError::inval("scrub rate must not be zero"));
+        }
+        let rt = tokio::runtime::Handle::try_current()
+            .map_err(|_| DaosError::no_sys("Scrubber::spawn needs a tokio runtime"))?;
+
+        let (tx, rx) = mpsc::channel(FINDINGS_CHANNEL_SIZE);
+        let task = rt.spawn(async move {
+            let epoch = cont.create_oit_snapshot_async().await?;
+            let res = scrub_pass(&cont, epoch, bytes_per_sec, &tx).await;
+            let cleanup = cont.destroy_snapshot_async(epoch).await;
+            let stats = res?;
+            cleanup.map(|_| stats)
+        });
+        Ok((Scrubber { task }, rx))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    // Stops the pass, leaving its snapshot behind if it was running.
+    pub fn abort(&self) {
+        self.task.abort();
+    }
+
+    // Waits for the end of the pass. Fails with the first error that kept
+    // it from going on, findings aren't errors.
+    pub async fn join(self) -> Result<ScrubStats> {
+        match self.task.await {
+            Ok(res) => res,
+            Err(e) if e.is_cancelled() => Err(DaosError::canceled("scrub pass was aborted")),
+            Err(e) => Err(DaosError::inval(format!("scrub pass panicked: {}", e))),
+        }
+    }
+}
+
+// Sleeps as long as it takes for bytes read since start to fit the rate.
+struct Pacer {
+    start: Instant,
+    bytes: u64,
+    bytes_per_sec: u64,
+}
+
+impl Pacer {
+    async fn consume(&mut self, bytes: u64) {
+        self.bytes += bytes;
+        let due = Duration::from_secs_f64(self.bytes as f64 / self.bytes_per_sec as f64);
+        let elapsed = self.start.elapsed();
+        if due > elapsed {
+            tokio::time::sleep(due - elapsed).await;
+        }
+    }
+}
+
+async fn scrub_pass(
+    cont: &DaosContainer,
+    epoch: u64,
+    bytes_per_sec: u64,
+    tx: &mpsc::Sender<ScrubFinding>,
+) -> Result<ScrubStats> {
+    let oit = DaosOit::open_async(cont, epoch).await?;
+    let mut stats = ScrubStats::default();
+    let mut pacer = Pacer {
+        start: Instant::now(),
+        bytes: 0,
+        bytes_per_sec,
+    };
+
+    let mut oit_lst = DaosOitList::new();
+    while !oit_lst.reach_end() {
+        oit_lst = oit.list_async(oit_lst).await?;
+        for &oid in oit_lst.oids() {
+            stats.objects += 1;
+            let res = match DaosObject::open_async(cont, oid, true).await {
+                Ok(obj) => scrub_object(&obj, &mut stats, &mut pacer).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = res {
+                stats.findings += 1;
+                tx.send(ScrubFinding::new(oid, e))
+                    .await
+                    .map_err(|_| DaosError::canceled("scrub findings receiver was dropped"))?;
+            }
+        }
+    }
+    Ok(stats)
+}
+
+// Reads every value of obj, stopping at the first that can't be read.
+async fn scrub_object(obj: &DaosObject, stats: &mut ScrubStats, pacer: &mut Pacer) -> Result<()> {
+    let txn = DaosTxn::txn_none();
+    for dkey in collect_keys(obj, &txn, None).await? {
+        for akey in collect_keys(obj, &txn, Some(&dkey)).await? {
+            let value = fetch_single(obj, &txn, &dkey, &akey).await?;
+            if !value.is_empty() {
+                stats.values += 1;
+                stats.bytes += value.len() as u64;
+                pacer.consume(value.len() as u64).await;
+                continue;
+            }
+
+            let recxs = obj
+                .list_recx_async(&txn, dkey.clone(), akey.clone())
+                .await?;
+            if recxs.extents.is_empty() {
+                continue;
+            }
+            if recxs.rec_size != 1 {
+                stats.skipped_arrays += 1;
+                continue;
+            }
+            stats.values += 1;
+            let mut data = Vec::new();
+            for extent in recxs.extents {
+                let mut offset = extent.offset;
+                let end = extent.offset + extent.len;
+                while offset < end {
+                    let len = (end - offset).min(EXTENT_CHUNK);
+                    data.resize(len as usize, 0);
+                    obj.fetch_recx_async(
+                        &txn,
+                        CondFlags::empty(),
+                        dkey.clone(),
+                        akey.clone(),
+                        offset,
+                        &mut data,
+                    )
+                    .await?;
+                    stats.bytes += len;
+                    pacer.consume(len).await;
+                    offset += len;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::daos_obj::{DAOS_OC_HINTS_NONE, DAOS_OC_UNKNOWN, DAOS_OT_MULTI_HASHED};
+    use crate::daos_oid_allocator::DaosAsyncOidAllocator;
+    use crate::daos_pool::DaosPool;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test]
+    async fn test_pacer() {
+        let mut pacer = Pacer {
+            start: Instant::now(),
+            bytes: 0,
+            bytes_per_sec: 1000,
+        };
+        pacer.consume(100).await;
+        assert!(pacer.start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_scrub_pass() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = DaosContainer::new(TEST_CONT_NAME);
+        cont.connect(&pool).expect("Failed to connect to container");
+        let cont = Arc::new(cont);
+
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            DAOS_OT_MULTI_HASHED,
+            DAOS_OC_UNKNOWN,
+            DAOS_OC_HINTS_NONE,
+            0,
+        )
+        .await
+        .expect("Failed to create object");
+        let txn = DaosTxn::txn_none();
+        obj.update_async(
+            &txn,
+            CondFlags::empty(),
+            b"dkey".to_vec(),
+            b"akey".to_vec(),
+            b"scrub me",
+        )
+        .await
+        .expect("Failed to update object");
+
+        assert!(Scrubber::spawn(cont.clone(), 0).is_err());
+        let (scrubber, mut findings) =
+            Scrubber::spawn(cont.clone(), 64 * 1024 * 1024).expect("Failed to spawn scrubber");
+        let stats = scrubber.join().await.expect("Scrub pass failed");
+        assert!(stats.objects >= 1);
+        assert!(stats.bytes >= 8);
+        assert_eq!(stats.findings, 0);
+        assert!(findings.recv().await.is_none());
+    }
+}
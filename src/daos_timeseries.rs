@@ -0,0 +1,269 @@
+//
+//  Copyright (C) 2024 github.com/chel-data
+//
+//  This program is free software: you can redistribute it and/or modify
+//  it under the terms of the GNU General Public License as published by
+//  the Free Software Foundation, either version 3 of the License, or
+//  (at your option) any later version.
+//
+//  This program is distributed in the hope that it will be useful,
+//  but WITHOUT ANY WARRANTY; without even the implied warranty of
+//  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+//  GNU General Public License for more details.
+//
+//  You should have received a copy of the GNU General Public License
+//  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+
+// Time-partitioned layout for fixed size samples:
+//   dkey = (series, bucket start) encoded with KeyEncoder
+//   akey = TS_AKEY, an array akey with one slot per timestamp in the bucket
+//   slot = presence marker byte followed by the value
+// Slots never written read back with a zero marker and are skipped.
+
+use crate::daos_key::KeyEncoder;
+use crate::daos_obj::{CondFlags, DaosObjAsyncOps, DaosObject};
+use crate::daos_txn::DaosTxn;
+use crate::error::{DaosError, Result};
+
+const TS_AKEY: &[u8] = b"ts";
+const SLOT_PRESENT: u8 = 1;
+// bytes a range query reads per fetch, a bucket is read in several
+const QUERY_CHUNK: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TimeSeriesConfig {
+    // timestamps covered by one dkey
+    pub bucket_width: u64,
+    // size in bytes of every sample value
+    pub value_size: usize,
+}
+
+#[derive(Debug)]
+pub struct TimeSeriesWriter {
+    obj: Box<DaosObject>,
+    config: TimeSeriesConfig,
+}
+
+impl TimeSeriesWriter {
+    pub fn new(obj: Box<DaosObject>, config: TimeSeriesConfig) -> Result<Self> {
+        if config.bucket_width == 0 || config.value_size == 0 {
+            return Err(DaosError::inval(
+                "bucket width and value size must be non-zero",
+            ));
+        }
+        Ok(TimeSeriesWriter { obj, config })
+    }
+
+    pub fn get_object(&self) -> &DaosObject {
+        self.obj.as_ref()
+    }
+
+    fn slot_size(&self) -> u64 {
+        self.config.value_size as u64 + 1
+    }
+
+    // Bytes taken by slots consecutive slots, Inval when that overflows,
+    // which a wide bucket with large values can reach.
+    fn slots_len(&self, slots: u64) -> Result<u64> {
+        slots
+            .checked_mul(self.slot_size())
+            .ok_or_else(|| DaosError::inval("slot offset overflows the bucket"))
+    }
+
+    // Returns the bucket start and the slot index of a timestamp.
+    fn locate(&self, ts: u64) -> (u64, u64) {
+        let width = self.config.bucket_width;
+        (ts - ts % width, ts % width)
+    }
+
+    fn bucket_dkey(series: &str, bucket: u64) -> Vec<u8> {
+        KeyEncoder::new().push_str(series).push_u64(bucket).finish()
+    }
+
+    pub async fn write_async(
+        &self,
+        txn: &DaosTxn,
+        series: &str,
+        ts: u64,
+        value: &[u8],
+    ) -> Result<()> {
+        if value.len() != self.config.value_size {
+            return Err(DaosError::inval(format!(
+                "sample size {} doesn't match value size {}",
+                value.len(),
+                self.config.value_size
+            )));
+        }
+
+        let (bucket, slot) = self.locate(ts);
+        let offset = self.slots_len(slot)?;
+        let mut data = Vec::with_capacity(self.slot_size() as usize);
+        data.push(SLOT_PRESENT);
+        data.extend_from_slice(value);
+        self.obj
+            .update_recx_async(
+                txn,
+                CondFlags::empty(),
+                Self::bucket_dkey(series, bucket),
+                TS_AKEY,
+                offset,
+                data.as_slice(),
+            )
+            .await
+    }
+
+    // Returns the samples with t0 <= ts < t1 in timestamp order, fetching
+    // the part of every bucket overlapping the range in extents of at most
+    // QUERY_CHUNK bytes, or one slot when a slot is larger.
+    pub async fn query_range_async(
+        &self,
+        txn: &DaosTxn,
+        series: &str,
+        t0: u64,
+        t1: u64,
+    ) -> Result<Vec<(u64, Vec<u8>)>> {
+        let mut samples = Vec::new();
+        if t0 >= t1 {
+            return Ok(samples);
+        }
+
+        let slot_size = self.slot_size();
+        let chunk_slots = (QUERY_CHUNK / slot_size).max(1);
+        let mut buf = Vec::new();
+        let (mut bucket, _) = self.locate(t0);
+        while bucket < t1 {
+            let last = t1.min(bucket.saturating_add(self.config.bucket_width));
+            let mut first = t0.max(bucket);
+            while first < last {
+                let end = last.min(first.saturating_add(chunk_slots));
+                let offset = self.slots_len(first - bucket)?;
+                // holes aren't written, they must read as absent slots
+                buf.clear();
+                buf.resize(self.slots_len(end - first)? as usize, 0);
+                self.obj
+                    .fetch_recx_async(
+                        txn,
+                        CondFlags::empty(),
+                        Self::bucket_dkey(series, bucket),
+                        TS_AKEY,
+                        offset,
+                        buf.as_mut_slice(),
+                    )
+                    .await?;
+
+                for (idx, slot) in buf.chunks_exact(slot_size as usize).enumerate() {
+                    if slot[0] == SLOT_PRESENT {
+                        samples.push((first + idx as u64, slot[1..].to_vec()));
+                    }
+                }
+                first = end;
+            }
+
+            bucket = match bucket.checked_add(self.config.bucket_width) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        Ok(samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings::{daos_otype_t_DAOS_OT_MULTI_HASHED, OC_UNKNOWN};
+    use crate::daos_cont::DaosContainer;
+    use crate::daos_oid_allocator::DaosAsyncOidAllocator;
+    use crate::daos_pool::DaosPool;
+    use std::sync::Arc;
+
+    const TEST_POOL_NAME: &str = "pool1";
+    const TEST_CONT_NAME: &str = "cont1";
+
+    #[tokio::test]
+    async fn test_time_series_query_range() {
+        let mut pool = DaosPool::new(TEST_POOL_NAME);
+        pool.connect().expect("Failed to connect to pool");
+
+        let mut cont = Box::new(DaosContainer::new(TEST_CONT_NAME));
+        cont.connect(&pool).expect("Failed to connect to container");
+
+        let cont: Arc<DaosContainer> = Arc::from(cont);
+        let allocator = Arc::from(DaosAsyncOidAllocator::new(cont.clone()).unwrap());
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator.clone(),
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .unwrap();
+
+        let config = TimeSeriesConfig {
+            bucket_width: 10,
+            value_size: 4,
+        };
+        let writer = TimeSeriesWriter::new(obj, config).unwrap();
+        let txn = DaosTxn::txn_none();
+        for ts in [3u64, 9, 10, 25] {
+            let value = (ts as u32).to_le_bytes();
+            let res = writer.write_async(&txn, "cpu", ts, &value).await;
+            assert!(res.is_ok());
+        }
+
+        let res = writer.query_range_async(&txn, "cpu", 5, 25).await;
+        assert!(res.is_ok());
+        let samples = res.unwrap();
+        let stamps: Vec<u64> = samples.iter().map(|(ts, _)| *ts).collect();
+        assert_eq!(stamps, vec![9, 10]);
+        assert_eq!(samples[1].1, 10u32.to_le_bytes().to_vec());
+
+        let res = writer.query_range_async(&txn, "mem", 0, 30).await;
+        assert!(res.unwrap().is_empty());
+
+        // a bucket wider than QUERY_CHUNK is read in several fetches
+        let obj = DaosObject::create_async(
+            cont.as_ref(),
+            allocator,
+            daos_otype_t_DAOS_OT_MULTI_HASHED,
+            OC_UNKNOWN,
+            0,
+            0,
+        )
+        .await
+        .unwrap();
+        let config = TimeSeriesConfig {
+            bucket_width: 1_000_000,
+            value_size: 4,
+        };
+        let writer = TimeSeriesWriter::new(obj, config).unwrap();
+        for ts in [3u64, 400_000, 999_999] {
+            let value = (ts as u32).to_le_bytes();
+            writer.write_async(&txn, "cpu", ts, &value).await.unwrap();
+        }
+        let samples = writer
+            .query_range_async(&txn, "cpu", 0, 1_000_000)
+            .await
+            .unwrap();
+        let stamps: Vec<u64> = samples.iter().map(|(ts, _)| *ts).collect();
+        assert_eq!(stamps, vec![3, 400_000, 999_999]);
+
+        // slot offsets past u64::MAX are refused
+        let config = TimeSeriesConfig {
+            bucket_width: u64::MAX,
+            value_size: 4,
+        };
+        let writer = TimeSeriesWriter::new(writer.obj, config).unwrap();
+        let res = writer
+            .write_async(&txn, "cpu", u64::MAX - 1, &[0u8; 4])
+            .await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+        let res = writer
+            .query_range_async(&txn, "cpu", u64::MAX - 2, u64::MAX)
+            .await;
+        assert!(matches!(res, Err(DaosError::Inval(_))));
+    }
+}
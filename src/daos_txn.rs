@@ -15,36 +15,90 @@
  *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::daos_event::DaosEvent;
+#[cfg(feature = "libdaos")]
+use crate::daos_event::DaosEventQueue;
+#[cfg(feature = "libdaos")]
 use crate::bindings::{
-    daos_event_t, daos_tx_abort, daos_tx_close, daos_tx_commit, daos_tx_open,
+    daos_event_t, daos_tx_abort, daos_tx_close, daos_tx_commit, daos_tx_hdl2epoch, daos_tx_open,
 };
-use crate::daos_pool::DaosHandle;
-use crate::daos_cont::DaosContainer;
+use crate::daos_pool::{DaosHandle, HandleFmt};
+#[cfg(feature = "libdaos")]
+use crate::daos_cont::{DaosContainer, DaosDeadlines};
+use crate::error::{DaosError, Result};
+use std::fmt;
 use std::future::Future;
+use std::option::Option;
 use std::ptr;
-use std::{
-    io::{Error, ErrorKind, Result},
-    option::Option,
-};
 
 pub struct DaosTxn {
     handle: Option<DaosHandle>,
-    event_que: Option<DaosHandle>,
+    #[cfg(feature = "libdaos")]
+    event_que: Option<DaosEventQueue>,
+    #[cfg(feature = "libdaos")]
+    deadlines: DaosDeadlines,
 }
 
 impl DaosTxn {
     pub fn txn_none() -> Self {
         DaosTxn {
             handle: None,
+            #[cfg(feature = "libdaos")]
             event_que: None,
+            #[cfg(feature = "libdaos")]
+            deadlines: DaosDeadlines::default(),
         }
     }
     pub fn get_handle(&self) -> Option<DaosHandle> {
-        self.handle.clone()
+        self.handle
+    }
+
+    #[cfg(feature = "libdaos")]
+    pub fn set_deadlines(&mut self, deadlines: DaosDeadlines) {
+        self.deadlines = deadlines;
+    }
+
+    #[cfg(feature = "libdaos")]
+    pub fn get_deadlines(&self) -> DaosDeadlines {
+        self.deadlines
+    }
+
+    // The epoch the transaction reads at. DAOS picks it with the first
+    // operation, before that this fails with DER_UNCERTAIN.
+    #[cfg(feature = "libdaos")]
+    pub fn epoch(&self) -> Result<u64> {
+        if self.handle.is_none() {
+            return Err(DaosError::no_handle("epoch of empty txn"));
+        }
+
+        let mut epoch = 0u64;
+        let res = unsafe { daos_tx_hdl2epoch(self.handle.unwrap(), &mut epoch) };
+        if res != 0 {
+            return Err(DaosError::from_call(
+                "daos_tx_hdl2epoch",
+                res,
+                "Failed to get the epoch of DAOS transaction",
+            ));
+        }
+
+        Ok(epoch)
+    }
+}
+
+impl fmt::Debug for DaosTxn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("DaosTxn");
+        s.field("handle", &HandleFmt(self.handle));
+        #[cfg(feature = "libdaos")]
+        s.field(
+            "event_que",
+            &HandleFmt(self.event_que.as_ref().and_then(|eq| eq.get_handle())),
+        )
+        .field("deadlines", &self.deadlines);
+        s.finish()
     }
 }
 
+#[cfg(feature = "libdaos")]
 pub trait DaosTxnSyncOps {
     fn open(cont: &DaosContainer, flags: u64) -> Result<Box<DaosTxn>>;
     fn commit(&self) -> Result<()>;
@@ -52,6 +106,7 @@ pub trait DaosTxnSyncOps {
     fn close(&self) -> Result<()>;
 }
 
+#[cfg(feature = "libdaos")]
 pub trait DaosTxnAsyncOps {
     fn open_async(
         cont: &DaosContainer,
@@ -62,6 +117,7 @@ pub trait DaosTxnAsyncOps {
     fn close_async(&self) -> impl Future<Output = Result<()>> + Send + 'static;
 }
 
+#[cfg(feature = "libdaos")]
 impl DaosTxnAsyncOps for DaosTxn {
     fn open_async(
         cont: &DaosContainer,
@@ -69,17 +125,15 @@ impl DaosTxnAsyncOps for DaosTxn {
     ) -> impl Future<Output = Result<Box<DaosTxn>>> + Send + 'static {
         let cont_hdl = cont.get_handle();
         let eq = cont.get_event_queue();
-        let eqh = eq.map(|e| e.get_handle().unwrap());
+        let eqh = eq.cloned();
         let evt = eq.map(|e| e.create_event());
+        let deadlines = cont.get_deadlines();
         async move {
             if cont_hdl.is_none() {
-                return Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    "empty container handle",
-                ));
+                return Err(DaosError::no_handle("empty container handle"));
             }
             if evt.is_none() {
-                return Err(Error::new(ErrorKind::InvalidInput, "empty event queue"));
+                return Err(DaosError::no_handle("empty event queue"));
             }
             let res = evt.unwrap();
             if res.is_err() {
@@ -103,30 +157,30 @@ impl DaosTxnAsyncOps for DaosTxn {
                 )
             };
             if res != 0 {
-                return Err(Error::new(
-                    ErrorKind::Other,
+                return Err(DaosError::from_call(
+                    "daos_tx_open",
+                    res,
                     "fail to open DAOS transaction",
                 ));
             }
 
-            match rx.await {
+            match event.wait(rx, deadlines.metadata).await {
                 Ok(ret) => {
                     if ret != 0 {
-                        Err(Error::new(
-                            ErrorKind::Other,
+                        Err(DaosError::from_call(
+                            "daos_tx_open",
+                            ret,
                             "async open txn request failed",
                         ))
                     } else {
                         Ok(Box::new(DaosTxn {
                             handle: Some(tx_hdl),
                             event_que: eqh,
+                            deadlines,
                         }))
                     }
                 }
-                Err(_) => Err(Error::new(
-                    ErrorKind::Other,
-                    "can't get response from the receiver end",
-                )),
+                Err(e) => Err(e),
             }
         }
     }
@@ -134,12 +188,13 @@ impl DaosTxnAsyncOps for DaosTxn {
     fn commit_async(&self) -> impl Future<Output = Result<()>> + Send + 'static {
         let txn_hdl = self.handle;
         let eq: Option<_> = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
         async move {
             if txn_hdl.is_none() || eq.is_none() {
-                return Err(Error::new(ErrorKind::InvalidData, "commit empty txn"));
+                return Err(DaosError::no_handle("commit empty txn"));
             }
 
-            let res = DaosEvent::new(eq.unwrap());
+            let res = eq.unwrap().create_event();
             if res.is_err() {
                 return Err(res.unwrap_err());
             }
@@ -153,24 +208,26 @@ impl DaosTxnAsyncOps for DaosTxn {
 
             let res = unsafe { daos_tx_commit(txn_hdl.unwrap(), event.as_mut()) };
             if res != 0 {
-                return Err(Error::new(
-                    ErrorKind::Other,
+                return Err(DaosError::from_call(
+                    "daos_tx_commit",
+                    res,
                     "Failed to commit DAOS transaction",
                 ));
             }
 
-            match rx.await {
+            match event.wait(rx, deadline).await {
                 Ok(ret) => {
                     if ret != 0 {
-                        Err(Error::new(ErrorKind::Other, "txn async commit failed"))
+                        Err(DaosError::from_call(
+                            "daos_tx_commit",
+                            ret,
+                            "txn async commit failed",
+                        ))
                     } else {
                         Ok(())
                     }
                 }
-                Err(_) => Err(Error::new(
-                    ErrorKind::Other,
-                    "txn async commit receiver error",
-                )),
+                Err(e) => Err(e),
             }
         }
     }
@@ -178,12 +235,13 @@ impl DaosTxnAsyncOps for DaosTxn {
     fn abort_async(&self) -> impl Future<Output = Result<()>> + Send + 'static {
         let tx_hdl = self.get_handle();
         let eq = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
         async move {
             if tx_hdl.is_none() || eq.is_none() {
-                return Err(Error::new(ErrorKind::InvalidData, "abort empty txn"));
+                return Err(DaosError::no_handle("abort empty txn"));
             }
 
-            let res = DaosEvent::new(eq.unwrap());
+            let res = eq.unwrap().create_event();
             if res.is_err() {
                 return Err(res.unwrap_err());
             }
@@ -197,24 +255,26 @@ impl DaosTxnAsyncOps for DaosTxn {
 
             let res = unsafe { daos_tx_abort(tx_hdl.unwrap(), event.as_mut()) };
             if res != 0 {
-                return Err(Error::new(
-                    ErrorKind::Other,
+                return Err(DaosError::from_call(
+                    "daos_tx_abort",
+                    res,
                     "Failed to abort DAOS transaction",
                 ));
             }
 
-            match rx.await {
+            match event.wait(rx, deadline).await {
                 Ok(ret) => {
                     if ret != 0 {
-                        Err(Error::new(ErrorKind::Other, "txn async abort failed"))
+                        Err(DaosError::from_call(
+                            "daos_tx_abort",
+                            ret,
+                            "txn async abort failed",
+                        ))
                     } else {
                         Ok(())
                     }
                 }
-                Err(_) => Err(Error::new(
-                    ErrorKind::Other,
-                    "txn async abort receiver error",
-                )),
+                Err(e) => Err(e),
             }
         }
     }
@@ -222,12 +282,13 @@ impl DaosTxnAsyncOps for DaosTxn {
     fn close_async(&self) -> impl Future<Output = Result<()>> + Send + 'static {
         let tx_hdl = self.get_handle();
         let eq = self.event_que.clone();
+        let deadline = self.deadlines.metadata;
         async move {
             if tx_hdl.is_none() || eq.is_none() {
-                return Err(Error::new(ErrorKind::InvalidData, "close empty txn"));
+                return Err(DaosError::no_handle("close empty txn"));
             }
 
-            let res = DaosEvent::new(eq.unwrap());
+            let res = eq.unwrap().create_event();
             if res.is_err() {
                 return Err(res.unwrap_err());
             }
@@ -241,65 +302,70 @@ impl DaosTxnAsyncOps for DaosTxn {
 
             let res = unsafe { daos_tx_close(tx_hdl.unwrap(), event.as_mut()) };
             if res != 0 {
-                return Err(Error::new(
-                    ErrorKind::Other,
+                return Err(DaosError::from_call(
+                    "daos_tx_close",
+                    res,
                     "Failed to close DAOS transaction",
                 ));
             }
 
-            match rx.await {
+            match event.wait(rx, deadline).await {
                 Ok(ret) => {
                     if ret != 0 {
-                        Err(Error::new(ErrorKind::Other, "txn async close failed"))
+                        Err(DaosError::from_call(
+                            "daos_tx_close",
+                            ret,
+                            "txn async close failed",
+                        ))
                     } else {
                         Ok(())
                     }
                 }
-                Err(_) => Err(Error::new(
-                    ErrorKind::Other,
-                    "txn async close receiver error",
-                )),
+                Err(e) => Err(e),
             }
         }
     }
 }
 
+#[cfg(feature = "libdaos")]
 impl DaosTxnSyncOps for DaosTxn {
     fn open(cont: &DaosContainer, flags: u64) -> Result<Box<DaosTxn>> {
         let cont_hdl = cont.get_handle();
         let eq = cont.get_event_queue();
-        let eqh = eq.map(|e| e.get_handle().unwrap());
+        let eqh = eq.cloned();
         if cont_hdl.is_none() {
-            return Err(Error::new(ErrorKind::InvalidInput, "empty container handle"));
+            return Err(DaosError::no_handle("empty container handle"));
         }
 
         let mut tx_hdl = DaosHandle { cookie: 0u64 };
-        let res = unsafe {
-            daos_tx_open(
-                cont_hdl.unwrap(),
-                &mut tx_hdl,
-                flags,
-                ptr::null_mut(),
-            )
-        };
+        let res = unsafe { daos_tx_open(cont_hdl.unwrap(), &mut tx_hdl, flags, ptr::null_mut()) };
         if res != 0 {
-            return Err(Error::new(ErrorKind::Other, "fail to open DAOS transaction"));
+            return Err(DaosError::from_call(
+                "daos_tx_open",
+                res,
+                "fail to open DAOS transaction",
+            ));
         }
 
         Ok(Box::new(DaosTxn {
             handle: Some(tx_hdl),
             event_que: eqh,
+            deadlines: cont.get_deadlines(),
         }))
     }
 
     fn commit(&self) -> Result<()> {
         if self.handle.is_none() {
-            return Err(Error::new(ErrorKind::InvalidData, "commit empty txn"));
+            return Err(DaosError::no_handle("commit empty txn"));
         }
 
         let res = unsafe { daos_tx_commit(self.handle.unwrap(), ptr::null_mut()) };
         if res != 0 {
-            return Err(Error::new(ErrorKind::Other, "Failed to commit DAOS transaction"));
+            return Err(DaosError::from_call(
+                "daos_tx_commit",
+                res,
+                "Failed to commit DAOS transaction",
+            ));
         }
 
         Ok(())
@@ -307,12 +373,16 @@ impl DaosTxnSyncOps for DaosTxn {
 
     fn abort(&self) -> Result<()> {
         if self.handle.is_none() {
-            return Err(Error::new(ErrorKind::InvalidData, "abort empty txn"));
+            return Err(DaosError::no_handle("abort empty txn"));
         }
 
         let res = unsafe { daos_tx_abort(self.handle.unwrap(), ptr::null_mut()) };
         if res != 0 {
-            return Err(Error::new(ErrorKind::Other, "Failed to abort DAOS transaction"));
+            return Err(DaosError::from_call(
+                "daos_tx_abort",
+                res,
+                "Failed to abort DAOS transaction",
+            ));
         }
 
         Ok(())
@@ -325,7 +395,11 @@ impl DaosTxnSyncOps for DaosTxn {
 
         let res = unsafe { daos_tx_close(self.handle.unwrap(), ptr::null_mut()) };
         if res != 0 {
-            return Err(Error::new(ErrorKind::Other, "Failed to close DAOS transaction"));
+            return Err(DaosError::from_call(
+                "daos_tx_close",
+                res,
+                "Failed to close DAOS transaction",
+            ));
         }
 
         Ok(())
@@ -0,0 +1,660 @@
+/*
+ *  Copyright (C) 2024 github.com/chel-data
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::bindings;
+#[cfg(feature = "libdaos")]
+use crate::bindings::{d_errdesc, d_errstr};
+use crate::daos_obj::InvalidFlags;
+use crate::daos_pool::DaosObjectId;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_char;
+use std::sync::RwLock;
+
+pub type Result<T> = std::result::Result<T, DaosError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaosErrorInfo {
+    // raw return code from libdaos (a negative DER_* value), or 0 when the
+    // error was raised by the bindings before reaching libdaos
+    pub rc: i32,
+    // the libdaos function that returned rc, empty if not known
+    pub func: &'static str,
+    pub msg: String,
+    // d_errstr()/d_errdesc() of rc, e.g. "DER_NONEXIST" and "The specified
+    // entity does not exist", empty when rc is 0
+    pub errstr: &'static str,
+    pub errdesc: &'static str,
+    // the object operation that failed, if the error came out of one
+    pub context: Option<Box<DaosErrorContext>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DaosErrorContext {
+    // name of the method that failed, e.g. "update_async"
+    pub op: &'static str,
+    pub oid: DaosObjectId,
+    pub dkey: Option<Vec<u8>>,
+    pub akey: Option<Vec<u8>>,
+}
+
+impl DaosErrorContext {
+    pub(crate) fn new(op: &'static str, oid: DaosObjectId) -> Self {
+        DaosErrorContext {
+            op,
+            oid,
+            dkey: None,
+            akey: None,
+        }
+    }
+
+    pub(crate) fn with_keys(mut self, dkey: &[u8], akey: &[u8]) -> Self {
+        self.dkey = Some(dkey.to_vec());
+        self.akey = Some(akey.to_vec());
+        self
+    }
+}
+
+// Keys are printed as text when they are, and as hex otherwise.
+fn fmt_key(f: &mut fmt::Formatter<'_>, key: &[u8]) -> fmt::Result {
+    match std::str::from_utf8(key) {
+        Ok(text) if !text.chars().any(char::is_control) => write!(f, "{:?}", text),
+        _ => {
+            f.write_str("0x")?;
+            for b in key {
+                write!(f, "{:02x}", b)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl fmt::Display for DaosErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} oid {}", self.op, self.oid)?;
+        if let Some(dkey) = &self.dkey {
+            f.write_str(" dkey ")?;
+            fmt_key(f, dkey)?;
+        }
+        if let Some(akey) = &self.akey {
+            f.write_str(" akey ")?;
+            fmt_key(f, akey)?;
+        }
+        Ok(())
+    }
+}
+
+// Called with every error a wrapped libdaos call returns.
+pub type DaosErrorHook = fn(&DaosErrorInfo);
+
+static ERROR_HOOK: RwLock<Option<DaosErrorHook>> = RwLock::new(None);
+
+// Installs the process wide error hook, replacing any previous one. It runs
+// on the thread that got the error, so it should be cheap, e.g. bump a
+// counter by info.func and info.rc.
+pub fn set_error_hook(hook: DaosErrorHook) {
+    *ERROR_HOOK.write().unwrap() = Some(hook);
+}
+
+pub fn clear_error_hook() {
+    *ERROR_HOOK.write().unwrap() = None;
+}
+
+// libdaos keeps its error strings in static tables.
+fn static_str(ptr: *const c_char) -> &'static str {
+    if ptr.is_null() {
+        return "";
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_str().unwrap_or("")
+}
+
+// Returns the DER_* name and the description libdaos has for rc.
+#[cfg(feature = "libdaos")]
+pub fn daos_errstr(rc: i32) -> (&'static str, &'static str) {
+    unsafe { (static_str(d_errstr(rc)), static_str(d_errdesc(rc))) }
+}
+
+// Without libdaos there are no error tables to look rc up in.
+#[cfg(not(feature = "libdaos"))]
+pub fn daos_errstr(_rc: i32) -> (&'static str, &'static str) {
+    ("", "")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DaosError {
+    #[error("{0}")]
+    NoPerm(DaosErrorInfo),
+    #[error("{0}")]
+    NoHandle(DaosErrorInfo),
+    #[error("{0}")]
+    Inval(DaosErrorInfo),
+    #[error("{0}")]
+    Exist(DaosErrorInfo),
+    #[error("{0}")]
+    NonExist(DaosErrorInfo),
+    #[error("{0}")]
+    Unreach(DaosErrorInfo),
+    #[error("{0}")]
+    NoSpace(DaosErrorInfo),
+    #[error("{0}")]
+    NoMem(DaosErrorInfo),
+    #[error("{0}")]
+    NoSys(DaosErrorInfo),
+    #[error("{0}")]
+    Timedout(DaosErrorInfo),
+    #[error("{0}")]
+    Busy(DaosErrorInfo),
+    #[error("{0}")]
+    Again(DaosErrorInfo),
+    #[error("{0}")]
+    Canceled(DaosErrorInfo),
+    // the key or record didn't fit the caller's buffer, required is the
+    // size libdaos reported when the call path exposes it
+    #[error("{info}")]
+    Key2Big {
+        info: DaosErrorInfo,
+        required: Option<usize>,
+    },
+    #[error("{info}")]
+    Rec2Big {
+        info: DaosErrorInfo,
+        required: Option<usize>,
+    },
+    // a single value larger than the fetch buffer, the first written bytes
+    // of the buffer hold the start of the actual_size bytes of the value
+    #[error("{info}")]
+    Truncated {
+        info: DaosErrorInfo,
+        actual_size: usize,
+        written: usize,
+    },
+    // transaction conflicts, see is_tx_conflict()
+    #[error("{0}")]
+    TxRestart(DaosErrorInfo),
+    #[error("{0}")]
+    TxBusy(DaosErrorInfo),
+    #[error("{0}")]
+    TxUncommitted(DaosErrorInfo),
+    // a write through a handle opened read-only, rejected before any RPC
+    #[error("{0}")]
+    ReadOnly(DaosErrorInfo),
+    // any DER_* code without a dedicated variant
+    #[error("{0}")]
+    Other(DaosErrorInfo),
+    #[error(transparent)]
+    InvalidFlags(#[from] InvalidFlags),
+}
+
+impl DaosError {
+    pub fn from_rc(rc: i32, msg: impl Into<String>) -> Self {
+        Self::classify(rc.unsigned_abs(), Self::rc_info("", rc, msg.into()))
+    }
+
+    // Error returned by the libdaos function func, reported to the error
+    // hook before it is handed to the caller.
+    pub(crate) fn from_call(func: &'static str, rc: i32, msg: impl Into<String>) -> Self {
+        let info = Self::rc_info(func, rc, msg.into());
+        let hook = *ERROR_HOOK.read().unwrap();
+        if let Some(hook) = hook {
+            hook(&info);
+        }
+        Self::classify(rc.unsigned_abs(), info)
+    }
+
+    fn rc_info(func: &'static str, rc: i32, msg: String) -> DaosErrorInfo {
+        let (errstr, errdesc) = daos_errstr(rc);
+        DaosErrorInfo {
+            rc,
+            func,
+            msg,
+            errstr,
+            errdesc,
+            context: None,
+        }
+    }
+
+    fn classify(code: u32, info: DaosErrorInfo) -> Self {
+        match code {
+            bindings::daos_errno_DER_NO_PERM => DaosError::NoPerm(info),
+            bindings::daos_errno_DER_NO_HDL => DaosError::NoHandle(info),
+            bindings::daos_errno_DER_INVAL => DaosError::Inval(info),
+            bindings::daos_errno_DER_EXIST => DaosError::Exist(info),
+            bindings::daos_errno_DER_NONEXIST => DaosError::NonExist(info),
+            bindings::daos_errno_DER_UNREACH => DaosError::Unreach(info),
+            bindings::daos_errno_DER_NOSPACE => DaosError::NoSpace(info),
+            bindings::daos_errno_DER_NOMEM => DaosError::NoMem(info),
+            bindings::daos_errno_DER_NOSYS => DaosError::NoSys(info),
+            bindings::daos_errno_DER_TIMEDOUT => DaosError::Timedout(info),
+            bindings::daos_errno_DER_BUSY => DaosError::Busy(info),
+            bindings::daos_errno_DER_AGAIN => DaosError::Again(info),
+            bindings::daos_errno_DER_CANCELED => DaosError::Canceled(info),
+            bindings::daos_errno_DER_KEY2BIG => DaosError::Key2Big {
+                info,
+                required: None,
+            },
+            bindings::daos_errno_DER_REC2BIG => DaosError::Rec2Big {
+                info,
+                required: None,
+            },
+            bindings::daos_errno_DER_TX_RESTART => DaosError::TxRestart(info),
+            bindings::daos_errno_DER_TX_BUSY => DaosError::TxBusy(info),
+            bindings::daos_errno_DER_TX_UNCOMMITTED => DaosError::TxUncommitted(info),
+            _ => DaosError::Other(info),
+        }
+    }
+
+    // Errors detected by the bindings themselves reuse the variant of the
+    // matching DER_* code but keep rc at 0.
+    fn local(code: u32, msg: impl Into<String>) -> Self {
+        Self::classify(code, Self::local_info(msg.into()))
+    }
+
+    fn local_info(msg: String) -> DaosErrorInfo {
+        DaosErrorInfo {
+            rc: 0,
+            func: "",
+            msg,
+            errstr: "",
+            errdesc: "",
+            context: None,
+        }
+    }
+
+    pub(crate) fn no_handle(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_NO_HDL, msg)
+    }
+
+    pub(crate) fn inval(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_INVAL, msg)
+    }
+
+    // only raised by the memory backend
+    #[cfg_attr(not(feature = "memory-backend"), allow(dead_code))]
+    pub(crate) fn exist(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_EXIST, msg)
+    }
+
+    pub(crate) fn non_exist(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_NONEXIST, msg)
+    }
+
+    pub(crate) fn unreach(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_UNREACH, msg)
+    }
+
+    pub(crate) fn no_space(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_NOSPACE, msg)
+    }
+
+    pub(crate) fn no_mem(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_NOMEM, msg)
+    }
+
+    pub(crate) fn no_sys(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_NOSYS, msg)
+    }
+
+    pub(crate) fn io_error(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_IO, msg)
+    }
+
+    pub(crate) fn timed_out(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_TIMEDOUT, msg)
+    }
+
+    pub(crate) fn busy(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_BUSY, msg)
+    }
+
+    pub(crate) fn canceled(msg: impl Into<String>) -> Self {
+        Self::local(bindings::daos_errno_DER_CANCELED, msg)
+    }
+
+    pub(crate) fn read_only(msg: impl Into<String>) -> Self {
+        DaosError::ReadOnly(Self::local_info(msg.into()))
+    }
+
+    pub(crate) fn rec2big(msg: impl Into<String>, required: usize) -> Self {
+        Self::local(bindings::daos_errno_DER_REC2BIG, msg).with_required_size(required)
+    }
+
+    // Records the buffer size needed for a retry on Key2Big/Rec2Big, other
+    // errors are returned unchanged.
+    pub(crate) fn with_required_size(self, size: usize) -> Self {
+        match self {
+            DaosError::Key2Big { info, .. } => DaosError::Key2Big {
+                info,
+                required: Some(size),
+            },
+            DaosError::Rec2Big { info, .. } => DaosError::Rec2Big {
+                info,
+                required: Some(size),
+            },
+            other => other,
+        }
+    }
+
+    // Turns the Rec2Big of a single value fetch into Truncated, written
+    // being how many bytes of the buffer the fetch filled. Other errors and
+    // a Rec2Big without required size are returned unchanged.
+    pub(crate) fn into_truncated(self, written: usize) -> Self {
+        match self {
+            DaosError::Rec2Big {
+                info,
+                required: Some(actual_size),
+            } => DaosError::Truncated {
+                info,
+                actual_size,
+                written,
+            },
+            other => other,
+        }
+    }
+
+    // Records which object operation failed. InvalidFlags carries no
+    // DaosErrorInfo and is returned unchanged.
+    pub(crate) fn with_context(mut self, context: DaosErrorContext) -> Self {
+        if let Some(info) = self.info_mut() {
+            info.context = Some(Box::new(context));
+        }
+        self
+    }
+
+    pub fn context(&self) -> Option<&DaosErrorContext> {
+        self.info().and_then(|info| info.context.as_deref())
+    }
+
+    pub fn required_size(&self) -> Option<usize> {
+        match self {
+            DaosError::Key2Big { required, .. } | DaosError::Rec2Big { required, .. } => *required,
+            DaosError::Truncated { actual_size, .. } => Some(*actual_size),
+            _ => None,
+        }
+    }
+
+    pub fn info(&self) -> Option<&DaosErrorInfo> {
+        match self {
+            DaosError::NoPerm(info)
+            | DaosError::NoHandle(info)
+            | DaosError::Inval(info)
+            | DaosError::Exist(info)
+            | DaosError::NonExist(info)
+            | DaosError::Unreach(info)
+            | DaosError::NoSpace(info)
+            | DaosError::NoMem(info)
+            | DaosError::NoSys(info)
+            | DaosError::Timedout(info)
+            | DaosError::Busy(info)
+            | DaosError::Again(info)
+            | DaosError::Canceled(info)
+            | DaosError::Key2Big { info, .. }
+            | DaosError::Rec2Big { info, .. }
+            | DaosError::Truncated { info, .. }
+            | DaosError::TxRestart(info)
+            | DaosError::TxBusy(info)
+            | DaosError::TxUncommitted(info)
+            | DaosError::ReadOnly(info)
+            | DaosError::Other(info) => Some(info),
+            DaosError::InvalidFlags(_) => None,
+        }
+    }
+
+    fn info_mut(&mut self) -> Option<&mut DaosErrorInfo> {
+        match self {
+            DaosError::NoPerm(info)
+            | DaosError::NoHandle(info)
+            | DaosError::Inval(info)
+            | DaosError::Exist(info)
+            | DaosError::NonExist(info)
+            | DaosError::Unreach(info)
+            | DaosError::NoSpace(info)
+            | DaosError::NoMem(info)
+            | DaosError::NoSys(info)
+            | DaosError::Timedout(info)
+            | DaosError::Busy(info)
+            | DaosError::Again(info)
+            | DaosError::Canceled(info)
+            | DaosError::Key2Big { info, .. }
+            | DaosError::Rec2Big { info, .. }
+            | DaosError::Truncated { info, .. }
+            | DaosError::TxRestart(info)
+            | DaosError::TxBusy(info)
+            | DaosError::TxUncommitted(info)
+            | DaosError::ReadOnly(info)
+            | DaosError::Other(info) => Some(info),
+            DaosError::InvalidFlags(_) => None,
+        }
+    }
+
+    // The transaction lost against a concurrent one, or read data of one
+    // not committed yet. An optimistic caller aborts and retries the whole
+    // transaction.
+    pub fn is_tx_conflict(&self) -> bool {
+        matches!(
+            self,
+            DaosError::TxRestart(_) | DaosError::TxBusy(_) | DaosError::TxUncommitted(_)
+        )
+    }
+
+    // libdaos no longer knows the pool or container handle, e.g. after the
+    // agent or the engines restarted, so reconnecting may help. A missing
+    // handle caught by the bindings themselves (rc 0) doesn't count.
+    pub fn is_connection_lost(&self) -> bool {
+        match self {
+            DaosError::NoHandle(info) => info.rc != 0,
+            DaosError::Unreach(_) => true,
+            DaosError::Other(info) => info.rc.unsigned_abs() == bindings::daos_errno_DER_EVICTED,
+            _ => false,
+        }
+    }
+
+    // The data read back didn't match its checksum, on every replica or
+    // shard libdaos tried.
+    pub fn is_checksum_error(&self) -> bool {
+        matches!(self, DaosError::Other(info) if info.rc.unsigned_abs() == bindings::daos_errno_DER_CSUM)
+    }
+
+    // The raw libdaos return code, 0 if the call never reached libdaos.
+    pub fn rc(&self) -> i32 {
+        self.info().map_or(0, |info| info.rc)
+    }
+}
+
+impl fmt::Display for DaosErrorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // same shape as DF_RC in the DAOS logs
+        if self.rc != 0 {
+            write!(
+                f,
+                "{}: {}({}): '{}'",
+                self.msg, self.errstr, self.rc, self.errdesc
+            )?;
+        } else {
+            f.write_str(&self.msg)?;
+        }
+        match &self.context {
+            Some(context) => write!(f, " [{}]", context),
+            None => Ok(()),
+        }
+    }
+}
+
+// Lets callers still on io::Result keep using `?` on DaosError. The
+// DaosError stays the inner error, so it can be recovered with downcast.
+#[cfg(feature = "std-io-compat")]
+impl From<DaosError> for std::io::Error {
+    fn from(err: DaosError) -> Self {
+        use std::io::ErrorKind;
+
+        let kind = match &err {
+            DaosError::NoPerm(_) | DaosError::ReadOnly(_) => ErrorKind::PermissionDenied,
+            DaosError::Inval(_) | DaosError::InvalidFlags(_) => ErrorKind::InvalidInput,
+            DaosError::Exist(_) => ErrorKind::AlreadyExists,
+            DaosError::NonExist(_) => ErrorKind::NotFound,
+            DaosError::Unreach(_) => ErrorKind::NotConnected,
+            DaosError::NoMem(_) => ErrorKind::OutOfMemory,
+            DaosError::NoSys(_) => ErrorKind::Unsupported,
+            DaosError::Timedout(_) => ErrorKind::TimedOut,
+            DaosError::Again(_) => ErrorKind::WouldBlock,
+            DaosError::Canceled(_) => ErrorKind::Interrupted,
+            _ => ErrorKind::Other,
+        };
+        std::io::Error::new(kind, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_rc() {
+        let rc = -(bindings::daos_errno_DER_NONEXIST as i32);
+        let err = DaosError::from_rc(rc, "Failed to fetch object");
+        assert!(matches!(err, DaosError::NonExist(_)));
+        assert_eq!(err.rc(), rc);
+        let info = err.info().unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Failed to fetch object: {}({}): '{}'",
+                info.errstr, rc, info.errdesc
+            )
+        );
+
+        let rc = -(bindings::daos_errno_DER_TX_RESTART as i32);
+        assert!(matches!(
+            DaosError::from_rc(rc, "commit"),
+            DaosError::TxRestart(_)
+        ));
+        for (code, busy) in [
+            (bindings::daos_errno_DER_TX_BUSY, true),
+            (bindings::daos_errno_DER_TX_UNCOMMITTED, false),
+        ] {
+            let err = DaosError::from_rc(-(code as i32), "txn async commit failed");
+            assert_eq!(matches!(err, DaosError::TxBusy(_)), busy);
+            assert!(err.is_tx_conflict());
+        }
+        assert!(matches!(
+            DaosError::from_rc(-999_999, "unknown"),
+            DaosError::Other(_)
+        ));
+        assert!(!DaosError::from_rc(-999_999, "unknown").is_tx_conflict());
+
+        let rc = -(bindings::daos_errno_DER_KEY2BIG as i32);
+        let err = DaosError::from_rc(rc, "list dkey").with_required_size(300);
+        assert!(matches!(err, DaosError::Key2Big { .. }));
+        assert_eq!(err.required_size(), Some(300));
+        assert_eq!(err.rc(), rc);
+        let err = DaosError::from_rc(-999_999, "unknown").with_required_size(300);
+        assert_eq!(err.required_size(), None);
+
+        let rc = -(bindings::daos_errno_DER_REC2BIG as i32);
+        let err = DaosError::from_rc(rc, "fetch")
+            .with_required_size(300)
+            .into_truncated(0);
+        assert!(matches!(
+            err,
+            DaosError::Truncated {
+                actual_size: 300,
+                written: 0,
+                ..
+            }
+        ));
+        assert_eq!(err.required_size(), Some(300));
+        assert_eq!(err.rc(), rc);
+        let err = DaosError::from_rc(rc, "fetch").into_truncated(0);
+        assert!(matches!(err, DaosError::Rec2Big { required: None, .. }));
+
+        let err = DaosError::no_handle("empty container handle");
+        assert!(matches!(err, DaosError::NoHandle(_)));
+        assert_eq!(err.rc(), 0);
+        assert_eq!(err.to_string(), "empty container handle");
+
+        for (code, lost) in [
+            (bindings::daos_errno_DER_NO_HDL, true),
+            (bindings::daos_errno_DER_UNREACH, true),
+            (bindings::daos_errno_DER_EVICTED, true),
+            (bindings::daos_errno_DER_NONEXIST, false),
+        ] {
+            let err = DaosError::from_rc(-(code as i32), "fetch");
+            assert_eq!(err.is_connection_lost(), lost);
+        }
+        assert!(!DaosError::no_handle("empty container handle").is_connection_lost());
+
+        let err = DaosError::read_only("update read-only object");
+        assert!(matches!(err, DaosError::ReadOnly(_)));
+        assert_eq!(err.rc(), 0);
+        assert_eq!(err.to_string(), "update read-only object");
+    }
+
+    #[cfg(feature = "std-io-compat")]
+    #[test]
+    fn test_io_compat() {
+        let rc = -(bindings::daos_errno_DER_NONEXIST as i32);
+        let err: std::io::Error = DaosError::from_rc(rc, "Failed to open container").into();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+        let inner = err.into_inner().unwrap().downcast::<DaosError>().unwrap();
+        assert_eq!(inner.rc(), rc);
+    }
+
+    static HOOK_CALLS: std::sync::Mutex<Vec<(&'static str, i32)>> =
+        std::sync::Mutex::new(Vec::new());
+
+    fn record_call(info: &DaosErrorInfo) {
+        if info.func == "daos_test_call" {
+            HOOK_CALLS.lock().unwrap().push((info.func, info.rc));
+        }
+    }
+
+    #[test]
+    fn test_error_hook() {
+        let rc = -(bindings::daos_errno_DER_BUSY as i32);
+        set_error_hook(record_call);
+        let err = DaosError::from_call("daos_test_call", rc, "busy");
+        assert!(matches!(err, DaosError::Busy(_)));
+        assert_eq!(err.info().unwrap().func, "daos_test_call");
+
+        // only errors of libdaos calls are reported
+        let _ = DaosError::from_rc(rc, "not a call");
+        clear_error_hook();
+        let _ = DaosError::from_call("daos_test_call", rc, "after clear");
+
+        assert_eq!(*HOOK_CALLS.lock().unwrap(), vec![("daos_test_call", rc)]);
+    }
+
+    #[test]
+    fn test_context() {
+        let oid = DaosObjectId { lo: 7, hi: 1 };
+        let context = DaosErrorContext::new("update_async", oid).with_keys(b"user", &[0u8, 0xff]);
+        let err = DaosError::no_handle("update uninitialized object").with_context(context);
+        assert!(matches!(err, DaosError::NoHandle(_)));
+        assert_eq!(err.context().unwrap().op, "update_async");
+        assert_eq!(err.context().unwrap().oid, oid);
+        assert_eq!(
+            err.to_string(),
+            "update uninitialized object [update_async oid 1.7 dkey \"user\" akey 0x00ff]"
+        );
+
+        let err = DaosError::no_handle("list uninitialized object")
+            .with_context(DaosErrorContext::new("list_dkey_async", oid));
+        assert_eq!(
+            err.to_string(),
+            "list uninitialized object [list_dkey_async oid 1.7]"
+        );
+    }
+}
@@ -15,14 +15,74 @@
  *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+// Without libdaos only the memory backend and what it shares with
+// DaosObject are built, the rest of the FFI glue goes unused.
+#![cfg_attr(not(feature = "libdaos"), allow(dead_code, unused_imports))]
+
+#[cfg(feature = "libdaos")]
 mod daos_event;
 #[allow(unused)]
 mod bindings;
 pub mod daos_pool;
+#[cfg(feature = "libdaos")]
+pub mod daos_archive;
+#[cfg(feature = "libdaos")]
 pub mod daos_cont;
+pub mod daos_compress;
 pub mod daos_obj;
+#[cfg(feature = "libdaos")]
+pub mod daos_obj_cache;
+#[cfg(feature = "libdaos")]
+pub mod daos_oclass;
+#[cfg(feature = "libdaos")]
+pub mod daos_oit;
 pub mod daos_txn;
+#[cfg(feature = "libdaos")]
 pub mod daos_oid_allocator;
+#[cfg(feature = "memory-backend")]
+pub mod daos_mem;
+#[cfg(feature = "patterns")]
+pub mod daos_key;
+#[cfg(feature = "kv")]
+pub mod daos_kv;
+#[cfg(feature = "patterns")]
+pub mod daos_map;
+#[cfg(feature = "libdaos")]
+pub mod daos_meta;
+#[cfg(feature = "patterns")]
+pub mod daos_namespace;
+#[cfg(feature = "libdaos")]
+pub mod daos_doctor;
+#[cfg(feature = "libdaos")]
+pub mod daos_reconnect;
+#[cfg(feature = "libdaos")]
+pub mod daos_runtime;
+#[cfg(feature = "patterns")]
+pub mod daos_schema;
+#[cfg(feature = "libdaos")]
+pub mod daos_scrub;
+#[cfg(feature = "patterns")]
+pub mod daos_timeseries;
+pub mod error;
+
+#[cfg(feature = "libdaos")]
+pub use daos_archive::{export_object_async, import_object_async};
+pub use daos_compress::DaosCompression;
+#[cfg(feature = "libdaos")]
+pub use daos_doctor::doctor;
+#[cfg(feature = "libdaos")]
+pub use daos_event::{with_timeout, DaosEventQueueConfig};
+#[cfg(feature = "libdaos")]
+pub use daos_obj_cache::ObjectCache;
+#[cfg(feature = "libdaos")]
+pub use daos_oclass::ObjectClass;
+#[cfg(feature = "libdaos")]
+pub use daos_runtime::DaosRuntime;
+#[cfg(feature = "libdaos")]
+pub use daos_scrub::Scrubber;
+#[cfg(feature = "mgmt")]
+pub use daos_pool::list_pools;
+pub use error::{DaosError as Error, Result};
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right